@@ -1,6 +1,7 @@
 #![deny(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+pub mod merkle;
 pub mod poseidon;
 pub mod roinput;
 pub use mina_curves::pasta::Fp;
@@ -156,7 +157,11 @@ pub trait Hasher<H: Hashable> {
 }
 
 /// Transform domain prefix string to field element
-fn domain_prefix_to_field<F: PrimeField>(prefix: String) -> F {
+///
+/// # Panics
+///
+/// Will panic if `prefix` is longer than 20 characters.
+pub fn domain_prefix_to_field<F: PrimeField>(prefix: String) -> F {
     const MAX_DOMAIN_STRING_LEN: usize = 20;
     assert!(prefix.len() <= MAX_DOMAIN_STRING_LEN);
     let prefix = &prefix[..std::cmp::min(prefix.len(), MAX_DOMAIN_STRING_LEN)];
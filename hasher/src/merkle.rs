@@ -0,0 +1,205 @@
+//! Mina Merkle tree
+//!
+//! A sparse Poseidon Merkle tree whose nodes are hashed as in Mina's ledger,
+//! that is with the kimchi hasher and a domain string depending on the height
+//! of the merged nodes.
+//!
+//! ```rust
+//! use mina_hasher::{merkle::MerkleTree, Fp};
+//!
+//! let mut tree = MerkleTree::new(4, Fp::from(0u32));
+//! tree.set_leaf(5, Fp::from(42u32));
+//!
+//! let path = tree.path(5);
+//! assert!(path.verify(Fp::from(42u32), tree.root()));
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{create_kimchi, Fp, Hashable, Hasher, PoseidonHasherKimchi, ROInput};
+
+/// Maximum depth of a [`MerkleTree`], as leaf indices are stored in a `u64`
+pub const MAX_DEPTH: usize = 64;
+
+/// A pair of sibling nodes merged into their parent node
+#[derive(Clone, Debug)]
+pub struct MerkleNode {
+    /// Left child
+    pub left: Fp,
+    /// Right child
+    pub right: Fp,
+}
+
+impl Hashable for MerkleNode {
+    /// Height of the merged children, leaves being at height 0
+    type D = u64;
+
+    fn to_roinput(&self) -> ROInput {
+        ROInput::new()
+            .append_field(self.left)
+            .append_field(self.right)
+    }
+
+    fn domain_string(height: Self::D) -> Option<String> {
+        format!("MinaMklTree{height:03}").into()
+    }
+}
+
+/// Authentication path of a leaf in a [`MerkleTree`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath {
+    /// Index of the leaf
+    pub index: u64,
+    /// Siblings of the nodes from the leaf up to the root (excluded)
+    pub siblings: Vec<Fp>,
+}
+
+impl MerklePath {
+    /// Depth of the tree the path belongs to
+    pub fn depth(&self) -> usize {
+        self.siblings.len()
+    }
+
+    /// Direction of each node from the leaf up to the root (excluded):
+    /// `true` when the node is a right child
+    pub fn bits(&self) -> Vec<bool> {
+        (0..self.depth())
+            .map(|height| (self.index >> height) & 1 == 1)
+            .collect()
+    }
+
+    /// Computes the root of the tree from a `leaf` placed at the position of the path
+    pub fn root(&self, leaf: Fp) -> Fp {
+        let mut hasher = create_kimchi::<MerkleNode>(0);
+        self.siblings.iter().zip(self.bits()).enumerate().fold(
+            leaf,
+            |node, (height, (sibling, is_right))| {
+                let (left, right) = if is_right {
+                    (*sibling, node)
+                } else {
+                    (node, *sibling)
+                };
+                merge(&mut hasher, height, left, right)
+            },
+        )
+    }
+
+    /// Checks that `leaf` is a member of the tree with the given `root`
+    pub fn verify(&self, leaf: Fp, root: Fp) -> bool {
+        self.root(leaf) == root
+    }
+}
+
+/// Sparse Merkle tree of fixed depth, in which unset leaves take a default value
+pub struct MerkleTree {
+    depth: usize,
+    hasher: PoseidonHasherKimchi<MerkleNode>,
+    /// Hash of an empty subtree, for each height
+    empty: Vec<Fp>,
+    /// Nodes differing from the empty ones, indexed by height and position
+    nodes: HashMap<(usize, u64), Fp>,
+}
+
+impl MerkleTree {
+    /// Creates a tree of the given `depth` whose leaves are all equal to `empty_leaf`
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `depth` is larger than [`MAX_DEPTH`].
+    pub fn new(depth: usize, empty_leaf: Fp) -> Self {
+        assert!(
+            depth <= MAX_DEPTH,
+            "merkle tree depth is at most {MAX_DEPTH}"
+        );
+
+        let mut hasher = create_kimchi::<MerkleNode>(0);
+        let mut node = empty_leaf;
+        let mut empty = Vec::with_capacity(depth + 1);
+        empty.push(node);
+        for height in 0..depth {
+            node = merge(&mut hasher, height, node, node);
+            empty.push(node);
+        }
+
+        Self {
+            depth,
+            hasher,
+            empty,
+            nodes: HashMap::new(),
+        }
+    }
+
+    /// Depth of the tree
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Root of the tree
+    pub fn root(&self) -> Fp {
+        self.node(self.depth, 0)
+    }
+
+    /// Leaf at position `index`
+    pub fn leaf(&self, index: u64) -> Fp {
+        self.check_index(index);
+        self.node(0, index)
+    }
+
+    /// Sets the leaf at position `index` and updates the nodes up to the root
+    pub fn set_leaf(&mut self, index: u64, leaf: Fp) {
+        self.check_index(index);
+
+        let mut node = leaf;
+        let mut position = index;
+        self.set_node(0, position, node);
+        for height in 0..self.depth {
+            let sibling = self.node(height, position ^ 1);
+            let (left, right) = if position & 1 == 1 {
+                (sibling, node)
+            } else {
+                (node, sibling)
+            };
+            node = merge(&mut self.hasher, height, left, right);
+            position >>= 1;
+            self.set_node(height + 1, position, node);
+        }
+    }
+
+    /// Authentication path of the leaf at position `index`
+    pub fn path(&self, index: u64) -> MerklePath {
+        self.check_index(index);
+
+        let siblings = (0..self.depth)
+            .map(|height| self.node(height, (index >> height) ^ 1))
+            .collect();
+        MerklePath { index, siblings }
+    }
+
+    fn node(&self, height: usize, position: u64) -> Fp {
+        self.nodes
+            .get(&(height, position))
+            .copied()
+            .unwrap_or(self.empty[height])
+    }
+
+    fn set_node(&mut self, height: usize, position: u64, node: Fp) {
+        if node == self.empty[height] {
+            self.nodes.remove(&(height, position));
+        } else {
+            self.nodes.insert((height, position), node);
+        }
+    }
+
+    fn check_index(&self, index: u64) {
+        assert!(
+            self.depth == MAX_DEPTH || index >> self.depth == 0,
+            "leaf index {index} is out of bounds for depth {}",
+            self.depth
+        );
+    }
+}
+
+/// Hashes two sibling nodes at `height` into their parent node
+fn merge(hasher: &mut PoseidonHasherKimchi<MerkleNode>, height: usize, left: Fp, right: Fp) -> Fp {
+    hasher.init_and_hash(height as u64, &MerkleNode { left, right })
+}
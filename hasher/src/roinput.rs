@@ -118,6 +118,12 @@ impl ROInput {
         self.append_bytes(&x.to_le_bytes())
     }
 
+    /// Number of field elements appended to the input,
+    /// which [`ROInput::to_fields`] returns before the field elements packing the bits
+    pub fn num_fields(&self) -> usize {
+        self.fields.len()
+    }
+
     /// Serialize random oracle input to bytes
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bits: BitVec<u8> = self.fields.iter().fold(BitVec::new(), |mut acc, fe| {
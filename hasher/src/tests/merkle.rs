@@ -0,0 +1,95 @@
+use crate::{
+    create_kimchi,
+    merkle::{MerkleNode, MerklePath, MerkleTree},
+    Fp, Hashable, Hasher,
+};
+
+fn leaf(i: u64) -> Fp {
+    Fp::from(1000 + i)
+}
+
+#[test]
+fn merkle_node_domain_strings() {
+    // the prefixes of Mina's ledger: "MinaMklTree" followed by the height on 3 digits
+    assert_eq!(
+        MerkleNode::domain_string(0),
+        Some("MinaMklTree000".to_string())
+    );
+    assert_eq!(
+        MerkleNode::domain_string(35),
+        Some("MinaMklTree035".to_string())
+    );
+}
+
+#[test]
+fn merkle_tree_root_matches_full_tree() {
+    let depth = 3;
+    let mut tree = MerkleTree::new(depth, Fp::from(0u32));
+    for i in 0..(1 << depth) {
+        tree.set_leaf(i, leaf(i));
+    }
+
+    // hash the full tree level by level
+    let mut hasher = create_kimchi::<MerkleNode>(0);
+    let mut level: Vec<Fp> = (0..(1 << depth)).map(leaf).collect();
+    for height in 0..depth {
+        level = level
+            .chunks(2)
+            .map(|pair| {
+                hasher.init_and_hash(
+                    height as u64,
+                    &MerkleNode {
+                        left: pair[0],
+                        right: pair[1],
+                    },
+                )
+            })
+            .collect();
+    }
+
+    assert_eq!(level, vec![tree.root()]);
+}
+
+#[test]
+fn merkle_tree_paths() {
+    let depth = 20;
+    let mut tree = MerkleTree::new(depth, Fp::from(7u32));
+    let indices = [0, 1, 2, 12345, (1 << depth) - 1];
+    for i in indices {
+        tree.set_leaf(i, leaf(i));
+    }
+
+    for i in indices {
+        assert_eq!(tree.leaf(i), leaf(i));
+        let path = tree.path(i);
+        assert_eq!(path.depth(), depth);
+        assert!(path.verify(leaf(i), tree.root()));
+        assert!(!path.verify(leaf(i + 1), tree.root()));
+    }
+
+    // an unset leaf keeps the default value
+    let path = tree.path(3);
+    assert!(path.verify(Fp::from(7u32), tree.root()));
+}
+
+#[test]
+fn merkle_tree_empty_root_is_deterministic() {
+    let mut tree = MerkleTree::new(10, Fp::from(0u32));
+    let empty_root = tree.root();
+
+    tree.set_leaf(42, leaf(42));
+    assert_ne!(tree.root(), empty_root);
+
+    // restoring the default leaf restores the empty root
+    tree.set_leaf(42, Fp::from(0u32));
+    assert_eq!(tree.root(), empty_root);
+}
+
+#[test]
+fn merkle_path_bits() {
+    let path = MerklePath {
+        index: 0b1011,
+        siblings: vec![Fp::from(0u32); 5],
+    };
+    assert_eq!(path.bits(), vec![true, true, false, true, false]);
+}
@@ -1,6 +1,7 @@
 use crate::{create_legacy, Hashable, Hasher, ROInput};
 
 mod hasher;
+mod merkle;
 
 #[test]
 fn interfaces() {
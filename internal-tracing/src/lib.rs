@@ -4,6 +4,10 @@ mod internal_tracing {
     pub use crate::*;
 }
 
+pub mod spans;
+
+pub use spans::Span;
+
 #[cfg(feature = "enabled")]
 pub use serde_json::{json, to_writer as json_to_writer, Value as JsonValue};
 
@@ -160,6 +164,47 @@ macro_rules! checkpoint {
     ($($_ignored:tt)+) => {};
 }
 
+/// Enter a span, which is exited when the returned [`Span`] is dropped.
+///
+/// ```ignore
+/// let _span = internal_tracing::span!(compute_quotient_poly);
+/// let _span = internal_tracing::span!(gate, { "argument": "Poseidon" });
+/// ```
+#[cfg(feature = "enabled")]
+#[macro_export]
+macro_rules! span {
+    ($name:ident) => {
+        $crate::Span::enter(stringify!($name), $crate::json!(null))
+    };
+    ($name:ident, {$($metadata:tt)+}) => {
+        $crate::Span::enter(stringify!($name), $crate::json!({$($metadata)+}))
+    };
+}
+/// Noop. Internal tracing not enabled!
+#[cfg(not(feature = "enabled"))]
+#[macro_export]
+macro_rules! span {
+    ($($_ignored:tt)+) => {
+        $crate::Span::disabled()
+    };
+}
+
+/// Exit the span held by a variable and enter the next one in its place,
+/// to trace the consecutive phases of a function.
+///
+/// ```ignore
+/// let mut _phase = internal_tracing::span!(pad_witness);
+/// // ...
+/// internal_tracing::next_span!(_phase; set_up_fq_sponge);
+/// ```
+#[macro_export]
+macro_rules! next_span {
+    ($span:ident; $($args:tt)+) => {
+        $span.exit();
+        $span = $crate::span!($($args)+);
+    };
+}
+
 #[cfg(feature = "enabled")]
 #[cfg(test)]
 mod tests {
@@ -190,4 +235,55 @@ mod tests {
         assert_eq!(traces.c4.0, 3);
         assert_eq!(traces.c4.1, serde_json::json!({ "arg": 2 }));
     }
+
+    #[test]
+    fn test_spans() {
+        let collection = spans::start_spans();
+
+        {
+            let outer = span!(outer);
+            let mut _phase = span!(first, { "arg": 1 });
+            next_span!(_phase; second);
+
+            // spans of other threads are collected too
+            let parent = outer.id();
+            std::thread::spawn(move || {
+                let _worker = Span::enter_with_parent("worker", parent, serde_json::Value::Null);
+                let _inner = span!(inner);
+            })
+            .join()
+            .unwrap();
+        }
+        // spans that are not exited are not collected
+        let _open = span!(open);
+
+        let spans = collection.take_spans();
+
+        let names: Vec<_> = spans.0.iter().map(|span| span.name).collect();
+        assert_eq!(names, vec!["outer", "first", "second", "worker", "inner"]);
+
+        let id = |name| spans.named(name).next().unwrap().id;
+        let parent = |name| spans.named(name).next().unwrap().parent;
+        assert_eq!(parent("outer"), None);
+        assert_eq!(parent("first"), Some(id("outer")));
+        assert_eq!(parent("second"), Some(id("outer")));
+        assert_eq!(parent("worker"), Some(id("outer")));
+        assert_eq!(parent("inner"), Some(id("worker")));
+
+        let thread = |name| spans.named(name).next().unwrap().thread;
+        assert_eq!(thread("outer"), thread("second"));
+        assert_ne!(thread("outer"), thread("inner"));
+
+        let stats = spans.aggregate();
+        assert_eq!(stats["first"].count, 1);
+        assert!(stats["outer"].total >= stats["first"].total + stats["second"].total);
+
+        let trace = spans.to_chrome_trace();
+        let events = trace["traceEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[1]["name"], "first");
+        assert_eq!(events[1]["ph"], "X");
+        assert_eq!(events[1]["args"]["arg"], 1);
+        assert_eq!(events[1]["args"]["parent"], id("outer"));
+    }
 }
@@ -0,0 +1,281 @@
+//! Span-based tracing.
+//!
+//! A [`Span`] is entered with [`span!`](crate::span) and exited when it is dropped
+//! (or with [`Span::exit`]). Its parent is the innermost span still open on the same thread.
+//! Spans of all threads are collected together by the [`SpanCollection`] returned by [`start_spans`],
+//! and can be aggregated by name or exported in the Chrome trace-event format,
+//! which can be opened in `chrome://tracing` or <https://ui.perfetto.dev>.
+//!
+//! Spans are only collected while a [`SpanCollection`] is alive,
+//! so that a long-running process doesn't accumulate them.
+//! There is at most one collection at a time: [`start_spans`] waits for the previous one to be dropped,
+//! so that concurrent callers (such as tests) don't discard or stop each other's collection.
+
+#[cfg(feature = "enabled")]
+pub use enabled::*;
+
+#[cfg(feature = "enabled")]
+mod enabled {
+    use crate::{now_micros, JsonValue};
+    use serde::Serialize;
+    use std::{
+        cell::RefCell,
+        collections::BTreeMap,
+        fmt,
+        io::Write,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Mutex, MutexGuard, PoisonError,
+        },
+    };
+
+    static ENABLED: AtomicBool = AtomicBool::new(false);
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    static NEXT_THREAD: AtomicU64 = AtomicU64::new(1);
+    static SPANS: Mutex<Vec<SpanRecord>> = Mutex::new(Vec::new());
+    static COLLECTION: Mutex<()> = Mutex::new(());
+
+    thread_local! {
+        /// The id of the thread in the traces
+        static THREAD: u64 = NEXT_THREAD.fetch_add(1, Ordering::Relaxed);
+        /// The spans open on the thread, the innermost last
+        static STACK: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn spans() -> MutexGuard<'static, Vec<SpanRecord>> {
+        SPANS.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// A span that was exited, with its times in microseconds since the UNIX epoch
+    #[derive(Serialize, Debug, Clone, PartialEq)]
+    pub struct SpanRecord {
+        pub id: u64,
+        pub parent: Option<u64>,
+        pub name: &'static str,
+        pub thread: u64,
+        pub start: u64,
+        pub end: u64,
+        pub metadata: JsonValue,
+    }
+
+    impl SpanRecord {
+        pub fn duration(&self) -> u64 {
+            self.end.saturating_sub(self.start)
+        }
+    }
+
+    /// An open span, which is recorded when it is exited or dropped
+    #[derive(Debug)]
+    pub struct Span {
+        record: Option<SpanRecord>,
+    }
+
+    impl Span {
+        /// Enters a span, whose parent is the innermost open span of the thread
+        pub fn enter(name: &'static str, metadata: JsonValue) -> Self {
+            let parent = current_span();
+            Self::enter_with_parent(name, parent, metadata)
+        }
+
+        /// Enters a span with the given parent,
+        /// to attach the spans of worker threads to the span of the thread that spawned them
+        pub fn enter_with_parent(
+            name: &'static str,
+            parent: Option<u64>,
+            metadata: JsonValue,
+        ) -> Self {
+            if !ENABLED.load(Ordering::Relaxed) {
+                return Self { record: None };
+            }
+            let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+            STACK.with(|stack| stack.borrow_mut().push(id));
+            let record = SpanRecord {
+                id,
+                parent,
+                name,
+                thread: THREAD.with(|thread| *thread),
+                start: now_micros(),
+                end: 0,
+                metadata,
+            };
+            Self {
+                record: Some(record),
+            }
+        }
+
+        /// The id of the span, if spans are collected
+        pub fn id(&self) -> Option<u64> {
+            self.record.as_ref().map(|record| record.id)
+        }
+
+        /// Exits the span, which does nothing if it was already exited
+        pub fn exit(&mut self) {
+            if let Some(mut record) = self.record.take() {
+                record.end = now_micros();
+                STACK.with(|stack| {
+                    let mut stack = stack.borrow_mut();
+                    if let Some(pos) = stack.iter().rposition(|id| *id == record.id) {
+                        stack.remove(pos);
+                    }
+                });
+                if ENABLED.load(Ordering::Relaxed) {
+                    spans().push(record);
+                }
+            }
+        }
+    }
+
+    impl Drop for Span {
+        fn drop(&mut self) {
+            self.exit();
+        }
+    }
+
+    /// The innermost span open on the current thread
+    pub fn current_span() -> Option<u64> {
+        STACK.with(|stack| stack.borrow().last().copied())
+    }
+
+    /// Starts collecting spans, until the returned collection is dropped.
+    /// Waits for the previous collection, if any, to be dropped.
+    pub fn start_spans() -> SpanCollection {
+        let lock = COLLECTION.lock().unwrap_or_else(PoisonError::into_inner);
+        spans().clear();
+        ENABLED.store(true, Ordering::Relaxed);
+        SpanCollection { _lock: lock }
+    }
+
+    /// A collection of spans, started by [`start_spans`].
+    /// Dropping it stops collecting spans, and discards the ones that were not taken.
+    #[derive(Debug)]
+    pub struct SpanCollection {
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    impl SpanCollection {
+        /// Takes the spans collected so far, on all threads.
+        /// Spans that are still open are not part of them.
+        pub fn take_spans(&self) -> Spans {
+            let mut spans = std::mem::take(&mut *spans());
+            spans.sort_by_key(|span| (span.start, span.id));
+            Spans(spans)
+        }
+    }
+
+    impl Drop for SpanCollection {
+        fn drop(&mut self) {
+            ENABLED.store(false, Ordering::Relaxed);
+            spans().clear();
+        }
+    }
+
+    /// The number of times a span was entered, and the time spent in it (in microseconds)
+    #[derive(Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct SpanStats {
+        pub count: u64,
+        pub total: u64,
+        pub min: u64,
+        pub max: u64,
+    }
+
+    /// Spans collected by [`SpanCollection::take_spans`], sorted by start time
+    #[derive(Serialize, Debug, Default, Clone, PartialEq)]
+    #[serde(transparent)]
+    pub struct Spans(pub Vec<SpanRecord>);
+
+    impl Spans {
+        /// The spans of the given name
+        pub fn named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a SpanRecord> {
+            self.0.iter().filter(move |span| span.name == name)
+        }
+
+        /// The statistics of the spans of each name, across all threads
+        pub fn aggregate(&self) -> BTreeMap<&'static str, SpanStats> {
+            let mut stats = BTreeMap::<_, SpanStats>::new();
+            for span in &self.0 {
+                let duration = span.duration();
+                let entry = stats.entry(span.name).or_default();
+                entry.min = if entry.count == 0 {
+                    duration
+                } else {
+                    entry.min.min(duration)
+                };
+                entry.max = entry.max.max(duration);
+                entry.count += 1;
+                entry.total += duration;
+            }
+            stats
+        }
+
+        /// The spans in the Chrome trace-event format, as complete events
+        pub fn to_chrome_trace(&self) -> JsonValue {
+            let pid = std::process::id();
+            let events: Vec<_> = self
+                .0
+                .iter()
+                .map(|span| {
+                    let mut args = match &span.metadata {
+                        JsonValue::Object(metadata) => metadata.clone(),
+                        JsonValue::Null => Default::default(),
+                        metadata => [("metadata".to_string(), metadata.clone())]
+                            .into_iter()
+                            .collect(),
+                    };
+                    args.insert("id".to_string(), span.id.into());
+                    args.insert("parent".to_string(), span.parent.into());
+                    serde_json::json!({
+                        "name": span.name,
+                        "cat": "kimchi",
+                        "ph": "X",
+                        "ts": span.start,
+                        "dur": span.duration(),
+                        "pid": pid,
+                        "tid": span.thread,
+                        "args": args,
+                    })
+                })
+                .collect();
+            serde_json::json!({
+                "traceEvents": events,
+                "displayTimeUnit": "ms",
+            })
+        }
+
+        /// Writes the spans in the Chrome trace-event format
+        pub fn write_chrome_trace<W: Write>(&self, writer: W) -> serde_json::Result<()> {
+            serde_json::to_writer(writer, &self.to_chrome_trace())
+        }
+    }
+
+    /// Prints the statistics of each span name, in milliseconds
+    impl fmt::Display for Spans {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let ms = |micros: u64| micros as f64 / 1000.0;
+            for (name, stats) in self.aggregate() {
+                writeln!(
+                    f,
+                    "{name}: {} x, total {:.3} ms, min {:.3} ms, max {:.3} ms",
+                    stats.count,
+                    ms(stats.total),
+                    ms(stats.min),
+                    ms(stats.max)
+                )?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Noop. Internal tracing not enabled!
+#[cfg(not(feature = "enabled"))]
+#[derive(Debug)]
+pub struct Span;
+
+#[cfg(not(feature = "enabled"))]
+impl Span {
+    pub fn disabled() -> Self {
+        Self
+    }
+
+    pub fn exit(&mut self) {}
+}
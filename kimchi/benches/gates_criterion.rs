@@ -0,0 +1,34 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, SamplingMode};
+use kimchi::bench::{BenchmarkCtx, GateFamily};
+use strum::IntoEnumIterator;
+
+pub fn bench_gate_families(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Gate families");
+    group.sample_size(10).sampling_mode(SamplingMode::Flat); // for slow benchmarks
+
+    for family in GateFamily::iter() {
+        for srs_size_log2 in [14, 16] {
+            let ctx = BenchmarkCtx::new_for_family(family, srs_size_log2);
+            group.bench_with_input(
+                BenchmarkId::new(format!("proof creation of {family} gates"), srs_size_log2),
+                &ctx,
+                |b, ctx| b.iter(|| black_box(ctx.create_proof())),
+            );
+
+            let proof_and_public = ctx.create_proof();
+            group.bench_with_input(
+                BenchmarkId::new(
+                    format!("proof verification of {family} gates"),
+                    srs_size_log2,
+                ),
+                &ctx,
+                |b, ctx| {
+                    b.iter(|| ctx.batch_verification(black_box(&vec![proof_and_public.clone()])))
+                },
+            );
+        }
+    }
+}
+
+criterion_group!(benches, bench_gate_families);
+criterion_main!(benches);
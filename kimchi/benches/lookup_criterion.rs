@@ -0,0 +1,33 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, SamplingMode};
+use kimchi::{bench::BenchmarkCtx, circuits::lookup::lookups::LookupBackend};
+
+pub fn bench_lookup_backends(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Lookup backends");
+    group.sample_size(10).sampling_mode(SamplingMode::Flat); // for slow benchmarks
+
+    // a circuit whose lookups are dominated by XOR and range check lookups
+    let (num_xors, num_range_checks) = (256, 256);
+
+    for lookup_backend in [LookupBackend::Plookup, LookupBackend::LogUp] {
+        let ctx = BenchmarkCtx::new_with_lookups(num_xors, num_range_checks, lookup_backend);
+        group.bench_function(
+            format!(
+                "proof creation with {lookup_backend:?} (SRS size 2^{})",
+                ctx.srs_size()
+            ),
+            |b| b.iter(|| black_box(ctx.create_proof())),
+        );
+
+        let proof_and_public = ctx.create_proof();
+        group.bench_function(
+            format!(
+                "proof verification with {lookup_backend:?} (SRS size 2^{})",
+                ctx.srs_size()
+            ),
+            |b| b.iter(|| ctx.batch_verification(black_box(&vec![proof_and_public.clone()]))),
+        );
+    }
+}
+
+criterion_group!(benches, bench_lookup_backends);
+criterion_main!(benches);
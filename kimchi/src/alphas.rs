@@ -59,9 +59,9 @@ impl<F: Field> Alphas<F> {
             panic!("you cannot register new constraints once initialized with a field element");
         }
 
-        // gates are a special case, as we reuse the same power of alpha
+        // gates (including custom gates) are a special case, as we reuse the same power of alpha
         // across all of them (they're mutually exclusive)
-        let ty = if matches!(ty, ArgumentType::Gate(_)) {
+        let ty = if matches!(ty, ArgumentType::Gate(_) | ArgumentType::CustomGate(_)) {
             // the zero gate is not used, so we default to it
             ArgumentType::Gate(GateType::Zero)
         } else {
@@ -85,7 +85,7 @@ impl<F: Field> Alphas<F> {
         ty: ArgumentType,
         num: u32,
     ) -> MustConsumeIterator<Range<u32>, u32> {
-        let ty = if matches!(ty, ArgumentType::Gate(_)) {
+        let ty = if matches!(ty, ArgumentType::Gate(_) | ArgumentType::CustomGate(_)) {
             ArgumentType::Gate(GateType::Zero)
         } else {
             ty
@@ -131,7 +131,7 @@ impl<F: Field> Alphas<F> {
         ty: ArgumentType,
         num: u32,
     ) -> MustConsumeIterator<Cloned<Take<Skip<Iter<F>>>>, F> {
-        let ty = if matches!(ty, ArgumentType::Gate(_)) {
+        let ty = if matches!(ty, ArgumentType::Gate(_) | ArgumentType::CustomGate(_)) {
             ArgumentType::Gate(GateType::Zero)
         } else {
             ty
@@ -324,7 +324,7 @@ mod tests {
         let gates = vec![CircuitGate::<Fp>::zero(Wire::for_row(0)); 2];
         let index = new_index_for_test::<Vesta>(gates, 0);
         let (_linearization, powers_of_alpha) =
-            expr_linearization::<Fp>(Some(&index.cs.feature_flags), true);
+            expr_linearization::<Fp>(Some(&index.cs.feature_flags), true, &[]);
         // make sure this is present in the specification
         let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
         let spec_path = Path::new(&manifest_dir)
@@ -1,22 +1,47 @@
-use std::array;
+use std::{array, collections::BTreeMap, fmt, time::Instant};
 
+use ark_ff::UniformRand;
+use ark_poly::{univariate::DensePolynomial, UVPolynomial};
 use groupmap::{BWParameters, GroupMap};
 use mina_curves::pasta::{Fp, Vesta, VestaParameters};
 use mina_poseidon::{
     constants::PlonkSpongeConstantsKimchi,
     sponge::{DefaultFqSponge, DefaultFrSponge},
 };
+use num_bigint::BigUint;
 use o1_utils::math;
-use poly_commitment::commitment::CommitmentCurve;
+use poly_commitment::commitment::{b_poly_coefficients, CommitmentCurve};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, EnumString};
 
 use crate::{
     circuits::{
-        gate::CircuitGate,
-        polynomials::generic::GenericGateSpec,
+        gate::{CircuitGate, GateType},
+        lookup::{
+            lookups::LookupBackend,
+            runtime_tables::{RuntimeTable, RuntimeTableCfg, RuntimeTableSpec},
+        },
+        polynomials::{
+            foreign_field_mul,
+            generic::GenericGateSpec,
+            permutation::ZK_ROWS,
+            poseidon::{self, POS_ROWS_PER_HASH},
+            range_check,
+            rot::{self, RotMode},
+            xor,
+        },
         wires::{Wire, COLUMNS},
     },
-    proof::ProverProof,
-    prover_index::{testing::new_index_for_test, ProverIndex},
+    curve::KimchiCurve,
+    proof::{ProverProof, RecursionChallenge},
+    prover_index::{
+        testing::{
+            new_index_for_test, new_index_for_test_with_custom_gates,
+            new_index_for_test_with_lookups,
+        },
+        ProverIndex,
+    },
     verifier::{batch_verify, Context},
     verifier_index::VerifierIndex,
 };
@@ -25,11 +50,75 @@ type SpongeParams = PlonkSpongeConstantsKimchi;
 type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
 type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
 
+/// The families of gates benchmarked on their own by [`BenchmarkCtx::new_for_family`]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    EnumString,
+    Display,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum GateFamily {
+    /// Generic gates
+    Generic,
+    /// Poseidon permutations
+    Poseidon,
+    /// Multi-range checks
+    RangeCheck,
+    /// Foreign field multiplications (modulo the secp256k1 base field)
+    ForeignFieldMul,
+    /// 64-bit XORs, each followed by a 64-bit rotation
+    XorRot,
+    /// Lookups in a runtime table
+    RuntimeTables,
+    /// Generic gates, proven with a previous recursion challenge
+    Recursion,
+}
+
+impl GateFamily {
+    /// The smallest domain (as the log2 of its size) that fits the gadgets of the family,
+    /// which is larger for the families that use the 4096 entries of the range check table
+    pub fn min_srs_size_log2(self) -> u32 {
+        match self {
+            GateFamily::RangeCheck | GateFamily::ForeignFieldMul | GateFamily::XorRot => 13,
+            GateFamily::Poseidon
+            | GateFamily::Generic
+            | GateFamily::RuntimeTables
+            | GateFamily::Recursion => 4,
+        }
+    }
+
+    /// The number of rows of a gadget of the family
+    fn gadget_rows(self) -> usize {
+        match self {
+            GateFamily::Generic | GateFamily::RuntimeTables | GateFamily::Recursion => 1,
+            // the last row of a Poseidon gadget holds its output
+            GateFamily::Poseidon => POS_ROWS_PER_HASH + 1,
+            GateFamily::RangeCheck => 4,
+            GateFamily::ForeignFieldMul => 2,
+            // 4 Xor16 gates and a zero row, then a Rot64 and a RangeCheck0 gate
+            GateFamily::XorRot => 7,
+        }
+    }
+}
+
 pub struct BenchmarkCtx {
-    num_gates: usize,
+    witness: [Vec<Fp>; COLUMNS],
     group_map: BWParameters<VestaParameters>,
     index: ProverIndex<Vesta>,
     verifier_index: VerifierIndex<Vesta>,
+    runtime_tables: Vec<RuntimeTable<Fp>>,
+    prev_challenges: Vec<RecursionChallenge<Vesta>>,
 }
 
 impl BenchmarkCtx {
@@ -67,29 +156,256 @@ impl BenchmarkCtx {
         // create the verifier index
         let verifier_index = index.verifier_index();
 
+        // create witness
+        let witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![1u32.into(); num_gates]);
+
         //
         BenchmarkCtx {
-            num_gates,
+            witness,
             group_map,
             index,
             verifier_index,
+            runtime_tables: vec![],
+            prev_challenges: vec![],
         }
     }
 
+    /// This will create a context that allows for benchmarks of a lookup-heavy circuit,
+    /// made of `num_xors` 64-bit XOR gadgets and `num_range_checks` multi-range-check gadgets,
+    /// whose lookups are proven with the given `lookup_backend`.
+    pub fn new_with_lookups(
+        num_xors: usize,
+        num_range_checks: usize,
+        lookup_backend: LookupBackend,
+    ) -> Self {
+        // create the circuit and its witness
+        let mut gates = vec![];
+        let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![]);
+        for i in 0..num_xors {
+            CircuitGate::extend_xor_gadget(&mut gates, 64);
+            let input = Fp::from(i as u64);
+            xor::extend_xor_witness(&mut witness, input, Fp::from(u64::MAX) - input, 64);
+        }
+        for i in 0..num_range_checks {
+            let mut curr_row = gates.len();
+            CircuitGate::extend_multi_range_check(&mut gates, &mut curr_row);
+            let limb = Fp::from(i as u64);
+            range_check::witness::extend_multi(&mut witness, limb, limb, limb);
+        }
+
+        // group map
+        let group_map = <Vesta as CommitmentCurve>::Map::setup();
+
+        // create the index
+        let index = new_index_for_test_with_custom_gates(
+            gates,
+            0,
+            0,
+            vec![],
+            None,
+            lookup_backend,
+            vec![],
+            false,
+        );
+
+        // create the verifier index
+        let verifier_index = index.verifier_index();
+
+        //
+        BenchmarkCtx {
+            witness,
+            group_map,
+            index,
+            verifier_index,
+            runtime_tables: vec![],
+            prev_challenges: vec![],
+        }
+    }
+
+    /// This will create a context that allows for benchmarks of a family of gates,
+    /// with as many of its gadgets as fit in a domain of size `2^srs_size_log2`.
+    pub fn new_for_family(family: GateFamily, srs_size_log2: u32) -> Self {
+        assert!(
+            srs_size_log2 >= family.min_srs_size_log2(),
+            "{family} gates need an SRS of size at least 2^{}",
+            family.min_srs_size_log2()
+        );
+
+        // the rows left by the zero-knowledge rows
+        let max_rows = (1 << srs_size_log2) - ZK_ROWS as usize - 1;
+        let num_gadgets = max_rows / family.gadget_rows();
+
+        // create the circuit and its witness
+        let mut gates = vec![];
+        let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![]);
+        let mut runtime_tables = vec![];
+        match family {
+            GateFamily::Generic | GateFamily::Recursion => {
+                for row in 0..num_gadgets {
+                    gates.push(CircuitGate::create_generic_gadget(
+                        Wire::for_row(row),
+                        GenericGateSpec::Const(1u32.into()),
+                        None,
+                    ));
+                }
+                witness = array::from_fn(|_| vec![1u32.into(); num_gadgets]);
+            }
+            GateFamily::Poseidon => {
+                let round_constants = &*Vesta::sponge_params().round_constants;
+                for _ in 0..num_gadgets {
+                    let row = gates.len();
+                    let first_and_last_row =
+                        [Wire::for_row(row), Wire::for_row(row + POS_ROWS_PER_HASH)];
+                    let (poseidon, _) = CircuitGate::create_poseidon_gadget(
+                        row,
+                        first_and_last_row,
+                        round_constants,
+                    );
+                    gates.extend(poseidon);
+                }
+                witness = array::from_fn(|_| vec![0u32.into(); gates.len()]);
+                for (i, row) in (0..gates.len()).step_by(POS_ROWS_PER_HASH + 1).enumerate() {
+                    let input = [Fp::from(i as u64), 1u32.into(), 2u32.into()];
+                    poseidon::generate_witness(row, Vesta::sponge_params(), &mut witness, input);
+                }
+            }
+            GateFamily::RangeCheck => {
+                for i in 0..num_gadgets {
+                    let mut curr_row = gates.len();
+                    CircuitGate::extend_multi_range_check(&mut gates, &mut curr_row);
+                    let limb = Fp::from(i as u64);
+                    range_check::witness::extend_multi(&mut witness, limb, limb, limb);
+                }
+            }
+            GateFamily::ForeignFieldMul => {
+                let secp256k1_modulus = BigUint::parse_bytes(
+                    b"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+                    16,
+                )
+                .unwrap();
+                for i in 0..num_gadgets {
+                    let (_, ffmul) =
+                        CircuitGate::create_foreign_field_mul(gates.len(), &secp256k1_modulus);
+                    gates.extend(ffmul);
+                    let left = &secp256k1_modulus - BigUint::from(i + 1);
+                    let right = BigUint::from(i + 2);
+                    let (ffmul_witness, _) =
+                        foreign_field_mul::witness::create::<Fp>(&left, &right, &secp256k1_modulus);
+                    for (col, ffmul_col) in witness.iter_mut().zip(ffmul_witness) {
+                        col.extend(ffmul_col);
+                    }
+                }
+            }
+            GateFamily::XorRot => {
+                for i in 0..num_gadgets {
+                    CircuitGate::extend_xor_gadget(&mut gates, 64);
+                    let input = Fp::from(i as u64);
+                    xor::extend_xor_witness(&mut witness, input, Fp::from(u64::MAX) - input, 64);
+
+                    // the last row of the XOR gadget is a zero row
+                    let zero_row = gates.len() - 1;
+                    let rot = (i % 63 + 1) as u32;
+                    CircuitGate::extend_rot(&mut gates, rot, RotMode::Left, zero_row);
+                    rot::extend_rot(&mut witness, i as u64, rot, RotMode::Left);
+                }
+            }
+            GateFamily::RuntimeTables => {
+                // an indexed table, queried 3 times per row
+                let len = 1 << (srs_size_log2 - 2);
+                let data: Vec<Fp> = (0..len).map(|i: u64| Fp::from(i * i)).collect();
+                witness = array::from_fn(|_| vec![0u32.into(); num_gadgets]);
+                gates.extend(
+                    (0..num_gadgets)
+                        .map(|row| CircuitGate::new(GateType::Lookup, Wire::for_row(row), vec![])),
+                );
+                for query in 0..3 {
+                    let indexes: Vec<usize> = (0..num_gadgets)
+                        .map(|row| (3 * row + query) % data.len())
+                        .collect();
+                    witness[1 + 2 * query] =
+                        indexes.iter().map(|&idx| Fp::from(idx as u64)).collect();
+                    witness[2 + 2 * query] = indexes.iter().map(|&idx| data[idx]).collect();
+                }
+                runtime_tables.push(RuntimeTable { id: 0, data });
+            }
+        }
+
+        // group map
+        let group_map = <Vesta as CommitmentCurve>::Map::setup();
+
+        // create the index
+        let runtime_tables_setup = (!runtime_tables.is_empty()).then(|| {
+            runtime_tables
+                .iter()
+                .map(|table| {
+                    RuntimeTableCfg::Indexed(RuntimeTableSpec {
+                        id: table.id,
+                        len: table.data.len(),
+                    })
+                })
+                .collect()
+        });
+        let num_prev_challenges = usize::from(family == GateFamily::Recursion);
+        let index = new_index_for_test_with_lookups(
+            gates,
+            0,
+            num_prev_challenges,
+            vec![],
+            runtime_tables_setup,
+            false,
+        );
+
+        assert_eq!(index.cs.domain.d1.log_size_of_group, srs_size_log2, "the benchmark of {family} gates wanted to use an SRS of size {srs_size_log2} but the domain size ended up being {}", index.cs.domain.d1.log_size_of_group);
+
+        // create the previous recursion challenges
+        let rng = &mut StdRng::from_seed([0u8; 32]);
+        let prev_challenges = (0..num_prev_challenges)
+            .map(|_| {
+                let k = math::ceil_log2(index.srs.g.len());
+                let chals: Vec<_> = (0..k).map(|_| Fp::rand(rng)).collect();
+                let comm = {
+                    let coeffs = b_poly_coefficients(&chals);
+                    let b = DensePolynomial::from_coefficients_vec(coeffs);
+                    index.srs.commit_non_hiding(&b, None)
+                };
+                RecursionChallenge::new(chals, comm)
+            })
+            .collect();
+
+        // create the verifier index
+        let verifier_index = index.verifier_index();
+
+        //
+        BenchmarkCtx {
+            witness,
+            group_map,
+            index,
+            verifier_index,
+            runtime_tables,
+            prev_challenges,
+        }
+    }
+
+    /// The number of gates of the circuit, without the padding
+    pub fn num_gates(&self) -> usize {
+        self.witness[0].len()
+    }
+
     /// Produces a proof
     pub fn create_proof(&self) -> (ProverProof<Vesta>, Vec<Fp>) {
-        // create witness
-        let witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![1u32.into(); self.num_gates]);
+        let witness = self.witness.clone();
 
         let public_input = witness[0][0..self.index.cs.public].to_vec();
 
         // add the proof to the batch
         (
-            ProverProof::create::<BaseSponge, ScalarSponge>(
+            ProverProof::create_recursive::<BaseSponge, ScalarSponge>(
                 &self.group_map,
                 witness,
-                &[],
+                &self.runtime_tables,
                 &self.index,
+                self.prev_challenges.clone(),
+                None,
             )
             .unwrap(),
             public_input,
@@ -110,10 +426,153 @@ impl BenchmarkCtx {
     }
 }
 
+/// The timings of the benchmark of a family of gates, in microseconds per proof
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BenchmarkResult {
+    pub family: GateFamily,
+    pub srs_size_log2: u32,
+    /// The number of gates of the circuit, without the padding
+    pub gates: usize,
+    pub iterations: usize,
+    pub prove: u64,
+    pub verify: u64,
+    /// The time spent in each span of the prover and of the verifier,
+    /// only measured with the `internal_tracing` feature
+    pub phases: BTreeMap<String, u64>,
+}
+
+impl BenchmarkResult {
+    /// Creates and verifies `iterations` proofs of a family of gates.
+    pub fn measure(family: GateFamily, srs_size_log2: u32, iterations: usize) -> Self {
+        assert!(iterations > 0, "a benchmark needs at least one iteration");
+        let ctx = BenchmarkCtx::new_for_family(family, srs_size_log2);
+
+        #[cfg(feature = "internal_tracing")]
+        let collection = internal_tracing::spans::start_spans();
+
+        let (mut prove, mut verify) = (0, 0);
+        for _ in 0..iterations {
+            let start = Instant::now();
+            let proof_and_public = ctx.create_proof();
+            prove += start.elapsed().as_micros() as u64;
+
+            let start = Instant::now();
+            ctx.batch_verification(&[proof_and_public]);
+            verify += start.elapsed().as_micros() as u64;
+        }
+        let iterations_u64 = iterations as u64;
+
+        #[cfg(feature = "internal_tracing")]
+        let phases = {
+            let spans = collection.take_spans();
+            drop(collection);
+            spans
+                .aggregate()
+                .into_iter()
+                .map(|(name, stats)| (name.to_string(), stats.total / iterations_u64))
+                .collect()
+        };
+        #[cfg(not(feature = "internal_tracing"))]
+        let phases = BTreeMap::new();
+
+        BenchmarkResult {
+            family,
+            srs_size_log2,
+            gates: ctx.num_gates(),
+            iterations,
+            prove: prove / iterations_u64,
+            verify: verify / iterations_u64,
+            phases,
+        }
+    }
+}
+
+/// Measurements under this many microseconds in both runs are not compared,
+/// as they are dominated by noise
+pub const NOISE_FLOOR_MICROS: u64 = 1000;
+
+/// A measurement of a benchmark, in two runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub family: GateFamily,
+    pub srs_size_log2: u32,
+    /// `prove`, `verify` or the name of a phase
+    pub measurement: String,
+    pub old: u64,
+    pub new: u64,
+}
+
+impl Change {
+    /// The relative change of the measurement, in percent
+    pub fn percent(&self) -> f64 {
+        (self.new as f64 - self.old as f64) * 100.0 / self.old.max(1) as f64
+    }
+
+    /// Whether the measurement got slower by more than `threshold` percent
+    pub fn is_regression(&self, threshold: f64) -> bool {
+        self.percent() > threshold
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = |micros: u64| micros as f64 / 1000.0;
+        write!(
+            f,
+            "{} (SRS size 2^{}) {}: {:.3} ms -> {:.3} ms ({:+.1}%)",
+            self.family,
+            self.srs_size_log2,
+            self.measurement,
+            ms(self.old),
+            ms(self.new),
+            self.percent()
+        )
+    }
+}
+
+/// Compares the measurements of the benchmarks found in both runs,
+/// skipping the ones under [`NOISE_FLOOR_MICROS`].
+pub fn compare(old: &[BenchmarkResult], new: &[BenchmarkResult]) -> Vec<Change> {
+    let mut changes = vec![];
+    for new in new {
+        let Some(old) = old
+            .iter()
+            .find(|old| old.family == new.family && old.srs_size_log2 == new.srs_size_log2)
+        else {
+            continue;
+        };
+        let measurements = [
+            ("prove", old.prove, new.prove),
+            ("verify", old.verify, new.verify),
+        ]
+        .into_iter()
+        .chain(new.phases.iter().filter_map(|(name, new_time)| {
+            old.phases
+                .get(name)
+                .map(|old_time| (name.as_str(), *old_time, *new_time))
+        }));
+        for (measurement, old_time, new_time) in measurements {
+            if old_time.max(new_time) < NOISE_FLOOR_MICROS {
+                continue;
+            }
+            changes.push(Change {
+                family: new.family,
+                srs_size_log2: new.srs_size_log2,
+                measurement: measurement.to_string(),
+                old: old_time,
+                new: new_time,
+            });
+        }
+    }
+    changes
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Instant;
 
+    use strum::IntoEnumIterator;
+
     use super::*;
 
     #[test]
@@ -135,4 +594,55 @@ mod tests {
         ctx.batch_verification(&vec![(proof, public_input)]);
         println!("proof verified in {}", start.elapsed().as_secs());
     }
+
+    #[test]
+    fn test_bench_lookups() {
+        for lookup_backend in [LookupBackend::Plookup, LookupBackend::LogUp] {
+            let ctx = BenchmarkCtx::new_with_lookups(2, 1, lookup_backend);
+            let (proof, public_input) = ctx.create_proof();
+            ctx.batch_verification(&vec![(proof, public_input)]);
+        }
+    }
+
+    #[test]
+    fn test_bench_families() {
+        for family in GateFamily::iter() {
+            let result = BenchmarkResult::measure(family, family.min_srs_size_log2(), 1);
+            assert!(result.gates > 0);
+        }
+    }
+
+    #[test]
+    fn test_compare() {
+        let result = |prove, phase| BenchmarkResult {
+            family: GateFamily::Poseidon,
+            srs_size_log2: 14,
+            gates: 16368,
+            iterations: 1,
+            prove,
+            verify: 500,
+            phases: [("compute_quotient_poly".to_string(), phase)]
+                .into_iter()
+                .collect(),
+        };
+        let old = [result(100_000, 40_000)];
+        let new = [result(150_000, 38_000)];
+
+        // the verification is under the noise floor
+        let changes = compare(&old, &new);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].measurement, "prove");
+        assert!(changes[0].is_regression(10.0));
+        assert_eq!(changes[1].measurement, "compute_quotient_poly");
+        assert!(!changes[1].is_regression(10.0));
+        assert_eq!(
+            changes[0].to_string(),
+            "poseidon (SRS size 2^14) prove: 100.000 ms -> 150.000 ms (+50.0%)"
+        );
+
+        // benchmarks missing from either run are not compared
+        let mut other = result(100_000, 40_000);
+        other.srs_size_log2 = 16;
+        assert!(compare(&old, &[other]).is_empty());
+    }
 }
@@ -1,6 +1,6 @@
 use std::env;
 
-use kimchi::bench::BenchmarkCtx;
+use kimchi::bench::{BenchmarkCtx, GateFamily};
 
 /// function to avoid optimizations by the compiler
 /// taken from <https://docs.rs/criterion/latest/src/criterion/lib.rs.html#171>
@@ -12,18 +12,29 @@ pub fn black_box<T>(dummy: T) -> T {
     }
 }
 
+/// usage: flamegraph <prove|verify> [gate family] [log2 of the SRS size]
 fn main() {
     let mode = env::args().nth(1);
+    let family = env::args()
+        .nth(2)
+        .map(|family| family.parse().expect("unknown gate family"))
+        .unwrap_or(GateFamily::Generic);
+    let srs_size_log2 = |default: u32| {
+        env::args()
+            .nth(3)
+            .map(|size| size.parse().expect("invalid SRS size"))
+            .unwrap_or_else(|| default.max(family.min_srs_size_log2()))
+    };
     match mode.as_deref() {
         Some("prove") => {
-            let ctx = BenchmarkCtx::new(14);
+            let ctx = BenchmarkCtx::new_for_family(family, srs_size_log2(14));
             loop {
                 let proof_and_public = ctx.create_proof();
                 black_box(proof_and_public);
             }
         }
         Some("verify") => {
-            let ctx = BenchmarkCtx::new(4);
+            let ctx = BenchmarkCtx::new_for_family(family, srs_size_log2(4));
             let proof_and_public = ctx.create_proof();
             loop {
                 ctx.batch_verification(black_box(&vec![proof_and_public.clone()]));
@@ -24,6 +24,10 @@ pub enum ArgumentType {
     /// As gates are mutually exclusive (a single gate is set per row),
     /// we can reuse the same powers of alpha across gates.
     Gate(GateType),
+    /// User-defined gates, identified by their selector id
+    /// (see [crate::circuits::custom_gate::CustomGate]).
+    /// Like built-in gates, they reuse the powers of alpha of the gates.
+    CustomGate(u32),
     /// The permutation argument
     Permutation,
     /// The lookup argument
@@ -157,12 +161,12 @@ pub trait Argument<F: PrimeField> {
         let alphas = alphas.get_exponents(Self::ARGUMENT_TYPE, Self::CONSTRAINTS);
         let combined_constraints = E::combine_constraints(alphas, constraints);
 
-        // An optional gate type, if used to define a gate.
+        // An optional gate type or selector id, if used to define a gate.
         // This is used to filter the gate, to avoid applying it on the entire domain.
-        if let ArgumentType::Gate(gate_type) = Self::ARGUMENT_TYPE {
-            index(gate_type) * combined_constraints
-        } else {
-            combined_constraints
+        match Self::ARGUMENT_TYPE {
+            ArgumentType::Gate(gate_type) => index(gate_type) * combined_constraints,
+            ArgumentType::CustomGate(id) => custom_index(id) * combined_constraints,
+            ArgumentType::Permutation | ArgumentType::Lookup => combined_constraints,
         }
     }
 }
@@ -171,6 +175,8 @@ pub trait DynArgument<F: PrimeField> {
     fn constraints(&self, cache: &mut Cache) -> Vec<E<F>>;
     fn combined_constraints(&self, alphas: &Alphas<F>, cache: &mut Cache) -> E<F>;
     fn argument_type(&self) -> ArgumentType;
+    fn num_constraints(&self) -> u32;
+    fn constraint_evaluations(&self, env: &ArgumentEnv<F, F>, cache: &mut Cache) -> Vec<F>;
 }
 
 impl<F: PrimeField, T: Argument<F>> DynArgument<F> for T {
@@ -183,4 +189,10 @@ impl<F: PrimeField, T: Argument<F>> DynArgument<F> for T {
     fn argument_type(&self) -> ArgumentType {
         <Self as Argument<F>>::ARGUMENT_TYPE
     }
+    fn num_constraints(&self) -> u32 {
+        <Self as Argument<F>>::CONSTRAINTS
+    }
+    fn constraint_evaluations(&self, env: &ArgumentEnv<F, F>, cache: &mut Cache) -> Vec<F> {
+        <Self as Argument<F>>::constraint_checks(env, cache)
+    }
 }
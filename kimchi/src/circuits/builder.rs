@@ -0,0 +1,378 @@
+//! This module implements a builder of circuits.
+//!
+//! The gadgets of kimchi are created at a given row (see for example
+//! [`CircuitGate::create_foreign_field_mul`]), and wired together with
+//! [`Connect::connect_cell_pair`] on absolute `(row, col)` pairs.
+//! The [`CircuitBuilder`] takes care of this bookkeeping:
+//!
+//! - it places gadgets one after the other, and returns a [`Gadget`] handle to their rows,
+//! - it hands out typed [`Cell`] handles, for public inputs and for the cells of gadgets,
+//! - it records copy constraints between cells, which are only resolved when the circuit is built,
+//! - it packs the public inputs in the first rows of the circuit, whenever they are declared,
+//! - it collects the witness generators of the gadgets, which are run in order
+//!   to compute the witness of the circuit.
+//!
+//! ```ignore
+//! let mut builder = CircuitBuilder::<Fp, (BigUint, BigUint)>::new();
+//! let ffmul = builder.gadget(|row| CircuitGate::create_foreign_field_mul(row, &modulus));
+//! let rc = builder.gadget(CircuitGate::create_multi_range_check);
+//! builder.copy(ffmul.cell(0, 0), rc.cell(0, 0));
+//! builder.witness(move |(left, right), witness| { ... });
+//! let circuit = builder.build();
+//! ```
+
+use crate::circuits::{
+    gate::{CircuitGate, Connect, GateType},
+    polynomials::generic::GenericGateSpec,
+    wires::{Wire, COLUMNS, PERMUTS},
+};
+use ark_ff::PrimeField;
+use std::array;
+
+/// The row of a [`Cell`], which is only known once all the public inputs are declared
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum CellRow {
+    /// The row of the i-th public input
+    Public(usize),
+    /// A row of the gadgets, which come after the public inputs
+    Gadget(usize),
+}
+
+/// A cell of a circuit being built by a [`CircuitBuilder`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Cell {
+    row: CellRow,
+    col: usize,
+}
+
+impl Cell {
+    /// The column of the cell
+    pub fn col(&self) -> usize {
+        self.col
+    }
+
+    // The row of the cell in a circuit with `public` public inputs
+    fn row(&self, public: usize) -> usize {
+        match self.row {
+            CellRow::Public(i) => i,
+            CellRow::Gadget(row) => public + row,
+        }
+    }
+
+    fn wire(&self, public: usize) -> Wire {
+        Wire::new(self.row(public), self.col)
+    }
+}
+
+/// The rows of a gadget placed by a [`CircuitBuilder`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Gadget {
+    start: usize,
+    len: usize,
+}
+
+impl Gadget {
+    /// The number of rows of the gadget
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the gadget has no rows
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The cell in column `col` of the `row`-th row of the gadget
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the gadget has no such row, or if `col` is not a column.
+    pub fn cell(&self, row: usize, col: usize) -> Cell {
+        assert!(
+            row < self.len,
+            "row {row} is out of a gadget of {} rows",
+            self.len
+        );
+        assert!(col < COLUMNS, "column {col} does not exist");
+        Cell {
+            row: CellRow::Gadget(self.start + row),
+            col,
+        }
+    }
+}
+
+/// The witness of a circuit being built by a [`CircuitBuilder`],
+/// as given to the witness generators of the circuit.
+pub struct Witness<F> {
+    public: usize,
+    witness: [Vec<F>; COLUMNS],
+}
+
+impl<F: PrimeField> Witness<F> {
+    /// The value of a cell
+    pub fn get(&self, cell: Cell) -> F {
+        self.witness[cell.col][cell.row(self.public)]
+    }
+
+    /// Sets the value of a cell
+    pub fn set(&mut self, cell: Cell, value: F) {
+        self.witness[cell.col][cell.row(self.public)] = value;
+    }
+
+    /// Sets the values of the rows of a gadget, and of the gadgets placed after it,
+    /// with a witness generator that extends an empty witness (like
+    /// [`extend_multi`](crate::circuits::polynomials::range_check::witness::extend_multi)).
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `extend` adds more rows than the circuit has after the start of `gadget`.
+    pub fn extend(&mut self, gadget: Gadget, extend: impl FnOnce(&mut [Vec<F>; COLUMNS])) {
+        let mut rows: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![]);
+        extend(&mut rows);
+        let start = self.public + gadget.start;
+        for (column, values) in self.witness.iter_mut().zip(rows) {
+            assert!(
+                start + values.len() <= column.len(),
+                "the witness generator of the gadget at row {start} adds too many rows"
+            );
+            column[start..start + values.len()].copy_from_slice(&values);
+        }
+    }
+}
+
+/// A witness generator, which computes the values of some cells from the inputs of the circuit
+type WitnessGenerator<F, I> = Box<dyn Fn(&I, &mut Witness<F>)>;
+
+/// The witness generator of a whole circuit, which computes its witness from its inputs
+type CircuitWitness<F, I> = Box<dyn Fn(&I) -> [Vec<F>; COLUMNS]>;
+
+/// A circuit built by a [`CircuitBuilder`]
+pub struct BuiltCircuit<F: PrimeField, I> {
+    /// The gates of the circuit, starting with the public inputs
+    pub gates: Vec<CircuitGate<F>>,
+    /// The number of public inputs
+    pub public: usize,
+    witness: CircuitWitness<F, I>,
+}
+
+impl<F: PrimeField, I> BuiltCircuit<F, I> {
+    /// Computes the witness of the circuit for the given inputs.
+    /// The public inputs are the first `public` values of its first column.
+    pub fn witness(&self, inputs: &I) -> [Vec<F>; COLUMNS] {
+        (self.witness)(inputs)
+    }
+
+    /// The row of the circuit at which `gadget` starts, for example to place its
+    /// witness layout in a [`WitnessGenerator`](crate::circuits::witness::WitnessGenerator)
+    pub fn start_row(&self, gadget: Gadget) -> usize {
+        self.public + gadget.start
+    }
+
+    /// Returns the gates of the circuit, and its witness generator
+    pub fn into_parts(self) -> (Vec<CircuitGate<F>>, CircuitWitness<F, I>) {
+        (self.gates, self.witness)
+    }
+}
+
+/// A builder of circuits, whose witness is computed from inputs of type `I`
+pub struct CircuitBuilder<F: PrimeField, I = ()> {
+    public: usize,
+    gates: Vec<CircuitGate<F>>,
+    copies: Vec<(Cell, Cell)>,
+    generators: Vec<WitnessGenerator<F, I>>,
+}
+
+impl<F: PrimeField, I> Default for CircuitBuilder<F, I> {
+    fn default() -> Self {
+        Self {
+            public: 0,
+            gates: vec![],
+            copies: vec![],
+            generators: vec![],
+        }
+    }
+}
+
+impl<F: PrimeField, I: 'static> CircuitBuilder<F, I> {
+    /// Creates an empty circuit builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares a new public input, and returns its cell.
+    /// Public inputs are placed in the first rows of the circuit, before all the gadgets.
+    pub fn public_input(&mut self) -> Cell {
+        self.public += 1;
+        Cell {
+            row: CellRow::Public(self.public - 1),
+            col: 0,
+        }
+    }
+
+    /// Places a gadget after the gadgets already placed, and returns its rows.
+    /// `create` takes the row at which the gadget starts, and returns the next row
+    /// and the gates of the gadget, like [`CircuitGate::create_multi_range_check`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the next row returned by `create` does not follow the gates of the gadget.
+    pub fn gadget(&mut self, create: impl FnOnce(usize) -> (usize, Vec<CircuitGate<F>>)) -> Gadget {
+        let start = self.gates.len();
+        let (next_row, gates) = create(start);
+        assert_eq!(
+            next_row,
+            start + gates.len(),
+            "the gadget at row {start} does not end at row {next_row}"
+        );
+        self.gates.extend(gates);
+        Gadget {
+            start,
+            len: next_row - start,
+        }
+    }
+
+    /// Places a single gate of type `typ` with the given coefficients, and returns its row
+    pub fn gate(&mut self, typ: GateType, coeffs: Vec<F>) -> Gadget {
+        self.gadget(|row| {
+            (
+                row + 1,
+                vec![CircuitGate::new(typ, Wire::for_row(row), coeffs)],
+            )
+        })
+    }
+
+    /// Places a generic gate, and returns its row
+    pub fn generic(
+        &mut self,
+        gate1: GenericGateSpec<F>,
+        gate2: Option<GenericGateSpec<F>>,
+    ) -> Gadget {
+        self.gadget(|row| {
+            (
+                row + 1,
+                vec![CircuitGate::create_generic_gadget(
+                    Wire::for_row(row),
+                    gate1,
+                    gate2,
+                )],
+            )
+        })
+    }
+
+    /// Constrains two cells to hold the same value
+    ///
+    /// # Panics
+    ///
+    /// Will panic if one of the cells is not in a column of the permutation (see [`PERMUTS`]).
+    pub fn copy(&mut self, cell1: Cell, cell2: Cell) {
+        for cell in [cell1, cell2] {
+            assert!(
+                cell.col < PERMUTS,
+                "column {} cannot be copied, only the first {PERMUTS} columns can",
+                cell.col
+            );
+        }
+        self.copies.push((cell1, cell2));
+    }
+
+    /// Adds a witness generator. The witness generators are run in the order they are added,
+    /// on a witness filled with zeros, and can read the values set by the previous ones.
+    pub fn witness(&mut self, generator: impl Fn(&I, &mut Witness<F>) + 'static) {
+        self.generators.push(Box::new(generator));
+    }
+
+    /// The number of public inputs declared so far
+    pub fn num_public(&self) -> usize {
+        self.public
+    }
+
+    /// The number of rows of the circuit built so far, including the public inputs
+    pub fn num_rows(&self) -> usize {
+        self.public + self.gates.len()
+    }
+
+    /// Builds the circuit: the public inputs are followed by the gadgets,
+    /// and the copy constraints are added to the wiring of the gates.
+    pub fn build(self) -> BuiltCircuit<F, I> {
+        let public = self.public;
+
+        let mut gates: Vec<_> = (0..public)
+            .map(|row| {
+                CircuitGate::create_generic_gadget(Wire::for_row(row), GenericGateSpec::Pub, None)
+            })
+            .collect();
+        gates.extend(self.gates.into_iter().map(|mut gate| {
+            for wire in gate.wires.iter_mut() {
+                wire.row += public;
+            }
+            gate
+        }));
+
+        // The wiring of the gates is a permutation, whose cycles are the sets of cells
+        // holding the same value. Swapping the wires of two cells merges their cycles,
+        // but splits them if they are already the same: we track the cycles to avoid this.
+        let mut cycles = Cycles::new(gates.len());
+        for (row, gate) in gates.iter().enumerate() {
+            for (col, wire) in gate.wires.iter().enumerate() {
+                cycles.union(Wire::new(row, col), *wire);
+            }
+        }
+        for (cell1, cell2) in &self.copies {
+            let (wire1, wire2) = (cell1.wire(public), cell2.wire(public));
+            if cycles.union(wire1, wire2) {
+                gates.connect_cell_pair((wire1.row, wire1.col), (wire2.row, wire2.col));
+            }
+        }
+
+        let rows = gates.len();
+        let generators = self.generators;
+        let witness = move |inputs: &I| {
+            let mut witness = Witness {
+                public,
+                witness: array::from_fn(|_| vec![F::zero(); rows]),
+            };
+            for generator in &generators {
+                generator(inputs, &mut witness);
+            }
+            witness.witness
+        };
+
+        BuiltCircuit {
+            gates,
+            public,
+            witness: Box::new(witness),
+        }
+    }
+}
+
+/// The cells wired together, as a union-find over the cells of the permutation
+struct Cycles {
+    parents: Vec<usize>,
+}
+
+impl Cycles {
+    fn new(rows: usize) -> Self {
+        Self {
+            parents: (0..rows * PERMUTS).collect(),
+        }
+    }
+
+    fn find(&mut self, mut cell: usize) -> usize {
+        while self.parents[cell] != cell {
+            self.parents[cell] = self.parents[self.parents[cell]];
+            cell = self.parents[cell];
+        }
+        cell
+    }
+
+    // Puts two cells in the same cycle, and returns whether they were in different cycles
+    fn union(&mut self, wire1: Wire, wire2: Wire) -> bool {
+        let root1 = self.find(wire1.row * PERMUTS + wire1.col);
+        let root2 = self.find(wire2.row * PERMUTS + wire2.col);
+        if root1 == root2 {
+            return false;
+        }
+        self.parents[root1] = root2;
+        true
+    }
+}
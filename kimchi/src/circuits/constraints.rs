@@ -2,10 +2,16 @@
 use super::lookup::runtime_tables::RuntimeTableCfg;
 use crate::{
     circuits::{
+        custom_gate::CustomGate,
         domain_constant_evaluation::DomainConstantEvaluations,
         domains::EvaluationDomains,
         gate::{CircuitGate, GateType},
-        lookup::{index::LookupConstraintSystem, lookups::LookupFeatures, tables::LookupTable},
+        lookup::{
+            index::LookupConstraintSystem,
+            lookups::{LookupBackend, LookupFeatures},
+            registry::{check_table_ids, remove_gate_tables, LookupTableRegistry},
+            tables::LookupTable,
+        },
         polynomial::{WitnessEvals, WitnessOverDomains, WitnessShifts},
         polynomials::permutation::{Shifts, ZK_ROWS},
         wires::*,
@@ -31,7 +37,7 @@ use std::sync::Arc;
 //
 
 /// Flags for optional features in the constraint system
-#[derive(Copy, Clone, Serialize, Deserialize, Debug)]
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct FeatureFlags {
     /// RangeCheck0 gate
     pub range_check0: bool,
@@ -130,6 +136,10 @@ pub struct ColumnEvaluations<F: PrimeField> {
     /// Rot gate selector over domain d8
     #[serde_as(as = "Option<o1_utils::serialization::SerdeAs>")]
     pub rot_selector8: Option<E<F, D<F>>>,
+
+    /// Custom gate selectors over domain d8, in the order the custom gates were registered
+    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    pub custom_selectors8: Vec<E<F, D<F>>>,
 }
 
 #[serde_as]
@@ -164,6 +174,13 @@ pub struct ConstraintSystem<F: PrimeField> {
     /// lookup constraint system
     #[serde(bound = "LookupConstraintSystem<F>: Serialize + DeserializeOwned")]
     pub lookup_constraint_system: Option<LookupConstraintSystem<F>>,
+    /// user-defined custom gates
+    /// (not serialized, they have to be registered again after deserialization,
+    /// see [ConstraintSystem::register_custom_gates])
+    #[serde(skip)]
+    pub custom_gates: Vec<CustomGate<F>>,
+    /// the selector ids and rows of the custom gates, which are serialized in their place
+    custom_gate_rows: Vec<(u32, Vec<usize>)>,
     /// precomputes
     #[serde(skip)]
     precomputations: OnceCell<Arc<DomainConstantEvaluations<F>>>,
@@ -189,6 +206,9 @@ pub struct Builder<F: PrimeField> {
     prev_challenges: usize,
     lookup_tables: Vec<LookupTable<F>>,
     runtime_tables: Option<Vec<RuntimeTableCfg<F>>>,
+    lookup_backend: LookupBackend,
+    table_registry: LookupTableRegistry<F>,
+    custom_gates: Vec<CustomGate<F>>,
     precomputations: Option<Arc<DomainConstantEvaluations<F>>>,
     disable_gates_checks: bool,
 }
@@ -232,12 +252,15 @@ impl<F: PrimeField> ConstraintSystem<F> {
     /// - `prev_challenges: 0`
     /// - `lookup_tables: vec![]`,
     /// - `runtime_tables: None`,
+    /// - `lookup_backend: LookupBackend::Plookup`,
+    /// - `table_registry: LookupTableRegistry::default()`,
+    /// - `custom_gates: vec![]`,
     /// - `precomputations: None`,
     /// - `disable_gates_checks: false`,
     ///
     /// How to use it:
     /// 1. Create your instance of your builder for the constraint system using `crate(gates, sponge params)`
-    /// 2. Iterativelly invoke any desired number of steps: `public(), lookup(), runtime(), precomputations()``
+    /// 2. Iterativelly invoke any desired number of steps: `public(), lookup(), runtime(), table_registry(), custom_gate(), precomputations()``
     /// 3. Finally call the `build()` method and unwrap the `Result` to obtain your `ConstraintSystem`
     pub fn create(gates: Vec<CircuitGate<F>>) -> Builder<F> {
         Builder {
@@ -246,6 +269,9 @@ impl<F: PrimeField> ConstraintSystem<F> {
             prev_challenges: 0,
             lookup_tables: vec![],
             runtime_tables: None,
+            lookup_backend: LookupBackend::default(),
+            table_registry: LookupTableRegistry::default(),
+            custom_gates: vec![],
             precomputations: None,
             disable_gates_checks: false,
         }
@@ -261,6 +287,37 @@ impl<F: PrimeField> ConstraintSystem<F> {
             .set(precomputations)
             .expect("Precomputation has been set before");
     }
+
+    /// Registers again the custom gates of a deserialized constraint system,
+    /// as only their selector ids and rows are serialized.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the selector ids and rows of `custom_gates` are not the ones
+    /// of the custom gates the constraint system was built with, in the same order.
+    pub fn register_custom_gates(
+        &mut self,
+        custom_gates: Vec<CustomGate<F>>,
+    ) -> Result<(), SetupError> {
+        let custom_gate_rows: Vec<_> = custom_gates
+            .iter()
+            .map(|custom_gate| (custom_gate.id(), custom_gate.rows().to_vec()))
+            .collect();
+        if custom_gate_rows != self.custom_gate_rows {
+            return Err(SetupError::CustomGate(format!(
+                "the constraint system has the custom gates {:?} (selector ids and rows), not {custom_gate_rows:?}",
+                self.custom_gate_rows
+            )));
+        }
+        if let Some(lcs) = &mut self.lookup_constraint_system {
+            lcs.configuration.custom_patterns = custom_gates
+                .iter()
+                .filter_map(CustomGate::lookup_pattern)
+                .collect();
+        }
+        self.custom_gates = custom_gates;
+        Ok(())
+    }
 }
 
 impl<F: PrimeField + SquareRootField, G: KimchiCurve<ScalarField = F>> ProverIndex<G> {
@@ -311,6 +368,15 @@ impl<F: PrimeField + SquareRootField, G: KimchiCurve<ScalarField = F>> ProverInd
                 .map_err(|err| GateError::Custom { row, err })?;
         }
 
+        // check the custom gates' satisfiability
+        for custom_gate in &self.cs.custom_gates {
+            for &row in custom_gate.rows() {
+                custom_gate
+                    .verify::<G>(row, &witness, &self.cs.gates[row], self.cs.endo)
+                    .map_err(|err| GateError::Custom { row, err })?;
+            }
+        }
+
         // all good!
         Ok(())
     }
@@ -565,6 +631,18 @@ impl<F: PrimeField + SquareRootField> ConstraintSystem<F> {
             }
         };
 
+        let custom_selectors8 = self
+            .custom_gates
+            .iter()
+            .map(|custom_gate| {
+                if cfg!(debug_assertions) && self.disable_gates_checks {
+                    DP::<F>::zero().evaluate_over_domain_by_ref(self.domain.d8)
+                } else {
+                    custom_gate.selector_polynomial(&self.domain, &self.domain.d8)
+                }
+            })
+            .collect();
+
         // TODO: This doesn't need to be degree 8 but that would require some changes in expr
         let coefficients8 = array::from_fn(|i| {
             evaluated_column_coefficients.coefficients[i]
@@ -586,6 +664,7 @@ impl<F: PrimeField + SquareRootField> ConstraintSystem<F> {
             foreign_field_mul_selector8,
             xor_selector8,
             rot_selector8,
+            custom_selectors8,
         }
     }
 }
@@ -608,9 +687,11 @@ impl<F: PrimeField + SquareRootField> Builder<F> {
     /// Set up the lookup tables.
     /// If not invoked, it is `vec![]` by default.
     ///
-    /// **Warning:** you have to make sure that the IDs of the lookup tables,
-    /// are unique and  not colliding with IDs of built-in lookup tables
-    /// (see [crate::circuits::lookup::tables]).
+    /// The IDs of the lookup tables must be unique and not collide with IDs of
+    /// built-in lookup tables (see [crate::circuits::lookup::tables]),
+    /// or [Builder::build] will fail.
+    /// A copy of the table of a built-in gate of the circuit is accepted, and only added once.
+    /// Prefer [Builder::table_registry] to have the IDs allocated for you.
     pub fn lookup(mut self, lookup_tables: Vec<LookupTable<F>>) -> Self {
         self.lookup_tables = lookup_tables;
         self
@@ -619,14 +700,44 @@ impl<F: PrimeField + SquareRootField> Builder<F> {
     /// Set up the runtime tables.
     /// If not invoked, it is `None` by default.
     ///
-    /// **Warning:** you have to make sure that the IDs of the runtime lookup tables,
-    /// are unique and not colliding with IDs of built-in lookup tables
-    /// (see [crate::circuits::lookup::tables]).
+    /// The IDs of the runtime lookup tables must be unique and not collide with IDs of
+    /// other lookup tables, or [Builder::build] will fail.
+    /// Prefer [Builder::table_registry] to have the IDs allocated for you.
     pub fn runtime(mut self, runtime_tables: Option<Vec<RuntimeTableCfg<F>>>) -> Self {
         self.runtime_tables = runtime_tables;
         self
     }
 
+    /// Set up the argument used to prove the lookups.
+    /// If not invoked, it is [LookupBackend::Plookup] by default.
+    pub fn lookup_backend(mut self, lookup_backend: LookupBackend) -> Self {
+        self.lookup_backend = lookup_backend;
+        self
+    }
+
+    /// Set up the registry of named lookup tables (see [LookupTableRegistry]).
+    /// Its tables are added to the ones given to [Builder::lookup] and [Builder::runtime].
+    /// If not invoked, it is [LookupTableRegistry::default] by default.
+    pub fn table_registry(mut self, table_registry: LookupTableRegistry<F>) -> Self {
+        self.table_registry = table_registry;
+        self
+    }
+
+    /// Returns the table ID of the lookup table registered as `name`, if any.
+    pub fn table_id(&self, name: &str) -> Option<i32> {
+        self.table_registry.table_id(name)
+    }
+
+    /// Registers a user-defined custom gate (see [CustomGate]).
+    /// Each custom gate must have its own selector id,
+    /// and can only be enabled on rows that hold [GateType::Zero] gates
+    /// and on which no other custom gate is enabled.
+    /// The lookups declared by the custom gate, if any, are added to the lookup argument.
+    pub fn custom_gate(mut self, custom_gate: CustomGate<F>) -> Self {
+        self.custom_gates.push(custom_gate);
+        self
+    }
+
     /// Set up the shared precomputations.
     /// If not invoked, it is `None` by default.
     pub fn shared_precomputations(
@@ -646,14 +757,61 @@ impl<F: PrimeField + SquareRootField> Builder<F> {
     /// Build the [ConstraintSystem] from a [Builder].
     pub fn build(self) -> Result<ConstraintSystem<F>, SetupError> {
         let mut gates = self.gates;
-        let lookup_tables = self.lookup_tables;
-        let runtime_tables = self.runtime_tables;
+        let mut lookup_tables = self.lookup_tables;
+        let mut runtime_tables = self.runtime_tables;
+
+        // add the tables of the registry
+        let (registry_tables, registry_runtime_tables) = self.table_registry.into_tables();
+        lookup_tables.extend(registry_tables);
+        if !registry_runtime_tables.is_empty() {
+            runtime_tables
+                .get_or_insert_with(Vec::new)
+                .extend(registry_runtime_tables);
+        }
 
         //~ 1. If the circuit is less than 2 gates, abort.
         // for some reason we need more than 1 gate for the circuit to work, see TODO below
         assert!(gates.len() > 1);
 
-        let lookup_features = LookupFeatures::from_gates(&gates, runtime_tables.is_some());
+        for (i, custom_gate) in self.custom_gates.iter().enumerate() {
+            custom_gate.check(&gates)?;
+            for other in &self.custom_gates[..i] {
+                if other.id() == custom_gate.id() {
+                    return Err(SetupError::CustomGate(format!(
+                        "the selector id {} is used by more than one custom gate",
+                        custom_gate.id()
+                    )));
+                }
+                if let Some(row) = custom_gate
+                    .rows()
+                    .iter()
+                    .find(|row| other.rows().contains(row))
+                {
+                    return Err(SetupError::CustomGate(format!(
+                        "custom gates {} and {} are both enabled on row {row}",
+                        other.id(),
+                        custom_gate.id()
+                    )));
+                }
+            }
+        }
+
+        let mut lookup_features = LookupFeatures::from_gates(&gates, runtime_tables.is_some());
+        lookup_features.backend = self.lookup_backend;
+
+        // the gate tables, the lookup tables and the runtime tables must have distinct IDs,
+        // and the lookup tables that are copies of gate tables are dropped
+        let gate_tables: Vec<_> = lookup_features
+            .patterns
+            .into_iter()
+            .filter_map(|pattern| pattern.table())
+            .collect();
+        remove_gate_tables(&gate_tables, &mut lookup_tables);
+        check_table_ids(
+            gate_tables,
+            &lookup_tables,
+            runtime_tables.as_deref().unwrap_or_default(),
+        )?;
 
         let num_lookups = {
             let mut num_lookups: usize = lookup_tables
@@ -729,9 +887,18 @@ impl<F: PrimeField + SquareRootField> Builder<F> {
         //
         // Lookup
         // ------
-        let lookup_constraint_system =
-            LookupConstraintSystem::create(&gates, lookup_tables, runtime_tables, &domain)
-                .map_err(|e| SetupError::ConstraintSystem(e.to_string()))?;
+        let lookup_constraint_system = LookupConstraintSystem::create(
+            &gates,
+            lookup_tables,
+            runtime_tables,
+            self.lookup_backend,
+            self.custom_gates
+                .iter()
+                .filter_map(CustomGate::lookup_pattern)
+                .collect(),
+            &domain,
+        )
+        .map_err(|e| SetupError::ConstraintSystem(e.to_string()))?;
 
         let sid = shifts.map[0].clone();
 
@@ -751,6 +918,12 @@ impl<F: PrimeField + SquareRootField> Builder<F> {
             //fr_sponge_params: self.sponge_params,
             lookup_constraint_system,
             feature_flags,
+            custom_gate_rows: self
+                .custom_gates
+                .iter()
+                .map(|custom_gate| (custom_gate.id(), custom_gate.rows().to_vec()))
+                .collect(),
+            custom_gates: self.custom_gates,
             precomputations: domain_constant_evaluation,
             disable_gates_checks: self.disable_gates_checks,
         };
@@ -0,0 +1,259 @@
+//! This module implements user-defined custom gates.
+//!
+//! A custom gate is any [Argument] whose [Argument::ARGUMENT_TYPE] is
+//! [ArgumentType::CustomGate], together with the rows on which it applies.
+//! Contrary to built-in gates, custom gates do not need a [GateType]:
+//! they are enabled by their own selector polynomial, which is identified by the selector id
+//! of their argument type.
+//!
+//! Custom gates are registered on the [Builder](crate::circuits::constraints::Builder)
+//! of the constraint system. Their selector polynomials, commitments and linearization terms
+//! are then handled like the ones of the built-in gates.
+//!
+//! The rows of a custom gate must hold [GateType::Zero] gates, and no other custom gate,
+//! so that no two gates (which share the same powers of alpha) are enabled on the same row.
+//! Like built-in gates, the degree of the constraints of a custom gate,
+//! once multiplied by the selector, must not exceed the degree of the quotient polynomial.
+//!
+//! A custom gate can also declare the lookups it performs on each of its rows
+//! (see [CustomGate::lookup]). They are added to the lookup argument as a
+//! [CustomLookupPattern], enabled by the selector of the custom gate.
+
+use crate::{
+    circuits::{
+        argument::{Argument, ArgumentEnv, ArgumentType, ArgumentWitness, DynArgument},
+        domains::EvaluationDomains,
+        expr::{self, Cache},
+        gate::{CircuitGate, CurrOrNext, GateType},
+        lookup::lookups::{
+            CustomLookupPattern, JointLookupSpec, LookupPattern, LookupTableID, MAX_LOOKUPS_PER_ROW,
+        },
+        wires::COLUMNS,
+    },
+    curve::KimchiCurve,
+    error::SetupError,
+};
+use ark_ff::PrimeField;
+use ark_poly::{
+    univariate::DensePolynomial as DP, EvaluationDomain, Evaluations as E,
+    Radix2EvaluationDomain as D,
+};
+use std::{array, fmt, sync::Arc};
+
+/// A user-defined gate, enabled on a set of rows of the circuit.
+#[derive(Clone)]
+pub struct CustomGate<F: PrimeField> {
+    id: u32,
+    rows: Vec<usize>,
+    argument: Arc<dyn DynArgument<F> + Send + Sync>,
+    lookups: Vec<JointLookupSpec<F>>,
+}
+
+impl<F: PrimeField> fmt::Debug for CustomGate<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomGate")
+            .field("id", &self.id)
+            .field("rows", &self.rows)
+            .field("constraints", &self.argument.num_constraints())
+            .field("lookups", &self.lookups.len())
+            .finish()
+    }
+}
+
+impl<F: PrimeField> CustomGate<F> {
+    /// Creates a custom gate from its `argument`, enabled on the given `rows`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the argument type of `argument` is not [ArgumentType::CustomGate].
+    pub fn new<A>(argument: A, rows: Vec<usize>) -> Self
+    where
+        A: Argument<F> + Send + Sync + 'static,
+    {
+        let id = match A::ARGUMENT_TYPE {
+            ArgumentType::CustomGate(id) => id,
+            ty => panic!("a custom gate must have a custom gate argument type, not {ty:?}"),
+        };
+        CustomGate {
+            id,
+            rows,
+            argument: Arc::new(argument),
+            lookups: vec![],
+        }
+    }
+
+    /// Declares a lookup performed on each row of the custom gate.
+    /// Its cells are relative to the row of the gate (current or next row),
+    /// and its table ID is either a constant or read from a column of the current row.
+    pub fn lookup(mut self, lookup: JointLookupSpec<F>) -> Self {
+        self.lookups.push(lookup);
+        self
+    }
+
+    /// The selector id of the custom gate
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// The rows on which the custom gate is enabled
+    pub fn rows(&self) -> &[usize] {
+        &self.rows
+    }
+
+    /// The argument defining the constraints of the custom gate
+    pub fn argument(&self) -> &dyn DynArgument<F> {
+        self.argument.as_ref()
+    }
+
+    /// The number of constraints of the custom gate
+    pub fn num_constraints(&self) -> u32 {
+        self.argument.num_constraints()
+    }
+
+    /// The lookups performed on each row of the custom gate
+    pub fn lookups(&self) -> &[JointLookupSpec<F>] {
+        &self.lookups
+    }
+
+    /// The lookup pattern of the custom gate, if it performs lookups
+    pub fn lookup_pattern(&self) -> Option<CustomLookupPattern<F>> {
+        if self.lookups.is_empty() {
+            return None;
+        }
+        Some(CustomLookupPattern {
+            id: self.id,
+            rows: self.rows.clone(),
+            lookups: self.lookups.clone(),
+        })
+    }
+
+    /// Checks that the custom gate can be enabled on the (unpadded) `gates` of a circuit
+    pub(crate) fn check(&self, gates: &[CircuitGate<F>]) -> Result<(), SetupError> {
+        for &row in &self.rows {
+            match gates.get(row) {
+                None => {
+                    return Err(SetupError::CustomGate(format!(
+                        "custom gate {} is enabled on row {row}, which is not in the circuit",
+                        self.id
+                    )))
+                }
+                Some(gate) if gate.typ != GateType::Zero => {
+                    return Err(SetupError::CustomGate(format!(
+                        "custom gate {} is enabled on row {row}, which holds a {:?} gate",
+                        self.id, gate.typ
+                    )))
+                }
+                Some(_) => (),
+            }
+        }
+        if !self.lookups.is_empty() {
+            self.check_lookups(gates)?;
+        }
+        Ok(())
+    }
+
+    // Checks that the lookups of the custom gate are well formed,
+    // and that they are not performed on rows that hold the lookups of a built-in gate
+    fn check_lookups(&self, gates: &[CircuitGate<F>]) -> Result<(), SetupError> {
+        let err = |msg: String| {
+            Err(SetupError::CustomGate(format!(
+                "custom gate {}: {msg}",
+                self.id
+            )))
+        };
+
+        if self.lookups.len() > MAX_LOOKUPS_PER_ROW {
+            return err(format!(
+                "{} lookups per row, at most {MAX_LOOKUPS_PER_ROW} are supported",
+                self.lookups.len()
+            ));
+        }
+        for lookup in &self.lookups {
+            if lookup.entry.is_empty() {
+                return err("a lookup has no value".to_string());
+            }
+            let columns = lookup
+                .entry
+                .iter()
+                .flat_map(|single| single.value.iter().map(|(_, pos)| pos.column));
+            let table_column = match lookup.table_id {
+                LookupTableID::WitnessColumn(column) => Some(column),
+                LookupTableID::Constant(_) => None,
+            };
+            if let Some(column) = columns.chain(table_column).find(|col| *col >= COLUMNS) {
+                return err(format!(
+                    "a lookup reads column {column}, which does not exist"
+                ));
+            }
+        }
+        for &row in &self.rows {
+            let builtin =
+                LookupPattern::from_gate(gates[row].typ, CurrOrNext::Curr).or_else(|| {
+                    row.checked_sub(1).and_then(|prev| {
+                        LookupPattern::from_gate(gates[prev].typ, CurrOrNext::Next)
+                    })
+                });
+            if let Some(pattern) = builtin {
+                return err(format!(
+                    "row {row} already performs the lookups of the {pattern:?} pattern"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates the selector polynomial of the custom gate, evaluated over `target_domain`
+    pub fn selector_polynomial(
+        &self,
+        domain: &EvaluationDomains<F>,
+        target_domain: &D<F>,
+    ) -> E<F, D<F>> {
+        let mut selector = vec![F::zero(); domain.d1.size()];
+        for &row in &self.rows {
+            selector[row] = F::one();
+        }
+        let coeff: DP<F> = E::from_vec_and_domain(selector, domain.d1).interpolate();
+        coeff.evaluate_over_domain_by_ref(*target_domain)
+    }
+
+    /// Verifies the constraints of the custom gate on a given `row` of the `witness`
+    ///
+    /// # Errors
+    ///
+    /// Will give error if one of the constraints of the custom gate is not satisfied.
+    pub fn verify<G: KimchiCurve<ScalarField = F>>(
+        &self,
+        row: usize,
+        witness: &[Vec<F>; COLUMNS],
+        gate: &CircuitGate<F>,
+        endo: F,
+    ) -> Result<(), String> {
+        let next = (row + 1) % witness[0].len();
+        let argument_witness = ArgumentWitness {
+            curr: array::from_fn(|col| witness[col][row]),
+            next: array::from_fn(|col| witness[col][next]),
+        };
+        // alpha, beta, gamma and joint_combiner are one because we are not running the prover
+        let constants = expr::Constants {
+            alpha: F::one(),
+            beta: F::one(),
+            gamma: F::one(),
+            joint_combiner: Some(F::one()),
+            endo_coefficient: endo,
+            mds: &G::sponge_params().mds,
+        };
+        let env = ArgumentEnv::<F, F>::create(argument_witness, gate.coeffs.clone(), constants);
+
+        let results = self
+            .argument
+            .constraint_evaluations(&env, &mut Cache::default());
+        match results.iter().position(|result| !result.is_zero()) {
+            None => Ok(()),
+            Some(i) => Err(format!(
+                "constraint {} of custom gate {} is not satisfied",
+                i + 1,
+                self.id
+            )),
+        }
+    }
+}
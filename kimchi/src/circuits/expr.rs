@@ -102,6 +102,8 @@ pub struct Environment<'a, F: FftField> {
     pub z: &'a Evaluations<F, D<F>>,
     /// The index selector polynomials.
     pub index: HashMap<GateType, &'a Evaluations<F, D<F>>>,
+    /// The selector polynomials of the custom gates, indexed by selector id.
+    pub custom_index: HashMap<u32, &'a Evaluations<F, D<F>>>,
     /// The value `prod_{j != 1} (1 - omega^j)`, used for efficiently
     /// computing the evaluations of the unnormalized Lagrange basis polynomials.
     pub l0_1: F,
@@ -131,6 +133,7 @@ impl<'a, F: FftField> Environment<'a, F> {
                 None => None,
                 Some(e) => Some(e),
             },
+            CustomIndex(id) => self.custom_index.get(id).copied(),
             Permutation(_) => None,
         }
     }
@@ -176,6 +179,7 @@ pub enum Column {
     LookupRuntimeSelector,
     LookupRuntimeTable,
     Index(GateType),
+    CustomIndex(u32),
     Coefficient(usize),
     Permutation(usize),
 }
@@ -202,6 +206,7 @@ impl Column {
             Column::Index(gate) => {
                 format!("{gate:?}")
             }
+            Column::CustomIndex(id) => format!("custom_{{{id}}}"),
             Column::Coefficient(i) => format!("c_{{{i}}}"),
             Column::Permutation(i) => format!("sigma_{{{i}}}"),
         }
@@ -220,6 +225,7 @@ impl Column {
             Column::Index(gate) => {
                 format!("{gate:?}")
             }
+            Column::CustomIndex(id) => format!("custom[{id}]"),
             Column::Coefficient(i) => format!("c[{i}]"),
             Column::Permutation(i) => format!("sigma_[{i}]"),
         }
@@ -677,7 +683,7 @@ impl Variable {
                 Index(GateType::Generic) => Ok(evals.generic_selector),
                 Permutation(i) => Ok(evals.s[i]),
                 Coefficient(i) => Ok(evals.coefficients[i]),
-                LookupKindIndex(_) | LookupRuntimeSelector | Index(_) => {
+                LookupKindIndex(_) | LookupRuntimeSelector | Index(_) | CustomIndex(_) => {
                     Err(ExprError::MissingIndexEvaluation(self.col))
                 }
             }
@@ -791,7 +797,8 @@ impl<C> Expr<C> {
         Expr::Constant(c)
     }
 
-    fn degree(&self, d1_size: u64) -> u64 {
+    /// The degree of the polynomial of this expression, for a domain of size `d1_size`
+    pub(crate) fn degree(&self, d1_size: u64) -> u64 {
         use Expr::*;
         match self {
             Double(x) => x.degree(d1_size),
@@ -2781,6 +2788,11 @@ pub fn index<F>(g: GateType) -> E<F> {
     E::<F>::cell(Column::Index(g), CurrOrNext::Curr)
 }
 
+/// Same as [index] but for a custom gate, given its selector id.
+pub fn custom_index<F>(id: u32) -> E<F> {
+    E::<F>::cell(Column::CustomIndex(id), CurrOrNext::Curr)
+}
+
 pub fn coeff<F>(i: usize) -> E<F> {
     E::<F>::cell(Column::Coefficient(i), CurrOrNext::Curr)
 }
@@ -2813,7 +2825,9 @@ pub use auto_clone_array;
 
 /// You can import this module like `use kimchi::circuits::expr::prologue::*` to obtain a number of handy aliases and helpers
 pub mod prologue {
-    pub use super::{coeff, constant, index, witness, witness_curr, witness_next, FeatureFlag, E};
+    pub use super::{
+        coeff, constant, custom_index, index, witness, witness_curr, witness_next, FeatureFlag, E,
+    };
 }
 
 #[cfg(test)]
@@ -2905,6 +2919,7 @@ pub mod test {
             l0_1: l0_1(index.cs.domain.d1),
             domain: index.cs.domain,
             index: HashMap::new(),
+            custom_index: HashMap::new(),
             lookup: None,
         };
 
@@ -19,6 +19,7 @@ use o1_utils::hasher::CryptoDigest;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use std::io::{Result as IoResult, Write};
+use strum_macros::EnumIter;
 use thiserror::Error;
 
 use super::{
@@ -74,6 +75,7 @@ impl CurrOrNext {
     Hash,
     PartialOrd,
     Ord,
+    EnumIter,
 )]
 #[cfg_attr(
     feature = "ocaml_types",
@@ -406,7 +408,6 @@ impl<F: PrimeField> Connect for Vec<CircuitGate<F>> {
         // Connect the 64-bit cells from previous Generic gate with zeros in first 12 bits
         self.connect_cell_pair((start_row, 1), (start_row, 2));
         self.connect_cell_pair((start_row, 2), (zero_row, 0));
-        self.connect_cell_pair((zero_row, 0), (start_row, 1));
     }
 
     fn connect_ffadd_range_checks(
@@ -2,8 +2,12 @@ use crate::{
     circuits::{
         expr::{prologue::*, Column, ConstantExpr},
         gate::{CircuitGate, CurrOrNext},
-        lookup::lookups::{
-            JointLookup, JointLookupSpec, JointLookupValue, LocalPosition, LookupInfo,
+        lookup::{
+            logup,
+            lookups::{
+                CustomLookupPattern, JointLookup, JointLookupSpec, JointLookupValue, LocalPosition,
+                LookupBackend, LookupInfo, MAX_LOOKUPS_PER_ROW,
+            },
         },
         wires::COLUMNS,
     },
@@ -20,7 +24,7 @@ use CurrOrNext::{Curr, Next};
 
 use super::runtime_tables;
 
-/// Number of constraints produced by the argument.
+/// Number of constraints produced by the plookup argument.
 pub const CONSTRAINTS: u32 = 7;
 
 /// The number of random values to append to columns for zero-knowledge.
@@ -92,7 +96,7 @@ pub fn sorted<F: PrimeField>(
     witness: &[Vec<F>; COLUMNS],
     joint_combiner: F,
     table_id_combiner: F,
-    lookup_info: &LookupInfo,
+    configuration: &LookupConfiguration<F>,
 ) -> Result<Vec<Vec<F>>, ProverError> {
     // We pad the lookups so that it is as if we lookup exactly
     // `max_lookups_per_row` in every row.
@@ -101,8 +105,8 @@ pub fn sorted<F: PrimeField>(
     let mut counts: HashMap<&F, usize> = HashMap::new();
 
     let lookup_rows = n - ZK_ROWS - 1;
-    let by_row = lookup_info.by_row(gates);
-    let max_lookups_per_row = lookup_info.max_per_row;
+    let by_row = configuration.by_row(gates);
+    let max_lookups_per_row = configuration.lookup_info.max_per_row;
 
     for t in joint_lookup_table_d8
         .evals
@@ -237,7 +241,7 @@ pub fn aggregation<R, F>(
     gamma: F,
     sorted: &[Evaluations<F, D<F>>],
     rng: &mut R,
-    lookup_info: &LookupInfo,
+    configuration: &LookupConfiguration<F>,
 ) -> Result<Evaluations<F, D<F>>, ProverError>
 where
     R: Rng + ?Sized,
@@ -265,7 +269,7 @@ where
     }));
     ark_ff::fields::batch_inversion::<F>(&mut lookup_aggreg[1..]);
 
-    let max_lookups_per_row = lookup_info.max_per_row;
+    let max_lookups_per_row = configuration.lookup_info.max_per_row;
 
     let complements_with_beta_term = {
         let mut v = vec![F::one()];
@@ -282,7 +286,7 @@ where
 
     AdjacentPairs::from(joint_lookup_table_d8.evals.iter().step_by(8))
         .take(lookup_rows)
-        .zip(lookup_info.by_row(gates))
+        .zip(configuration.by_row(gates))
         .enumerate()
         .for_each(|(i, ((t0, t1), spec))| {
             let f_chunk = {
@@ -345,6 +349,11 @@ pub struct LookupConfiguration<F> {
     /// lookups.
     #[serde_as(as = "JointLookupValue<o1_utils::serialization::SerdeAs>")]
     pub dummy_lookup: JointLookupValue<F>,
+
+    /// The lookups declared by user-defined gates
+    /// (not serialized, they come with the custom gates, which have to be registered again)
+    #[serde(skip)]
+    pub custom_patterns: Vec<CustomLookupPattern<F>>,
 }
 
 impl<F: Zero> LookupConfiguration<F> {
@@ -358,11 +367,35 @@ impl<F: Zero> LookupConfiguration<F> {
         LookupConfiguration {
             lookup_info,
             dummy_lookup,
+            custom_patterns: vec![],
+        }
+    }
+
+    /// Adds the lookups declared by user-defined gates.
+    /// Note that `lookup_info` must already account for them
+    /// (see [LookupInfo::create_with_custom_patterns]).
+    pub fn with_custom_patterns(mut self, custom_patterns: Vec<CustomLookupPattern<F>>) -> Self {
+        self.custom_patterns = custom_patterns;
+        self
+    }
+}
+
+impl<F: PrimeField> LookupConfiguration<F> {
+    /// For each row in the circuit, which lookup-constraints should be enforced at that row,
+    /// including the ones of user-defined gates.
+    pub fn by_row(&self, gates: &[CircuitGate<F>]) -> Vec<Vec<JointLookupSpec<F>>> {
+        let mut kinds = self.lookup_info.by_row(gates);
+        for pattern in &self.custom_patterns {
+            for &row in &pattern.rows {
+                kinds[row] = pattern.lookups.clone();
+            }
         }
+        kinds
     }
 }
 
-/// Specifies the lookup constraints as expressions.
+/// Specifies the lookup constraints as expressions,
+/// for the backend selected in the lookup features.
 ///
 /// # Panics
 ///
@@ -370,6 +403,17 @@ impl<F: Zero> LookupConfiguration<F> {
 pub fn constraints<F: FftField>(
     configuration: &LookupConfiguration<F>,
     generate_feature_flags: bool,
+) -> Vec<E<F>> {
+    match configuration.lookup_info.features.backend {
+        LookupBackend::Plookup => plookup_constraints(configuration, generate_feature_flags),
+        LookupBackend::LogUp => logup::constraints(configuration, generate_feature_flags),
+    }
+}
+
+/// Specifies the plookup constraints as expressions.
+fn plookup_constraints<F: FftField>(
+    configuration: &LookupConfiguration<F>,
+    generate_feature_flags: bool,
 ) -> Vec<E<F>> {
     // Something important to keep in mind is that the last 2 rows of
     // all columns will have random values in them to maintain zero-knowledge.
@@ -412,26 +456,20 @@ pub fn constraints<F: FftField>(
                     }
                     term
                 })
+                // the lookups of the custom gates are enabled by their selectors
+                .chain(
+                    configuration
+                        .custom_patterns
+                        .iter()
+                        .map(|pattern| custom_index(pattern.id)),
+                )
                 .fold(E::zero(), |acc: E<F>, x| acc + x);
 
             E::one() - lookup_indicator
         };
 
         let joint_combiner = E::Constant(ConstantExpr::JointCombiner);
-        let table_id_combiner =
-            // Compute `joint_combiner.pow(lookup_info.max_joint_size)`, injecting feature flags if
-            // needed.
-            (1..lookup_info.max_joint_size).fold(joint_combiner.clone(), |acc, i| {
-                let mut new_term = joint_combiner.clone();
-                if generate_feature_flags {
-                    new_term = E::IfFeature(
-                        FeatureFlag::TableWidth((i + 1) as isize),
-                        Box::new(new_term),
-                        Box::new(E::one()),
-                    );
-                }
-                acc * new_term
-            });
+        let table_id_combiner = table_id_combiner(lookup_info, generate_feature_flags);
 
         // combine the columns of the dummy lookup row
         let dummy_lookup = {
@@ -530,6 +568,12 @@ pub fn constraints<F: FftField>(
                     }
                     term
                 })
+                .chain(
+                    configuration
+                        .custom_patterns
+                        .iter()
+                        .map(|pattern| custom_index(pattern.id) * f_term(&pattern.lookups)),
+                )
                 .fold(dummy_rows, |acc, x| acc + x)
         };
 
@@ -638,28 +682,57 @@ pub fn constraints<F: FftField>(
 
     // Padding to make sure that the position of the runtime tables constraints is always
     // consistent.
-    res.extend((lookup_info.max_per_row..4).map(|_| E::zero()));
+    res.extend((lookup_info.max_per_row..MAX_LOOKUPS_PER_ROW).map(|_| E::zero()));
+
+    res.extend(runtime_constraints(lookup_info, generate_feature_flags));
+
+    res
+}
+
+/// Computes `joint_combiner^max_joint_size`, the combiner of the table ids,
+/// injecting feature flags if needed.
+pub(crate) fn table_id_combiner<F: FftField>(
+    lookup_info: &LookupInfo,
+    generate_feature_flags: bool,
+) -> E<F> {
+    let joint_combiner = E::Constant(ConstantExpr::JointCombiner);
+    (1..lookup_info.max_joint_size).fold(joint_combiner.clone(), |acc, i| {
+        let mut new_term = joint_combiner.clone();
+        if generate_feature_flags {
+            new_term = E::IfFeature(
+                FeatureFlag::TableWidth((i + 1) as isize),
+                Box::new(new_term),
+                Box::new(E::one()),
+            );
+        }
+        acc * new_term
+    })
+}
 
+/// The runtime table constraints, if runtime tables are used.
+pub(crate) fn runtime_constraints<F: FftField>(
+    lookup_info: &LookupInfo,
+    generate_feature_flags: bool,
+) -> Vec<E<F>> {
     // if we are using runtime tables, we add:
     // $RT(x) (1 - \text{selector}_{RT}(x)) = 0$
-    if configuration.lookup_info.features.uses_runtime_tables {
-        let mut rt_constraints = runtime_tables::constraints();
-        if generate_feature_flags {
-            for term in rt_constraints.iter_mut() {
-                // Dummy value, to appease the borrow checker.
-                let mut boxed_term = Box::new(constant(F::zero()));
-                std::mem::swap(term, &mut *boxed_term);
-                *term = E::IfFeature(
-                    FeatureFlag::RuntimeLookupTables,
-                    boxed_term,
-                    Box::new(E::zero()),
-                )
-            }
+    if !lookup_info.features.uses_runtime_tables {
+        return vec![];
+    }
+    let mut rt_constraints = runtime_tables::constraints();
+    if generate_feature_flags {
+        for term in rt_constraints.iter_mut() {
+            // Dummy value, to appease the borrow checker.
+            let mut boxed_term = Box::new(constant(F::zero()));
+            std::mem::swap(term, &mut *boxed_term);
+            *term = E::IfFeature(
+                FeatureFlag::RuntimeLookupTables,
+                boxed_term,
+                Box::new(E::zero()),
+            )
         }
-        res.extend(rt_constraints);
     }
-
-    res
+    rt_constraints
 }
 
 /// Checks that all the lookup constraints are satisfied.
@@ -678,7 +751,7 @@ pub fn verify<F: PrimeField, I: Iterator<Item = F>, TABLE: Fn() -> I>(
     joint_combiner: &F,
     table_id_combiner: &F,
     sorted: &[Evaluations<F, D<F>>],
-    lookup_info: &LookupInfo,
+    configuration: &LookupConfiguration<F>,
 ) {
     sorted
         .iter()
@@ -716,7 +789,7 @@ pub fn verify<F: PrimeField, I: Iterator<Item = F>, TABLE: Fn() -> I>(
     }
     assert_eq!(s_index, sorted_joined.len());
 
-    let by_row = lookup_info.by_row(gates);
+    let by_row = configuration.by_row(gates);
 
     // Compute lookups||table and check multiset equality
     let sorted_counts: HashMap<F, usize> = {
@@ -753,7 +826,8 @@ pub fn verify<F: PrimeField, I: Iterator<Item = F>, TABLE: Fn() -> I>(
             *all_lookups.entry(joint_lookup_evaluation).or_insert(0) += 1;
         }
 
-        *all_lookups.entry(dummy_lookup_value).or_insert(0) += lookup_info.max_per_row - spec.len();
+        *all_lookups.entry(dummy_lookup_value).or_insert(0) +=
+            configuration.lookup_info.max_per_row - spec.len();
     }
 
     assert_eq!(
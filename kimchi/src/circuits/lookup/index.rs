@@ -3,8 +3,8 @@ use crate::circuits::{
     domains::EvaluationDomains,
     gate::CircuitGate,
     lookup::{
-        constraints::LookupConfiguration,
-        lookups::{LookupInfo, LookupPattern},
+        constraints::{constraints, LookupConfiguration},
+        lookups::{CustomLookupPattern, LookupBackend, LookupInfo, LookupPattern},
         tables::LookupTable,
     },
     polynomials::permutation::ZK_ROWS,
@@ -33,6 +33,8 @@ pub enum LookupError {
     },
     #[error("The table with id 0 must have an entry of all zeros")]
     TableIDZeroMustHaveZeroEntry,
+    #[error("The lookup constraints have a degree larger than allowed by the quotient domain. Observed: {degree}, expected: {maximum_allowed}")]
+    ConstraintDegreeTooHigh { degree: u64, maximum_allowed: u64 },
 }
 
 /// Lookup selectors
@@ -203,12 +205,16 @@ impl<F: PrimeField + SquareRootField> LookupConstraintSystem<F> {
         gates: &[CircuitGate<F>],
         lookup_tables: Vec<LookupTable<F>>,
         runtime_tables: Option<Vec<RuntimeTableCfg<F>>>,
+        backend: LookupBackend,
+        custom_patterns: Vec<CustomLookupPattern<F>>,
         domain: &EvaluationDomains<F>,
     ) -> Result<Option<Self>, LookupError> {
         //~ 1. If no lookup is used in the circuit, do not create a lookup index
-        match LookupInfo::create_from_gates(gates, runtime_tables.is_some()) {
+        match LookupInfo::create_from_gates(gates, runtime_tables.is_some(), &custom_patterns) {
             None => Ok(None),
-            Some(lookup_info) => {
+            Some(mut lookup_info) => {
+                lookup_info.features.backend = backend;
+
                 let d1_size = domain.d1.size();
 
                 // The maximum number of entries that can be provided across all tables.
@@ -431,7 +437,24 @@ impl<F: PrimeField + SquareRootField> LookupConstraintSystem<F> {
                 let runtime_tables =
                     runtime_tables.map(|rt| rt.into_iter().map(Into::into).collect());
 
-                let configuration = LookupConfiguration::new(lookup_info);
+                let configuration =
+                    LookupConfiguration::new(lookup_info).with_custom_patterns(custom_patterns);
+
+                //~ 10. Check that the degree of the lookup constraints fits in the domain `d8`
+                //~     over which the quotient polynomial is computed.
+                //~     With LogUp, this degree grows with the number of lookups of a pattern.
+                let degree = constraints(&configuration, false)
+                    .iter()
+                    .map(|constraint| constraint.degree(d1_size as u64))
+                    .max()
+                    .unwrap_or(0);
+                let maximum_allowed = domain.d8.size;
+                if degree > maximum_allowed {
+                    return Err(LookupError::ConstraintDegreeTooHigh {
+                        degree,
+                        maximum_allowed,
+                    });
+                }
 
                 Ok(Some(Self {
                     lookup_selectors,
@@ -0,0 +1,277 @@
+//! This module implements the LogUp lookup argument,
+//! an alternative to plookup based on logarithmic derivatives.
+//! See <https://eprint.iacr.org/2022/1530.pdf>
+//!
+//! Instead of a sorted concatenation of the lookups and the table,
+//! the prover commits to the multiplicity $m_i$ of each entry $t_i$ of the table,
+//! that is the number of times it is looked up.
+//! The lookups are then valid if
+//!
+//! $$\sum_{i} \sum_{j} \frac{1}{\gamma + f_{i,j}} = \sum_{i} \frac{m_i}{\gamma + t_i}$$
+//!
+//! where $f_{i,j}$ is the $j$-th (combined) lookup of the $i$-th row.
+//! This sum is accumulated row by row in a polynomial $\phi$, with $\phi_0 = 0$ and
+//!
+//! $$\phi_{i+1} = \phi_i + \sum_{j} \frac{1}{\gamma + f_{i,j}} - \frac{m_i}{\gamma + t_i}$$
+//!
+//! and the final value of the accumulator must be 0.
+//!
+//! The argument reuses the columns (and thus the proof format) of plookup:
+//! the multiplicities are the single [Column::LookupSorted] polynomial,
+//! and the accumulator is the [Column::LookupAggreg] polynomial.
+//! Contrary to plookup, the rows do not need to be padded with dummy lookups.
+
+use crate::{
+    circuits::{
+        expr::{prologue::*, Column, ConstantExpr},
+        gate::{CircuitGate, CurrOrNext},
+        lookup::{
+            constraints::{
+                runtime_constraints, table_id_combiner, zk_patch, LookupConfiguration, ZK_ROWS,
+            },
+            lookups::LocalPosition,
+        },
+        wires::COLUMNS,
+    },
+    error::ProverError,
+};
+use ark_ff::{FftField, One, PrimeField, Zero};
+use ark_poly::{EvaluationDomain, Evaluations, Radix2EvaluationDomain as D};
+use rand::Rng;
+use std::collections::HashMap;
+use CurrOrNext::{Curr, Next};
+
+/// Computes the multiplicities of the entries of the lookup table.
+/// The multiplicity of a value appearing several times in the table
+/// is assigned to its first occurrence.
+///
+/// # Errors
+///
+/// Will give error if a lookup is not in the table.
+#[allow(clippy::too_many_arguments)]
+pub fn multiplicities<F: PrimeField>(
+    joint_lookup_table_d8: &Evaluations<F, D<F>>,
+    d1: D<F>,
+    gates: &[CircuitGate<F>],
+    witness: &[Vec<F>; COLUMNS],
+    joint_combiner: F,
+    table_id_combiner: F,
+    configuration: &LookupConfiguration<F>,
+) -> Result<Vec<F>, ProverError> {
+    let n = d1.size();
+    let lookup_rows = n - ZK_ROWS - 1;
+
+    // the position of the first occurrence of each value of the table
+    let mut positions: HashMap<&F, usize> = HashMap::new();
+    for (i, t) in joint_lookup_table_d8
+        .evals
+        .iter()
+        .step_by(8)
+        .take(lookup_rows)
+        .enumerate()
+    {
+        positions.entry(t).or_insert(i);
+    }
+
+    let mut counts = vec![0u64; lookup_rows];
+    for (i, spec) in configuration
+        .by_row(gates)
+        .iter()
+        .enumerate()
+        .take(lookup_rows)
+    {
+        let eval = |pos: LocalPosition| -> F {
+            let row = match pos.row {
+                Curr => i,
+                Next => i + 1,
+            };
+            witness[pos.column][row]
+        };
+        for joint_lookup in spec {
+            let joint_lookup_evaluation =
+                joint_lookup.evaluate(&joint_combiner, &table_id_combiner, &eval);
+            match positions.get(&joint_lookup_evaluation) {
+                None => return Err(ProverError::ValueNotInTable),
+                Some(&position) => counts[position] += 1,
+            }
+        }
+    }
+
+    Ok(counts.into_iter().map(F::from).collect())
+}
+
+/// Computes the accumulator of the LogUp argument, whose $(k+1)$-th entry is
+///
+/// $$\sum_{i \leq k} \left( \sum_{j} \frac{1}{\gamma + f_{i,j}} - \frac{m_i}{\gamma + t_i} \right)$$
+///
+/// where $t_i$ is the $i$-th entry of the table, $m_i$ its multiplicity,
+/// and $f_{i,j}$ the $j$-th lookup of the $i$-th row of the witness.
+///
+/// # Panics
+///
+/// Will panic if the final evaluation is not 0.
+#[allow(clippy::too_many_arguments)]
+pub fn aggregation<R, F>(
+    joint_lookup_table_d8: &Evaluations<F, D<F>>,
+    d1: D<F>,
+    gates: &[CircuitGate<F>],
+    witness: &[Vec<F>; COLUMNS],
+    joint_combiner: &F,
+    table_id_combiner: &F,
+    gamma: F,
+    multiplicities: &Evaluations<F, D<F>>,
+    rng: &mut R,
+    configuration: &LookupConfiguration<F>,
+) -> Result<Evaluations<F, D<F>>, ProverError>
+where
+    R: Rng + ?Sized,
+    F: PrimeField,
+{
+    let n = d1.size();
+    let lookup_rows = n - ZK_ROWS - 1;
+    let by_row = configuration.by_row(gates);
+
+    // the denominators of the table terms, followed by the ones of the lookups of each row
+    let mut denominators: Vec<F> = joint_lookup_table_d8
+        .evals
+        .iter()
+        .step_by(8)
+        .take(lookup_rows)
+        .map(|t| gamma + t)
+        .collect();
+    let mut lookups_per_row = Vec::with_capacity(lookup_rows);
+    for (i, spec) in by_row.iter().enumerate().take(lookup_rows) {
+        let eval = |pos: LocalPosition| -> F {
+            let row = match pos.row {
+                Curr => i,
+                Next => i + 1,
+            };
+            witness[pos.column][row]
+        };
+        denominators.extend(
+            spec.iter()
+                .map(|j| gamma + j.evaluate(joint_combiner, table_id_combiner, &eval)),
+        );
+        lookups_per_row.push(spec.len());
+    }
+    ark_ff::fields::batch_inversion::<F>(&mut denominators);
+
+    let (table_inverses, mut lookup_inverses) = denominators.split_at(lookup_rows);
+    let mut lookup_aggreg = Vec::with_capacity(lookup_rows + 1);
+    lookup_aggreg.push(F::zero());
+    for (i, num_lookups) in lookups_per_row.into_iter().enumerate() {
+        let (row_inverses, rest) = lookup_inverses.split_at(num_lookups);
+        lookup_inverses = rest;
+        let prev = lookup_aggreg[i];
+        let next = row_inverses.iter().fold(prev, |acc, x| acc + x)
+            - multiplicities[i] * table_inverses[i];
+        lookup_aggreg.push(next);
+    }
+
+    let res = zk_patch(lookup_aggreg, d1, rng);
+
+    // check that the final evaluation is equal to 0
+    if cfg!(debug_assertions) {
+        let final_val = res.evals[d1.size() - (ZK_ROWS + 1)];
+        if !final_val.is_zero() {
+            panic!("aggregation incorrect: {final_val}");
+        }
+    }
+
+    Ok(res)
+}
+
+/// Specifies the LogUp constraints as expressions.
+///
+/// Writing $A = \phi(\omega x) - \phi(x)$, $T = \gamma + t(x)$,
+/// and for each lookup pattern $D = \prod_j (\gamma + f_j)$ and $N = \sum_j \prod_{l \neq j} (\gamma + f_l)$,
+/// the accumulator is constrained on the rows of each pattern by
+/// $A \cdot D \cdot T - N \cdot T + m \cdot D = 0$,
+/// and on the rows without lookups by $A \cdot T + m = 0$.
+pub fn constraints<F: FftField>(
+    configuration: &LookupConfiguration<F>,
+    generate_feature_flags: bool,
+) -> Vec<E<F>> {
+    let lookup_info = &configuration.lookup_info;
+
+    let column = |col: Column| E::cell(col, Curr);
+    let gamma = E::<F>::Constant(ConstantExpr::Gamma);
+    let joint_combiner = E::Constant(ConstantExpr::JointCombiner);
+    let table_id_combiner = table_id_combiner(lookup_info, generate_feature_flags);
+
+    let aggreg_diff = E::cell(Column::LookupAggreg, Next) - column(Column::LookupAggreg);
+    let table_term = gamma.clone() + column(Column::LookupTable);
+    let multiplicities = column(Column::LookupSorted(0));
+
+    // the selector, the lookups and the feature flag of each pattern,
+    // the lookups of the custom gates being enabled by their selectors
+    let patterns = lookup_info
+        .features
+        .patterns
+        .into_iter()
+        .map(|pattern| {
+            (
+                column(Column::LookupKindIndex(pattern)),
+                pattern.lookups::<F>(),
+                Some(FeatureFlag::LookupPattern(pattern)),
+            )
+        })
+        .chain(
+            configuration
+                .custom_patterns
+                .iter()
+                .map(|pattern| (custom_index(pattern.id), pattern.lookups.clone(), None)),
+        );
+
+    let mut lookup_indicator = E::zero();
+    let mut aggreg_equation = E::zero();
+    for (mut selector, lookups, feature) in patterns {
+        let eval = |pos: LocalPosition| witness(pos.column, pos.row);
+        let denominators: Vec<E<F>> = lookups
+            .iter()
+            .map(|j| gamma.clone() + j.evaluate(&joint_combiner, &table_id_combiner, &eval))
+            .collect();
+        let product = denominators
+            .iter()
+            .fold(E::one(), |acc: E<F>, x| acc * x.clone());
+        let sum_of_products = (0..denominators.len())
+            .map(|j| {
+                denominators
+                    .iter()
+                    .enumerate()
+                    .filter(|(l, _)| *l != j)
+                    .fold(E::one(), |acc: E<F>, (_, x)| acc * x.clone())
+            })
+            .fold(E::zero(), |acc: E<F>, x| acc + x);
+
+        let mut term = selector.clone()
+            * (aggreg_diff.clone() * product.clone() * table_term.clone()
+                - sum_of_products * table_term.clone()
+                + multiplicities.clone() * product);
+        if let Some(feature) = feature.filter(|_| generate_feature_flags) {
+            selector = E::IfFeature(feature, Box::new(selector), Box::new(E::zero()));
+            term = E::IfFeature(feature, Box::new(term), Box::new(E::zero()));
+        }
+        lookup_indicator += selector;
+        aggreg_equation += term;
+    }
+
+    // the rows without lookups only remove the table term
+    aggreg_equation += (E::one() - lookup_indicator) * (aggreg_diff * table_term + multiplicities);
+
+    let final_lookup_row: i32 = -(ZK_ROWS as i32) - 1;
+
+    let mut res = vec![
+        // the accumulator except for the last 4 rows
+        // (contains the zk-rows and the last value of the accumulator)
+        E::VanishesOnLast4Rows * aggreg_equation,
+        // the initial value of the accumulator
+        E::UnnormalizedLagrangeBasis(0) * column(Column::LookupAggreg),
+        // Check that the final value of the accumulator is 0
+        E::UnnormalizedLagrangeBasis(final_lookup_row) * column(Column::LookupAggreg),
+    ];
+
+    res.extend(runtime_constraints(lookup_info, generate_feature_flags));
+
+    res
+}
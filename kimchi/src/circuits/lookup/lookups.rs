@@ -30,6 +30,10 @@ type Evaluations<Field> = E<Field, D<Field>>;
 //~    `JointLookup { SingleLookup { value: vec![(scale1, col1), ..., (scale2, col2)] } }`
 //~   * Any combination of these
 
+/// The maximum number of lookups per row,
+/// beyond which the degree of the lookup constraints exceeds the degree of the quotient polynomial.
+pub const MAX_LOOKUPS_PER_ROW: usize = 4;
+
 fn max_lookups_per_row(kinds: LookupPatterns) -> usize {
     kinds
         .into_iter()
@@ -128,6 +132,21 @@ impl LookupPatterns {
     }
 }
 
+/// The argument used to prove the lookups.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "ocaml_types",
+    derive(ocaml::IntoValue, ocaml::FromValue, ocaml_gen::Enum)
+)]
+pub enum LookupBackend {
+    /// The plookup argument, see <https://eprint.iacr.org/2020/315.pdf>
+    #[default]
+    Plookup,
+    /// The logarithmic derivative argument with explicit multiplicities,
+    /// see <https://eprint.iacr.org/2022/1530.pdf>
+    LogUp,
+}
+
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(
     feature = "ocaml_types",
@@ -140,6 +159,9 @@ pub struct LookupFeatures {
     pub joint_lookup_used: bool,
     /// True if runtime lookup tables are used.
     pub uses_runtime_tables: bool,
+    /// The argument used to prove the lookups
+    #[serde(default)]
+    pub backend: LookupBackend,
 }
 
 impl LookupFeatures {
@@ -152,6 +174,7 @@ impl LookupFeatures {
             patterns,
             uses_runtime_tables,
             joint_lookup_used,
+            backend: LookupBackend::default(),
         }
     }
 }
@@ -182,17 +205,47 @@ impl LookupInfo {
         }
     }
 
+    /// The number of [Column::LookupSorted](crate::circuits::expr::Column::LookupSorted)
+    /// polynomials of the argument: the sorted table for plookup,
+    /// or the single multiplicities polynomial for LogUp.
+    pub fn num_sorted(&self) -> usize {
+        match self.features.backend {
+            LookupBackend::Plookup => self.max_per_row + 1,
+            LookupBackend::LogUp => 1,
+        }
+    }
+
+    /// Create the lookup configuration of the given features,
+    /// extended with the lookups declared by user-defined gates.
+    /// Returns `None` if no lookup is used.
+    pub fn create_with_custom_patterns<F>(
+        features: LookupFeatures,
+        custom_patterns: &[CustomLookupPattern<F>],
+    ) -> Option<Self> {
+        if features.patterns == LookupPatterns::default() && custom_patterns.is_empty() {
+            return None;
+        }
+
+        let mut lookup_info = Self::create(features);
+        for pattern in custom_patterns {
+            lookup_info.max_per_row =
+                std::cmp::max(lookup_info.max_per_row, pattern.max_lookups_per_row());
+            lookup_info.max_joint_size =
+                std::cmp::max(lookup_info.max_joint_size, pattern.max_joint_size());
+            if pattern.max_joint_size() > 1 {
+                lookup_info.features.joint_lookup_used = true;
+            }
+        }
+        Some(lookup_info)
+    }
+
     pub fn create_from_gates<F: PrimeField>(
         gates: &[CircuitGate<F>],
         uses_runtime_tables: bool,
+        custom_patterns: &[CustomLookupPattern<F>],
     ) -> Option<Self> {
         let features = LookupFeatures::from_gates(gates, uses_runtime_tables);
-
-        if features.patterns == LookupPatterns::default() {
-            None
-        } else {
-            Some(Self::create(features))
-        }
+        Self::create_with_custom_patterns(features, custom_patterns)
     }
 
     /// Each entry in `kinds` has a corresponding selector polynomial that controls whether that
@@ -264,6 +317,46 @@ impl LookupInfo {
     }
 }
 
+/// The lookups declared by a user-defined gate
+/// (see [`CustomGate`](crate::circuits::custom_gate::CustomGate)).
+/// Contrary to a [`LookupPattern`], it is enabled by the selector of the custom gate,
+/// on the rows of the custom gate.
+#[derive(Clone)]
+pub struct CustomLookupPattern<F> {
+    /// The selector id of the custom gate
+    pub id: u32,
+    /// The rows on which the lookups are performed
+    pub rows: Vec<usize>,
+    /// The lookups performed on each of these rows
+    pub lookups: Vec<JointLookupSpec<F>>,
+}
+
+impl<F> std::fmt::Debug for CustomLookupPattern<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomLookupPattern")
+            .field("id", &self.id)
+            .field("rows", &self.rows)
+            .field("lookups", &self.lookups.len())
+            .finish()
+    }
+}
+
+impl<F> CustomLookupPattern<F> {
+    /// Returns the number of lookups per row of the pattern.
+    pub fn max_lookups_per_row(&self) -> usize {
+        self.lookups.len()
+    }
+
+    /// Returns the maximum number of values that are used in any vector lookup in this pattern.
+    pub fn max_joint_size(&self) -> u32 {
+        self.lookups
+            .iter()
+            .map(|lookup| lookup.entry.len() as u32)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
 /// A position in the circuit relative to a given row.
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct LocalPosition {
@@ -271,6 +364,24 @@ pub struct LocalPosition {
     pub column: usize,
 }
 
+impl LocalPosition {
+    /// The cell of the given `column` on the current row
+    pub fn curr(column: usize) -> Self {
+        LocalPosition {
+            row: CurrOrNext::Curr,
+            column,
+        }
+    }
+
+    /// The cell of the given `column` on the next row
+    pub fn next(column: usize) -> Self {
+        LocalPosition {
+            row: CurrOrNext::Next,
+            column,
+        }
+    }
+}
+
 /// Look up a single value in a lookup table. The value may be computed as a linear
 /// combination of locally-accessible cells.
 #[derive(Clone, Serialize, Deserialize)]
@@ -305,9 +416,9 @@ pub enum LookupTableID {
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct JointLookup<SingleLookup, LookupTableID> {
     /// The ID for the table associated with this lookup.
-    /// Positive IDs are intended to be used for the fixed tables associated with individual gates,
-    /// with negative IDs reserved for gates defined by the particular constraint system to avoid
-    /// accidental collisions.
+    /// Non-negative IDs are used for the fixed tables associated with individual gates,
+    /// with negative IDs reserved for tables defined by the particular constraint system to avoid
+    /// accidental collisions (see [`LookupTableRegistry`](super::registry::LookupTableRegistry)).
     pub table_id: LookupTableID,
     pub entry: Vec<SingleLookup>,
 }
@@ -320,8 +431,9 @@ pub type JointLookupSpec<F> = JointLookup<SingleLookup<F>, LookupTableID>;
 pub type JointLookupValue<F> = JointLookup<F, F>;
 
 impl<F: Zero + One + Clone + Neg<Output = F> + From<u64>> JointLookupValue<F> {
-    // TODO: Support multiple tables
     /// Evaluate the combined value of a joint-lookup.
+    /// The table ID is combined with `table_id_combiner`, so that lookups in different tables
+    /// are distinguished.
     pub fn evaluate(&self, joint_combiner: &F, table_id_combiner: &F) -> F {
         combine_table_entry(
             joint_combiner,
@@ -332,6 +444,21 @@ impl<F: Zero + One + Clone + Neg<Output = F> + From<u64>> JointLookupValue<F> {
     }
 }
 
+impl<F: One> JointLookup<SingleLookup<F>, LookupTableID> {
+    /// Creates a joint lookup of the values of the given `cells` in the table `table_id`.
+    pub fn from_cells(table_id: LookupTableID, cells: &[LocalPosition]) -> Self {
+        JointLookup {
+            table_id,
+            entry: cells
+                .iter()
+                .map(|cell| SingleLookup {
+                    value: vec![(F::one(), *cell)],
+                })
+                .collect(),
+        }
+    }
+}
+
 impl<F: Copy> JointLookup<SingleLookup<F>, LookupTableID> {
     /// Reduce linear combinations in the lookup entries to a single value, resolving local
     /// positions using the given function.
@@ -1,8 +1,12 @@
 //! This module implement the lookup argument.
 //! See <https://eprint.iacr.org/2020/315.pdf>
+//! The LogUp argument (see [logup]) can be used instead of plookup.
+//! The table IDs of a circuit can be allocated with a [registry::LookupTableRegistry].
 
 pub mod constraints;
 pub mod index;
+pub mod logup;
 pub mod lookups;
+pub mod registry;
 pub mod runtime_tables;
 pub mod tables;
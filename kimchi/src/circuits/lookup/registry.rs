@@ -0,0 +1,207 @@
+//! This module implements a registry of lookup tables.
+//!
+//! Lookup tables are identified by a table ID. The fixed tables of the built-in gates
+//! (see [`GateLookupTable`]) use non-negative IDs, and the registry hands out negative IDs
+//! to the tables defined for a particular constraint system, so that they never collide.
+//! Gadget authors can then refer to tables by name, and find their IDs with
+//! [`LookupTableRegistry::table_id`].
+//!
+//! A registry is given to the [`Builder`](crate::circuits::constraints::Builder) of the
+//! constraint system, which checks that the IDs of all the tables of the circuit
+//! (gate tables, fixed tables and runtime tables) are unique.
+//! A fixed table that is identical to the table of a gate of the circuit is not a collision:
+//! it is the same table, and is only added once.
+
+use crate::{
+    circuits::lookup::{
+        runtime_tables::{RuntimeTableCfg, RuntimeTableSpec},
+        tables::{get_table, GateLookupTable, LookupTable},
+    },
+    error::SetupError,
+};
+use ark_ff::FftField;
+use std::collections::BTreeMap;
+
+/// A registry of named lookup tables, which allocates their table IDs.
+#[derive(Clone, Debug)]
+pub struct LookupTableRegistry<F> {
+    /// The table IDs, by name
+    ids: BTreeMap<String, i32>,
+    /// The fixed tables registered
+    tables: Vec<LookupTable<F>>,
+    /// The runtime tables registered
+    runtime_tables: Vec<RuntimeTableCfg<F>>,
+    /// The next table ID to allocate
+    next_id: i32,
+}
+
+impl<F> Default for LookupTableRegistry<F> {
+    fn default() -> Self {
+        let ids = GateLookupTable::ALL
+            .iter()
+            .map(|table| (table.name().to_string(), table.id()))
+            .collect();
+        Self {
+            ids,
+            tables: vec![],
+            runtime_tables: vec![],
+            next_id: -1,
+        }
+    }
+}
+
+impl<F> LookupTableRegistry<F> {
+    /// Creates a registry that only knows about the tables of the built-in gates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the table ID of the table registered as `name`, if any.
+    /// The tables of the built-in gates are registered under [`GateLookupTable::name`].
+    pub fn table_id(&self, name: &str) -> Option<i32> {
+        self.ids.get(name).copied()
+    }
+
+    /// Returns the names and table IDs of all the registered tables.
+    pub fn table_ids(&self) -> impl Iterator<Item = (&str, i32)> {
+        self.ids.iter().map(|(name, id)| (name.as_str(), *id))
+    }
+
+    /// Registers a fixed lookup table, given as a list of columns, and returns its table ID.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if a table is already registered as `name`,
+    /// or if the columns of the table are empty or of different lengths.
+    pub fn add_table(&mut self, name: &str, data: Vec<Vec<F>>) -> Result<i32, SetupError> {
+        match data.first() {
+            Some(first) if !first.is_empty() => {
+                if data.iter().any(|col| col.len() != first.len()) {
+                    return Err(SetupError::LookupTable(format!(
+                        "the columns of table {name} have different lengths"
+                    )));
+                }
+            }
+            _ => return Err(SetupError::LookupTable(format!("table {name} is empty"))),
+        }
+        let id = self.allocate(name)?;
+        self.tables.push(LookupTable { id, data });
+        Ok(id)
+    }
+
+    /// Registers an indexed runtime table of `len` entries (see [`RuntimeTableCfg::Indexed`]),
+    /// and returns its table ID.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if a table is already registered as `name`.
+    pub fn add_runtime_table(&mut self, name: &str, len: usize) -> Result<i32, SetupError> {
+        let id = self.allocate(name)?;
+        self.runtime_tables
+            .push(RuntimeTableCfg::Indexed(RuntimeTableSpec { id, len }));
+        Ok(id)
+    }
+
+    /// Registers a custom runtime table (see [`RuntimeTableCfg::Custom`]),
+    /// and returns its table ID.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if a table is already registered as `name`.
+    pub fn add_custom_runtime_table(
+        &mut self,
+        name: &str,
+        first_column: Vec<F>,
+    ) -> Result<i32, SetupError> {
+        let id = self.allocate(name)?;
+        self.runtime_tables
+            .push(RuntimeTableCfg::Custom { id, first_column });
+        Ok(id)
+    }
+
+    /// The fixed tables registered
+    pub fn tables(&self) -> &[LookupTable<F>] {
+        &self.tables
+    }
+
+    /// The configurations of the runtime tables registered
+    pub fn runtime_tables(&self) -> &[RuntimeTableCfg<F>] {
+        &self.runtime_tables
+    }
+
+    /// Returns the fixed tables and the runtime tables registered.
+    pub fn into_tables(self) -> (Vec<LookupTable<F>>, Vec<RuntimeTableCfg<F>>) {
+        (self.tables, self.runtime_tables)
+    }
+
+    fn allocate(&mut self, name: &str) -> Result<i32, SetupError> {
+        if self.ids.contains_key(name) {
+            return Err(SetupError::LookupTable(format!(
+                "a table is already registered as {name}"
+            )));
+        }
+        let id = self.next_id;
+        self.next_id = id
+            .checked_sub(1)
+            .ok_or_else(|| SetupError::LookupTable("no table ID left to allocate".to_string()))?;
+        self.ids.insert(name.to_string(), id);
+        Ok(id)
+    }
+}
+
+/// Removes the fixed tables that are identical to one of the gate tables (same ID and same data),
+/// as the gate tables of a circuit are already part of its lookup tables.
+pub fn remove_gate_tables<F: FftField>(
+    gate_tables: &[GateLookupTable],
+    lookup_tables: &mut Vec<LookupTable<F>>,
+) {
+    for gate_table in gate_tables {
+        let mut table: Option<LookupTable<F>> = None;
+        lookup_tables.retain(|lookup_table| {
+            if lookup_table.id != gate_table.id() {
+                return true;
+            }
+            let table = table.get_or_insert_with(|| get_table(*gate_table));
+            lookup_table.data != table.data
+        });
+    }
+}
+
+/// Checks that the gate tables, the fixed tables and the runtime tables of a circuit
+/// have distinct table IDs.
+///
+/// # Errors
+///
+/// Will give error if two tables share the same table ID.
+pub fn check_table_ids<F>(
+    gate_tables: impl IntoIterator<Item = GateLookupTable>,
+    lookup_tables: &[LookupTable<F>],
+    runtime_tables: &[RuntimeTableCfg<F>],
+) -> Result<(), SetupError> {
+    let mut owners: BTreeMap<i32, String> = BTreeMap::new();
+    let mut claim = |id: i32, owner: String| match owners.get(&id) {
+        Some(other) => Err(SetupError::LookupTable(format!(
+            "table ID {id} is used by both the {other} and the {owner}"
+        ))),
+        None => {
+            owners.insert(id, owner);
+            Ok(())
+        }
+    };
+
+    // several gates can share the same table
+    let mut seen = vec![];
+    for table in gate_tables {
+        if !seen.contains(&table) {
+            claim(table.id(), format!("{} gate table", table.name()))?;
+            seen.push(table);
+        }
+    }
+    for (i, table) in lookup_tables.iter().enumerate() {
+        claim(table.id, format!("lookup table #{i}"))?;
+    }
+    for (i, table) in runtime_tables.iter().enumerate() {
+        claim(table.id(), format!("runtime table #{i}"))?;
+    }
+    Ok(())
+}
@@ -10,7 +10,7 @@ use ark_ff::Field;
 use serde::{Deserialize, Serialize};
 
 /// The specification of a runtime table.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RuntimeTableSpec {
     /// The table ID.
     pub id: i32,
@@ -20,7 +20,9 @@ pub struct RuntimeTableSpec {
 
 /// Use this type at setup time, to list all the runtime tables.
 ///
-/// Note: care must be taken as table IDs can collide with IDs of other types of lookup tables.
+/// Note: table IDs must not collide with IDs of other types of lookup tables,
+/// which is checked when building the constraint system.
+/// Use a [`LookupTableRegistry`](super::registry::LookupTableRegistry) to allocate them.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RuntimeTableCfg<F> {
     /// An indexed runtime table has a counter (starting at zero) in its first column.
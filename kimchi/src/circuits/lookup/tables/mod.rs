@@ -67,6 +67,26 @@ pub fn get_table<F: FftField>(table_name: GateLookupTable) -> LookupTable<F> {
 }
 
 impl GateLookupTable {
+    /// All the gate lookup tables.
+    pub const ALL: [GateLookupTable; 2] = [GateLookupTable::Xor, GateLookupTable::RangeCheck];
+
+    /// Returns the table ID of a [`GateLookupTable`].
+    pub fn id(&self) -> i32 {
+        match self {
+            GateLookupTable::Xor => XOR_TABLE_ID,
+            GateLookupTable::RangeCheck => RANGE_CHECK_TABLE_ID,
+        }
+    }
+
+    /// Returns the name under which a [`GateLookupTable`] is registered
+    /// (see [`registry`](crate::circuits::lookup::registry)).
+    pub fn name(&self) -> &'static str {
+        match self {
+            GateLookupTable::Xor => "xor",
+            GateLookupTable::RangeCheck => "range_check",
+        }
+    }
+
     /// Returns the lookup table associated to a [`GateLookupTable`].
     pub fn table_size(&self) -> usize {
         match self {
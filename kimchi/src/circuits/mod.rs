@@ -2,7 +2,9 @@
 pub mod macros;
 
 pub mod argument;
+pub mod builder;
 pub mod constraints;
+pub mod custom_gate;
 pub mod domain_constant_evaluation;
 pub mod domains;
 pub mod expr;
@@ -0,0 +1,655 @@
+//! This module includes the BLAKE2b gadget and its witness generation.
+//! Note that this module does not need any new gate type.
+
+use super::{
+    range_check::{self, bits::RangeCheckBitsLayout},
+    rot::{self, RotMode},
+    xor,
+};
+use crate::circuits::{
+    gate::{CircuitGate, Connect, GateType},
+    polynomial::COLUMNS,
+    wires::Wire,
+};
+use ark_ff::{PrimeField, SquareRootField};
+use o1_utils::Two;
+use std::array;
+
+//~ The BLAKE2b gadget proves that a digest is the BLAKE2b-256 or BLAKE2b-512 hash
+//~ (without key) of a message whose length in bytes is fixed when creating the circuit.
+//~
+//~ The message is given as little-endian 64-bit words, padded with zeros to a multiple of 128 bytes.
+//~ Each word is range checked to the number of bytes of the message it contains (see the bit-width
+//~ range check gadget), and words of padding are the zero cell.
+//~ The initial chaining value, the initialization vector, the byte counter and the final block flag
+//~ only depend on the variant and the length of the message, so they are constants of the circuit
+//~ stored in generic gates (two per row).
+//~
+//~ Each block goes through 12 rounds of 8 mixing functions $G$, computing on 64-bit words
+//~
+//~ $$
+//~ \begin{aligned}
+//~ a &= a + b + x \\
+//~ d &= (d \oplus a) \ggg 32 \\
+//~ c &= c + d \\
+//~ b &= (b \oplus c) \ggg 24 \\
+//~ a &= a + b + y \\
+//~ d &= (d \oplus a) \ggg 16 \\
+//~ c &= c + d \\
+//~ b &= (b \oplus c) \ggg 63 \\
+//~ \end{aligned}
+//~ $$
+//~
+//~ where the XORs are 64-bit `Xor16` gadgets, the rotations are `Rot64` gadgets (the rotation to the right
+//~ by 63 bits being a rotation to the left by one bit), and the additions modulo $2^{64}$ use the following rows:
+//~
+//~ | Row | `CircuitGate` |  0    |  1     |  2     |  3     |  4     |  5    |
+//~ |:---:|:-------------:|:-----:|:------:|:------:|:------:|:------:|:-----:|
+//~ | 0   | `Generic`     | $a$   | $b$    | $t$    | $t$    | $x$    | $s$   |
+//~ | 1   | `Generic`     | $k_0$ | $k_0$  |        | $k_1$  | $k_1$  |       |
+//~ | 2   | `Generic`     | $s$   | $k_0$  | $s'$   | $s'$   | $k_1$  | $c$   |
+//~ | 3   | `RangeCheck0` | $c$   | 0      | 0      | ...    |        |       |
+//~
+//~ with $t = a + b$, $s = t + x$, booleans $k_0$ and $k_1$, $s' = s - 2^{64} k_0$ and $c = s' - 2^{64} k_1$.
+//~ As $a, b, x < 2^{64}$, the 64-bit range check of $c$ makes it the sum modulo $2^{64}$.
+//~ Additions of two words use the zero cell as $x$.
+//~
+//~ The chaining value is finally XORed with both halves of the state, and the digest is made of
+//~ the little-endian bytes of the first 4 (BLAKE2b-256) or 8 (BLAKE2b-512) words of the last chaining value.
+//~
+//~ ```admonish warning
+//~ The gadget uses both the XOR and the range check lookup tables.
+//~ ```
+
+/// Number of bytes of a BLAKE2b block
+pub const BLAKE2B_BLOCK_BYTES: usize = 128;
+
+/// Number of rounds of the BLAKE2b compression function
+pub const BLAKE2B_ROUNDS: usize = 12;
+
+/// Number of rows of an addition modulo 2^64
+const ADD_ROWS: usize = 4;
+
+/// Initialization vector of BLAKE2b
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// Permutations of the message words in each round
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// State words mixed by each of the 8 mixing functions of a round
+const MIXES: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+/// Rotations of the mixing function, rotating right by 63 bits being rotating left by 1 bit
+const ROTATIONS: [(u32, RotMode); 4] = [
+    (32, RotMode::Right),
+    (24, RotMode::Right),
+    (16, RotMode::Right),
+    (1, RotMode::Left),
+];
+
+/// The variants of BLAKE2b supported by the gadget
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Blake2bVariant {
+    /// BLAKE2b with a 32-byte digest
+    Blake2b256,
+    /// BLAKE2b with a 64-byte digest
+    Blake2b512,
+}
+
+impl Blake2bVariant {
+    /// Number of bytes of the digest
+    pub fn digest_bytes(self) -> usize {
+        match self {
+            Self::Blake2b256 => 32,
+            Self::Blake2b512 => 64,
+        }
+    }
+
+    /// Number of 64-bit words of the digest
+    pub fn digest_words(self) -> usize {
+        self.digest_bytes() / 8
+    }
+}
+
+/// Number of blocks hashed for a message of `message_len` bytes
+pub fn num_blocks(message_len: usize) -> usize {
+    std::cmp::max(
+        1,
+        (message_len + BLAKE2B_BLOCK_BYTES - 1) / BLAKE2B_BLOCK_BYTES,
+    )
+}
+
+/// Number of 64-bit words of a message of `message_len` bytes
+pub fn num_message_words(message_len: usize) -> usize {
+    (message_len + 7) / 8
+}
+
+/// The operations of BLAKE2b on 64-bit words, shared by the layout, the gates and the witness
+trait Blake2bOps {
+    type Word: Copy;
+
+    /// The words holding the constants `values`
+    fn constants(&mut self, values: &[u64]) -> Vec<Self::Word>;
+
+    /// The `index`-th word of the message, containing `bytes` bytes of the message
+    fn message(&mut self, index: usize, bytes: usize) -> Self::Word;
+
+    /// Addition modulo 2^64 of two or three words
+    fn add(&mut self, words: &[Self::Word]) -> Self::Word;
+
+    /// XOR of two words
+    fn xor(&mut self, left: Self::Word, right: Self::Word) -> Self::Word;
+
+    /// Rotation of a word by `rot` bits towards `side`
+    fn rot(&mut self, word: Self::Word, rot: u32, side: RotMode) -> Self::Word;
+}
+
+/// Hashes a message of `message_len` bytes, and returns the words of the digest
+fn blake2b<O: Blake2bOps>(
+    ops: &mut O,
+    variant: Blake2bVariant,
+    message_len: usize,
+) -> Vec<O::Word> {
+    let mut initial = IV;
+    initial[0] ^= 0x0101_0000 ^ variant.digest_bytes() as u64;
+    let mut h = ops.constants(&initial);
+
+    let blocks = num_blocks(message_len);
+    for block in 0..blocks {
+        let m: Vec<_> = (0..16)
+            .map(|i| {
+                let index = 16 * block + i;
+                let bytes = message_len.saturating_sub(8 * index).min(8);
+                ops.message(index, bytes)
+            })
+            .collect();
+
+        // the byte counter and the final block flag
+        let last = block == blocks - 1;
+        let counter = if last {
+            message_len as u128
+        } else {
+            (BLAKE2B_BLOCK_BYTES * (block + 1)) as u128
+        };
+        let mut iv = IV;
+        iv[4] ^= counter as u64;
+        iv[5] ^= (counter >> 64) as u64;
+        if last {
+            iv[6] = !iv[6];
+        }
+
+        let mut v = h.clone();
+        v.extend(ops.constants(&iv));
+
+        for round in 0..BLAKE2B_ROUNDS {
+            let s = &SIGMA[round % SIGMA.len()];
+            for (i, &[a, b, c, d]) in MIXES.iter().enumerate() {
+                // a = a + b + x, d = (d ^ a) >>> 32, c = c + d, b = (b ^ c) >>> 24,
+                // a = a + b + y, d = (d ^ a) >>> 16, c = c + d, b = (b ^ c) >>> 63
+                for (half, message) in [m[s[2 * i]], m[s[2 * i + 1]]].into_iter().enumerate() {
+                    let (rot_d, side_d) = ROTATIONS[2 * half];
+                    let (rot_b, side_b) = ROTATIONS[2 * half + 1];
+                    v[a] = ops.add(&[v[a], v[b], message]);
+                    let x = ops.xor(v[d], v[a]);
+                    v[d] = ops.rot(x, rot_d, side_d);
+                    v[c] = ops.add(&[v[c], v[d]]);
+                    let x = ops.xor(v[b], v[c]);
+                    v[b] = ops.rot(x, rot_b, side_b);
+                }
+            }
+        }
+
+        // only the words of the digest are needed after the last block
+        let words = if last { variant.digest_words() } else { 8 };
+        h = (0..words)
+            .map(|i| {
+                let x = ops.xor(h[i], v[i]);
+                ops.xor(x, v[i + 8])
+            })
+            .collect();
+    }
+
+    h
+}
+
+/// Allocates the cells of the gadget
+#[derive(Clone, Debug)]
+struct Blake2bRows {
+    next_row: usize,
+    zero: (usize, usize),
+    message: Vec<(usize, usize)>,
+}
+
+impl Blake2bRows {
+    /// Starts the gadget at `start_row` with the row of the zero cell
+    fn new(start_row: usize) -> Self {
+        Self {
+            next_row: start_row + 1,
+            zero: (start_row, 0),
+            message: vec![],
+        }
+    }
+
+    fn allocate(&mut self, rows: usize) -> usize {
+        let row = self.next_row;
+        self.next_row += rows;
+        row
+    }
+}
+
+impl Blake2bOps for Blake2bRows {
+    type Word = (usize, usize);
+
+    fn constants(&mut self, values: &[u64]) -> Vec<Self::Word> {
+        let row = self.allocate((values.len() + 1) / 2);
+        (0..values.len())
+            .map(|i| (row + i / 2, 3 * (i % 2)))
+            .collect()
+    }
+
+    fn message(&mut self, _index: usize, bytes: usize) -> Self::Word {
+        if bytes == 0 {
+            return self.zero;
+        }
+        let layout = RangeCheckBitsLayout::new(self.next_row, 8 * bytes);
+        self.allocate(layout.num_rows());
+        self.message.push(layout.value());
+        layout.value()
+    }
+
+    fn add(&mut self, _words: &[Self::Word]) -> Self::Word {
+        (self.allocate(ADD_ROWS) + ADD_ROWS - 1, 0)
+    }
+
+    fn xor(&mut self, _left: Self::Word, _right: Self::Word) -> Self::Word {
+        (self.allocate(xor::num_xors(64) + 1), 2)
+    }
+
+    fn rot(&mut self, _word: Self::Word, _rot: u32, _side: RotMode) -> Self::Word {
+        (self.allocate(2), 1)
+    }
+}
+
+/// Describes where the cells of a BLAKE2b gadget are located in the circuit.
+/// Cells are given as `(row, col)` pairs, as expected by [`Connect::connect_cell_pair`].
+#[derive(Clone, Debug)]
+pub struct Blake2bLayout {
+    start_row: usize,
+    next_row: usize,
+    message: Vec<(usize, usize)>,
+    digest: Vec<(usize, usize)>,
+}
+
+impl Blake2bLayout {
+    /// Creates the layout of a gadget starting at `start_row` that hashes a message
+    /// of `message_len` bytes with the given `variant`
+    pub fn new(start_row: usize, variant: Blake2bVariant, message_len: usize) -> Self {
+        let mut rows = Blake2bRows::new(start_row);
+        let digest = blake2b(&mut rows, variant, message_len);
+        Self {
+            start_row,
+            next_row: rows.next_row,
+            message: rows.message,
+            digest,
+        }
+    }
+
+    /// First row of the gadget
+    pub fn start_row(&self) -> usize {
+        self.start_row
+    }
+
+    /// Next empty row after the gadget
+    pub fn next_row(&self) -> usize {
+        self.next_row
+    }
+
+    /// Number of rows used by the gadget
+    pub fn num_rows(&self) -> usize {
+        self.next_row - self.start_row
+    }
+
+    /// Cell of the `i`-th little-endian 64-bit word of the message
+    pub fn message(&self, i: usize) -> (usize, usize) {
+        self.message[i]
+    }
+
+    /// Cell of the `i`-th little-endian 64-bit word of the digest
+    pub fn digest(&self, i: usize) -> (usize, usize) {
+        self.digest[i]
+    }
+}
+
+/// Creates the gates of the gadget, following the cells allocated by [`Blake2bRows`]
+struct Blake2bGates<F: PrimeField> {
+    rows: Blake2bRows,
+    start_row: usize,
+    gates: Vec<CircuitGate<F>>,
+}
+
+impl<F: PrimeField + SquareRootField> Blake2bGates<F> {
+    fn new(start_row: usize) -> Self {
+        let mut coeffs = [F::zero(); 10];
+        coeffs[0] = F::one();
+        Self {
+            rows: Blake2bRows::new(start_row),
+            start_row,
+            gates: vec![CircuitGate::create_generic(
+                Wire::for_row(start_row),
+                coeffs,
+            )],
+        }
+    }
+
+    /// Connects two cells given with their absolute rows
+    fn connect(&mut self, cell1: (usize, usize), cell2: (usize, usize)) {
+        self.gates.connect_cell_pair(
+            (cell1.0 - self.start_row, cell1.1),
+            (cell2.0 - self.start_row, cell2.1),
+        );
+    }
+
+    fn generic(&mut self, row: usize, coeffs: [F; 10]) {
+        self.gates
+            .push(CircuitGate::create_generic(Wire::for_row(row), coeffs));
+    }
+}
+
+impl<F: PrimeField + SquareRootField> Blake2bOps for Blake2bGates<F> {
+    type Word = (usize, usize);
+
+    fn constants(&mut self, values: &[u64]) -> Vec<Self::Word> {
+        let row = self.rows.next_row;
+        let cells = self.rows.constants(values);
+        for (i, pair) in values.chunks(2).enumerate() {
+            let mut coeffs = [F::zero(); 10];
+            for (j, &value) in pair.iter().enumerate() {
+                coeffs[5 * j] = F::one();
+                coeffs[5 * j + 4] = -F::from(value);
+            }
+            self.generic(row + i, coeffs);
+        }
+        cells
+    }
+
+    fn message(&mut self, index: usize, bytes: usize) -> Self::Word {
+        let row = self.rows.next_row;
+        let cell = self.rows.message(index, bytes);
+        if bytes > 0 {
+            let (_, range_check) = CircuitGate::create_range_check_bits(row, 8 * bytes);
+            self.gates.extend(range_check);
+        }
+        cell
+    }
+
+    fn add(&mut self, words: &[Self::Word]) -> Self::Word {
+        let row = self.rows.next_row;
+        let sum = self.rows.add(words);
+        let (one, zero) = (F::one(), F::zero());
+        let two_to_64 = F::two_pow(64);
+
+        // t = a + b, s = t + x
+        self.generic(
+            row,
+            [one, one, -one, zero, zero, one, one, -one, zero, zero],
+        );
+        // k0 and k1 are booleans
+        self.generic(
+            row + 1,
+            [-one, zero, zero, one, zero, -one, zero, zero, one, zero],
+        );
+        // s' = s - 2^64 * k0, c = s' - 2^64 * k1
+        self.generic(
+            row + 2,
+            [
+                one, -two_to_64, -one, zero, zero, one, -two_to_64, -one, zero, zero,
+            ],
+        );
+        self.gates.push(CircuitGate::new(
+            GateType::RangeCheck0,
+            Wire::for_row(row + 3),
+            vec![zero],
+        ));
+
+        self.connect((row, 2), (row, 3));
+        self.connect((row + 1, 0), (row + 1, 1));
+        self.connect((row + 1, 3), (row + 1, 4));
+        self.connect((row, 5), (row + 2, 0));
+        self.connect((row + 1, 0), (row + 2, 1));
+        self.connect((row + 2, 2), (row + 2, 3));
+        self.connect((row + 1, 3), (row + 2, 4));
+        self.connect((row + 2, 5), sum);
+        let zero_cell = self.rows.zero;
+        self.connect(zero_cell, (row + 3, 1));
+        self.connect(zero_cell, (row + 3, 2));
+
+        let x = words.get(2).copied().unwrap_or(zero_cell);
+        for (word, col) in [(words[0], 0), (words[1], 1), (x, 4)] {
+            self.connect(word, (row, col));
+        }
+        sum
+    }
+
+    fn xor(&mut self, left: Self::Word, right: Self::Word) -> Self::Word {
+        let row = self.rows.next_row;
+        let out = self.rows.xor(left, right);
+        let (zero_row, xor_gates) = CircuitGate::create_xor_gadget(row, 64);
+        self.gates.extend(xor_gates);
+
+        // check that the final row of the XOR is zero
+        let zero_row = zero_row - 1;
+        self.connect((zero_row, 0), (zero_row, 1));
+        self.connect((zero_row, 0), (zero_row, 2));
+
+        self.connect(left, (row, 0));
+        self.connect(right, (row, 1));
+        out
+    }
+
+    fn rot(&mut self, word: Self::Word, rot: u32, side: RotMode) -> Self::Word {
+        let row = self.rows.next_row;
+        let rotated = self.rows.rot(word, rot, side);
+        let (_, rot_gates) = CircuitGate::create_rot(row, rot, side);
+        self.gates.extend(rot_gates);
+
+        // check that the shifted word has 64 bits
+        let zero_cell = self.rows.zero;
+        self.connect(zero_cell, (row + 1, 1));
+        self.connect(zero_cell, (row + 1, 2));
+
+        self.connect(word, (row, 0));
+        rotated
+    }
+}
+
+impl<F: PrimeField + SquareRootField> CircuitGate<F> {
+    /// Extends a BLAKE2b gadget to a circuit
+    /// Input:
+    /// - gates       : vector of circuit gates
+    /// - variant     : BLAKE2b-256 or BLAKE2b-512
+    /// - message_len : number of bytes of the hashed message
+    /// Output:
+    /// - layout of the gadget, giving the cells of the message and of the digest
+    /// Warning:
+    /// - don't forget to wire the message and the digest using the returned layout
+    pub fn extend_blake2b_gadget(
+        gates: &mut Vec<Self>,
+        variant: Blake2bVariant,
+        message_len: usize,
+    ) -> Blake2bLayout {
+        let start_row = gates.len();
+        let (_, blake2b_gates) = Self::create_blake2b_gadget(start_row, variant, message_len);
+        gates.extend(blake2b_gates);
+        Blake2bLayout::new(start_row, variant, message_len)
+    }
+
+    /// Creates a BLAKE2b gadget
+    /// Input:
+    /// - new_row     : row to start the BLAKE2b gadget
+    /// - variant     : BLAKE2b-256 or BLAKE2b-512
+    /// - message_len : number of bytes of the hashed message
+    /// Outputs tuple (next_row, circuit_gates) where
+    /// - next_row  : next row after this gadget
+    /// - gates     : vector of circuit gates comprising this gadget
+    /// Note: use [`Blake2bLayout`] to find the cells of the message and of the digest
+    pub fn create_blake2b_gadget(
+        new_row: usize,
+        variant: Blake2bVariant,
+        message_len: usize,
+    ) -> (usize, Vec<Self>) {
+        let mut builder = Blake2bGates::new(new_row);
+        blake2b(&mut builder, variant, message_len);
+        (builder.rows.next_row, builder.gates)
+    }
+}
+
+/// Creates the witness of the gadget
+struct Blake2bWitness<'a, F> {
+    witness: &'a mut [Vec<F>; COLUMNS],
+    message: &'a [u8],
+}
+
+impl<'a, F: PrimeField> Blake2bWitness<'a, F> {
+    fn new(witness: &'a mut [Vec<F>; COLUMNS], message: &'a [u8]) -> Self {
+        let mut builder = Self { witness, message };
+        builder.push_row(&[]);
+        builder
+    }
+
+    /// Appends a row starting with `values`
+    fn push_row(&mut self, values: &[F]) {
+        for (col, column) in self.witness.iter_mut().enumerate() {
+            column.push(values.get(col).copied().unwrap_or_else(F::zero));
+        }
+    }
+}
+
+impl<'a, F: PrimeField> Blake2bOps for Blake2bWitness<'a, F> {
+    type Word = u64;
+
+    fn constants(&mut self, values: &[u64]) -> Vec<Self::Word> {
+        for pair in values.chunks(2) {
+            let right = pair.get(1).copied().unwrap_or(0);
+            self.push_row(&[F::from(pair[0]), F::zero(), F::zero(), F::from(right)]);
+        }
+        values.to_vec()
+    }
+
+    fn message(&mut self, index: usize, bytes: usize) -> Self::Word {
+        if bytes == 0 {
+            return 0;
+        }
+        let mut le_bytes = [0u8; 8];
+        le_bytes[..bytes].copy_from_slice(&self.message[8 * index..8 * index + bytes]);
+        let word = u64::from_le_bytes(le_bytes);
+        range_check::bits::extend_bits(self.witness, F::from(word), 8 * bytes);
+        word
+    }
+
+    fn add(&mut self, words: &[Self::Word]) -> Self::Word {
+        let (a, b) = (words[0], words[1]);
+        let x = words.get(2).copied().unwrap_or(0);
+        let sum = a as u128 + b as u128 + x as u128;
+        let (k0, k1) = (sum >> 64 >= 1, sum >> 64 == 2);
+
+        let t = F::from(a) + F::from(b);
+        let s = t + F::from(x);
+        let s_prime = s - F::two_pow(64) * F::from(k0);
+        let c = sum as u64;
+        self.push_row(&[F::from(a), F::from(b), t, t, F::from(x), s]);
+        self.push_row(&[
+            F::from(k0),
+            F::from(k0),
+            F::zero(),
+            F::from(k1),
+            F::from(k1),
+        ]);
+        self.push_row(&[s, F::from(k0), s_prime, s_prime, F::from(k1), F::from(c)]);
+        let range_check = range_check::witness::create(F::from(c));
+        for (col, range_check_col) in self.witness.iter_mut().zip(range_check) {
+            col.extend(range_check_col);
+        }
+        c
+    }
+
+    fn xor(&mut self, left: Self::Word, right: Self::Word) -> Self::Word {
+        xor::extend_xor_witness(self.witness, F::from(left), F::from(right), 64);
+        left ^ right
+    }
+
+    fn rot(&mut self, word: Self::Word, rot: u32, side: RotMode) -> Self::Word {
+        rot::extend_rot(self.witness, word, rot, side);
+        match side {
+            RotMode::Left => word.rotate_left(rot),
+            RotMode::Right => word.rotate_right(rot),
+        }
+    }
+}
+
+/// Create a witness for the BLAKE2b gadget hashing `message` with the given `variant`,
+/// starting at row 0, and returns it along with the digest
+pub fn create_blake2b_witness<F: PrimeField>(
+    variant: Blake2bVariant,
+    message: &[u8],
+) -> ([Vec<F>; COLUMNS], Vec<u8>) {
+    let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![]);
+    let digest = extend_blake2b_witness(&mut witness, variant, message);
+    (witness, digest)
+}
+
+/// Extend an existing witness with the BLAKE2b gadget hashing `message` with the given `variant`,
+/// and returns the digest
+pub fn extend_blake2b_witness<F: PrimeField>(
+    witness: &mut [Vec<F>; COLUMNS],
+    variant: Blake2bVariant,
+    message: &[u8],
+) -> Vec<u8> {
+    let mut builder = Blake2bWitness::new(witness, message);
+    let words = blake2b(&mut builder, variant, message.len());
+    words
+        .into_iter()
+        .flat_map(u64::to_le_bytes)
+        .take(variant.digest_bytes())
+        .collect()
+}
+
+/// The little-endian 64-bit words of `bytes`, as the gadget reads messages and digests
+pub fn words_of_bytes<F: PrimeField>(bytes: &[u8]) -> Vec<F> {
+    bytes
+        .chunks(8)
+        .map(|chunk| {
+            let mut le_bytes = [0u8; 8];
+            le_bytes[..chunk.len()].copy_from_slice(chunk);
+            F::from(u64::from_le_bytes(le_bytes))
+        })
+        .collect()
+}
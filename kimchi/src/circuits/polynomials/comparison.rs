@@ -0,0 +1,440 @@
+//! This module includes the unsigned integer comparison gadgets and their witness generation.
+//! Note that this module does not need any new gate type.
+
+use super::range_check::bits::{self, RangeCheckBitsLayout};
+use crate::circuits::{
+    gate::{CircuitGate, Connect},
+    polynomial::COLUMNS,
+    wires::Wire,
+};
+use ark_ff::{PrimeField, SquareRootField};
+use std::array;
+
+//~ The comparison gadgets compute a boolean telling whether $a < b$ (`less_than`) or
+//~ $a \leq b$ (`less_or_equal`), for unsigned integers $a$ and $b$ of $n$ bits,
+//~ with $n$ being 8, 16, 32, 64 or 128.
+//~
+//~ Both gadgets compute $x = b - a + 2^n - \delta$ where $\delta = 1$ for `less_than` and
+//~ $\delta = 0$ for `less_or_equal`. As $a, b < 2^n$, we have $0 \leq x < 2^{n+1}$, and
+//~ $x \geq 2^n$ exactly when the comparison holds.
+//~ The gadget thus decomposes $x = 2^n \cdot out + r$ with a boolean $out$ and a value $r$
+//~ range checked on $n$ bits (see the bit-width range check gadget), which makes $out$ the result.
+//~
+//~ | Row   |  0    |  1  |  2  |   3   |   4   | 5 |
+//~ |:-----:|:-----:|:---:|:---:|:-----:|:-----:|:-:|
+//~ | 0     | $b$   | $a$ | $x$ | $out$ | $out$ |   |
+//~ | 1     | $out$ | $r$ | $x$ |       |       |   |
+//~
+//~ followed by the range check of $r$ on $n$ bits.
+//~
+//~ The `is_in_interval` gadget checks $lo \leq v \leq hi$ with two `less_or_equal` gadgets,
+//~ whose results are multiplied in a last generic row $(out_1, out_2, out)$.
+//~
+//~ ```admonish warning
+//~ The gadgets assume that their inputs are unsigned integers of $n$ bits,
+//~ which must be range checked by the circuit if they are not already.
+//~ ```
+
+/// The unsigned integer types supported by the comparison gadgets
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UInt {
+    /// 8-bit unsigned integer
+    U8,
+    /// 16-bit unsigned integer
+    U16,
+    /// 32-bit unsigned integer
+    U32,
+    /// 64-bit unsigned integer
+    U64,
+    /// 128-bit unsigned integer
+    U128,
+}
+
+impl UInt {
+    /// Number of bits of the type
+    pub fn bits(self) -> usize {
+        match self {
+            Self::U8 => 8,
+            Self::U16 => 16,
+            Self::U32 => 32,
+            Self::U64 => 64,
+            Self::U128 => 128,
+        }
+    }
+
+    /// Whether `value` fits in the type
+    pub fn contains(self, value: u128) -> bool {
+        self == Self::U128 || value >> self.bits() == 0
+    }
+}
+
+/// Describes where the cells of a comparison gadget are located in the circuit.
+/// Cells are given as `(row, col)` pairs, as expected by [`Connect::connect_cell_pair`].
+#[derive(Clone, Copy, Debug)]
+pub struct ComparisonLayout {
+    start_row: usize,
+    range_check: RangeCheckBitsLayout,
+}
+
+impl ComparisonLayout {
+    /// Creates the layout of a comparison gadget of `uint` values starting at `start_row`
+    pub fn new(start_row: usize, uint: UInt) -> Self {
+        Self {
+            start_row,
+            range_check: RangeCheckBitsLayout::new(start_row + 2, uint.bits()),
+        }
+    }
+
+    /// First row of the gadget
+    pub fn start_row(&self) -> usize {
+        self.start_row
+    }
+
+    /// Next empty row after the gadget
+    pub fn next_row(&self) -> usize {
+        self.range_check.next_row()
+    }
+
+    /// Number of rows used by the gadget
+    pub fn num_rows(&self) -> usize {
+        self.next_row() - self.start_row
+    }
+
+    /// Cell of the left operand `a`
+    pub fn a(&self) -> (usize, usize) {
+        (self.start_row, 1)
+    }
+
+    /// Cell of the right operand `b`
+    pub fn b(&self) -> (usize, usize) {
+        (self.start_row, 0)
+    }
+
+    /// Cell of the boolean result
+    pub fn out(&self) -> (usize, usize) {
+        (self.start_row, 3)
+    }
+}
+
+/// Describes where the cells of an interval gadget are located in the circuit.
+/// Cells are given as `(row, col)` pairs, as expected by [`Connect::connect_cell_pair`].
+#[derive(Clone, Copy, Debug)]
+pub struct IntervalLayout {
+    lower: ComparisonLayout,
+    upper: ComparisonLayout,
+}
+
+impl IntervalLayout {
+    /// Creates the layout of an interval gadget of `uint` values starting at `start_row`
+    pub fn new(start_row: usize, uint: UInt) -> Self {
+        let lower = ComparisonLayout::new(start_row, uint);
+        let upper = ComparisonLayout::new(lower.next_row(), uint);
+        Self { lower, upper }
+    }
+
+    /// First row of the gadget
+    pub fn start_row(&self) -> usize {
+        self.lower.start_row()
+    }
+
+    /// Next empty row after the gadget
+    pub fn next_row(&self) -> usize {
+        self.upper.next_row() + 1
+    }
+
+    /// Number of rows used by the gadget
+    pub fn num_rows(&self) -> usize {
+        self.next_row() - self.start_row()
+    }
+
+    /// Cell of the checked value
+    pub fn value(&self) -> (usize, usize) {
+        self.lower.b()
+    }
+
+    /// Cell of the lower bound of the interval
+    pub fn lower_bound(&self) -> (usize, usize) {
+        self.lower.a()
+    }
+
+    /// Cell of the upper bound of the interval
+    pub fn upper_bound(&self) -> (usize, usize) {
+        self.upper.b()
+    }
+
+    /// Cell of the boolean result
+    pub fn out(&self) -> (usize, usize) {
+        (self.upper.next_row(), 2)
+    }
+}
+
+impl<F: PrimeField + SquareRootField> CircuitGate<F> {
+    /// Extends a `less_than` gadget for `uint` values to a circuit
+    /// Input:
+    /// - gates : vector of circuit gates
+    /// - uint  : type of the compared values
+    /// Output:
+    /// - layout of the gadget, giving the cells of the operands and of the result
+    /// Warning:
+    /// - don't forget to wire the operands using the returned layout
+    pub fn extend_less_than(gates: &mut Vec<Self>, uint: UInt) -> ComparisonLayout {
+        let layout = ComparisonLayout::new(gates.len(), uint);
+        gates.extend(Self::comparison_gates(&layout, uint, true));
+        layout
+    }
+
+    /// Creates a `less_than` gadget for `uint` values
+    /// Outputs tuple (next_row, circuit_gates) where
+    /// - next_row  : next row after this gadget
+    /// - gates     : vector of circuit gates comprising this gadget
+    pub fn create_less_than(new_row: usize, uint: UInt) -> (usize, Vec<Self>) {
+        let layout = ComparisonLayout::new(new_row, uint);
+        (
+            layout.next_row(),
+            Self::comparison_gates(&layout, uint, true),
+        )
+    }
+
+    /// Extends a `less_or_equal` gadget for `uint` values to a circuit
+    /// Input:
+    /// - gates : vector of circuit gates
+    /// - uint  : type of the compared values
+    /// Output:
+    /// - layout of the gadget, giving the cells of the operands and of the result
+    /// Warning:
+    /// - don't forget to wire the operands using the returned layout
+    pub fn extend_less_or_equal(gates: &mut Vec<Self>, uint: UInt) -> ComparisonLayout {
+        let layout = ComparisonLayout::new(gates.len(), uint);
+        gates.extend(Self::comparison_gates(&layout, uint, false));
+        layout
+    }
+
+    /// Creates a `less_or_equal` gadget for `uint` values
+    /// Outputs tuple (next_row, circuit_gates) where
+    /// - next_row  : next row after this gadget
+    /// - gates     : vector of circuit gates comprising this gadget
+    pub fn create_less_or_equal(new_row: usize, uint: UInt) -> (usize, Vec<Self>) {
+        let layout = ComparisonLayout::new(new_row, uint);
+        (
+            layout.next_row(),
+            Self::comparison_gates(&layout, uint, false),
+        )
+    }
+
+    /// Extends an `is_in_interval` gadget for `uint` values to a circuit
+    /// Input:
+    /// - gates : vector of circuit gates
+    /// - uint  : type of the value and bounds
+    /// Output:
+    /// - layout of the gadget, giving the cells of the value, bounds and result
+    /// Warning:
+    /// - don't forget to wire the value and bounds using the returned layout
+    pub fn extend_is_in_interval(gates: &mut Vec<Self>, uint: UInt) -> IntervalLayout {
+        let start_row = gates.len();
+        let (_, interval) = Self::create_is_in_interval(start_row, uint);
+        gates.extend(interval);
+        IntervalLayout::new(start_row, uint)
+    }
+
+    /// Creates an `is_in_interval` gadget for `uint` values
+    /// Outputs tuple (next_row, circuit_gates) where
+    /// - next_row  : next row after this gadget
+    /// - gates     : vector of circuit gates comprising this gadget
+    pub fn create_is_in_interval(new_row: usize, uint: UInt) -> (usize, Vec<Self>) {
+        let layout = IntervalLayout::new(new_row, uint);
+        let rel = |(row, col): (usize, usize)| (row - new_row, col);
+
+        let mut gates = Self::comparison_gates(&layout.lower, uint, false);
+        gates.extend(Self::comparison_gates(&layout.upper, uint, false));
+        // out = out_lower * out_upper
+        gates.push(CircuitGate::create_generic(
+            Wire::for_row(layout.upper.next_row()),
+            [
+                F::zero(),
+                F::zero(),
+                -F::one(),
+                F::one(),
+                F::zero(),
+                F::zero(),
+                F::zero(),
+                F::zero(),
+                F::zero(),
+                F::zero(),
+            ],
+        ));
+
+        gates.connect_cell_pair(rel(layout.lower.b()), rel(layout.upper.a()));
+        gates.connect_cell_pair(rel(layout.lower.out()), rel((layout.upper.next_row(), 0)));
+        gates.connect_cell_pair(rel(layout.upper.out()), rel((layout.upper.next_row(), 1)));
+
+        (layout.next_row(), gates)
+    }
+
+    fn comparison_gates(layout: &ComparisonLayout, uint: UInt, strict: bool) -> Vec<Self> {
+        let start_row = layout.start_row();
+        let one = F::one();
+        let zero = F::zero();
+        let two_to_n = F::from(2u64).pow([uint.bits() as u64]);
+        let offset = if strict { two_to_n - one } else { two_to_n };
+
+        let mut gates = vec![
+            // x = b - a + 2^n - delta, and out * out = out
+            CircuitGate::create_generic(
+                Wire::for_row(start_row),
+                [one, -one, -one, zero, offset, -one, zero, zero, one, zero],
+            ),
+            // x = 2^n * out + r
+            CircuitGate::create_generic(
+                Wire::for_row(start_row + 1),
+                [
+                    two_to_n, one, -one, zero, zero, zero, zero, zero, zero, zero,
+                ],
+            ),
+        ];
+        let (_, range_check) = CircuitGate::create_range_check_bits(start_row + 2, uint.bits());
+        gates.extend(range_check);
+
+        gates.connect_cell_pair((0, 3), (0, 4));
+        gates.connect_cell_pair((0, 3), (1, 0));
+        gates.connect_cell_pair((0, 2), (1, 2));
+        let (row, col) = layout.range_check.value();
+        gates.connect_cell_pair((1, 1), (row - start_row, col));
+
+        gates
+    }
+}
+
+/// Create a witness for the `less_than` gadget comparing `a < b` for `uint` values,
+/// starting at row 0, and returns it along with the result of the comparison
+///
+/// # Panics
+///
+/// Will panic if `a` or `b` does not fit in `uint`.
+pub fn create_less_than_witness<F: PrimeField>(
+    uint: UInt,
+    a: u128,
+    b: u128,
+) -> ([Vec<F>; COLUMNS], bool) {
+    comparison_witness(uint, a, b, true)
+}
+
+/// Create a witness for the `less_or_equal` gadget comparing `a <= b` for `uint` values,
+/// starting at row 0, and returns it along with the result of the comparison
+///
+/// # Panics
+///
+/// Will panic if `a` or `b` does not fit in `uint`.
+pub fn create_less_or_equal_witness<F: PrimeField>(
+    uint: UInt,
+    a: u128,
+    b: u128,
+) -> ([Vec<F>; COLUMNS], bool) {
+    comparison_witness(uint, a, b, false)
+}
+
+/// Create a witness for the `is_in_interval` gadget checking `lower <= value <= upper`
+/// for `uint` values, starting at row 0, and returns it along with the result of the check
+///
+/// # Panics
+///
+/// Will panic if `value`, `lower` or `upper` does not fit in `uint`.
+pub fn create_is_in_interval_witness<F: PrimeField>(
+    uint: UInt,
+    value: u128,
+    lower: u128,
+    upper: u128,
+) -> ([Vec<F>; COLUMNS], bool) {
+    let (mut witness, above) = comparison_witness::<F>(uint, lower, value, false);
+    let (upper_witness, below) = comparison_witness::<F>(uint, value, upper, false);
+    let out = above && below;
+    for col in 0..COLUMNS {
+        witness[col].extend(upper_witness[col].iter());
+    }
+    let products = [F::from(above), F::from(below), F::from(out)];
+    for (col, column) in witness.iter_mut().enumerate() {
+        column.push(products.get(col).copied().unwrap_or_else(F::zero));
+    }
+    (witness, out)
+}
+
+/// Extend an existing witness with a `less_than` gadget comparing `a < b` for `uint` values,
+/// and returns the result of the comparison
+pub fn extend_less_than_witness<F: PrimeField>(
+    witness: &mut [Vec<F>; COLUMNS],
+    uint: UInt,
+    a: u128,
+    b: u128,
+) -> bool {
+    let (comparison, out) = create_less_than_witness(uint, a, b);
+    for col in 0..COLUMNS {
+        witness[col].extend(comparison[col].iter())
+    }
+    out
+}
+
+/// Extend an existing witness with a `less_or_equal` gadget comparing `a <= b` for `uint` values,
+/// and returns the result of the comparison
+pub fn extend_less_or_equal_witness<F: PrimeField>(
+    witness: &mut [Vec<F>; COLUMNS],
+    uint: UInt,
+    a: u128,
+    b: u128,
+) -> bool {
+    let (comparison, out) = create_less_or_equal_witness(uint, a, b);
+    for col in 0..COLUMNS {
+        witness[col].extend(comparison[col].iter())
+    }
+    out
+}
+
+/// Extend an existing witness with an `is_in_interval` gadget checking
+/// `lower <= value <= upper` for `uint` values, and returns the result of the check
+pub fn extend_is_in_interval_witness<F: PrimeField>(
+    witness: &mut [Vec<F>; COLUMNS],
+    uint: UInt,
+    value: u128,
+    lower: u128,
+    upper: u128,
+) -> bool {
+    let (interval, out) = create_is_in_interval_witness(uint, value, lower, upper);
+    for col in 0..COLUMNS {
+        witness[col].extend(interval[col].iter())
+    }
+    out
+}
+
+fn comparison_witness<F: PrimeField>(
+    uint: UInt,
+    a: u128,
+    b: u128,
+    strict: bool,
+) -> ([Vec<F>; COLUMNS], bool) {
+    assert!(
+        uint.contains(a) && uint.contains(b),
+        "operands do not fit in {uint:?}"
+    );
+    let out = if strict { a < b } else { a <= b };
+
+    let two_to_n = F::from(2u64).pow([uint.bits() as u64]);
+    let offset = if strict {
+        two_to_n - F::one()
+    } else {
+        two_to_n
+    };
+    let x = F::from(b) - F::from(a) + offset;
+    let out_f = F::from(out);
+    let r = x - two_to_n * out_f;
+
+    let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero(); 2]);
+    witness[0][0] = F::from(b);
+    witness[1][0] = F::from(a);
+    witness[2][0] = x;
+    witness[3][0] = out_f;
+    witness[4][0] = out_f;
+    witness[0][1] = out_f;
+    witness[1][1] = r;
+    witness[2][1] = x;
+    bits::extend_bits(&mut witness, r, uint.bits());
+
+    (witness, out)
+}
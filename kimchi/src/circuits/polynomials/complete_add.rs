@@ -297,3 +297,47 @@ impl<F: PrimeField> CircuitGate<F> {
         Ok(())
     }
 }
+
+/// Writes the witness of a complete-add gate at `row` adding `p1` and `p2`,
+/// and returns the result of the addition.
+/// The values that cannot be computed (for instance when doubling a point with a zero
+/// `y` coordinate, which is not on a prime order curve) are set to zero.
+pub fn witness<F: Field>(w: &mut [Vec<F>; COLUMNS], row: usize, p1: (F, F), p2: (F, F)) -> (F, F) {
+    let (x1, y1) = p1;
+    let (x2, y2) = p2;
+    let same_x = x1 == x2;
+    let inf = same_x && y1 != y2;
+
+    let s = if same_x {
+        // 2 * s * y1 = 3 * x1^2
+        let x1_squared = x1.square();
+        (x1_squared.double() + x1_squared) * y1.double().inverse().unwrap_or_else(F::zero)
+    } else {
+        // (x2 - x1) * s = y2 - y1
+        (y2 - y1) * (x2 - x1).inverse().unwrap_or_else(F::zero)
+    };
+    let x3 = s.square() - x1 - x2;
+    let y3 = s * (x1 - x3) - y1;
+
+    w[0][row] = x1;
+    w[1][row] = y1;
+    w[2][row] = x2;
+    w[3][row] = y2;
+    w[4][row] = x3;
+    w[5][row] = y3;
+    w[6][row] = F::from(u64::from(inf));
+    w[7][row] = F::from(u64::from(same_x));
+    w[8][row] = s;
+    w[9][row] = if inf {
+        (y2 - y1).inverse().unwrap_or_else(F::zero)
+    } else {
+        F::zero()
+    };
+    w[10][row] = if same_x {
+        F::zero()
+    } else {
+        (x2 - x1).inverse().unwrap_or_else(F::zero)
+    };
+
+    (x3, y3)
+}
@@ -0,0 +1,558 @@
+//! This module implements a read-write memory (RAM) consistency argument,
+//! built on top of the lookup argument and of runtime tables.
+//!
+//! It includes the `mem_read` and `mem_write` gadgets, the definition of the
+//! `MemoryAccess` and `MemoryCheck` custom gates, and the code for witness generation.
+
+use crate::circuits::{
+    argument::{Argument, ArgumentEnv, ArgumentType},
+    custom_gate::CustomGate,
+    expr::{constraints::ExprOps, Cache},
+    gate::{CircuitGate, Connect, GateType},
+    lookup::runtime_tables::{RuntimeTable, RuntimeTableCfg},
+    polynomial::COLUMNS,
+    polynomials::range_check::bits,
+    wires::Wire,
+};
+use ark_ff::{BigInteger, PrimeField, SquareRootField};
+use o1_utils::field_helpers::i32_to_field;
+use std::{collections::HashMap, marker::PhantomData};
+
+//~ The memory argument lets a circuit read and write a memory, that is a map from addresses
+//~ to values where every address initially holds zero.
+//~ Each access of the memory is an `(address, value, timestamp, is_write)` tuple,
+//~ where the timestamps are the positions of the accesses in the circuit
+//~ (from $0$ to $n - 1$ for $n$ accesses).
+//~
+//~ The argument follows the classical approach of sorting the accesses:
+//~
+//~ 1. Every access is written in a runtime table $R$ indexed by its timestamp.
+//~    Its entries are the pairs `(t, address)`, `(t + 2^64, value)` and `(t + 2^65, is_write)`
+//~    for each timestamp $t$, so that the prover fixes a single access per timestamp.
+//~ 2. The accesses of the circuit (the `mem_read` and `mem_write` gadgets), whose timestamps
+//~    are fixed by the circuit, are looked up in $R$.
+//~ 3. The accesses are repeated in a region of the circuit, sorted by address and then
+//~    by timestamp, and are also looked up in $R$.
+//~    Two accesses with the same address have increasing timestamps, so that
+//~    the sorted region holds every access of $R$ exactly once.
+//~    Consecutive accesses are checked to be consistent: a read returns the value
+//~    of the previous access to the same address, or zero for the first access of an address.
+//~
+//~ The lookups are performed by `Lookup` gates, whose table ID and indexes are constrained by
+//~ custom gates on the row above them.
+//~
+//~ **Memory access (`mem_read` or `mem_write`):**
+//~
+//~ | Row | `CircuitGate` | Purpose                                                 |
+//~ | --- | ------------- | ------------------------------------------------------- |
+//~ |   0 | `MemoryAccess` (custom) | Fix table ID, timestamp and kind of the access  |
+//~ |   1 | `Lookup`      | Look up the access in $R$                               |
+//~
+//~ where the `Lookup` row holds `(id, t, address, t + 2^64, value, t + 2^65, is_write)`.
+//~
+//~ **Sorted accesses:** for each access,
+//~
+//~ | Row | `CircuitGate` | Purpose                                                 |
+//~ | --- | ------------- | ------------------------------------------------------- |
+//~ |   0 | `MemoryCheck` (custom) | Check consistency with the previous access     |
+//~ |   1 | `Lookup`      | Look up the access in $R$                               |
+//~ |  2+ | range check   | Range check the gap between the two accesses            |
+//~
+//~ where the `MemoryCheck` row holds the address, value and timestamp of the previous access
+//~ (copied from its `Lookup` row), the flag $s$ telling if both accesses have the same address,
+//~ and the gap $d$ between the two accesses.
+//~ When $s = 1$, the gap is $t - t_{prev} - 1$, and otherwise it is
+//~ $address - address_{prev} - 1$, so that the accesses are sorted.
+//~
+//~ ```admonish warning
+//~ The custom gates of the memory use reserved selector ids, so that a circuit
+//~ can only hold a single memory.
+//~ ```
+
+/// The selector id of the `MemoryAccess` custom gate
+pub const MEMORY_ACCESS_GATE_ID: u32 = 0xFFFF_0000;
+
+/// The selector id of the `MemoryCheck` custom gate
+pub const MEMORY_CHECK_GATE_ID: u32 = 0xFFFF_0001;
+
+/// Maximum number of bits of the gap between two sorted accesses
+pub const MAX_MEMORY_GAP_BITS: usize = 64;
+
+/// Number of rows of a memory access
+const ACCESS_ROWS: usize = 2;
+
+/// The kind of a memory access
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryOp {
+    /// Read the value stored at an address
+    Read,
+    /// Write a value at an address
+    Write,
+}
+
+/// Describes where the cells of a memory access are located in the circuit.
+/// Cells are given as `(row, col)` pairs, as expected by [`Connect::connect_cell_pair`].
+#[derive(Clone, Copy, Debug)]
+pub struct MemoryAccessLayout {
+    start_row: usize,
+    timestamp: usize,
+    op: MemoryOp,
+}
+
+impl MemoryAccessLayout {
+    /// First row of the access
+    pub fn start_row(&self) -> usize {
+        self.start_row
+    }
+
+    /// Next empty row after the access
+    pub fn next_row(&self) -> usize {
+        self.start_row + ACCESS_ROWS
+    }
+
+    /// Timestamp of the access
+    pub fn timestamp(&self) -> usize {
+        self.timestamp
+    }
+
+    /// Kind of the access
+    pub fn op(&self) -> MemoryOp {
+        self.op
+    }
+
+    /// Cell holding the address
+    pub fn address(&self) -> (usize, usize) {
+        (self.start_row + 1, 2)
+    }
+
+    /// Cell holding the value read or written
+    pub fn value(&self) -> (usize, usize) {
+        (self.start_row + 1, 4)
+    }
+}
+
+/// The circuit side of a memory, which records the accesses of the circuit
+#[derive(Clone, Debug)]
+pub struct Memory {
+    table_id: i32,
+    gap_bits: usize,
+    accesses: Vec<MemoryAccessLayout>,
+}
+
+impl Memory {
+    /// Creates a memory whose accesses are stored in the runtime table `table_id`,
+    /// and whose sorted accesses have gaps (of addresses or timestamps) of at most
+    /// `2^gap_bits`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `table_id` is zero, or if `gap_bits` is zero or larger than
+    /// [`MAX_MEMORY_GAP_BITS`].
+    pub fn new(table_id: i32, gap_bits: usize) -> Self {
+        assert_ne!(table_id, 0, "the table ID 0 is reserved");
+        assert!(
+            (1..=MAX_MEMORY_GAP_BITS).contains(&gap_bits),
+            "memory gaps are of 1 to {MAX_MEMORY_GAP_BITS} bits, got {gap_bits}"
+        );
+        Self {
+            table_id,
+            gap_bits,
+            accesses: vec![],
+        }
+    }
+
+    /// The ID of the runtime table of the memory
+    pub fn table_id(&self) -> i32 {
+        self.table_id
+    }
+
+    /// The number of accesses recorded so far
+    pub fn num_accesses(&self) -> usize {
+        self.accesses.len()
+    }
+
+    /// Extends a memory read gadget to a circuit
+    /// Input:
+    /// - gates : vector of circuit gates
+    /// Output:
+    /// - layout of the access, giving the cells of the address and of the value read
+    /// Warning:
+    /// - don't forget to wire the address and the value using the returned layout
+    pub fn mem_read<F: PrimeField>(
+        &mut self,
+        gates: &mut Vec<CircuitGate<F>>,
+    ) -> MemoryAccessLayout {
+        self.extend_access(gates, MemoryOp::Read)
+    }
+
+    /// Extends a memory write gadget to a circuit
+    /// Input:
+    /// - gates : vector of circuit gates
+    /// Output:
+    /// - layout of the access, giving the cells of the address and of the value written
+    /// Warning:
+    /// - don't forget to wire the address and the value using the returned layout
+    pub fn mem_write<F: PrimeField>(
+        &mut self,
+        gates: &mut Vec<CircuitGate<F>>,
+    ) -> MemoryAccessLayout {
+        self.extend_access(gates, MemoryOp::Write)
+    }
+
+    fn extend_access<F: PrimeField>(
+        &mut self,
+        gates: &mut Vec<CircuitGate<F>>,
+        op: MemoryOp,
+    ) -> MemoryAccessLayout {
+        let layout = MemoryAccessLayout {
+            start_row: gates.len(),
+            timestamp: self.accesses.len(),
+            op,
+        };
+        let is_write = F::from(op == MemoryOp::Write);
+        gates.push(CircuitGate::new(
+            GateType::Zero,
+            Wire::for_row(layout.start_row),
+            vec![
+                i32_to_field(self.table_id),
+                F::from(layout.timestamp as u64),
+                is_write,
+            ],
+        ));
+        gates.push(CircuitGate::new(
+            GateType::Lookup,
+            Wire::for_row(layout.start_row + 1),
+            vec![],
+        ));
+        self.accesses.push(layout);
+        layout
+    }
+
+    /// Extends the circuit with the sorted accesses of the memory, which must come
+    /// after all the accesses of the circuit
+    /// Input:
+    /// - gates : vector of circuit gates
+    /// Output:
+    /// - the runtime table and the custom gates to register on the constraint system
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the memory has no access, or if the timestamps do not fit in the gaps.
+    pub fn finalize<F: PrimeField + SquareRootField>(
+        self,
+        gates: &mut Vec<CircuitGate<F>>,
+    ) -> MemoryCircuit<F> {
+        let num_accesses = self.accesses.len();
+        assert!(num_accesses > 0, "the memory has no access");
+        assert!(
+            self.gap_bits == MAX_MEMORY_GAP_BITS || num_accesses <= 1 << self.gap_bits,
+            "{num_accesses} accesses do not fit in gaps of {} bits",
+            self.gap_bits
+        );
+
+        let table_id = i32_to_field(self.table_id);
+        let mut check_rows = Vec::with_capacity(num_accesses);
+        let mut prev_lookup_row: Option<usize> = None;
+        for _ in 0..num_accesses {
+            let check_row = gates.len();
+            let first = F::from(prev_lookup_row.is_none());
+            gates.push(CircuitGate::new(
+                GateType::Zero,
+                Wire::for_row(check_row),
+                vec![table_id, first],
+            ));
+            gates.push(CircuitGate::new(
+                GateType::Lookup,
+                Wire::for_row(check_row + 1),
+                vec![],
+            ));
+            let gap = CircuitGate::extend_range_check_bits(gates, self.gap_bits);
+            gates.connect_cell_pair((check_row, 4), gap.value());
+
+            // copy the address, value and timestamp of the previous access
+            if let Some(prev) = prev_lookup_row {
+                gates.connect_cell_pair((check_row, 0), (prev, 2));
+                gates.connect_cell_pair((check_row, 1), (prev, 4));
+                gates.connect_cell_pair((check_row, 2), (prev, 1));
+            }
+            prev_lookup_row = Some(check_row + 1);
+            check_rows.push(check_row);
+        }
+
+        let custom_gates = vec![
+            CustomGate::new(
+                MemoryAccess::default(),
+                self.accesses.iter().map(|a| a.start_row).collect(),
+            ),
+            CustomGate::new(MemoryCheck::default(), check_rows),
+        ];
+
+        MemoryCircuit {
+            table_id: self.table_id,
+            num_accesses,
+            custom_gates,
+        }
+    }
+}
+
+/// The result of [`Memory::finalize`], to be registered on the
+/// [`Builder`](crate::circuits::constraints::Builder) of the constraint system
+#[derive(Clone, Debug)]
+pub struct MemoryCircuit<F: PrimeField> {
+    table_id: i32,
+    num_accesses: usize,
+    custom_gates: Vec<CustomGate<F>>,
+}
+
+impl<F: PrimeField> MemoryCircuit<F> {
+    /// The configuration of the runtime table of the memory
+    pub fn runtime_table_cfg(&self) -> RuntimeTableCfg<F> {
+        RuntimeTableCfg::Custom {
+            id: self.table_id,
+            first_column: table_keys(self.num_accesses),
+        }
+    }
+
+    /// The custom gates of the memory
+    pub fn custom_gates(&self) -> &[CustomGate<F>] {
+        &self.custom_gates
+    }
+}
+
+// The keys of the entries of the runtime table: `t`, `t + 2^64` and `t + 2^65` for each timestamp
+fn table_keys<F: PrimeField>(num_accesses: usize) -> Vec<F> {
+    (0..3)
+        .flat_map(|i| (0..num_accesses).map(move |t| lookup_keys::<F>(t)[i]))
+        .collect()
+}
+
+//~ `MemoryAccess` - fixes the table ID, the indexes and the kind of a memory access
+//~
+//~ * This custom gate operates on the `Curr` and `Next` rows, and only constrains the `Next` row,
+//~   which holds a `Lookup` gate.
+//~ * Its coefficients are the table ID, the timestamp $t$ and `is_write`.
+
+/// The constraints of a memory access
+#[derive(Default)]
+pub struct MemoryAccess<F>(PhantomData<F>);
+
+impl<F> Argument<F> for MemoryAccess<F>
+where
+    F: PrimeField,
+{
+    const ARGUMENT_TYPE: ArgumentType = ArgumentType::CustomGate(MEMORY_ACCESS_GATE_ID);
+    const CONSTRAINTS: u32 = 5;
+
+    fn constraint_checks<T: ExprOps<F>>(env: &ArgumentEnv<F, T>, _cache: &mut Cache) -> Vec<T> {
+        let timestamp = env.witness_next(1);
+        vec![
+            // the table of the memory
+            env.witness_next(0) - env.coeff(0),
+            // the keys of the access
+            timestamp.clone() - env.coeff(1),
+            env.witness_next(3) - timestamp.clone() - T::two_pow(64),
+            env.witness_next(5) - timestamp - T::two_pow(65),
+            // the kind of the access
+            env.witness_next(6) - env.coeff(2),
+        ]
+    }
+}
+
+//~ `MemoryCheck` - checks that a sorted access is consistent with the previous one
+//~
+//~ * This custom gate operates on the `Curr` and `Next` rows, the `Next` row holding a `Lookup` gate.
+//~ * Its coefficients are the table ID and a flag set on the first sorted access,
+//~   which has no previous access.
+//~
+//~ | Column |          `Curr`  |          `Next`  |
+//~ | ------ | ---------------- | ---------------- |
+//~ |      0 | `address_prev`   | table ID         |
+//~ |      1 | `value_prev`     | $t$              |
+//~ |      2 | `t_prev`         | `address`        |
+//~ |      3 | $s$              | $t + 2^{64}$     |
+//~ |      4 | $d$              | `value`          |
+//~ |      5 |                  | $t + 2^{65}$     |
+//~ |      6 |                  | `is_write`       |
+
+/// The consistency checks of the sorted memory accesses
+#[derive(Default)]
+pub struct MemoryCheck<F>(PhantomData<F>);
+
+impl<F> Argument<F> for MemoryCheck<F>
+where
+    F: PrimeField,
+{
+    const ARGUMENT_TYPE: ArgumentType = ArgumentType::CustomGate(MEMORY_CHECK_GATE_ID);
+    const CONSTRAINTS: u32 = 9;
+
+    fn constraint_checks<T: ExprOps<F>>(env: &ArgumentEnv<F, T>, cache: &mut Cache) -> Vec<T> {
+        let prev_address = env.witness_curr(0);
+        let prev_value = env.witness_curr(1);
+        let prev_timestamp = env.witness_curr(2);
+        let same = env.witness_curr(3);
+        let gap = env.witness_curr(4);
+
+        let timestamp = env.witness_next(1);
+        let address = env.witness_next(2);
+        let value = env.witness_next(4);
+        let is_write = env.witness_next(6);
+
+        let address_diff = (address - prev_address).cache(cache);
+        let expected_gap = same.clone() * (timestamp.clone() - prev_timestamp - T::one())
+            + (T::one() - same.clone()) * (address_diff.clone() - T::one());
+
+        vec![
+            // the table of the memory
+            env.witness_next(0) - env.coeff(0),
+            // the keys of the access
+            env.witness_next(3) - timestamp.clone() - T::two_pow(64),
+            env.witness_next(5) - timestamp - T::two_pow(65),
+            // the flags are boolean
+            is_write.boolean(),
+            same.boolean(),
+            // the first access has no previous access
+            env.coeff(1) * same.clone(),
+            // same addresses are equal
+            same.clone() * address_diff,
+            // the accesses are sorted
+            gap - expected_gap,
+            // a read returns the previous value of the address, or zero
+            (T::one() - is_write) * (value - same * prev_value),
+        ]
+    }
+}
+
+/// The witness side of a memory, which keeps track of its content
+#[derive(Clone, Debug)]
+pub struct MemoryWitness<F: PrimeField> {
+    table_id: i32,
+    gap_bits: usize,
+    content: HashMap<F, F>,
+    accesses: Vec<(F, F, MemoryOp)>,
+}
+
+impl<F: PrimeField> MemoryWitness<F> {
+    /// Creates the witness of a memory, with the same parameters as [`Memory::new`]
+    pub fn new(table_id: i32, gap_bits: usize) -> Self {
+        Self {
+            table_id,
+            gap_bits,
+            content: HashMap::new(),
+            accesses: vec![],
+        }
+    }
+
+    /// Extend an existing witness with a memory read of `address`,
+    /// and returns the value read
+    pub fn extend_read(&mut self, witness: &mut [Vec<F>; COLUMNS], address: F) -> F {
+        let value = self.content.get(&address).copied().unwrap_or_else(F::zero);
+        self.extend_access(witness, address, value, MemoryOp::Read);
+        value
+    }
+
+    /// Extend an existing witness with a memory write of `value` at `address`
+    pub fn extend_write(&mut self, witness: &mut [Vec<F>; COLUMNS], address: F, value: F) {
+        self.content.insert(address, value);
+        self.extend_access(witness, address, value, MemoryOp::Write);
+    }
+
+    fn extend_access(
+        &mut self,
+        witness: &mut [Vec<F>; COLUMNS],
+        address: F,
+        value: F,
+        op: MemoryOp,
+    ) {
+        let timestamp = self.accesses.len();
+        for col in witness.iter_mut() {
+            col.extend([F::zero(); ACCESS_ROWS]);
+        }
+        let row = witness[0].len() - 1;
+        self.set_lookup_row(witness, row, timestamp, address, value, op);
+        self.accesses.push((address, value, op));
+    }
+
+    fn set_lookup_row(
+        &self,
+        witness: &mut [Vec<F>; COLUMNS],
+        row: usize,
+        timestamp: usize,
+        address: F,
+        value: F,
+        op: MemoryOp,
+    ) {
+        let keys = lookup_keys(timestamp);
+        witness[0][row] = i32_to_field(self.table_id);
+        witness[1][row] = keys[0];
+        witness[2][row] = address;
+        witness[3][row] = keys[1];
+        witness[4][row] = value;
+        witness[5][row] = keys[2];
+        witness[6][row] = F::from(op == MemoryOp::Write);
+    }
+
+    /// Extend an existing witness with the sorted accesses of the memory,
+    /// which must come after all the accesses, and returns the runtime table of the memory
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the gap between two sorted addresses does not fit in the gaps of the memory.
+    pub fn finalize(self, witness: &mut [Vec<F>; COLUMNS]) -> RuntimeTable<F> {
+        let mut sorted: Vec<_> = self.accesses.iter().enumerate().collect();
+        sorted.sort_by_key(|(timestamp, (address, _, _))| (address.into_repr(), *timestamp));
+
+        let mut prev: Option<(usize, F, F)> = None;
+        for &(timestamp, &(address, value, op)) in &sorted {
+            let (same, gap, (prev_timestamp, prev_address, prev_value)) = match prev {
+                Some((prev_timestamp, prev_address, prev_value)) if prev_address == address => (
+                    true,
+                    F::from((timestamp - prev_timestamp - 1) as u64),
+                    (prev_timestamp, prev_address, prev_value),
+                ),
+                Some(prev) => (false, address - prev.1 - F::one(), prev),
+                // the first access follows a virtual access to the previous address
+                None => (false, F::zero(), (0, address - F::one(), F::zero())),
+            };
+            assert!(
+                gap.into_repr().num_bits() as usize <= self.gap_bits,
+                "the gap between addresses {} and {address} does not fit in {} bits",
+                prev_address,
+                self.gap_bits
+            );
+
+            for col in witness.iter_mut() {
+                col.extend([F::zero(); ACCESS_ROWS]);
+            }
+            let row = witness[0].len() - ACCESS_ROWS;
+            witness[0][row] = prev_address;
+            witness[1][row] = prev_value;
+            witness[2][row] = lookup_keys::<F>(prev_timestamp)[0];
+            witness[3][row] = F::from(same);
+            witness[4][row] = gap;
+            self.set_lookup_row(witness, row + 1, timestamp, address, value, op);
+            bits::extend_bits(witness, gap, self.gap_bits);
+
+            prev = Some((timestamp, address, value));
+        }
+
+        let mut data = Vec::with_capacity(3 * self.accesses.len());
+        data.extend(self.accesses.iter().map(|(address, _, _)| *address));
+        data.extend(self.accesses.iter().map(|(_, value, _)| *value));
+        data.extend(
+            self.accesses
+                .iter()
+                .map(|(_, _, op)| F::from(*op == MemoryOp::Write)),
+        );
+        RuntimeTable {
+            id: self.table_id,
+            data,
+        }
+    }
+}
+
+// The keys of the runtime table looked up by an access at `timestamp`
+fn lookup_keys<F: PrimeField>(timestamp: usize) -> [F; 3] {
+    let shift = F::from(2u64).pow([64]);
+    let t = F::from(timestamp as u64);
+    [t, t + shift, t + shift.double()]
+}
@@ -0,0 +1,304 @@
+//! This module includes the Poseidon Merkle path membership gadget and its witness generation.
+//! Nodes are hashed as in Mina's ledger, see [`mina_hasher::merkle`].
+//! Note that this module does not need any new gate type.
+
+use super::{
+    poseidon::{self, POS_ROWS_PER_HASH, SPONGE_WIDTH},
+    poseidon_sponge::SPONGE_PERMUTATION_ROWS,
+};
+use crate::circuits::{
+    gate::{CircuitGate, Connect},
+    polynomial::COLUMNS,
+    wires::Wire,
+};
+use ark_ff::{PrimeField, SquareRootField};
+use mina_hasher::{domain_prefix_to_field, merkle::MerkleNode, Hashable};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    poseidon::{ArithmeticSponge, ArithmeticSpongeParams, Sponge},
+};
+use std::array;
+
+//~ The Merkle path gadget proves that a leaf belongs to a Poseidon Merkle tree of a given depth.
+//~ Each level of the path merges the current node with its sibling, in an order given by
+//~ the bit of the leaf index at that level (`1` when the current node is a right child).
+//~
+//~ Mina's ledger hashes a node with the kimchi hasher, initialized with the domain string
+//~ `MinaMklTree<height>` where `height` is the height of the merged children (leaves being at height 0).
+//~ The hasher absorbs the domain string and squeezes once, so the state of the sponge
+//~ before absorbing the children is a constant $(c_0, c_1, c_2)$ for each height.
+//~ Absorbing the two children and squeezing is then a single permutation of the state
+//~ $(c_0 + left, c_1 + right, c_2)$.
+//~
+//~ Each level of the gadget uses the following rows:
+//~
+//~ | Row          | `CircuitGate`               | Purpose                                               |
+//~ | ------------ | --------------------------- | ----------------------------------------------------- |
+//~ | 0            | `Generic`                   | `diff = sibling - node` and `t = bit * diff`          |
+//~ | 1            | `Generic`                   | `s0 = node + t + c0` and `s1 = sibling - t + c1`      |
+//~ | 2            | `Generic`                   | `bit * bit = bit` and `s2 = c2`                       |
+//~ | 3 .. 14      | `Poseidon` (x 11) + `Zero`  | Permutation of `(s0, s1, s2)`, the parent is in `14`  |
+//~
+//~ with the following layout for the generic rows:
+//~
+//~ | Row |    0    |  1   |  2   |    3    |   4  |   5  |
+//~ |:---:|:-------:|:----:|:----:|:-------:|:----:|:----:|
+//~ |  0  | sibling | node | diff | bit     | diff | t    |
+//~ |  1  | node    | t    | s0   | sibling | t    | s1   |
+//~ |  2  | bit     | bit  |      | s2      |      |      |
+//~
+//~ Note that `node + t` is the left child and `sibling - t` is the right child.
+//~ The parent node (in column 0 of the last row of the permutation) is wired to the
+//~ `node` cells of the next level, and the parent of the last level is the root of the tree.
+
+/// Number of rows used by each level of the Merkle path gadget
+pub const MERKLE_LEVEL_ROWS: usize = 3 + SPONGE_PERMUTATION_ROWS;
+
+/// Describes where the cells of a Merkle path gadget are located in the circuit.
+/// Cells are given as `(row, col)` pairs, as expected by [`Connect::connect_cell_pair`].
+#[derive(Clone, Copy, Debug)]
+pub struct MerklePathLayout {
+    start_row: usize,
+    depth: usize,
+}
+
+impl MerklePathLayout {
+    /// Creates the layout of a Merkle path gadget of `depth` levels starting at `start_row`
+    pub fn new(start_row: usize, depth: usize) -> Self {
+        Self { start_row, depth }
+    }
+
+    /// Next empty row after the gadget
+    pub fn next_row(&self) -> usize {
+        self.start_row + self.depth * MERKLE_LEVEL_ROWS
+    }
+
+    /// Cell holding the leaf
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the depth of the gadget is zero.
+    pub fn leaf(&self) -> (usize, usize) {
+        assert!(self.depth > 0, "merkle path of depth zero has no leaf cell");
+        (self.level_row(0), 1)
+    }
+
+    /// Cell holding the sibling of the node at `height`
+    pub fn sibling(&self, height: usize) -> (usize, usize) {
+        (self.level_row(height), 0)
+    }
+
+    /// Cell holding the direction bit of the node at `height`
+    pub fn bit(&self, height: usize) -> (usize, usize) {
+        (self.level_row(height), 3)
+    }
+
+    /// Cell holding the root of the tree
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the depth of the gadget is zero.
+    pub fn root(&self) -> (usize, usize) {
+        assert!(self.depth > 0, "merkle path of depth zero has no root cell");
+        (self.parent_row(self.depth - 1), 0)
+    }
+
+    fn level_row(&self, height: usize) -> usize {
+        assert!(height < self.depth, "height {height} is out of bounds");
+        self.start_row + height * MERKLE_LEVEL_ROWS
+    }
+
+    fn permutation_row(&self, height: usize) -> usize {
+        self.level_row(height) + 3
+    }
+
+    fn parent_row(&self, height: usize) -> usize {
+        self.permutation_row(height) + POS_ROWS_PER_HASH
+    }
+}
+
+/// Returns the state of the kimchi hasher once initialized for merging nodes at `height`,
+/// that is after absorbing the domain string of [`MerkleNode`] and squeezing once
+pub fn merkle_node_initial_state<F: PrimeField>(
+    params: &'static ArithmeticSpongeParams<F>,
+    height: usize,
+) -> [F; SPONGE_WIDTH] {
+    let domain_string =
+        MerkleNode::domain_string(height as u64).expect("merkle nodes have a domain string");
+    let mut sponge = ArithmeticSponge::<F, PlonkSpongeConstantsKimchi>::new(params);
+    sponge.absorb(&[domain_prefix_to_field::<F>(domain_string)]);
+    sponge.squeeze();
+    array::from_fn(|i| sponge.state[i])
+}
+
+impl<F: PrimeField + SquareRootField> CircuitGate<F> {
+    /// Extends a Merkle path membership gadget to a circuit
+    /// Includes, for each level of the path:
+    /// - 3 double Generic gates performing the conditional swap
+    /// - 1 Poseidon gadget hashing the children
+    /// Input:
+    /// - gates  : vector of circuit gates
+    /// - depth  : depth of the Merkle tree
+    /// - params : parameters of the kimchi sponge
+    /// Output:
+    /// - layout of the gadget, giving the cells of the leaf, the path and the root
+    /// Warning:
+    /// - don't forget to wire the leaf and the root using the returned layout
+    pub fn extend_merkle_path_gadget(
+        gates: &mut Vec<Self>,
+        depth: usize,
+        params: &'static ArithmeticSpongeParams<F>,
+    ) -> MerklePathLayout {
+        let layout = MerklePathLayout::new(gates.len(), depth);
+        let (_, mut merkle_gates) = Self::create_merkle_path_gadget(gates.len(), depth, params);
+        gates.append(&mut merkle_gates);
+        layout
+    }
+
+    /// Creates a Merkle path membership gadget for a tree of depth `depth`
+    /// Input:
+    /// - new_row : row to start the Merkle path gadget
+    /// - depth   : depth of the Merkle tree
+    /// - params  : parameters of the kimchi sponge
+    /// Outputs tuple (next_row, circuit_gates) where
+    /// - next_row  : next row after this gadget
+    /// - gates     : vector of circuit gates comprising this gadget
+    /// Note: use [`MerklePathLayout`] to find the cells of the leaf, the path and the root
+    pub fn create_merkle_path_gadget(
+        new_row: usize,
+        depth: usize,
+        params: &'static ArithmeticSpongeParams<F>,
+    ) -> (usize, Vec<Self>) {
+        let layout = MerklePathLayout::new(new_row, depth);
+
+        // cells are indexed relative to the first row of the gadget in the gates vector
+        let rel = |(row, col): (usize, usize)| (row - new_row, col);
+
+        let mut gates: Vec<Self> = Vec::with_capacity(depth * MERKLE_LEVEL_ROWS);
+        for height in 0..depth {
+            let [c0, c1, c2] = merkle_node_initial_state(params, height);
+            let row = layout.level_row(height);
+            let one = F::one();
+            let zero = F::zero();
+
+            // diff = sibling - node, t = bit * diff
+            gates.push(CircuitGate::create_generic(
+                Wire::for_row(row),
+                [one, -one, -one, zero, zero, zero, zero, -one, one, zero],
+            ));
+            // s0 = node + t + c0, s1 = sibling - t + c1
+            gates.push(CircuitGate::create_generic(
+                Wire::for_row(row + 1),
+                [one, one, -one, zero, c0, one, -one, -one, zero, c1],
+            ));
+            // bit * bit - bit = 0, s2 = c2
+            gates.push(CircuitGate::create_generic(
+                Wire::for_row(row + 2),
+                [-one, zero, zero, one, zero, one, zero, zero, zero, -c2],
+            ));
+
+            // node
+            gates.connect_cell_pair(rel((row, 1)), rel((row + 1, 0)));
+            if height > 0 {
+                gates.connect_cell_pair(rel((layout.parent_row(height - 1), 0)), rel((row, 1)));
+            }
+            // sibling
+            gates.connect_cell_pair(rel((row, 0)), rel((row + 1, 3)));
+            // bit
+            gates.connect_cell_pair(rel((row, 3)), rel((row + 2, 0)));
+            gates.connect_cell_pair(rel((row + 2, 0)), rel((row + 2, 1)));
+            // diff
+            gates.connect_cell_pair(rel((row, 2)), rel((row, 4)));
+            // t
+            gates.connect_cell_pair(rel((row, 5)), rel((row + 1, 1)));
+            gates.connect_cell_pair(rel((row + 1, 1)), rel((row + 1, 4)));
+
+            // hash the children
+            let perm_row = layout.permutation_row(height);
+            let (poseidon, _) = CircuitGate::create_poseidon_gadget(
+                perm_row,
+                [
+                    Wire::for_row(perm_row),
+                    Wire::for_row(layout.parent_row(height)),
+                ],
+                &params.round_constants,
+            );
+            gates.extend(poseidon);
+            gates.connect_cell_pair(rel((row + 1, 2)), rel((perm_row, 0)));
+            gates.connect_cell_pair(rel((row + 1, 5)), rel((perm_row, 1)));
+            gates.connect_cell_pair(rel((row + 2, 3)), rel((perm_row, 2)));
+        }
+
+        (layout.next_row(), gates)
+    }
+}
+
+/// Create a Merkle path witness starting at row 0
+/// Input: sponge parameters, leaf, index of the leaf and siblings from the leaf up to the root
+/// Outputs tuple (witness, root) where
+/// - witness  : witness of the gadget
+/// - root     : root of the tree, as computed by [`mina_hasher::merkle::MerklePath::root`]
+pub fn create_merkle_path_witness<F: PrimeField>(
+    params: &'static ArithmeticSpongeParams<F>,
+    leaf: F,
+    index: u64,
+    siblings: &[F],
+) -> ([Vec<F>; COLUMNS], F) {
+    let depth = siblings.len();
+    let layout = MerklePathLayout::new(0, depth);
+    let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero(); layout.next_row()]);
+
+    let mut node = leaf;
+    for (height, sibling) in siblings.iter().enumerate() {
+        let [c0, c1, c2] = merkle_node_initial_state(params, height);
+        let bit = if (index >> height) & 1 == 1 {
+            F::one()
+        } else {
+            F::zero()
+        };
+        let diff = *sibling - node;
+        let t = bit * diff;
+        let state = [node + t + c0, *sibling - t + c1, c2];
+
+        let row = layout.level_row(height);
+        witness[0][row] = *sibling;
+        witness[1][row] = node;
+        witness[2][row] = diff;
+        witness[3][row] = bit;
+        witness[4][row] = diff;
+        witness[5][row] = t;
+
+        witness[0][row + 1] = node;
+        witness[1][row + 1] = t;
+        witness[2][row + 1] = state[0];
+        witness[3][row + 1] = *sibling;
+        witness[4][row + 1] = t;
+        witness[5][row + 1] = state[1];
+
+        witness[0][row + 2] = bit;
+        witness[1][row + 2] = bit;
+        witness[3][row + 2] = state[2];
+
+        poseidon::generate_witness(layout.permutation_row(height), params, &mut witness, state);
+        node = witness[0][layout.parent_row(height)];
+    }
+
+    (witness, node)
+}
+
+/// Extends a Merkle path witness to the whole witness
+/// Input: witness, sponge parameters, leaf, index of the leaf and siblings from the leaf up to the root
+/// Output: the root of the tree
+pub fn extend_merkle_path_witness<F: PrimeField>(
+    witness: &mut [Vec<F>; COLUMNS],
+    params: &'static ArithmeticSpongeParams<F>,
+    leaf: F,
+    index: u64,
+    siblings: &[F],
+) -> F {
+    let (merkle_witness, root) = create_merkle_path_witness(params, leaf, index, siblings);
+    for col in 0..COLUMNS {
+        witness[col].extend(merkle_witness[col].iter());
+    }
+    root
+}
@@ -1,15 +1,21 @@
 pub mod and;
+pub mod blake2b;
+pub mod comparison;
 pub mod complete_add;
 pub mod endomul_scalar;
 pub mod endosclmul;
 pub mod foreign_field_add;
 pub mod foreign_field_mul;
 pub mod generic;
+pub mod memory;
+pub mod merkle;
 pub mod not;
 pub mod permutation;
 pub mod poseidon;
+pub mod poseidon_sponge;
 pub mod range_check;
 pub mod rot;
+pub mod schnorr;
 pub mod turshi;
 pub mod varbasemul;
 pub mod xor;
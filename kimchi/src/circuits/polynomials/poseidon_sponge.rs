@@ -0,0 +1,492 @@
+//! This module includes the Poseidon sponge gadget and its witness generation.
+//! Note that this module does not need any new gate type: it chains `Poseidon`
+//! permutations with double generic gates performing the absorption.
+
+use super::{
+    generic::GenericGateSpec,
+    poseidon::{self, POS_ROWS_PER_HASH, SPONGE_WIDTH},
+};
+use crate::circuits::{
+    gate::{CircuitGate, Connect},
+    polynomial::COLUMNS,
+    wires::Wire,
+};
+use ark_ff::{PrimeField, SquareRootField};
+use mina_poseidon::{
+    constants::{PlonkSpongeConstantsKimchi, SpongeConstants},
+    poseidon::ArithmeticSpongeParams,
+};
+use std::array;
+
+//~ The Poseidon sponge gadget computes the same outputs as
+//~ `ArithmeticSponge<F, PlonkSpongeConstantsKimchi>` after absorbing a list of
+//~ field elements and squeezing a number of outputs.
+//~ The sponge has a rate of 2 and a capacity of 1, and it starts from the all-zero state.
+//~ The gadget can also start from a constant state instead, for instance the state of a
+//~ `mina-hasher` hasher once initialized with a domain string.
+//~
+//~ The inputs are absorbed in blocks of (at most) two elements. Each block is added into
+//~ the first two elements of the state with a double generic gate, and a permutation is
+//~ applied before every block but the first one, as in the native sponge.
+//~ Squeezing applies one permutation per pair of outputs, and each output is read
+//~ from the first two cells of the row holding the result of a permutation.
+//~
+//~ The gadget is laid out as follows:
+//~
+//~ | Row         | `CircuitGate`                | Purpose                                      |
+//~ | ----------- | ---------------------------- | -------------------------------------------- |
+//~ | 0           | `Generic`                    | Zero cell used as the initial state           |
+//~ | (1)         | `Generic`                    | Constant initial state, if any                |
+//~ | 1 (2)       | `Generic`                    | Absorb the first block                        |
+//~ | 2 .. 13     | `Poseidon` (x 11) + `Zero`   | Permutation                                   |
+//~ | 14 (15)     | `Generic`                    | Absorb the second block                       |
+//~ | ...         | ...                          | ...                                           |
+//~ | r .. r + 11 | `Poseidon` (x 11) + `Zero`   | Permutation used by the squeezes              |
+//~
+//~ The absorption rows use the following layout, where `s0` and `s1` are the first two
+//~ elements of the current state and `a` and `b` are the inputs of the block:
+//~
+//~ |  0 | 1 |     2    |  3 | 4 |     5    |
+//~ |:--:|:-:|:--------:|:--:|:-:|:--------:|
+//~ | s0 | a | s0 + a   | s1 | b | s1 + b   |
+//~
+//~ When the number of inputs is odd, the missing input `b` of the last block is wired to the zero cell.
+//~
+//~ When the sponge starts from a constant state `(c0, c1, c2)`, the first two rows hold
+//~ the zero cell and the constants as follows:
+//~
+//~ |  0 | 3  |
+//~ |:--:|:--:|
+//~ | 0  | c0 |
+//~ | c1 | c2 |
+//~
+//~ ```admonish warning
+//~ The gadget absorbs all the inputs before squeezing. Interleaving absorptions and squeezes
+//~ requires chaining several gadgets by hand.
+//~ ```
+
+/// Number of rows used by a single permutation of the sponge (including its output row)
+pub const SPONGE_PERMUTATION_ROWS: usize = POS_ROWS_PER_HASH + 1;
+
+/// A step of the sponge, positioned at an absolute row of the circuit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SpongeStep {
+    /// Double generic gate adding the inputs of `block` into the rate of the state
+    Absorb { row: usize, block: usize },
+    /// Poseidon permutation, whose output is stored `POS_ROWS_PER_HASH` rows below `row`
+    Permute { row: usize },
+}
+
+/// Describes where the cells of a Poseidon sponge gadget are located in the circuit.
+/// Cells are given as `(row, col)` pairs, as expected by [`Connect::connect_cell_pair`].
+#[derive(Clone, Debug)]
+pub struct PoseidonSpongeLayout {
+    start_row: usize,
+    next_row: usize,
+    initial_state: [(usize, usize); SPONGE_WIDTH],
+    num_inputs: usize,
+    num_squeezes: usize,
+    steps: Vec<SpongeStep>,
+    absorb_rows: Vec<usize>,
+    squeeze_rows: Vec<usize>,
+}
+
+impl PoseidonSpongeLayout {
+    /// Creates the layout of a sponge gadget starting at `start_row`
+    /// that absorbs `num_inputs` elements and squeezes `num_squeezes` outputs.
+    pub fn new(start_row: usize, num_inputs: usize, num_squeezes: usize) -> Self {
+        // the first row holds the zero cell, which is also the initial state
+        let zero = (start_row, 0);
+        Self::with_initial_state(
+            start_row + 1,
+            [zero; SPONGE_WIDTH],
+            num_inputs,
+            num_squeezes,
+        )
+    }
+
+    /// Creates the layout of a sponge gadget starting at `start_row` from a constant state,
+    /// that absorbs `num_inputs` elements and squeezes `num_squeezes` outputs.
+    pub fn new_from_state(start_row: usize, num_inputs: usize, num_squeezes: usize) -> Self {
+        // the first two rows hold the zero cell and the constant initial state
+        let initial_state = [(start_row, 3), (start_row + 1, 0), (start_row + 1, 3)];
+        Self::with_initial_state(start_row + 2, initial_state, num_inputs, num_squeezes)
+    }
+
+    fn with_initial_state(
+        first_block_row: usize,
+        initial_state: [(usize, usize); SPONGE_WIDTH],
+        num_inputs: usize,
+        num_squeezes: usize,
+    ) -> Self {
+        let start_row = initial_state[0].0;
+        let mut steps = vec![];
+        let mut absorb_rows = vec![];
+        let mut squeeze_rows = vec![];
+
+        let mut row = first_block_row;
+
+        for block in 0..num_blocks(num_inputs) {
+            if block > 0 {
+                steps.push(SpongeStep::Permute { row });
+                row += SPONGE_PERMUTATION_ROWS;
+            }
+            steps.push(SpongeStep::Absorb { row, block });
+            absorb_rows.push(row);
+            row += 1;
+        }
+
+        for _ in 0..num_blocks(num_squeezes) {
+            steps.push(SpongeStep::Permute { row });
+            squeeze_rows.push(row + POS_ROWS_PER_HASH);
+            row += SPONGE_PERMUTATION_ROWS;
+        }
+
+        Self {
+            start_row,
+            next_row: row,
+            initial_state,
+            num_inputs,
+            num_squeezes,
+            steps,
+            absorb_rows,
+            squeeze_rows,
+        }
+    }
+
+    /// First row of the gadget
+    pub fn start_row(&self) -> usize {
+        self.start_row
+    }
+
+    /// Next empty row after the gadget
+    pub fn next_row(&self) -> usize {
+        self.next_row
+    }
+
+    /// Number of rows used by the gadget
+    pub fn num_rows(&self) -> usize {
+        self.next_row - self.start_row
+    }
+
+    /// Cell constrained to be zero, also used as the initial state of the sponge
+    /// when it does not start from a constant state
+    pub fn zero_cell(&self) -> (usize, usize) {
+        (self.start_row, 0)
+    }
+
+    /// Cells holding the initial state of the sponge
+    pub fn initial_state(&self) -> [(usize, usize); SPONGE_WIDTH] {
+        self.initial_state
+    }
+
+    /// Cell holding the `i`-th absorbed input
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `i` is not smaller than the number of inputs of the gadget.
+    pub fn input(&self, i: usize) -> (usize, usize) {
+        assert!(i < self.num_inputs, "sponge input {i} is out of bounds");
+        (self.absorb_rows[i / 2], 1 + 3 * (i % 2))
+    }
+
+    /// Cell holding the `j`-th squeezed output
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `j` is not smaller than the number of squeezes of the gadget.
+    pub fn output(&self, j: usize) -> (usize, usize) {
+        assert!(j < self.num_squeezes, "sponge output {j} is out of bounds");
+        (self.squeeze_rows[j / 2], j % 2)
+    }
+}
+
+impl<F: PrimeField + SquareRootField> CircuitGate<F> {
+    /// Extends a Poseidon sponge gadget to a circuit
+    /// Includes:
+    /// - 1 Generic gate holding the zero initial state
+    /// - 1 double Generic gate per block of two inputs
+    /// - 1 Poseidon gadget per permutation of the sponge
+    /// Input:
+    /// - gates           : vector of circuit gates
+    /// - num_inputs      : number of elements to absorb
+    /// - num_squeezes    : number of outputs to squeeze
+    /// - round_constants : round constants of the sponge
+    /// Output:
+    /// - layout of the gadget, giving the cells of the inputs and outputs
+    /// Warning:
+    /// - don't forget to wire the inputs and outputs of the sponge using the returned layout
+    pub fn extend_poseidon_sponge_gadget(
+        gates: &mut Vec<Self>,
+        num_inputs: usize,
+        num_squeezes: usize,
+        round_constants: &[Vec<F>],
+    ) -> PoseidonSpongeLayout {
+        let layout = PoseidonSpongeLayout::new(gates.len(), num_inputs, num_squeezes);
+        gates.extend(Self::poseidon_sponge_gates(&layout, None, round_constants));
+        layout
+    }
+
+    /// Extends a Poseidon sponge gadget starting from a constant state to a circuit
+    /// Includes:
+    /// - 2 double Generic gates holding the zero cell and the initial state
+    /// - 1 double Generic gate per block of two inputs
+    /// - 1 Poseidon gadget per permutation of the sponge
+    /// Input:
+    /// - gates           : vector of circuit gates
+    /// - initial_state   : constant state the sponge starts from
+    /// - num_inputs      : number of elements to absorb
+    /// - num_squeezes    : number of outputs to squeeze
+    /// - round_constants : round constants of the sponge
+    /// Output:
+    /// - layout of the gadget, giving the cells of the inputs and outputs
+    /// Warning:
+    /// - don't forget to wire the inputs and outputs of the sponge using the returned layout
+    pub fn extend_poseidon_sponge_gadget_from_state(
+        gates: &mut Vec<Self>,
+        initial_state: [F; SPONGE_WIDTH],
+        num_inputs: usize,
+        num_squeezes: usize,
+        round_constants: &[Vec<F>],
+    ) -> PoseidonSpongeLayout {
+        let layout = PoseidonSpongeLayout::new_from_state(gates.len(), num_inputs, num_squeezes);
+        gates.extend(Self::poseidon_sponge_gates(
+            &layout,
+            Some(initial_state),
+            round_constants,
+        ));
+        layout
+    }
+
+    /// Creates a Poseidon sponge gadget absorbing `num_inputs` elements and squeezing
+    /// `num_squeezes` outputs, compatible with `ArithmeticSponge<F, PlonkSpongeConstantsKimchi>`
+    /// Input:
+    /// - new_row         : row to start the sponge gadget
+    /// - num_inputs      : number of elements to absorb
+    /// - num_squeezes    : number of outputs to squeeze
+    /// - round_constants : round constants of the sponge
+    /// Outputs tuple (next_row, circuit_gates) where
+    /// - next_row  : next row after this gadget
+    /// - gates     : vector of circuit gates comprising this gadget
+    /// Note: use [`PoseidonSpongeLayout`] to find the cells of the inputs and outputs
+    pub fn create_poseidon_sponge_gadget(
+        new_row: usize,
+        num_inputs: usize,
+        num_squeezes: usize,
+        round_constants: &[Vec<F>],
+    ) -> (usize, Vec<Self>) {
+        let layout = PoseidonSpongeLayout::new(new_row, num_inputs, num_squeezes);
+        let gates = Self::poseidon_sponge_gates(&layout, None, round_constants);
+        (layout.next_row(), gates)
+    }
+
+    /// Creates a Poseidon sponge gadget starting from the constant `initial_state`
+    /// Input:
+    /// - new_row         : row to start the sponge gadget
+    /// - initial_state   : constant state the sponge starts from
+    /// - num_inputs      : number of elements to absorb
+    /// - num_squeezes    : number of outputs to squeeze
+    /// - round_constants : round constants of the sponge
+    /// Outputs tuple (next_row, circuit_gates) where
+    /// - next_row  : next row after this gadget
+    /// - gates     : vector of circuit gates comprising this gadget
+    /// Note: use [`PoseidonSpongeLayout::new_from_state`] to find the cells of the inputs and outputs
+    pub fn create_poseidon_sponge_gadget_from_state(
+        new_row: usize,
+        initial_state: [F; SPONGE_WIDTH],
+        num_inputs: usize,
+        num_squeezes: usize,
+        round_constants: &[Vec<F>],
+    ) -> (usize, Vec<Self>) {
+        let layout = PoseidonSpongeLayout::new_from_state(new_row, num_inputs, num_squeezes);
+        let gates = Self::poseidon_sponge_gates(&layout, Some(initial_state), round_constants);
+        (layout.next_row(), gates)
+    }
+
+    fn poseidon_sponge_gates(
+        layout: &PoseidonSpongeLayout,
+        initial_state: Option<[F; SPONGE_WIDTH]>,
+        round_constants: &[Vec<F>],
+    ) -> Vec<Self> {
+        let new_row = layout.start_row();
+
+        // cells are indexed relative to the first row of the gadget in the gates vector
+        let rel = |(row, col): (usize, usize)| (row - new_row, col);
+
+        let mut gates = match initial_state {
+            None => vec![CircuitGate::create_generic_gadget(
+                Wire::for_row(new_row),
+                GenericGateSpec::Const(F::zero()),
+                None,
+            )],
+            Some([c0, c1, c2]) => vec![
+                CircuitGate::create_generic_gadget(
+                    Wire::for_row(new_row),
+                    GenericGateSpec::Const(F::zero()),
+                    Some(GenericGateSpec::Const(c0)),
+                ),
+                CircuitGate::create_generic_gadget(
+                    Wire::for_row(new_row + 1),
+                    GenericGateSpec::Const(c1),
+                    Some(GenericGateSpec::Const(c2)),
+                ),
+            ],
+        };
+
+        // cells holding the current state of the sponge
+        let zero = layout.zero_cell();
+        let mut state = layout.initial_state();
+
+        for step in &layout.steps {
+            match *step {
+                SpongeStep::Absorb { row, block } => {
+                    let add = || GenericGateSpec::Add {
+                        left_coeff: None,
+                        right_coeff: None,
+                        output_coeff: None,
+                    };
+                    gates.push(CircuitGate::create_generic_gadget(
+                        Wire::for_row(row),
+                        add(),
+                        Some(add()),
+                    ));
+                    gates.connect_cell_pair(rel(state[0]), rel((row, 0)));
+                    gates.connect_cell_pair(rel(state[1]), rel((row, 3)));
+                    // an odd number of inputs leaves the second half of the last block empty
+                    if 2 * block + 1 == layout.num_inputs {
+                        gates.connect_cell_pair(rel(zero), rel((row, 4)));
+                    }
+                    state = [(row, 2), (row, 5), state[2]];
+                }
+                SpongeStep::Permute { row } => {
+                    let last_row = row + POS_ROWS_PER_HASH;
+                    let (poseidon, _) = CircuitGate::create_poseidon_gadget(
+                        row,
+                        [Wire::for_row(row), Wire::for_row(last_row)],
+                        round_constants,
+                    );
+                    gates.extend(poseidon);
+                    for (col, cell) in state.iter().enumerate() {
+                        gates.connect_cell_pair(rel(*cell), rel((row, col)));
+                    }
+                    state = array::from_fn(|col| (last_row, col));
+                }
+            }
+        }
+
+        gates
+    }
+}
+
+/// Create a Poseidon sponge witness starting at row 0
+/// Input: sponge parameters, inputs to absorb and number of outputs to squeeze
+/// Outputs tuple (witness, outputs) where
+/// - witness  : witness of the gadget
+/// - outputs  : squeezed outputs, as computed by `ArithmeticSponge<F, PlonkSpongeConstantsKimchi>`
+pub fn create_poseidon_sponge_witness<F: PrimeField>(
+    params: &'static ArithmeticSpongeParams<F>,
+    inputs: &[F],
+    num_squeezes: usize,
+) -> ([Vec<F>; COLUMNS], Vec<F>) {
+    let layout = PoseidonSpongeLayout::new(0, inputs.len(), num_squeezes);
+    poseidon_sponge_witness(&layout, params, [F::zero(); SPONGE_WIDTH], inputs)
+}
+
+/// Create a witness of a Poseidon sponge starting from a constant state, starting at row 0
+/// Input: sponge parameters, initial state, inputs to absorb and number of outputs to squeeze
+/// Outputs tuple (witness, outputs) where
+/// - witness  : witness of the gadget
+/// - outputs  : squeezed outputs
+pub fn create_poseidon_sponge_witness_from_state<F: PrimeField>(
+    params: &'static ArithmeticSpongeParams<F>,
+    initial_state: [F; SPONGE_WIDTH],
+    inputs: &[F],
+    num_squeezes: usize,
+) -> ([Vec<F>; COLUMNS], Vec<F>) {
+    let layout = PoseidonSpongeLayout::new_from_state(0, inputs.len(), num_squeezes);
+    poseidon_sponge_witness(&layout, params, initial_state, inputs)
+}
+
+/// Extends a Poseidon sponge witness to the whole witness
+/// Input: witness, sponge parameters, inputs to absorb and number of outputs to squeeze
+/// Output: the squeezed outputs
+pub fn extend_poseidon_sponge_witness<F: PrimeField>(
+    witness: &mut [Vec<F>; COLUMNS],
+    params: &'static ArithmeticSpongeParams<F>,
+    inputs: &[F],
+    num_squeezes: usize,
+) -> Vec<F> {
+    let (sponge_witness, outputs) = create_poseidon_sponge_witness(params, inputs, num_squeezes);
+    for col in 0..COLUMNS {
+        witness[col].extend(sponge_witness[col].iter());
+    }
+    outputs
+}
+
+/// Extends the witness of a Poseidon sponge starting from a constant state to the whole witness
+/// Input: witness, sponge parameters, initial state, inputs to absorb and number of outputs to squeeze
+/// Output: the squeezed outputs
+pub fn extend_poseidon_sponge_witness_from_state<F: PrimeField>(
+    witness: &mut [Vec<F>; COLUMNS],
+    params: &'static ArithmeticSpongeParams<F>,
+    initial_state: [F; SPONGE_WIDTH],
+    inputs: &[F],
+    num_squeezes: usize,
+) -> Vec<F> {
+    let (sponge_witness, outputs) =
+        create_poseidon_sponge_witness_from_state(params, initial_state, inputs, num_squeezes);
+    for col in 0..COLUMNS {
+        witness[col].extend(sponge_witness[col].iter());
+    }
+    outputs
+}
+
+fn poseidon_sponge_witness<F: PrimeField>(
+    layout: &PoseidonSpongeLayout,
+    params: &'static ArithmeticSpongeParams<F>,
+    initial_state: [F; SPONGE_WIDTH],
+    inputs: &[F],
+) -> ([Vec<F>; COLUMNS], Vec<F>) {
+    let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero(); layout.num_rows()]);
+
+    // the zero cell is already set, and is overwritten by a zero initial state
+    for ((row, col), value) in layout.initial_state().into_iter().zip(initial_state) {
+        witness[col][row] = value;
+    }
+
+    let mut state = initial_state;
+    for step in &layout.steps {
+        match *step {
+            SpongeStep::Absorb { row, block } => {
+                let a = inputs[2 * block];
+                let b = inputs.get(2 * block + 1).copied().unwrap_or_else(F::zero);
+                witness[0][row] = state[0];
+                witness[1][row] = a;
+                witness[2][row] = state[0] + a;
+                witness[3][row] = state[1];
+                witness[4][row] = b;
+                witness[5][row] = state[1] + b;
+                state[0] += a;
+                state[1] += b;
+            }
+            SpongeStep::Permute { row } => {
+                poseidon::generate_witness(row, params, &mut witness, state);
+                state = array::from_fn(|col| witness[col][row + POS_ROWS_PER_HASH]);
+            }
+        }
+    }
+
+    let outputs = (0..layout.num_squeezes)
+        .map(|j| {
+            let (row, col) = layout.output(j);
+            witness[col][row]
+        })
+        .collect();
+
+    (witness, outputs)
+}
+
+/// Number of blocks of `PlonkSpongeConstantsKimchi::SPONGE_RATE` elements needed for `len` elements
+fn num_blocks(len: usize) -> usize {
+    (len + PlonkSpongeConstantsKimchi::SPONGE_RATE - 1) / PlonkSpongeConstantsKimchi::SPONGE_RATE
+}
@@ -0,0 +1,450 @@
+//! Range check gadget for values of any bit width up to 253 bits
+
+use ark_ff::{PrimeField, SquareRootField};
+use num_bigint::BigUint;
+use o1_utils::{field_helpers::BigUintFieldHelpers, FieldHelpers};
+use std::array;
+
+use crate::circuits::{
+    gate::{CircuitGate, Connect, GateType},
+    polynomial::COLUMNS,
+    wires::Wire,
+};
+
+use super::witness;
+
+//~ The bit-width range check gadget constrains a value $v$ to be smaller than $2^b$
+//~ for any $b$ between 1 and 253, so that $v$ has a unique representation in the native field.
+//~ It chooses between two layouts, and uses the one requiring the fewest rows.
+//~
+//~ **Generic layout:**
+//~
+//~ The value is decomposed into its $b$ bits $b_0, \ldots, b_{b-1}$ (from the most significant one),
+//~ each row constraining one bit to be boolean and accumulating it into the value.
+//~
+//~ | Row   |     0     |     1     | 2 |      3      |     4     |     5     |
+//~ |:-----:|:---------:|:---------:|:-:|:-----------:|:---------:|:---------:|
+//~ | 0     | $b_0$     | $b_0$     |   |             | $b_0$     | $acc_0$   |
+//~ | i     | $b_i$     | $b_i$     |   | $acc_{i-1}$ | $b_i$     | $acc_i$   |
+//~
+//~ where $acc_0 = b_0$ and $acc_i = 2 \cdot acc_{i-1} + b_i$, the value being $acc_{b-1}$.
+//~
+//~ **Lookup layout:**
+//~
+//~ The value is split into $n = \lceil b / 64 \rceil$ limbs of 64 bits $v = \sum_i 2^{64 i} \cdot l_i$,
+//~ each of them being checked by a 64-bit `RangeCheck0` gate (whose columns 1 and 2 are wired to zero).
+//~ When the most significant limb $l_{n-1}$ should have $t < 64$ bits, the shifted limb
+//~ $2^{64 - t} \cdot l_{n-1}$ is also checked to fit in 64 bits.
+//~
+//~ | Rows    | `CircuitGate`   | Purpose                                                           |
+//~ | ------- | --------------- | ----------------------------------------------------------------- |
+//~ | k       | `Generic`       | Zero cell, shifted limb, and $acc_i = l_i + 2^{64} \cdot acc_{i+1}$ |
+//~ | n       | `RangeCheck0`   | 64-bit check of each limb $l_i$                                   |
+//~ | (1)     | `RangeCheck0`   | 64-bit check of the shifted most significant limb                 |
+//~
+//~ where the generic gates are packed two per row, and the value is $acc_0$
+//~ (or $l_0$ if there is a single limb).
+//~
+//~ ```admonish warning
+//~ The lookup layout requires the range check lookup table, which is added
+//~ to the circuit by the first range check gate it contains.
+//~ ```
+
+/// Maximum number of bits of a value constrained by the bit-width range check gadget
+pub const MAX_RANGE_CHECK_BITS: usize = 253;
+
+/// Number of bits of the limbs used by the lookup layout
+const LIMB_BITS: usize = 64;
+
+/// The layouts of the bit-width range check gadget
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RangeCheckBitsMode {
+    /// Bit decomposition in generic gates
+    Generic,
+    /// Decomposition in 64-bit limbs checked with the range check lookup table
+    Lookup,
+}
+
+impl RangeCheckBitsMode {
+    /// Returns the layout requiring the fewest rows to range check `bits` bits
+    pub fn cheapest(bits: usize) -> Self {
+        if Self::Generic.num_rows(bits) <= Self::Lookup.num_rows(bits) {
+            Self::Generic
+        } else {
+            Self::Lookup
+        }
+    }
+
+    /// Number of rows used to range check `bits` bits with this layout
+    pub fn num_rows(self, bits: usize) -> usize {
+        match self {
+            Self::Generic => bits,
+            Self::Lookup => {
+                let limbs = LookupLimbs::new(bits);
+                limbs.generic_rows() + limbs.range_check_rows()
+            }
+        }
+    }
+}
+
+/// Shape of the lookup layout
+#[derive(Clone, Copy, Debug)]
+struct LookupLimbs {
+    /// Number of 64-bit limbs
+    count: usize,
+    /// Number of bits of the most significant limb
+    top_bits: usize,
+}
+
+impl LookupLimbs {
+    fn new(bits: usize) -> Self {
+        let count = (bits + LIMB_BITS - 1) / LIMB_BITS;
+        Self {
+            count,
+            top_bits: bits - LIMB_BITS * (count - 1),
+        }
+    }
+
+    fn shifted(&self) -> bool {
+        self.top_bits < LIMB_BITS
+    }
+
+    /// Number of generic gates: the zero cell, the shifted limb and the recombination of the limbs
+    fn generic_gates(&self) -> usize {
+        1 + usize::from(self.shifted()) + self.count - 1
+    }
+
+    fn generic_rows(&self) -> usize {
+        (self.generic_gates() + 1) / 2
+    }
+
+    fn range_check_rows(&self) -> usize {
+        self.count + usize::from(self.shifted())
+    }
+
+    /// Cell of the `i`-th generic gate, relative to the first row
+    fn generic_cell(i: usize, col: usize) -> (usize, usize) {
+        (i / 2, 3 * (i % 2) + col)
+    }
+
+    /// Index of the generic gate shifting the most significant limb
+    fn shift_gate(&self) -> usize {
+        1
+    }
+
+    /// Index of the generic gate computing `acc_i`, for `i < count - 1`
+    fn recombination_gate(&self, i: usize) -> usize {
+        1 + usize::from(self.shifted()) + (self.count - 2 - i)
+    }
+
+    /// Row of the range check of the `i`-th limb (or of the shifted limb for `i == count`),
+    /// relative to the first row
+    fn range_check_row(&self, i: usize) -> usize {
+        self.generic_rows() + i
+    }
+}
+
+/// Describes where the cells of a bit-width range check gadget are located in the circuit.
+/// Cells are given as `(row, col)` pairs, as expected by [`Connect::connect_cell_pair`].
+#[derive(Clone, Copy, Debug)]
+pub struct RangeCheckBitsLayout {
+    start_row: usize,
+    bits: usize,
+    mode: RangeCheckBitsMode,
+}
+
+impl RangeCheckBitsLayout {
+    /// Creates the layout of a gadget starting at `start_row` that range checks
+    /// `bits` bits, with the layout requiring the fewest rows
+    pub fn new(start_row: usize, bits: usize) -> Self {
+        Self::with_mode(start_row, bits, RangeCheckBitsMode::cheapest(bits))
+    }
+
+    /// Creates the layout of a gadget starting at `start_row` that range checks
+    /// `bits` bits, with the given `mode`
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `bits` is zero or larger than [`MAX_RANGE_CHECK_BITS`].
+    pub fn with_mode(start_row: usize, bits: usize, mode: RangeCheckBitsMode) -> Self {
+        assert!(
+            (1..=MAX_RANGE_CHECK_BITS).contains(&bits),
+            "range checks are for 1 to {MAX_RANGE_CHECK_BITS} bits, got {bits}"
+        );
+        Self {
+            start_row,
+            bits,
+            mode,
+        }
+    }
+
+    /// First row of the gadget
+    pub fn start_row(&self) -> usize {
+        self.start_row
+    }
+
+    /// Next empty row after the gadget
+    pub fn next_row(&self) -> usize {
+        self.start_row + self.num_rows()
+    }
+
+    /// Number of rows used by the gadget
+    pub fn num_rows(&self) -> usize {
+        self.mode.num_rows(self.bits)
+    }
+
+    /// Number of bits of the range check
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+
+    /// Layout used by the gadget
+    pub fn mode(&self) -> RangeCheckBitsMode {
+        self.mode
+    }
+
+    /// Cell holding the range checked value
+    pub fn value(&self) -> (usize, usize) {
+        let (row, col) = match self.mode {
+            RangeCheckBitsMode::Generic => (self.bits - 1, 5),
+            RangeCheckBitsMode::Lookup => {
+                let limbs = LookupLimbs::new(self.bits);
+                if limbs.count == 1 {
+                    (limbs.range_check_row(0), 0)
+                } else {
+                    LookupLimbs::generic_cell(limbs.recombination_gate(0), 2)
+                }
+            }
+        };
+        (self.start_row + row, col)
+    }
+}
+
+impl<F: PrimeField + SquareRootField> CircuitGate<F> {
+    /// Extends a range check gadget for `bits` bits to a circuit, with the cheapest layout
+    /// Input:
+    /// - gates : vector of circuit gates
+    /// - bits  : number of bits of the range checked value
+    /// Output:
+    /// - layout of the gadget, giving the cell of the value
+    /// Warning:
+    /// - don't forget to wire the value using the returned layout
+    pub fn extend_range_check_bits(gates: &mut Vec<Self>, bits: usize) -> RangeCheckBitsLayout {
+        let layout = RangeCheckBitsLayout::new(gates.len(), bits);
+        gates.extend(Self::range_check_bits_gates(&layout));
+        layout
+    }
+
+    /// Creates a range check gadget for `bits` bits, with the cheapest layout
+    /// Input:
+    /// - new_row : row to start the range check gadget
+    /// - bits    : number of bits of the range checked value
+    /// Outputs tuple (next_row, circuit_gates) where
+    /// - next_row  : next row after this gadget
+    /// - gates     : vector of circuit gates comprising this gadget
+    /// Note: use [`RangeCheckBitsLayout`] to find the cell of the value
+    pub fn create_range_check_bits(new_row: usize, bits: usize) -> (usize, Vec<Self>) {
+        Self::create_range_check_bits_with_mode(new_row, bits, RangeCheckBitsMode::cheapest(bits))
+    }
+
+    /// Creates a range check gadget for `bits` bits with the given layout `mode`
+    /// Input:
+    /// - new_row : row to start the range check gadget
+    /// - bits    : number of bits of the range checked value
+    /// - mode    : layout of the gadget
+    /// Outputs tuple (next_row, circuit_gates) where
+    /// - next_row  : next row after this gadget
+    /// - gates     : vector of circuit gates comprising this gadget
+    pub fn create_range_check_bits_with_mode(
+        new_row: usize,
+        bits: usize,
+        mode: RangeCheckBitsMode,
+    ) -> (usize, Vec<Self>) {
+        let layout = RangeCheckBitsLayout::with_mode(new_row, bits, mode);
+        (layout.next_row(), Self::range_check_bits_gates(&layout))
+    }
+
+    fn range_check_bits_gates(layout: &RangeCheckBitsLayout) -> Vec<Self> {
+        let start_row = layout.start_row();
+        let one = F::one();
+        let zero = F::zero();
+
+        match layout.mode() {
+            RangeCheckBitsMode::Generic => {
+                let mut gates: Vec<Self> = (0..layout.bits())
+                    .map(|i| {
+                        // bit * bit - bit = 0, acc_i = 2 * acc_{i-1} + bit
+                        let acc_coeff = if i == 0 { zero } else { F::from(2u64) };
+                        CircuitGate::create_generic(
+                            Wire::for_row(start_row + i),
+                            [
+                                -one, zero, zero, one, zero, acc_coeff, one, -one, zero, zero,
+                            ],
+                        )
+                    })
+                    .collect();
+                for i in 0..layout.bits() {
+                    gates.connect_cell_pair((i, 0), (i, 1));
+                    gates.connect_cell_pair((i, 0), (i, 4));
+                    if i > 0 {
+                        gates.connect_cell_pair((i - 1, 5), (i, 3));
+                    }
+                }
+                gates
+            }
+            RangeCheckBitsMode::Lookup => {
+                let limbs = LookupLimbs::new(layout.bits());
+                let two_to_limb = F::from(2u64).pow([LIMB_BITS as u64]);
+
+                let mut coeffs = vec![[zero; 5]; 2 * limbs.generic_rows()];
+                // zero cell
+                coeffs[0] = [one, zero, zero, zero, zero];
+                // shifted = 2^(64 - t) * top
+                if limbs.shifted() {
+                    let shift = F::from(2u64).pow([(LIMB_BITS - limbs.top_bits) as u64]);
+                    coeffs[limbs.shift_gate()] = [shift, -one, zero, zero, zero];
+                }
+                // acc_i = l_i + 2^64 * acc_{i+1}
+                for i in 0..limbs.count - 1 {
+                    coeffs[limbs.recombination_gate(i)] = [one, two_to_limb, -one, zero, zero];
+                }
+
+                let mut gates: Vec<Self> = coeffs
+                    .chunks(2)
+                    .enumerate()
+                    .map(|(row, pair)| {
+                        let mut c = [zero; 10];
+                        c[..5].copy_from_slice(&pair[0]);
+                        c[5..].copy_from_slice(&pair[1]);
+                        CircuitGate::create_generic(Wire::for_row(start_row + row), c)
+                    })
+                    .collect();
+
+                let zero_cell = LookupLimbs::generic_cell(0, 0);
+                for i in 0..limbs.range_check_rows() {
+                    let row = limbs.range_check_row(i);
+                    gates.push(CircuitGate::new(
+                        GateType::RangeCheck0,
+                        Wire::for_row(start_row + row),
+                        vec![zero],
+                    ));
+                    gates.connect_cell_pair(zero_cell, (row, 1));
+                    gates.connect_cell_pair(zero_cell, (row, 2));
+                }
+
+                let top = (limbs.range_check_row(limbs.count - 1), 0);
+                if limbs.shifted() {
+                    let shift_gate = limbs.shift_gate();
+                    gates.connect_cell_pair(top, LookupLimbs::generic_cell(shift_gate, 0));
+                    gates.connect_cell_pair(
+                        (limbs.range_check_row(limbs.count), 0),
+                        LookupLimbs::generic_cell(shift_gate, 1),
+                    );
+                }
+                for i in 0..limbs.count - 1 {
+                    let gate = limbs.recombination_gate(i);
+                    gates.connect_cell_pair(
+                        (limbs.range_check_row(i), 0),
+                        LookupLimbs::generic_cell(gate, 0),
+                    );
+                    let acc = if i + 1 == limbs.count - 1 {
+                        top
+                    } else {
+                        LookupLimbs::generic_cell(limbs.recombination_gate(i + 1), 2)
+                    };
+                    gates.connect_cell_pair(acc, LookupLimbs::generic_cell(gate, 1));
+                }
+                gates
+            }
+        }
+    }
+}
+
+/// Create a witness for the range check of `value` on `bits` bits, starting at row 0,
+/// with the cheapest layout
+pub fn create_bits<F: PrimeField>(value: F, bits: usize) -> [Vec<F>; COLUMNS] {
+    create_bits_with_mode(value, bits, RangeCheckBitsMode::cheapest(bits))
+}
+
+/// Create a witness for the range check of `value` on `bits` bits, starting at row 0,
+/// with the layout `mode`
+pub fn create_bits_with_mode<F: PrimeField>(
+    value: F,
+    bits: usize,
+    mode: RangeCheckBitsMode,
+) -> [Vec<F>; COLUMNS] {
+    let layout = RangeCheckBitsLayout::with_mode(0, bits, mode);
+    let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero(); layout.num_rows()]);
+    let value = value.to_biguint();
+
+    match mode {
+        RangeCheckBitsMode::Generic => {
+            // the bits from the most significant one
+            let bit_values = (0..bits).rev().map(|i| F::from(value.bit(i as u64)));
+            let mut acc = F::zero();
+            for (i, bit) in bit_values.enumerate() {
+                witness[0][i] = bit;
+                witness[1][i] = bit;
+                witness[3][i] = acc;
+                witness[4][i] = bit;
+                acc = acc.double() + bit;
+                witness[5][i] = acc;
+            }
+        }
+        RangeCheckBitsMode::Lookup => {
+            let limbs = LookupLimbs::new(bits);
+            let limb_mask = (BigUint::from(1u32) << LIMB_BITS) - 1u32;
+            let limb_values: Vec<F> = (0..limbs.count)
+                .map(|i| {
+                    ((&value >> (LIMB_BITS * i)) & &limb_mask)
+                        .to_field()
+                        .expect("failed to convert to field element")
+                })
+                .collect();
+            let top = limb_values[limbs.count - 1];
+
+            let mut set = |(row, col): (usize, usize), value: F| witness[col][row] = value;
+            if limbs.shifted() {
+                let shift = F::from(2u64).pow([(LIMB_BITS - limbs.top_bits) as u64]);
+                set(LookupLimbs::generic_cell(limbs.shift_gate(), 0), top);
+                set(
+                    LookupLimbs::generic_cell(limbs.shift_gate(), 1),
+                    shift * top,
+                );
+            }
+            let two_to_limb = F::from(2u64).pow([LIMB_BITS as u64]);
+            let mut acc = top;
+            for i in (0..limbs.count - 1).rev() {
+                let gate = limbs.recombination_gate(i);
+                set(LookupLimbs::generic_cell(gate, 0), limb_values[i]);
+                set(LookupLimbs::generic_cell(gate, 1), acc);
+                acc = limb_values[i] + two_to_limb * acc;
+                set(LookupLimbs::generic_cell(gate, 2), acc);
+            }
+
+            let mut checked = limb_values;
+            if limbs.shifted() {
+                let shift = F::from(2u64).pow([(LIMB_BITS - limbs.top_bits) as u64]);
+                checked.push(shift * top);
+            }
+            for (i, limb) in checked.into_iter().enumerate() {
+                let row = limbs.range_check_row(i);
+                let limb_witness = witness::create(limb);
+                for col in 0..COLUMNS {
+                    witness[col][row] = limb_witness[col][0];
+                }
+            }
+        }
+    }
+
+    witness
+}
+
+/// Extend an existing witness with the range check of `value` on `bits` bits,
+/// with the cheapest layout
+pub fn extend_bits<F: PrimeField>(witness: &mut [Vec<F>; COLUMNS], value: F, bits: usize) {
+    let bits_witness = create_bits(value, bits);
+    for col in 0..COLUMNS {
+        witness[col].extend(bits_witness[col].iter())
+    }
+}
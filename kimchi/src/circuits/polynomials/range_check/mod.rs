@@ -1,5 +1,6 @@
 //! Range check gate module
 
+pub mod bits;
 pub mod circuitgates;
 pub mod gadget;
 pub mod witness;
@@ -18,7 +18,7 @@ use crate::{
 };
 use o1_utils::foreign_field::BigUintForeignFieldHelpers;
 
-/// Witness layout
+/// Witness layout of a multi range check
 ///   * The values and cell contents are in little-endian order.
 ///     This is important for compatibility with other gates, where
 ///     elements of the first 7 columns could be copied and reused by them.
@@ -29,7 +29,7 @@ use o1_utils::foreign_field::BigUintForeignFieldHelpers;
 ///     For example, we can convert the `RangeCheck0` circuit gate into
 ///     a 64-bit lookup by adding two copy constraints to constrain
 ///     columns 1 and 2 to zero.
-fn layout<F: PrimeField>() -> Vec<[Box<dyn WitnessCell<F>>; COLUMNS]> {
+pub fn multi_layout<F: PrimeField>() -> Vec<[Box<dyn WitnessCell<F>>; COLUMNS]> {
     vec![
         /* row 1, RangeCheck0 row */
         range_check_0_row("v0", 0),
@@ -115,7 +115,7 @@ pub fn range_check_0_row<F: PrimeField>(
 
 /// Create a multi range check witness from three 88-bit values: v0, v1 and v2
 pub fn create_multi<F: PrimeField>(v0: F, v1: F, v2: F) -> [Vec<F>; COLUMNS] {
-    let layout = layout();
+    let layout = multi_layout();
     let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero(); 4]);
 
     init_row(&mut witness, 0, 0, &layout, &variables!(v0));
@@ -135,7 +135,7 @@ pub fn create_multi<F: PrimeField>(v0: F, v1: F, v2: F) -> [Vec<F>; COLUMNS] {
 /// Create a multi range check witness from two limbs: v01 (176 bits), v2 (88 bits),
 /// where v2 is the most significant limb and v01 is the least significant limb
 pub fn create_multi_compact<F: PrimeField>(v01: F, v2: F) -> [Vec<F>; COLUMNS] {
-    let layout = layout();
+    let layout = multi_layout();
     let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero(); 4]);
 
     let (v1, v0) = v01.to_biguint().div_rem(&BigUint::two_to_limb());
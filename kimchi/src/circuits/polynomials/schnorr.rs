@@ -0,0 +1,628 @@
+//! This module includes the Mina Schnorr signature verification gadget and its witness generation.
+//! It accepts the signatures produced by `mina-signer` with the kimchi hasher.
+//! Note that this module does not need any new gate type.
+
+use super::{
+    complete_add, endomul_scalar,
+    generic::GenericGateSpec,
+    poseidon::SPONGE_WIDTH,
+    poseidon_sponge::{self, PoseidonSpongeLayout},
+    varbasemul,
+};
+use crate::circuits::{
+    gate::{CircuitGate, Connect, GateType},
+    polynomial::COLUMNS,
+    wires::Wire,
+};
+use ark_ec::{
+    short_weierstrass_jacobian::GroupAffine, AffineCurve, ProjectiveCurve, SWModelParameters,
+};
+use ark_ff::{BigInteger, BitIteratorLE, Field, One, PrimeField, SquareRootField, Zero};
+use mina_hasher::{domain_prefix_to_field, Fp, Hashable, ROInput};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    poseidon::{ArithmeticSponge, ArithmeticSpongeParams, Sponge},
+};
+use std::array;
+
+//~ The Schnorr gadget verifies a Mina signature $(r_x, s)$ of a message under a public key $P$,
+//~ as `mina-signer` does with the kimchi hasher. Let $G$ be the generator of the curve.
+//~ The signature is valid when the point $R = [s]G - [e]P$ is not the point at infinity,
+//~ has an even $y$ coordinate and has $r_x$ as $x$ coordinate, where $e$ is the hash of the
+//~ random oracle input of the message to which $P_x$, $P_y$ and $r_x$ are appended.
+//~ As the field elements of a random oracle input come before the ones packing its bits,
+//~ the sponge absorbs the field elements of the message, then $P_x$, $P_y$ and $r_x$,
+//~ and finally the field elements packing the bits of the message.
+//~
+//~ The hash $e$ is computed with a Poseidon sponge starting from the state of the hasher once
+//~ initialized with the domain string of the message. The gadget then witnesses $R = (r_x, r_y)$
+//~ and checks that it is on the curve, just like $P$.
+//~
+//~ The scalar multiplications use `VarBaseMul` chains of 255 bits. Starting from $[2]T$,
+//~ a chain over the bits of $n$ computes $[2n + 1 + 2^{255}]T$, so the gadget computes
+//~
+//~ * $A = [2e + 1 + 2^{255}]P$
+//~ * $Z = [1 + 2^{255}]P$ with a chain over the zero scalar
+//~ * $B = [2s + 1 + 2^{255}]G$
+//~
+//~ and checks $B + Z = (A + [2]R) + [1 + 2^{255}]G$ with `CompleteAdd` gates, the last term
+//~ being a constant. The most significant bit of each chain is constrained to be zero,
+//~ so that each scalar has a unique decomposition. As a consequence, signatures whose
+//~ $e$ or $s$ is not smaller than $2^{254}$ are rejected, which happens with negligible probability.
+//~
+//~ Finally, $r_y$ is decomposed in 2-bit crumbs with `EndoMulScalar` gates. The most significant
+//~ crumb is constrained to be zero, and the 16 low bits of $r_y$ are decomposed again as
+//~ $2k$ with $k < 2^{16}$, which shows that $r_y$ is even.
+//~
+//~ The gadget is laid out as follows:
+//~
+//~ | Rows      | `CircuitGate`                     | Purpose                                     |
+//~ | --------- | --------------------------------- | ------------------------------------------- |
+//~ | sponge    | Poseidon sponge gadget            | Hash of the message, $P$ and $r_x$          |
+//~ | 4         | `Generic`                         | $P$ and $R$ are on the curve                |
+//~ | 3         | `Generic`                         | Constants $G$, $[2]G$ and $[1 + 2^{255}]G$  |
+//~ | 5         | `CompleteAdd`                     | $[2]P$, $[2]R$, and the final equation      |
+//~ | 3 x 102   | (`VarBaseMul` + `Zero`) (x 51)    | Chains computing $A$, $Z$ and $B$           |
+//~ | 17        | `EndoMulScalar`                   | Decomposition of $r_y$ and of its low bits  |
+//~ | 1         | `Generic`                         | $r_y = 2^{16} \cdot hi + 2k$                |
+//~
+//~ The generic rows checking that a point $(x, y)$ is on the curve use the following layout:
+//~
+//~ |  0 | 1 |   2   |   3   | 4     |   5   |
+//~ |:--:|:-:|:-----:|:-----:|:-----:|:-----:|
+//~ | x  | x | $x^2$ | $x^2$ | x     | $x^3$ |
+//~ | y  | y | $y^2$ | $y^2$ | $x^3$ |       |
+//~
+//~ ```admonish warning
+//~ The message fields, the public key and the signature are not wired to anything:
+//~ the caller is responsible for connecting them, using the returned layout.
+//~ ```
+
+/// Number of bits of the scalars multiplied by the gadget
+pub const SCALAR_BITS: usize = 255;
+
+/// Number of rows of a `VarBaseMul` chain over `SCALAR_BITS` bits
+pub const CHAIN_ROWS: usize = 2 * SCALAR_BITS / 5;
+
+/// Number of `EndoMulScalar` rows decomposing the `y` coordinate of the signature point
+const PARITY_ROWS: usize = 16;
+
+// offsets of the CompleteAdd rows of the gadget
+const DOUBLE_P: usize = 0;
+const DOUBLE_R: usize = 1;
+const ADD_A: usize = 2;
+const ADD_SHIFT_G: usize = 3;
+const ADD_B: usize = 4;
+
+// indices of the VarBaseMul chains of the gadget
+const CHAIN_E: usize = 0;
+const CHAIN_ZERO: usize = 1;
+const CHAIN_S: usize = 2;
+
+/// Describes where the cells of a Schnorr verification gadget are located in the circuit.
+/// Cells are given as `(row, col)` pairs, as expected by [`Connect::connect_cell_pair`].
+#[derive(Clone, Debug)]
+pub struct SchnorrLayout {
+    sponge: PoseidonSpongeLayout,
+    num_fields: usize,
+    num_packed: usize,
+    curve_row: usize,
+    const_row: usize,
+    add_row: usize,
+    chain_rows: [usize; 3],
+    parity_row: usize,
+}
+
+impl SchnorrLayout {
+    /// Creates the layout of a Schnorr gadget starting at `start_row`, for messages
+    /// of `num_fields` field elements and whose bits are packed in `num_packed` field elements
+    /// (see [`ROInput::num_fields`] and [`ROInput::to_fields`])
+    pub fn new(start_row: usize, num_fields: usize, num_packed: usize) -> Self {
+        let sponge =
+            PoseidonSpongeLayout::new_from_state(start_row, num_fields + 3 + num_packed, 1);
+        let curve_row = sponge.next_row();
+        let const_row = curve_row + 4;
+        let add_row = const_row + 3;
+        let chain_rows = array::from_fn(|i| add_row + 5 + i * CHAIN_ROWS);
+        let parity_row = chain_rows[2] + CHAIN_ROWS;
+        Self {
+            sponge,
+            num_fields,
+            num_packed,
+            curve_row,
+            const_row,
+            add_row,
+            chain_rows,
+            parity_row,
+        }
+    }
+
+    /// First row of the gadget
+    pub fn start_row(&self) -> usize {
+        self.sponge.start_row()
+    }
+
+    /// Next empty row after the gadget
+    pub fn next_row(&self) -> usize {
+        self.parity_row + PARITY_ROWS + 2
+    }
+
+    /// Number of rows used by the gadget
+    pub fn num_rows(&self) -> usize {
+        self.next_row() - self.start_row()
+    }
+
+    /// Cell holding the `i`-th field element of the message, as given by [`ROInput::to_fields`]
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `i` is not smaller than the number of field elements of the message.
+    pub fn message(&self, i: usize) -> (usize, usize) {
+        assert!(
+            i < self.num_fields + self.num_packed,
+            "message field {i} is out of bounds"
+        );
+        if i < self.num_fields {
+            self.sponge.input(i)
+        } else {
+            // the packed bits come after the public key and rx
+            self.sponge.input(i + 3)
+        }
+    }
+
+    /// Cells holding the coordinates of the public key
+    pub fn public_key(&self) -> [(usize, usize); 2] {
+        [
+            self.sponge.input(self.num_fields),
+            self.sponge.input(self.num_fields + 1),
+        ]
+    }
+
+    /// Cell holding the `rx` component of the signature
+    pub fn rx(&self) -> (usize, usize) {
+        self.sponge.input(self.num_fields + 2)
+    }
+
+    /// Cell holding the `s` component of the signature
+    pub fn s(&self) -> (usize, usize) {
+        self.chain_final_n(CHAIN_S)
+    }
+
+    /// Cell holding the hash of the message, the public key and `rx`
+    pub fn hash(&self) -> (usize, usize) {
+        self.sponge.output(0)
+    }
+
+    fn ry(&self) -> (usize, usize) {
+        (self.curve_row + 3, 0)
+    }
+
+    fn constant(&self, i: usize) -> [(usize, usize); 2] {
+        [(self.const_row + i, 0), (self.const_row + i, 3)]
+    }
+
+    fn chain_final_n(&self, chain: usize) -> (usize, usize) {
+        (self.chain_rows[chain] + CHAIN_ROWS - 2, 5)
+    }
+
+    fn chain_result(&self, chain: usize) -> [(usize, usize); 2] {
+        let row = self.chain_rows[chain] + CHAIN_ROWS - 1;
+        [(row, 0), (row, 1)]
+    }
+}
+
+/// Returns the state of the kimchi hasher used by `mina-signer` to sign messages of type `H`,
+/// once initialized with the domain string given by `domain_param`
+pub fn schnorr_initial_state<F: PrimeField, H: Hashable>(
+    params: &'static ArithmeticSpongeParams<F>,
+    domain_param: H::D,
+) -> [F; SPONGE_WIDTH] {
+    let mut sponge = ArithmeticSponge::<F, PlonkSpongeConstantsKimchi>::new(params);
+    if let Some(domain_string) = H::domain_string(domain_param) {
+        sponge.absorb(&[domain_prefix_to_field::<F>(domain_string)]);
+        sponge.squeeze();
+    }
+    array::from_fn(|i| sponge.state[i])
+}
+
+/// Returns the constant points used by the gadget: `G`, `[2]G` and `[1 + 2^SCALAR_BITS]G`
+fn generator_constants<C: SWModelParameters>() -> [(C::BaseField, C::BaseField); 3] {
+    let g = GroupAffine::<C>::prime_subgroup_generator();
+    let shift = C::ScalarField::from(2u64).pow([SCALAR_BITS as u64]) + C::ScalarField::one();
+    let double_g = g.into_projective().double().into_affine();
+    let shifted_g = g.mul(shift.into_repr()).into_affine();
+    [
+        (g.x, g.y),
+        (double_g.x, double_g.y),
+        (shifted_g.x, shifted_g.y),
+    ]
+}
+
+impl<F: PrimeField + SquareRootField> CircuitGate<F> {
+    /// Extends a Schnorr signature verification gadget to a circuit
+    /// Input:
+    /// - gates           : vector of circuit gates
+    /// - initial_state   : state of the hasher, see [`schnorr_initial_state`]
+    /// - num_fields      : number of field elements of the message
+    /// - num_packed      : number of field elements packing the bits of the message
+    /// - round_constants : round constants of the sponge
+    /// Output:
+    /// - layout of the gadget, giving the cells of the message, public key and signature
+    /// Warning:
+    /// - don't forget to wire the message, public key and signature using the returned layout
+    pub fn extend_schnorr_gadget<C: SWModelParameters<BaseField = F>>(
+        gates: &mut Vec<Self>,
+        initial_state: [F; SPONGE_WIDTH],
+        num_fields: usize,
+        num_packed: usize,
+        round_constants: &[Vec<F>],
+    ) -> SchnorrLayout {
+        let layout = SchnorrLayout::new(gates.len(), num_fields, num_packed);
+        let (_, mut schnorr_gates) = Self::create_schnorr_gadget::<C>(
+            gates.len(),
+            initial_state,
+            num_fields,
+            num_packed,
+            round_constants,
+        );
+        gates.append(&mut schnorr_gates);
+        layout
+    }
+
+    /// Creates a gadget verifying a Mina Schnorr signature over the curve `C`,
+    /// whose base field is the native field of the circuit
+    /// Input:
+    /// - new_row         : row to start the Schnorr gadget
+    /// - initial_state   : state of the hasher, see [`schnorr_initial_state`]
+    /// - num_fields      : number of field elements of the message
+    /// - num_packed      : number of field elements packing the bits of the message
+    /// - round_constants : round constants of the sponge
+    /// Outputs tuple (next_row, circuit_gates) where
+    /// - next_row  : next row after this gadget
+    /// - gates     : vector of circuit gates comprising this gadget
+    /// Note: use [`SchnorrLayout`] to find the cells of the message, public key and signature
+    pub fn create_schnorr_gadget<C: SWModelParameters<BaseField = F>>(
+        new_row: usize,
+        initial_state: [F; SPONGE_WIDTH],
+        num_fields: usize,
+        num_packed: usize,
+        round_constants: &[Vec<F>],
+    ) -> (usize, Vec<Self>) {
+        let layout = SchnorrLayout::new(new_row, num_fields, num_packed);
+
+        // cells are indexed relative to the first row of the gadget in the gates vector
+        let rel = |(row, col): (usize, usize)| (row - new_row, col);
+
+        let (_, mut gates) = Self::create_poseidon_sponge_gadget_from_state(
+            new_row,
+            initial_state,
+            num_fields + 3 + num_packed,
+            1,
+            round_constants,
+        );
+        let zero = layout.sponge.zero_cell();
+        let [px, py] = layout.public_key();
+        let (rx, ry) = (layout.rx(), layout.ry());
+
+        // P and R are on the curve
+        let one = F::one();
+        let (nil, neg) = (F::zero(), -F::one());
+        for (row, x, y) in [(layout.curve_row, px, py), (layout.curve_row + 2, rx, ry)] {
+            // x^2 = x * x, x^3 = x^2 * x
+            gates.push(CircuitGate::create_generic(
+                Wire::for_row(row),
+                [nil, nil, neg, one, nil, nil, nil, neg, one, nil],
+            ));
+            // y^2 = y * y, y^2 - x^3 - b = 0
+            gates.push(CircuitGate::create_generic(
+                Wire::for_row(row + 1),
+                [nil, nil, neg, one, nil, one, neg, nil, nil, -C::COEFF_B],
+            ));
+            for col in [0, 1, 4] {
+                gates.connect_cell_pair(rel(x), rel((row, col)));
+            }
+            for col in [0, 1] {
+                gates.connect_cell_pair(rel(y), rel((row + 1, col)));
+            }
+            gates.connect_cell_pair(rel((row, 2)), rel((row, 3)));
+            gates.connect_cell_pair(rel((row, 5)), rel((row + 1, 4)));
+            gates.connect_cell_pair(rel((row + 1, 2)), rel((row + 1, 3)));
+        }
+
+        // G, [2]G and [1 + 2^SCALAR_BITS]G
+        for (i, (x, y)) in generator_constants::<C>().into_iter().enumerate() {
+            gates.push(CircuitGate::create_generic_gadget(
+                Wire::for_row(layout.const_row + i),
+                GenericGateSpec::Const(x),
+                Some(GenericGateSpec::Const(y)),
+            ));
+        }
+
+        // none of the additions results in the point at infinity
+        for i in 0..5 {
+            let row = layout.add_row + i;
+            gates.push(CircuitGate::new(
+                GateType::CompleteAdd,
+                Wire::for_row(row),
+                vec![],
+            ));
+            gates.connect_cell_pair(rel(zero), rel((row, 6)));
+        }
+        let add_cells = |i: usize, cols: [usize; 2]| cols.map(|col| (layout.add_row + i, col));
+        let connect_points =
+            |gates: &mut Vec<Self>, from: [(usize, usize); 2], to: [(usize, usize); 2]| {
+                gates.connect_cell_pair(rel(from[0]), rel(to[0]));
+                gates.connect_cell_pair(rel(from[1]), rel(to[1]));
+            };
+
+        // [2]P and [2]R
+        connect_points(&mut gates, [px, py], add_cells(DOUBLE_P, [0, 1]));
+        connect_points(&mut gates, [px, py], add_cells(DOUBLE_P, [2, 3]));
+        connect_points(&mut gates, [rx, ry], add_cells(DOUBLE_R, [0, 1]));
+        connect_points(&mut gates, [rx, ry], add_cells(DOUBLE_R, [2, 3]));
+
+        // VarBaseMul chains, whose scalars start from zero and have a zero most significant bit
+        let double_p = add_cells(DOUBLE_P, [4, 5]);
+        for (chain, base, acc0) in [
+            (CHAIN_E, [px, py], double_p),
+            (CHAIN_ZERO, [px, py], double_p),
+            (CHAIN_S, layout.constant(0), layout.constant(1)),
+        ] {
+            let start_row = layout.chain_rows[chain];
+            for chunk in 0..(CHAIN_ROWS / 2) {
+                let row = start_row + 2 * chunk;
+                gates.extend(CircuitGate::create_vbmul(&[
+                    Wire::for_row(row),
+                    Wire::for_row(row + 1),
+                ]));
+                if chunk == 0 {
+                    connect_points(&mut gates, base, [(row, 0), (row, 1)]);
+                    connect_points(&mut gates, acc0, [(row, 2), (row, 3)]);
+                    gates.connect_cell_pair(rel(zero), rel((row, 4)));
+                    gates.connect_cell_pair(rel(zero), rel((row + 1, 2)));
+                } else {
+                    connect_points(
+                        &mut gates,
+                        [(row - 2, 0), (row - 2, 1)],
+                        [(row, 0), (row, 1)],
+                    );
+                    connect_points(
+                        &mut gates,
+                        [(row - 1, 0), (row - 1, 1)],
+                        [(row, 2), (row, 3)],
+                    );
+                    gates.connect_cell_pair(rel((row - 2, 5)), rel((row, 4)));
+                }
+            }
+        }
+        gates.connect_cell_pair(rel(layout.hash()), rel(layout.chain_final_n(CHAIN_E)));
+        gates.connect_cell_pair(rel(zero), rel(layout.chain_final_n(CHAIN_ZERO)));
+
+        // A + [2]R + [1 + 2^SCALAR_BITS]G = B + Z
+        connect_points(
+            &mut gates,
+            layout.chain_result(CHAIN_E),
+            add_cells(ADD_A, [0, 1]),
+        );
+        connect_points(
+            &mut gates,
+            add_cells(DOUBLE_R, [4, 5]),
+            add_cells(ADD_A, [2, 3]),
+        );
+        connect_points(
+            &mut gates,
+            add_cells(ADD_A, [4, 5]),
+            add_cells(ADD_SHIFT_G, [0, 1]),
+        );
+        connect_points(
+            &mut gates,
+            layout.constant(2),
+            add_cells(ADD_SHIFT_G, [2, 3]),
+        );
+        connect_points(
+            &mut gates,
+            layout.chain_result(CHAIN_S),
+            add_cells(ADD_B, [0, 1]),
+        );
+        connect_points(
+            &mut gates,
+            layout.chain_result(CHAIN_ZERO),
+            add_cells(ADD_B, [2, 3]),
+        );
+        connect_points(
+            &mut gates,
+            add_cells(ADD_SHIFT_G, [4, 5]),
+            add_cells(ADD_B, [4, 5]),
+        );
+
+        // ry = 2^16 * hi + 2 * k, with ry < 2^254 and k < 2^16
+        for i in 0..=PARITY_ROWS {
+            let row = layout.parity_row + i;
+            gates.push(CircuitGate::new(
+                GateType::EndoMulScalar,
+                Wire::for_row(row),
+                vec![],
+            ));
+            if i == 0 || i == PARITY_ROWS {
+                gates.connect_cell_pair(rel(zero), rel((row, 0)));
+            } else {
+                gates.connect_cell_pair(rel((row - 1, 1)), rel((row, 0)));
+            }
+        }
+        let parity_row = layout.parity_row;
+        let check_row = parity_row + PARITY_ROWS + 1;
+        gates.connect_cell_pair(rel(zero), rel((parity_row, 6)));
+        gates.connect_cell_pair(rel(ry), rel((parity_row + PARITY_ROWS - 1, 1)));
+        gates.push(CircuitGate::create_generic_gadget(
+            Wire::for_row(check_row),
+            GenericGateSpec::Add {
+                left_coeff: None,
+                right_coeff: Some(-F::from(1u64 << 16)),
+                output_coeff: Some(-F::from(2u64)),
+            },
+            None,
+        ));
+        gates.connect_cell_pair(rel(ry), rel((check_row, 0)));
+        gates.connect_cell_pair(rel((parity_row + PARITY_ROWS - 1, 0)), rel((check_row, 1)));
+        gates.connect_cell_pair(rel((parity_row + PARITY_ROWS, 1)), rel((check_row, 2)));
+
+        (layout.next_row(), gates)
+    }
+}
+
+/// Create a Schnorr signature verification witness starting at row 0
+/// Input:
+/// - params        : sponge parameters
+/// - initial_state : state of the hasher, see [`schnorr_initial_state`]
+/// - message       : random oracle input of the message
+/// - public_key    : public key of the signer
+/// - signature     : components `(rx, s)` of the signature
+/// Output: witness of the gadget, which only satisfies the constraints for valid signatures
+pub fn create_schnorr_witness<C>(
+    params: &'static ArithmeticSpongeParams<Fp>,
+    initial_state: [Fp; SPONGE_WIDTH],
+    message: &ROInput,
+    public_key: GroupAffine<C>,
+    signature: (Fp, C::ScalarField),
+) -> [Vec<Fp>; COLUMNS]
+where
+    C: SWModelParameters<BaseField = Fp>,
+{
+    let num_fields = message.num_fields();
+    let num_packed = message.to_fields().len() - num_fields;
+    let layout = SchnorrLayout::new(0, num_fields, num_packed);
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); layout.num_rows()]);
+    let (rx, s) = signature;
+    let p = (public_key.x, public_key.y);
+
+    // e = H(message || px || py || rx), as computed by mina-signer
+    let inputs = message
+        .clone()
+        .append_field(p.0)
+        .append_field(p.1)
+        .append_field(rx)
+        .to_fields();
+    let (sponge_witness, hash) = poseidon_sponge::create_poseidon_sponge_witness_from_state(
+        params,
+        initial_state,
+        &inputs,
+        1,
+    );
+    for col in 0..COLUMNS {
+        witness[col][..sponge_witness[col].len()].copy_from_slice(&sponge_witness[col]);
+    }
+    let e = hash[0];
+
+    // R = [s]G - [e]P
+    let g = GroupAffine::<C>::prime_subgroup_generator();
+    let e_scalar = C::ScalarField::from_le_bytes_mod_order(&e.into_repr().to_bytes_le());
+    let ry = (g.mul(s.into_repr()) - public_key.mul(e_scalar.into_repr()))
+        .into_affine()
+        .y;
+    let r = (rx, ry);
+
+    for (row, (x, y)) in [(layout.curve_row, p), (layout.curve_row + 2, r)] {
+        let x_squared = x.square();
+        let x_cubed = x_squared * x;
+        let y_squared = y.square();
+        for (col, value) in [x, x, x_squared, x_squared, x, x_cubed]
+            .into_iter()
+            .enumerate()
+        {
+            witness[col][row] = value;
+        }
+        for (col, value) in [y, y, y_squared, y_squared, x_cubed]
+            .into_iter()
+            .enumerate()
+        {
+            witness[col][row + 1] = value;
+        }
+    }
+
+    let constants = generator_constants::<C>();
+    for (i, (x, y)) in constants.into_iter().enumerate() {
+        witness[0][layout.const_row + i] = x;
+        witness[3][layout.const_row + i] = y;
+    }
+
+    let double_p = complete_add::witness(&mut witness, layout.add_row + DOUBLE_P, p, p);
+    let double_r = complete_add::witness(&mut witness, layout.add_row + DOUBLE_R, r, r);
+
+    let bits_msb = |x: C::ScalarField| -> Vec<bool> {
+        let mut bits: Vec<_> = BitIteratorLE::new(x.into_repr())
+            .take(SCALAR_BITS)
+            .collect();
+        bits.reverse();
+        bits
+    };
+    let chain_e = varbasemul::witness(
+        &mut witness,
+        layout.chain_rows[CHAIN_E],
+        p,
+        &bits_msb(e_scalar),
+        double_p,
+    );
+    let chain_zero = varbasemul::witness(
+        &mut witness,
+        layout.chain_rows[CHAIN_ZERO],
+        p,
+        &[false; SCALAR_BITS],
+        double_p,
+    );
+    let chain_s = varbasemul::witness(
+        &mut witness,
+        layout.chain_rows[CHAIN_S],
+        constants[0],
+        &bits_msb(s),
+        constants[1],
+    );
+
+    let sum_a = complete_add::witness(&mut witness, layout.add_row + ADD_A, chain_e.acc, double_r);
+    complete_add::witness(
+        &mut witness,
+        layout.add_row + ADD_SHIFT_G,
+        sum_a,
+        constants[2],
+    );
+    complete_add::witness(
+        &mut witness,
+        layout.add_row + ADD_B,
+        chain_s.acc,
+        chain_zero.acc,
+    );
+
+    // decompose ry, and twice its 16 low bits
+    let k = Fp::from((ry.into_repr().as_ref()[0] & 0xffff) >> 1);
+    let mut parity_witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![]);
+    endomul_scalar::gen_witness(&mut parity_witness, ry, Fp::zero(), 16 * PARITY_ROWS);
+    endomul_scalar::gen_witness(&mut parity_witness, k, Fp::zero(), 16);
+    for col in 0..COLUMNS {
+        witness[col][layout.parity_row..=layout.parity_row + PARITY_ROWS]
+            .copy_from_slice(&parity_witness[col]);
+    }
+    let check_row = layout.parity_row + PARITY_ROWS + 1;
+    witness[0][check_row] = ry;
+    witness[1][check_row] = parity_witness[0][PARITY_ROWS - 1];
+    witness[2][check_row] = k;
+
+    witness
+}
+
+/// Extends a Schnorr signature verification witness to the whole witness
+/// Input: witness, sponge parameters, state of the hasher, message, public key and signature
+pub fn extend_schnorr_witness<C>(
+    witness: &mut [Vec<Fp>; COLUMNS],
+    params: &'static ArithmeticSpongeParams<Fp>,
+    initial_state: [Fp; SPONGE_WIDTH],
+    message: &ROInput,
+    public_key: GroupAffine<C>,
+    signature: (Fp, C::ScalarField),
+) where
+    C: SWModelParameters<BaseField = Fp>,
+{
+    let schnorr_witness =
+        create_schnorr_witness(params, initial_state, message, public_key, signature);
+    for col in 0..COLUMNS {
+        witness[col].extend(schnorr_witness[col].iter());
+    }
+}
@@ -29,4 +29,8 @@ impl<F: Field> WitnessCell<F> for CopyBitsCell {
         F::from_bits(&witness[self.col][self.row].to_bits()[self.start..self.end])
             .expect("failed to deserialize field bits for copy bits cell")
     }
+
+    fn copied_from(&self) -> Option<(usize, usize)> {
+        Some((self.row, self.col))
+    }
 }
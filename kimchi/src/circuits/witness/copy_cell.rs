@@ -20,4 +20,8 @@ impl<F: Field> WitnessCell<F> for CopyCell {
     fn value(&self, witness: &mut [Vec<F>; COLUMNS], _variables: &Variables<F>) -> F {
         witness[self.col][self.row]
     }
+
+    fn copied_from(&self) -> Option<(usize, usize)> {
+        Some((self.row, self.col))
+    }
 }
@@ -0,0 +1,235 @@
+//! This module implements the generation of the witness of a whole circuit.
+//!
+//! Each gadget registers its witness layout (see [WitnessCell]) at the row where it starts,
+//! together with the named [Variables] it knows about.
+//! The variables of a gadget that are not given are instead taken from the cells wired to
+//! them by the copy constraints of the circuit, so that the outputs of a gadget flow into the
+//! inputs of the next ones.
+//! The cells are computed in topological order: a cell is computed once the cell it is copied
+//! from (within its layout), or the cell it is wired to, is known.
+//! The cells that are not in any layout are set to zero, unless they are wired to a computed cell.
+
+use super::{Variables, WitnessCell};
+use crate::{
+    circuits::{
+        gate::CircuitGate,
+        wires::{COLUMNS, PERMUTS},
+    },
+    error::WitnessError,
+};
+use ark_ff::PrimeField;
+use std::{array, collections::VecDeque};
+
+/// The witness layout of a gadget, placed at a given row of the circuit
+struct PlacedLayout<'a, F: PrimeField> {
+    start: usize,
+    cells: Vec<[Box<dyn WitnessCell<F>>; COLUMNS]>,
+    variables: Variables<'a, F>,
+}
+
+/// How the value of a cell is obtained
+#[derive(Clone, Copy)]
+enum Source {
+    /// From the formula of the cell in a layout
+    Layout(usize),
+    /// From the cells it is wired to
+    Wiring,
+}
+
+/// A generator of the witness of a whole circuit, from the witness layouts of its gadgets
+pub struct WitnessGenerator<'a, F: PrimeField> {
+    rows: usize,
+    /// The cells of each cycle of the permutation
+    cycles: Vec<Vec<usize>>,
+    /// The cycle of each cell of the permutation columns
+    cycle_of: Vec<usize>,
+    layouts: Vec<PlacedLayout<'a, F>>,
+}
+
+impl<'a, F: PrimeField> WitnessGenerator<'a, F> {
+    /// Creates a witness generator for the circuit made of `gates`,
+    /// whose copy constraints are given by the wiring of the gates.
+    pub fn new(gates: &[CircuitGate<F>]) -> Self {
+        let rows = gates.len();
+        let mut cycles = vec![];
+        let mut cycle_of = vec![usize::MAX; rows * PERMUTS];
+        for row in 0..rows {
+            for col in 0..PERMUTS {
+                if cycle_of[row * PERMUTS + col] != usize::MAX {
+                    continue;
+                }
+                let mut cycle = vec![];
+                let (mut r, mut c) = (row, col);
+                while cycle_of[r * PERMUTS + c] == usize::MAX {
+                    cycle_of[r * PERMUTS + c] = cycles.len();
+                    cycle.push(r * COLUMNS + c);
+                    let wire = gates[r].wires[c];
+                    (r, c) = (wire.row, wire.col);
+                }
+                cycles.push(cycle);
+            }
+        }
+        Self {
+            rows,
+            cycles,
+            cycle_of,
+            layouts: vec![],
+        }
+    }
+
+    /// Registers the witness `layout` of a gadget starting at row `start`,
+    /// with the values of its known `variables`.
+    pub fn layout(
+        &mut self,
+        start: usize,
+        layout: Vec<[Box<dyn WitnessCell<F>>; COLUMNS]>,
+        variables: Variables<'a, F>,
+    ) -> &mut Self {
+        self.layouts.push(PlacedLayout {
+            start,
+            cells: layout,
+            variables,
+        });
+        self
+    }
+
+    /// Generates the witness of the circuit.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the layouts do not fit in the circuit or overlap,
+    /// or if some variables are missing and cannot be copied from other cells.
+    pub fn generate(&self) -> Result<[Vec<F>; COLUMNS], WitnessError> {
+        let cells = self.rows * COLUMNS;
+
+        // the layout and the row in the layout of each cell
+        let mut owners: Vec<Option<(usize, usize)>> = vec![None; cells];
+        for (i, layout) in self.layouts.iter().enumerate() {
+            if layout.start + layout.cells.len() > self.rows {
+                return Err(WitnessError::LayoutOutOfBounds(layout.start));
+            }
+            for local_row in 0..layout.cells.len() {
+                for col in 0..COLUMNS {
+                    let cell = (layout.start + local_row) * COLUMNS + col;
+                    if owners[cell].is_some() {
+                        return Err(WitnessError::OverlappingLayouts(
+                            layout.start + local_row,
+                            col,
+                        ));
+                    }
+                    owners[cell] = Some((i, local_row));
+                }
+            }
+        }
+
+        // the cells computed from each cell, and the cells that can be computed right away
+        let mut dependents: Vec<Vec<usize>> = vec![vec![]; cells];
+        let mut queue = VecDeque::new();
+        for (i, layout) in self.layouts.iter().enumerate() {
+            for (local_row, row_cells) in layout.cells.iter().enumerate() {
+                for (col, cell) in row_cells.iter().enumerate() {
+                    let id = (layout.start + local_row) * COLUMNS + col;
+                    if let Some((from_row, from_col)) = cell.copied_from() {
+                        if from_row >= layout.cells.len() || from_col >= COLUMNS {
+                            return Err(WitnessError::CopyOutOfLayout(
+                                layout.start + local_row,
+                                col,
+                            ));
+                        }
+                        dependents[(layout.start + from_row) * COLUMNS + from_col].push(id);
+                    } else if cell
+                        .variable()
+                        .map_or(true, |name| layout.variables.get(name).is_some())
+                    {
+                        queue.push_back((id, Source::Layout(i)));
+                    }
+                }
+            }
+        }
+
+        // compute the cells in topological order
+        let mut values: Vec<Option<F>> = vec![None; cells];
+        let mut locals: Vec<[Vec<F>; COLUMNS]> = self
+            .layouts
+            .iter()
+            .map(|layout| array::from_fn(|_| vec![F::zero(); layout.cells.len()]))
+            .collect();
+        let mut wired_values: Vec<Option<F>> = vec![None; self.cycles.len()];
+        while let Some((id, source)) = queue.pop_front() {
+            if values[id].is_some() {
+                continue;
+            }
+            let (row, col) = (id / COLUMNS, id % COLUMNS);
+            let value = match source {
+                Source::Layout(i) => {
+                    let local_row = row - self.layouts[i].start;
+                    self.layouts[i].cells[local_row][col]
+                        .value(&mut locals[i], &self.layouts[i].variables)
+                }
+                Source::Wiring => {
+                    wired_values[self.cycle_of[row * PERMUTS + col]].expect("cell wired to a value")
+                }
+            };
+            values[id] = Some(value);
+            if let Some((i, local_row)) = owners[id] {
+                locals[i][col][local_row] = value;
+            }
+
+            for dependent in &dependents[id] {
+                let (i, _) = owners[*dependent].expect("a copy cell belongs to a layout");
+                queue.push_back((*dependent, Source::Layout(i)));
+            }
+            if col < PERMUTS {
+                let cycle = self.cycle_of[row * PERMUTS + col];
+                if wired_values[cycle].is_none() {
+                    wired_values[cycle] = Some(value);
+                    for peer in &self.cycles[cycle] {
+                        if self.is_free(*peer, &owners) {
+                            queue.push_back((*peer, Source::Wiring));
+                        }
+                    }
+                }
+            }
+        }
+
+        // report the cells that could not be computed
+        let mut missing = vec![];
+        let mut unresolved = None;
+        let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero(); self.rows]);
+        for (id, value) in values.iter().enumerate() {
+            let (row, col) = (id / COLUMNS, id % COLUMNS);
+            match (value, owners[id]) {
+                (Some(value), _) => witness[col][row] = *value,
+                (None, None) => (),
+                (None, Some((i, local_row))) => {
+                    match self.layouts[i].cells[local_row][col].variable() {
+                        Some(name) => missing.push((name.to_string(), row, col)),
+                        None => unresolved = unresolved.or(Some((row, col))),
+                    }
+                }
+            }
+        }
+        // the cells copied from a missing variable are not reported
+        if !missing.is_empty() {
+            return Err(WitnessError::MissingVariables(missing));
+        }
+        if let Some((row, col)) = unresolved {
+            return Err(WitnessError::CyclicCopy(row, col));
+        }
+        Ok(witness)
+    }
+
+    // Whether a cell takes its value from the cells it is wired to:
+    // either it is in no layout, or its variable is missing
+    fn is_free(&self, id: usize, owners: &[Option<(usize, usize)>]) -> bool {
+        match owners[id] {
+            None => true,
+            Some((i, local_row)) => {
+                let layout = &self.layouts[i];
+                layout.cells[local_row][id % COLUMNS]
+                    .variable()
+                    .map_or(false, |name| layout.variables.get(name).is_none())
+            }
+        }
+    }
+}
@@ -3,6 +3,7 @@ mod constant_cell;
 mod copy_bits_cell;
 mod copy_cell;
 mod copy_shift_cell;
+mod generator;
 mod variable_bits_cell;
 mod variable_cell;
 mod variables;
@@ -12,6 +13,7 @@ pub use self::{
     copy_bits_cell::CopyBitsCell,
     copy_cell::CopyCell,
     copy_shift_cell::CopyShiftCell,
+    generator::WitnessGenerator,
     variable_bits_cell::VariableBitsCell,
     variable_cell::VariableCell,
     variables::{variable_map, variables, Variables},
@@ -22,6 +24,16 @@ use super::polynomial::COLUMNS;
 /// Witness cell interface
 pub trait WitnessCell<F: Field> {
     fn value(&self, witness: &mut [Vec<F>; COLUMNS], variables: &Variables<F>) -> F;
+
+    /// The cell (row, col) of the layout whose value this cell is computed from, if any
+    fn copied_from(&self) -> Option<(usize, usize)> {
+        None
+    }
+
+    /// The name of the variable this cell is computed from, if any
+    fn variable(&self) -> Option<&str> {
+        None
+    }
 }
 
 /// Initialize a witness cell based on layout and computed variables
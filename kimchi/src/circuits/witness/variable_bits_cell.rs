@@ -28,4 +28,8 @@ impl<'a, F: Field> WitnessCell<F> for VariableBitsCell<'a> {
         };
         bits.expect("failed to deserialize field bits for variable bits cell")
     }
+
+    fn variable(&self) -> Option<&str> {
+        Some(self.name)
+    }
 }
@@ -19,4 +19,8 @@ impl<'a, F: Field> WitnessCell<F> for VariableCell<'a> {
     fn value(&self, _witness: &mut [Vec<F>; COLUMNS], variables: &Variables<F>) -> F {
         variables[self.name]
     }
+
+    fn variable(&self) -> Option<&str> {
+        Some(self.name)
+    }
 }
@@ -62,6 +62,11 @@ impl<'a, F: Field> Variables<'a, F> {
     pub fn insert(&mut self, name: &'a str, value: F) {
         self.0.insert(name, value);
     }
+
+    /// Get the value of a variable, if it is in the variable map
+    pub fn get(&self, name: &str) -> Option<F> {
+        self.0.get(name).copied()
+    }
 }
 
 impl<'a, F: Field> Index<&'a str> for Variables<'a, F> {
@@ -0,0 +1,764 @@
+//! This module implements a semantic diff between two circuits ([`CircuitDiff`])
+//! and between two verifier indexes ([`VerifierIndexDiff`]),
+//! to find out why the digest of a [`VerifierIndex`] changed.
+//!
+//! The gates of the two circuits are aligned on their types and coefficients,
+//! so that inserting a gate reports an inserted row rather than a change of every following row.
+//! The wiring of aligned rows is then compared through this alignment.
+
+use crate::{
+    circuits::{
+        constraints::{ConstraintSystem, FeatureFlags},
+        gate::CircuitGate,
+        lookup::index::LookupConstraintSystem,
+        wires::{Wire, PERMUTS},
+    },
+    curve::KimchiCurve,
+    verifier_index::VerifierIndex,
+};
+use ark_ff::PrimeField;
+use ark_poly::EvaluationDomain;
+use poly_commitment::commitment::PolyComm;
+use std::fmt;
+
+/// An edit of the alignment of two sequences
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Edit {
+    /// The element at this index of the first sequence is the one at this index of the second sequence
+    Equal(usize, usize),
+    /// The element at this index of the first sequence is removed
+    Delete(usize),
+    /// The element at this index of the second sequence is inserted
+    Insert(usize),
+}
+
+/// Aligns two sequences with a shortest edit script, following Myers' algorithm,
+/// see <http://www.xmailserver.org/diff2.pdf>.
+fn align<T, U>(old: &[T], new: &[U], eq: impl Fn(&T, &U) -> bool) -> Vec<Edit> {
+    // the common prefix and suffix are aligned as they are
+    let prefix = old.iter().zip(new).take_while(|(a, b)| eq(a, b)).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| eq(a, b))
+        .count();
+    let (a, b) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+
+    // v[k] is the furthest x reached on the diagonal k = x - y,
+    // and trace[d] keeps v[-d..=d] as it was before looking for a path of d edits
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    let mut trace: Vec<Vec<isize>> = vec![];
+    let at = |v: &[isize], k: isize| v[(k + offset) as usize];
+
+    'search: for d in 0..=max {
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && at(&v, k - 1) < at(&v, k + 1)) {
+                at(&v, k + 1)
+            } else {
+                at(&v, k - 1) + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && eq(&a[x as usize], &b[y as usize]) {
+                x += 1;
+                y += 1;
+            }
+            v[(k + offset) as usize] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    // walk back the path, from the last edit
+    let mut edits = vec![];
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let at = |k: isize| v[(k + d) as usize];
+        if d == 0 {
+            while x > 0 && y > 0 {
+                x -= 1;
+                y -= 1;
+                edits.push(Edit::Equal(x as usize, y as usize));
+            }
+            break;
+        }
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Equal(x as usize, y as usize));
+        }
+        if x == prev_x {
+            edits.push(Edit::Insert(prev_y as usize));
+        } else {
+            edits.push(Edit::Delete(prev_x as usize));
+        }
+        (x, y) = (prev_x, prev_y);
+    }
+    edits.reverse();
+
+    // shift the edits back to the full sequences
+    let shifted = edits.into_iter().map(|edit| match edit {
+        Edit::Equal(i, j) => Edit::Equal(i + prefix, j + prefix),
+        Edit::Delete(i) => Edit::Delete(i + prefix),
+        Edit::Insert(j) => Edit::Insert(j + prefix),
+    });
+    (0..prefix)
+        .map(|i| Edit::Equal(i, i))
+        .chain(shifted)
+        .chain((0..suffix).map(|i| Edit::Equal(old.len() - suffix + i, new.len() - suffix + i)))
+        .collect()
+}
+
+/// A difference between the gates of two circuits.
+/// Rows are given in the old circuit for removed gates, and in both circuits otherwise.
+#[derive(Clone, Debug)]
+pub enum GateDiff<F: PrimeField> {
+    Inserted {
+        new_row: usize,
+        gate: CircuitGate<F>,
+    },
+    Removed {
+        old_row: usize,
+        gate: CircuitGate<F>,
+    },
+    /// The gate type or the coefficients changed
+    Changed {
+        old_row: usize,
+        new_row: usize,
+        old: CircuitGate<F>,
+        new: CircuitGate<F>,
+    },
+    /// The wires of these columns go to other cells, given by their position in the new circuit
+    /// (`None` for cells of removed rows)
+    Rewired {
+        old_row: usize,
+        new_row: usize,
+        wires: Vec<(usize, Option<Wire>, Wire)>,
+    },
+}
+
+/// A difference between the lookup configurations of two circuits
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LookupDiff {
+    /// Lookups are used by only one of the circuits
+    Enabled(bool, bool),
+    /// The number of columns of the lookup tables changed
+    TableWidth(usize, usize),
+    /// The content of this column of the lookup tables changed
+    TableColumn(usize),
+    /// The table ids of the lookup tables changed
+    TableIds,
+    /// The runtime table configurations changed, given as `(id, len)`
+    RuntimeTables(Vec<(i32, usize)>, Vec<(i32, usize)>),
+}
+
+/// A semantic diff between two circuits, see the [module documentation](self)
+#[derive(Clone, Debug)]
+pub struct CircuitDiff<F: PrimeField> {
+    pub public: Option<(usize, usize)>,
+    pub prev_challenges: Option<(usize, usize)>,
+    pub domain_size: Option<(usize, usize)>,
+    pub feature_flags: Option<(FeatureFlags, FeatureFlags)>,
+    pub gates: Vec<GateDiff<F>>,
+    pub lookup: Vec<LookupDiff>,
+}
+
+/// Returns the pair of values if they differ
+fn changed<T: PartialEq>(old: T, new: T) -> Option<(T, T)> {
+    (old != new).then_some((old, new))
+}
+
+fn same_gate<F: PrimeField>(a: &CircuitGate<F>, b: &CircuitGate<F>) -> bool {
+    a.typ == b.typ && a.coeffs == b.coeffs
+}
+
+impl<F: PrimeField> CircuitDiff<F> {
+    /// Computes the differences between two constraint systems
+    pub fn new(old: &ConstraintSystem<F>, new: &ConstraintSystem<F>) -> Self {
+        Self {
+            public: changed(old.public, new.public),
+            prev_challenges: changed(old.prev_challenges, new.prev_challenges),
+            domain_size: changed(old.domain.d1.size(), new.domain.d1.size()),
+            feature_flags: changed(old.feature_flags, new.feature_flags),
+            gates: Self::gates(&old.gates, &new.gates),
+            lookup: Self::lookup(
+                old.lookup_constraint_system.as_ref(),
+                new.lookup_constraint_system.as_ref(),
+            ),
+        }
+    }
+
+    /// Whether the two circuits are the same
+    pub fn is_empty(&self) -> bool {
+        self.public.is_none()
+            && self.prev_challenges.is_none()
+            && self.domain_size.is_none()
+            && self.feature_flags.is_none()
+            && self.gates.is_empty()
+            && self.lookup.is_empty()
+    }
+
+    /// Computes the differences between two lists of gates
+    pub fn gates(old: &[CircuitGate<F>], new: &[CircuitGate<F>]) -> Vec<GateDiff<F>> {
+        let edits = align(old, new, same_gate);
+
+        // pair the removed and inserted gates between two aligned rows as changed gates
+        let mut diffs = vec![];
+        let mut matched = vec![];
+        let (mut removed, mut inserted) = (vec![], vec![]);
+        let flush = |removed: &mut Vec<usize>,
+                     inserted: &mut Vec<usize>,
+                     diffs: &mut Vec<GateDiff<F>>,
+                     matched: &mut Vec<(usize, usize)>| {
+            for (&old_row, &new_row) in removed.iter().zip(inserted.iter()) {
+                matched.push((old_row, new_row));
+                diffs.push(GateDiff::Changed {
+                    old_row,
+                    new_row,
+                    old: old[old_row].clone(),
+                    new: new[new_row].clone(),
+                });
+            }
+            let paired = removed.len().min(inserted.len());
+            for &old_row in &removed[paired..] {
+                diffs.push(GateDiff::Removed {
+                    old_row,
+                    gate: old[old_row].clone(),
+                });
+            }
+            for &new_row in &inserted[paired..] {
+                diffs.push(GateDiff::Inserted {
+                    new_row,
+                    gate: new[new_row].clone(),
+                });
+            }
+            removed.clear();
+            inserted.clear();
+        };
+        for edit in edits {
+            match edit {
+                Edit::Equal(old_row, new_row) => {
+                    flush(&mut removed, &mut inserted, &mut diffs, &mut matched);
+                    matched.push((old_row, new_row));
+                }
+                Edit::Delete(old_row) => removed.push(old_row),
+                Edit::Insert(new_row) => inserted.push(new_row),
+            }
+        }
+        flush(&mut removed, &mut inserted, &mut diffs, &mut matched);
+
+        // compare the wiring of the matched rows, in the rows of the new circuit
+        let mut old_to_new = vec![None; old.len()];
+        for &(old_row, new_row) in &matched {
+            old_to_new[old_row] = Some(new_row);
+        }
+        for (old_row, new_row) in matched {
+            let wires: Vec<_> = (0..PERMUTS)
+                .filter_map(|col| {
+                    let Wire { row, col: to_col } = old[old_row].wires[col];
+                    let moved = old_to_new
+                        .get(row)
+                        .copied()
+                        .flatten()
+                        .map(|row| Wire { row, col: to_col });
+                    let wire = new[new_row].wires[col];
+                    (moved != Some(wire)).then_some((col, moved, wire))
+                })
+                .collect();
+            if !wires.is_empty() {
+                diffs.push(GateDiff::Rewired {
+                    old_row,
+                    new_row,
+                    wires,
+                });
+            }
+        }
+
+        diffs
+    }
+
+    /// Computes the differences between two lookup configurations
+    pub fn lookup(
+        old: Option<&LookupConstraintSystem<F>>,
+        new: Option<&LookupConstraintSystem<F>>,
+    ) -> Vec<LookupDiff> {
+        let (old, new) = match (old, new) {
+            (None, None) => return vec![],
+            (Some(old), Some(new)) => (old, new),
+            (old, new) => return vec![LookupDiff::Enabled(old.is_some(), new.is_some())],
+        };
+
+        let mut diffs = vec![];
+        if old.lookup_table.len() != new.lookup_table.len() {
+            diffs.push(LookupDiff::TableWidth(
+                old.lookup_table.len(),
+                new.lookup_table.len(),
+            ));
+        }
+        for (col, (old, new)) in old.lookup_table.iter().zip(&new.lookup_table).enumerate() {
+            if old != new {
+                diffs.push(LookupDiff::TableColumn(col));
+            }
+        }
+        if old.table_ids != new.table_ids {
+            diffs.push(LookupDiff::TableIds);
+        }
+        if old.runtime_tables != new.runtime_tables {
+            let specs = |lcs: &LookupConstraintSystem<F>| {
+                lcs.runtime_tables
+                    .iter()
+                    .flatten()
+                    .map(|spec| (spec.id, spec.len))
+                    .collect()
+            };
+            diffs.push(LookupDiff::RuntimeTables(specs(old), specs(new)));
+        }
+        diffs
+    }
+}
+
+/// Writes the feature flags that differ
+fn fmt_feature_flags(
+    f: &mut fmt::Formatter<'_>,
+    old: &FeatureFlags,
+    new: &FeatureFlags,
+) -> fmt::Result {
+    let flags = |flags: &FeatureFlags| {
+        [
+            ("range_check0", flags.range_check0),
+            ("range_check1", flags.range_check1),
+            ("foreign_field_add", flags.foreign_field_add),
+            ("foreign_field_mul", flags.foreign_field_mul),
+            ("xor", flags.xor),
+            ("rot", flags.rot),
+        ]
+    };
+    for ((name, old), (_, new)) in flags(old).into_iter().zip(flags(new)) {
+        if old != new {
+            writeln!(f, "feature flag {name}: {old} -> {new}")?;
+        }
+    }
+    if old.lookup_features != new.lookup_features {
+        writeln!(
+            f,
+            "lookup features: {:?} -> {:?}",
+            old.lookup_features, new.lookup_features
+        )?;
+    }
+    Ok(())
+}
+
+fn fmt_wire(wire: Option<Wire>) -> String {
+    match wire {
+        Some(Wire { row, col }) => format!("row{row}[{col}]"),
+        None => "a removed row".to_string(),
+    }
+}
+
+impl<F: PrimeField> fmt::Display for GateDiff<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GateDiff::Inserted { new_row, gate } => {
+                write!(f, "row{new_row}: inserted {:?} gate", gate.typ)
+            }
+            GateDiff::Removed { old_row, gate } => {
+                write!(f, "old row{old_row}: removed {:?} gate", gate.typ)
+            }
+            GateDiff::Changed {
+                old_row,
+                new_row,
+                old,
+                new,
+            } => {
+                write!(f, "row{new_row} (was row{old_row}): ")?;
+                if old.typ != new.typ {
+                    write!(f, "{:?} gate replaced by {:?} gate", old.typ, new.typ)
+                } else if old.coeffs.len() != new.coeffs.len() {
+                    write!(
+                        f,
+                        "{} coefficients instead of {}",
+                        new.coeffs.len(),
+                        old.coeffs.len()
+                    )
+                } else {
+                    let cols: Vec<_> = old
+                        .coeffs
+                        .iter()
+                        .zip(&new.coeffs)
+                        .enumerate()
+                        .filter(|(_, (old, new))| old != new)
+                        .map(|(i, _)| i.to_string())
+                        .collect();
+                    write!(f, "coefficients {} changed", cols.join(", "))
+                }
+            }
+            GateDiff::Rewired {
+                old_row,
+                new_row,
+                wires,
+            } => {
+                write!(f, "row{new_row} (was row{old_row}): ")?;
+                let wires: Vec<_> = wires
+                    .iter()
+                    .map(|(col, old, new)| {
+                        format!(
+                            "[{col}] -> {} (was {})",
+                            fmt_wire(Some(*new)),
+                            fmt_wire(*old)
+                        )
+                    })
+                    .collect();
+                write!(f, "rewired {}", wires.join(", "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for LookupDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LookupDiff::Enabled(old, new) => write!(f, "lookups: {old} -> {new}"),
+            LookupDiff::TableWidth(old, new) => {
+                write!(f, "lookup table columns: {old} -> {new}")
+            }
+            LookupDiff::TableColumn(col) => write!(f, "lookup table column {col} changed"),
+            LookupDiff::TableIds => write!(f, "lookup table ids changed"),
+            LookupDiff::RuntimeTables(old, new) => {
+                write!(f, "runtime tables (id, len): {old:?} -> {new:?}")
+            }
+        }
+    }
+}
+
+/// Writes the value that differs, if any
+fn fmt_changed<T: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    value: &Option<(T, T)>,
+) -> fmt::Result {
+    match value {
+        Some((old, new)) => writeln!(f, "{name}: {old} -> {new}"),
+        None => Ok(()),
+    }
+}
+
+impl<F: PrimeField> fmt::Display for CircuitDiff<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_changed(f, "public input size", &self.public)?;
+        fmt_changed(f, "previous challenges", &self.prev_challenges)?;
+        fmt_changed(f, "domain size", &self.domain_size)?;
+        if let Some((old, new)) = &self.feature_flags {
+            fmt_feature_flags(f, old, new)?;
+        }
+        for diff in &self.gates {
+            writeln!(f, "{diff}")?;
+        }
+        for diff in &self.lookup {
+            writeln!(f, "{diff}")?;
+        }
+        Ok(())
+    }
+}
+
+fn custom_gate_comm<G: KimchiCurve>(index: &VerifierIndex<G>, id: u32) -> Option<&PolyComm<G>> {
+    index
+        .custom_gates
+        .iter()
+        .find(|(gate_id, _)| *gate_id == id)
+        .map(|(_, comm)| comm)
+}
+
+fn lookup_table_comms<G: KimchiCurve>(index: &VerifierIndex<G>) -> &[PolyComm<G>] {
+    index
+        .lookup_index
+        .as_ref()
+        .map_or(&[][..], |lookup| &lookup.lookup_table[..])
+}
+
+/// A diff between two verifier indexes, listing the commitments that differ
+#[derive(Clone, Debug)]
+pub struct VerifierIndexDiff {
+    pub domain_size: Option<(usize, usize)>,
+    pub max_poly_size: Option<(usize, usize)>,
+    pub public: Option<(usize, usize)>,
+    pub prev_challenges: Option<(usize, usize)>,
+    pub feature_flags: Option<(FeatureFlags, FeatureFlags)>,
+    /// The names of the commitments that differ, or that are only in one of the indexes
+    pub commitments: Vec<String>,
+}
+
+impl VerifierIndexDiff {
+    /// Computes the differences between two verifier indexes
+    pub fn new<G: KimchiCurve>(old: &VerifierIndex<G>, new: &VerifierIndex<G>) -> Self {
+        let mut commitments = vec![];
+        let mut compare = |name: String, old: Option<&PolyComm<G>>, new: Option<&PolyComm<G>>| {
+            if old != new {
+                commitments.push(name);
+            }
+        };
+
+        for (i, (old, new)) in old.sigma_comm.iter().zip(&new.sigma_comm).enumerate() {
+            compare(format!("sigma_comm[{i}]"), Some(old), Some(new));
+        }
+        for (i, (old, new)) in old
+            .coefficients_comm
+            .iter()
+            .zip(&new.coefficients_comm)
+            .enumerate()
+        {
+            compare(format!("coefficients_comm[{i}]"), Some(old), Some(new));
+        }
+        for ((name, old), (_, new)) in Self::selector_commitments(old)
+            .into_iter()
+            .zip(Self::selector_commitments(new))
+        {
+            compare(name, old, new);
+        }
+
+        // custom gates are compared by their selector id, and lookup tables by their column
+        let mut ids: Vec<_> = old
+            .custom_gates
+            .iter()
+            .chain(&new.custom_gates)
+            .map(|(id, _)| *id)
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        for id in ids {
+            compare(
+                format!("custom_gates[{id}]"),
+                custom_gate_comm(old, id),
+                custom_gate_comm(new, id),
+            );
+        }
+
+        let (old_table, new_table) = (lookup_table_comms(old), lookup_table_comms(new));
+        for i in 0..old_table.len().max(new_table.len()) {
+            compare(
+                format!("lookup_table[{i}]"),
+                old_table.get(i),
+                new_table.get(i),
+            );
+        }
+
+        Self {
+            domain_size: changed(old.domain.size(), new.domain.size()),
+            max_poly_size: changed(old.max_poly_size, new.max_poly_size),
+            public: changed(old.public, new.public),
+            prev_challenges: changed(old.prev_challenges, new.prev_challenges),
+            feature_flags: changed(old.feature_flags(), new.feature_flags()),
+            commitments,
+        }
+    }
+
+    /// The selector commitments of the index by name, which are `None` for features it doesn't use
+    fn selector_commitments<G: KimchiCurve>(
+        index: &VerifierIndex<G>,
+    ) -> Vec<(String, Option<&PolyComm<G>>)> {
+        let lookup = index.lookup_index.as_ref();
+        let selectors = lookup.map(|lookup| &lookup.lookup_selectors);
+        [
+            ("generic_comm", Some(&index.generic_comm)),
+            ("psm_comm", Some(&index.psm_comm)),
+            ("complete_add_comm", Some(&index.complete_add_comm)),
+            ("mul_comm", Some(&index.mul_comm)),
+            ("emul_comm", Some(&index.emul_comm)),
+            ("endomul_scalar_comm", Some(&index.endomul_scalar_comm)),
+            ("range_check0_comm", index.range_check0_comm.as_ref()),
+            ("range_check1_comm", index.range_check1_comm.as_ref()),
+            (
+                "foreign_field_add_comm",
+                index.foreign_field_add_comm.as_ref(),
+            ),
+            (
+                "foreign_field_mul_comm",
+                index.foreign_field_mul_comm.as_ref(),
+            ),
+            ("xor_comm", index.xor_comm.as_ref()),
+            ("rot_comm", index.rot_comm.as_ref()),
+            (
+                "lookup_selectors.xor",
+                selectors.and_then(|s| s.xor.as_ref()),
+            ),
+            (
+                "lookup_selectors.lookup",
+                selectors.and_then(|s| s.lookup.as_ref()),
+            ),
+            (
+                "lookup_selectors.range_check",
+                selectors.and_then(|s| s.range_check.as_ref()),
+            ),
+            (
+                "lookup_selectors.ffmul",
+                selectors.and_then(|s| s.ffmul.as_ref()),
+            ),
+            ("table_ids", lookup.and_then(|l| l.table_ids.as_ref())),
+            (
+                "runtime_tables_selector",
+                lookup.and_then(|l| l.runtime_tables_selector.as_ref()),
+            ),
+        ]
+        .into_iter()
+        .map(|(name, comm)| (name.to_string(), comm))
+        .collect()
+    }
+
+    /// Whether the two verifier indexes have the same commitments and parameters
+    pub fn is_empty(&self) -> bool {
+        self.domain_size.is_none()
+            && self.max_poly_size.is_none()
+            && self.public.is_none()
+            && self.prev_challenges.is_none()
+            && self.feature_flags.is_none()
+            && self.commitments.is_empty()
+    }
+}
+
+impl fmt::Display for VerifierIndexDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_changed(f, "domain size", &self.domain_size)?;
+        fmt_changed(f, "max_poly_size", &self.max_poly_size)?;
+        fmt_changed(f, "public input size", &self.public)?;
+        fmt_changed(f, "previous challenges", &self.prev_challenges)?;
+        if let Some((old, new)) = &self.feature_flags {
+            fmt_feature_flags(f, old, new)?;
+        }
+        for name in &self.commitments {
+            writeln!(f, "commitment {name} differs")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuits::{
+            gate::{Connect, GateType},
+            polynomials::generic::GenericGateSpec,
+        },
+        prover_index::testing::new_index_for_test,
+    };
+    use mina_curves::pasta::{Fp, Vesta};
+
+    fn add(row: usize) -> CircuitGate<Fp> {
+        CircuitGate::create_generic_gadget(
+            Wire::for_row(row),
+            GenericGateSpec::Add {
+                left_coeff: None,
+                right_coeff: None,
+                output_coeff: None,
+            },
+            None,
+        )
+    }
+
+    fn constant(row: usize, cst: u64) -> CircuitGate<Fp> {
+        CircuitGate::create_generic_gadget(
+            Wire::for_row(row),
+            GenericGateSpec::Const(cst.into()),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_align() {
+        let old = b"abcabba";
+        let new = b"cbabac";
+        let edits = align(old, new, |a, b| a == b);
+        let equal = edits
+            .iter()
+            .filter(|edit| matches!(edit, Edit::Equal(..)))
+            .count();
+        // the longest common subsequence has 4 elements
+        assert_eq!(equal, 4);
+        assert_eq!(edits.len(), old.len() + new.len() - equal);
+        for edit in edits {
+            if let Edit::Equal(i, j) = edit {
+                assert_eq!(old[i], new[j]);
+            }
+        }
+        assert!(align::<u8, u8>(&[], &[], |a, b| a == b).is_empty());
+    }
+
+    #[test]
+    fn test_circuit_diff() {
+        let mut old = vec![constant(0, 1), add(1), constant(2, 2), add(3)];
+        old.connect_cell_pair((0, 2), (1, 0));
+        old.connect_cell_pair((2, 2), (3, 0));
+
+        // insert a constant, change the second one, and rewire the last addition
+        let mut new = vec![
+            constant(0, 1),
+            constant(1, 5),
+            add(2),
+            constant(3, 3),
+            add(4),
+        ];
+        new.connect_cell_pair((0, 2), (2, 0));
+        new.connect_cell_pair((1, 2), (4, 0));
+
+        let diffs = CircuitDiff::gates(&old, &new);
+        let report: Vec<_> = diffs.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            report,
+            vec![
+                "row1: inserted Generic gate",
+                "row3 (was row2): coefficients 4 changed",
+                "row3 (was row2): rewired [2] -> row3[2] (was row4[0])",
+                "row4 (was row3): rewired [0] -> row1[2] (was row3[2])",
+            ]
+        );
+
+        // the same circuits give the same verifier index
+        let index = new_index_for_test::<Vesta>(old.clone(), 0);
+        let same = new_index_for_test::<Vesta>(old.clone(), 0);
+        let diff = CircuitDiff::new(&index.cs, &same.cs);
+        assert!(diff.is_empty(), "{diff}");
+        let diff = VerifierIndexDiff::new(&index.verifier_index(), &same.verifier_index());
+        assert!(diff.is_empty(), "{diff}");
+
+        // the new circuit changes the coefficients and the permutation
+        let other = new_index_for_test::<Vesta>(new, 1);
+        let diff = CircuitDiff::new(&index.cs, &other.cs);
+        assert_eq!(diff.public, Some((0, 1)));
+        // the circuits are padded to the same domain, so the new one has one padding row less
+        assert_eq!(diff.gates.len(), 5);
+        assert!(matches!(
+            diff.gates[2],
+            GateDiff::Removed {
+                old_row: 4,
+                gate: CircuitGate {
+                    typ: GateType::Zero,
+                    ..
+                }
+            }
+        ));
+        let diff = VerifierIndexDiff::new(&index.verifier_index(), &other.verifier_index());
+        assert_eq!(diff.public, Some((0, 1)));
+        assert!(diff
+            .commitments
+            .contains(&"coefficients_comm[4]".to_string()));
+        assert!(diff.commitments.contains(&"sigma_comm[0]".to_string()));
+        assert!(!diff.commitments.contains(&"psm_comm".to_string()));
+        assert!(diff.feature_flags.is_none());
+    }
+}
@@ -83,6 +83,12 @@ pub enum SetupError {
 
     #[error("the domain could not be constructed: {0}")]
     DomainCreation(&'static str),
+
+    #[error("the custom gate is invalid: {0}")]
+    CustomGate(String),
+
+    #[error("the lookup tables are invalid: {0}")]
+    LookupTable(String),
 }
 
 /// Errors that can arise when creating a verifier index
@@ -91,3 +97,81 @@ pub enum VerifierIndexError {
     #[error("srs has already been set")]
     SRSHasBeenSet,
 }
+
+/// Errors that can arise when writing or reading a prover index
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ProverIndexError {
+    #[error("couldn't access the prover index: {0}")]
+    Io(String),
+
+    #[error("couldn't serialize the prover index: {0}")]
+    Serialization(String),
+
+    #[error("couldn't deserialize the prover index: {0}")]
+    Deserialization(String),
+
+    #[error("the data is not a prover index")]
+    InvalidFormat,
+
+    #[error("the prover index has version {0}, but only version {1} is supported")]
+    UnsupportedVersion(u32, u32),
+
+    #[error("the prover index is not the one of the expected circuit")]
+    CircuitDigestMismatch,
+
+    #[error("the prover index was created with an SRS of size {0}, not {1}")]
+    SRSSizeMismatch(usize, usize),
+
+    #[error("the custom gates don't match the ones of the prover index: {0}")]
+    CustomGates(String),
+}
+
+/// Errors that can arise when generating the witness of a circuit
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum WitnessError {
+    #[error("the layout starting at row {0} does not fit in the circuit")]
+    LayoutOutOfBounds(usize),
+
+    #[error("the cell ({0}, {1}) is assigned by two layouts")]
+    OverlappingLayouts(usize, usize),
+
+    #[error("the cell ({0}, {1}) is copied from a cell outside of its layout")]
+    CopyOutOfLayout(usize, usize),
+
+    #[error(
+        "the variables of the cells {0:?} are missing, and they are not copied from other cells"
+    )]
+    MissingVariables(Vec<(String, usize, usize)>),
+
+    #[error("the cell ({0}, {1}) is copied from itself")]
+    CyclicCopy(usize, usize),
+}
+
+/// Errors that can arise when running a snarky circuit
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SnarkyError {
+    #[error("the witness does not satisfy the constraint {0}")]
+    UnsatisfiedConstraint(String),
+}
+
+/// Errors that can arise when parsing a circuit written in the asm format
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AsmError {
+    #[error("line {0}: {1}")]
+    Syntax(usize, String),
+
+    #[error("line {0}: unknown gate type {1}")]
+    UnknownGate(usize, String),
+
+    #[error("line {0}: unknown constant {1}")]
+    UnknownConstant(usize, String),
+
+    #[error("line {0}: expected row {1}")]
+    UnexpectedRow(usize, usize),
+
+    #[error("line {0}: public inputs must be the first rows of the circuit")]
+    PublicInputAfterGates(usize),
+
+    #[error("line {0}: the wire to the cell ({1}, {2}) is out of the circuit")]
+    InvalidWire(usize, usize, usize),
+}
@@ -14,6 +14,7 @@ pub mod alphas;
 pub mod bench;
 pub mod circuits;
 pub mod curve;
+pub mod diff;
 pub mod error;
 pub mod lagrange_basis_evaluations;
 pub mod linearization;
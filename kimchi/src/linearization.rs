@@ -2,11 +2,12 @@
 
 use crate::alphas::Alphas;
 use crate::circuits::argument::{Argument, ArgumentType};
+use crate::circuits::custom_gate::CustomGate;
 use crate::circuits::expr;
 use crate::circuits::lookup;
 use crate::circuits::lookup::{
     constraints::LookupConfiguration,
-    lookups::{LookupFeatures, LookupInfo, LookupPatterns},
+    lookups::{LookupBackend, LookupFeatures, LookupInfo, LookupPatterns},
 };
 use crate::circuits::polynomials::{
     complete_add::CompleteAdd,
@@ -28,9 +29,9 @@ use crate::circuits::{
     gate::GateType,
     wires::COLUMNS,
 };
-use ark_ff::{FftField, PrimeField, SquareRootField, Zero};
+use ark_ff::{PrimeField, SquareRootField, Zero};
 
-/// Get the expresion of constraints.
+/// Get the expresion of constraints, including the ones of the `custom_gates`.
 ///
 /// # Panics
 ///
@@ -38,16 +39,34 @@ use ark_ff::{FftField, PrimeField, SquareRootField, Zero};
 pub fn constraints_expr<F: PrimeField + SquareRootField>(
     feature_flags: Option<&FeatureFlags>,
     generic: bool,
+    custom_gates: &[CustomGate<F>],
+) -> (Expr<ConstantExpr<F>>, Alphas<F>) {
+    constraints_expr_with_backend(
+        feature_flags,
+        LookupBackend::default(),
+        generic,
+        custom_gates,
+    )
+}
+
+/// Get the expresion of constraints, generating the lookup constraints of `lookup_backend`
+/// when no `feature_flags` are given (otherwise, the backend of the feature flags is used).
+fn constraints_expr_with_backend<F: PrimeField + SquareRootField>(
+    feature_flags: Option<&FeatureFlags>,
+    lookup_backend: LookupBackend,
+    generic: bool,
+    custom_gates: &[CustomGate<F>],
 ) -> (Expr<ConstantExpr<F>>, Alphas<F>) {
     // register powers of alpha so that we don't reuse them across mutually inclusive constraints
     let mut powers_of_alpha = Alphas::<F>::default();
 
     // Set up powers of alpha. Only the max number of constraints matters.
     // The gate type argument can just be the zero gate.
-    powers_of_alpha.register(
-        ArgumentType::Gate(GateType::Zero),
-        VarbaseMul::<F>::CONSTRAINTS,
-    );
+    let gate_constraints = custom_gates
+        .iter()
+        .map(CustomGate::num_constraints)
+        .fold(VarbaseMul::<F>::CONSTRAINTS, u32::max);
+    powers_of_alpha.register(ArgumentType::Gate(GateType::Zero), gate_constraints);
 
     let mut cache = expr::Cache::default();
 
@@ -153,6 +172,12 @@ pub fn constraints_expr<F: PrimeField + SquareRootField>(
         }
     }
 
+    for custom_gate in custom_gates {
+        expr += custom_gate
+            .argument()
+            .combined_constraints(&powers_of_alpha, &mut cache);
+    }
+
     if generic {
         expr += generic::Generic::combined_constraints(&powers_of_alpha, &mut cache);
     }
@@ -160,11 +185,18 @@ pub fn constraints_expr<F: PrimeField + SquareRootField>(
     // permutation
     powers_of_alpha.register(ArgumentType::Permutation, permutation::CONSTRAINTS);
 
-    // lookup
+    // lookup, including the lookups of the custom gates
+    let custom_patterns: Vec<_> = custom_gates
+        .iter()
+        .filter_map(CustomGate::lookup_pattern)
+        .collect();
+    let has_custom_lookups = !custom_patterns.is_empty();
     if let Some(feature_flags) = feature_flags {
-        if feature_flags.lookup_features.patterns != LookupPatterns::default() {
+        if let Some(lookup_info) =
+            LookupInfo::create_with_custom_patterns(feature_flags.lookup_features, &custom_patterns)
+        {
             let lookup_configuration =
-                LookupConfiguration::new(LookupInfo::create(feature_flags.lookup_features));
+                LookupConfiguration::new(lookup_info).with_custom_patterns(custom_patterns);
             let constraints = lookup::constraints::constraints(&lookup_configuration, false);
 
             // note: the number of constraints depends on the lookup configuration,
@@ -189,8 +221,12 @@ pub fn constraints_expr<F: PrimeField + SquareRootField>(
             },
             uses_runtime_tables: true,
             joint_lookup_used: true,
+            backend: lookup_backend,
         };
-        let lookup_configuration = LookupConfiguration::new(LookupInfo::create(all_features));
+        let lookup_info = LookupInfo::create_with_custom_patterns(all_features, &custom_patterns)
+            .expect("all the lookup patterns are enabled");
+        let lookup_configuration =
+            LookupConfiguration::new(lookup_info).with_custom_patterns(custom_patterns);
         let constraints = lookup::constraints::constraints(&lookup_configuration, true);
 
         // note: the number of constraints depends on the lookup configuration,
@@ -220,9 +256,15 @@ pub fn constraints_expr<F: PrimeField + SquareRootField>(
 
     // Check that the feature flags correctly turn on or off the constraints generated by the given
     // flags.
+    // Note that the lookups of the custom gates are not controlled by feature flags.
     if cfg!(feature = "check_feature_flags") {
-        if let Some(feature_flags) = feature_flags {
-            let (feature_flagged_expr, _) = constraints_expr(None, generic);
+        if let Some(feature_flags) = feature_flags.filter(|_| !has_custom_lookups) {
+            let (feature_flagged_expr, _) = constraints_expr_with_backend(
+                None,
+                feature_flags.lookup_features.backend,
+                generic,
+                custom_gates,
+            );
             let feature_flagged_expr = feature_flagged_expr.apply_feature_flags(feature_flags);
             assert_eq!(expr, feature_flagged_expr);
         }
@@ -233,9 +275,10 @@ pub fn constraints_expr<F: PrimeField + SquareRootField>(
 }
 
 /// Adds the polynomials that are evaluated as part of the proof
-/// for the linearization to work.
-pub fn linearization_columns<F: FftField + SquareRootField>(
+/// for the linearization to work, given the lookups of the `custom_gates`.
+pub fn linearization_columns<F: PrimeField + SquareRootField>(
     feature_flags: Option<&FeatureFlags>,
+    custom_gates: &[CustomGate<F>],
 ) -> std::collections::HashSet<Column> {
     let mut h = std::collections::HashSet::new();
     use Column::*;
@@ -261,6 +304,7 @@ pub fn linearization_columns<F: FftField + SquareRootField>(
                     },
                     joint_lookup_used: true,
                     uses_runtime_tables: true,
+                    backend: LookupBackend::Plookup,
                 },
             }
         }
@@ -276,15 +320,16 @@ pub fn linearization_columns<F: FftField + SquareRootField>(
         h.insert(Coefficient(i));
     }
 
-    let lookup_info = if feature_flags.lookup_features.patterns == LookupPatterns::default() {
-        None
-    } else {
-        Some(LookupInfo::create(feature_flags.lookup_features))
-    };
+    let custom_patterns: Vec<_> = custom_gates
+        .iter()
+        .filter_map(CustomGate::lookup_pattern)
+        .collect();
+    let lookup_info =
+        LookupInfo::create_with_custom_patterns(feature_flags.lookup_features, &custom_patterns);
 
     // the lookup polynomials
     if let Some(lookup_info) = lookup_info {
-        for i in 0..=lookup_info.max_per_row {
+        for i in 0..lookup_info.num_sorted() {
             h.insert(LookupSorted(i));
         }
         h.insert(LookupAggreg);
@@ -312,6 +357,7 @@ pub fn linearization_columns<F: FftField + SquareRootField>(
 ///
 /// If the `feature_flags` argument is `None`, this will generate an expression using the
 /// `Expr::IfFeature` variant for each of the flags.
+/// The constraints of the `custom_gates` are always included.
 ///
 /// # Panics
 ///
@@ -319,10 +365,11 @@ pub fn linearization_columns<F: FftField + SquareRootField>(
 pub fn expr_linearization<F: PrimeField + SquareRootField>(
     feature_flags: Option<&FeatureFlags>,
     generic: bool,
+    custom_gates: &[CustomGate<F>],
 ) -> (Linearization<Vec<PolishToken<F>>>, Alphas<F>) {
-    let evaluated_cols = linearization_columns::<F>(feature_flags);
+    let evaluated_cols = linearization_columns::<F>(feature_flags, custom_gates);
 
-    let (expr, powers_of_alpha) = constraints_expr(feature_flags, generic);
+    let (expr, powers_of_alpha) = constraints_expr(feature_flags, generic, custom_gates);
 
     let linearization = expr
         .linearize(evaluated_cols)
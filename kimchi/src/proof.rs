@@ -43,6 +43,7 @@ pub struct PointEvaluations<Evals> {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LookupEvaluations<Evals> {
     /// sorted lookup table polynomial
+    /// (the multiplicities polynomial with the LogUp backend)
     pub sorted: Vec<Evals>,
     /// lookup aggregation polynomial
     pub aggreg: Evals,
@@ -83,7 +84,8 @@ pub struct ProofEvaluations<Evals> {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound = "G: ark_serialize::CanonicalDeserialize + ark_serialize::CanonicalSerialize")]
 pub struct LookupCommitments<G: AffineCurve> {
-    /// Commitments to the sorted lookup table polynomial (may have chunks)
+    /// Commitments to the sorted lookup table polynomial (may have chunks),
+    /// or to the multiplicities polynomial with the LogUp backend
     pub sorted: Vec<PolyComm<G>>,
     /// Commitment to the lookup aggregation polynomial
     pub aggreg: PolyComm<G>,
@@ -407,6 +409,7 @@ impl<F> ProofEvaluations<F> {
             Column::Index(GateType::Generic) => Some(&self.generic_selector),
             Column::Index(GateType::Poseidon) => Some(&self.poseidon_selector),
             Column::Index(_) => None,
+            Column::CustomIndex(_) => None,
             Column::Coefficient(i) => Some(&self.coefficients[i]),
             Column::Permutation(i) => Some(&self.s[i]),
         }
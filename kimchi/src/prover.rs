@@ -5,7 +5,9 @@ use crate::{
         argument::{Argument, ArgumentType},
         expr::{self, l0_1, Constants, Environment, LookupEnvironment},
         gate::GateType,
-        lookup::{self, runtime_tables::RuntimeTable, tables::combine_table_entry},
+        lookup::{
+            self, lookups::LookupBackend, runtime_tables::RuntimeTable, tables::combine_table_entry,
+        },
         polynomials::{
             complete_add::CompleteAdd,
             endomul_scalar::EndomulScalar,
@@ -163,6 +165,7 @@ where
         blinders: Option<[Option<PolyComm<G::ScalarField>>; COLUMNS]>,
     ) -> Result<Self> {
         internal_tracing::checkpoint!(internal_traces; create_recursive);
+        let _span = internal_tracing::span!(create_recursive);
 
         // make sure that the SRS is not smaller than the domain size
         let d1_size = index.cs.domain.d1.size();
@@ -199,6 +202,7 @@ where
         //~ 1. Pad the witness columns with Zero gates to make them the same length as the domain.
         //~    Then, randomize the last `ZK_ROWS` of each columns.
         internal_tracing::checkpoint!(internal_traces; pad_witness);
+        let mut _phase = internal_tracing::span!(pad_witness);
         for w in &mut witness {
             if w.len() != length_witness {
                 return Err(ProverError::WitnessCsInconsistent);
@@ -215,6 +219,7 @@ where
 
         //~ 1. Setup the Fq-Sponge.
         internal_tracing::checkpoint!(internal_traces; set_up_fq_sponge);
+        internal_tracing::next_span!(_phase; set_up_fq_sponge);
         let mut fq_sponge = EFqSponge::new(G::OtherCurve::sponge_params());
 
         //~ 1. Absorb the digest of the VerifierIndex.
@@ -261,6 +266,7 @@ where
         //~    Note: since the witness is in evaluation form,
         //~    we can use the `commit_evaluation` optimization.
         internal_tracing::checkpoint!(internal_traces; commit_to_witness_columns);
+        internal_tracing::next_span!(_phase; commit_to_witness_columns);
         let mut w_comm = vec![];
         for col in 0..COLUMNS {
             // witness coeff -> witness eval
@@ -320,6 +326,9 @@ where
                 "uses_lookup": true,
                 "uses_runtime_tables": lcs.runtime_tables.is_some(),
             });
+            internal_tracing::next_span!(_phase; use_lookup, {
+                "uses_runtime_tables": lcs.runtime_tables.is_some(),
+            });
             //~~ * if using runtime table:
             if let Some(cfg_runtime_tables) = &lcs.runtime_tables {
                 //~~~ * check that all the provided runtime tables have length and IDs that match the runtime table configuration of the index
@@ -472,20 +481,32 @@ where
             // TODO: This interpolation is avoidable.
             let joint_lookup_table = joint_lookup_table_d8.interpolate_by_ref();
 
-            //~~ * Compute the sorted evaluations.
+            //~~ * Compute the sorted evaluations
+            //~~   (or, with the LogUp backend, the multiplicities of the table entries).
             // TODO: Once we switch to committing using lagrange commitments,
             // `witness` will be consumed when we interpolate, so interpolation will
             // have to moved below this.
-            let sorted: Vec<_> = lookup::constraints::sorted(
-                dummy_lookup_value,
-                &joint_lookup_table_d8,
-                index.cs.domain.d1,
-                &index.cs.gates,
-                &witness,
-                joint_combiner,
-                table_id_combiner,
-                &lcs.configuration.lookup_info,
-            )?;
+            let sorted: Vec<_> = match lcs.configuration.lookup_info.features.backend {
+                LookupBackend::Plookup => lookup::constraints::sorted(
+                    dummy_lookup_value,
+                    &joint_lookup_table_d8,
+                    index.cs.domain.d1,
+                    &index.cs.gates,
+                    &witness,
+                    joint_combiner,
+                    table_id_combiner,
+                    &lcs.configuration,
+                )?,
+                LookupBackend::LogUp => vec![lookup::logup::multiplicities(
+                    &joint_lookup_table_d8,
+                    index.cs.domain.d1,
+                    &index.cs.gates,
+                    &witness,
+                    joint_combiner,
+                    table_id_combiner,
+                    &lcs.configuration,
+                )?],
+            };
 
             //~~ * Randomize the last `EVALS` rows in each of the sorted polynomials
             //~~   in order to add zero-knowledge to the protocol.
@@ -533,20 +554,34 @@ where
             //~~ * Compute the lookup aggregation polynomial.
             let joint_lookup_table_d8 = lookup_context.joint_lookup_table_d8.as_ref().unwrap();
 
-            let aggreg = lookup::constraints::aggregation::<_, G::ScalarField>(
-                lookup_context.dummy_lookup_value.unwrap(),
-                joint_lookup_table_d8,
-                index.cs.domain.d1,
-                &index.cs.gates,
-                &witness,
-                &lookup_context.joint_combiner.unwrap(),
-                &lookup_context.table_id_combiner.unwrap(),
-                beta,
-                gamma,
-                lookup_context.sorted.as_ref().unwrap(),
-                rng,
-                &lcs.configuration.lookup_info,
-            )?;
+            let aggreg = match lcs.configuration.lookup_info.features.backend {
+                LookupBackend::Plookup => lookup::constraints::aggregation::<_, G::ScalarField>(
+                    lookup_context.dummy_lookup_value.unwrap(),
+                    joint_lookup_table_d8,
+                    index.cs.domain.d1,
+                    &index.cs.gates,
+                    &witness,
+                    &lookup_context.joint_combiner.unwrap(),
+                    &lookup_context.table_id_combiner.unwrap(),
+                    beta,
+                    gamma,
+                    lookup_context.sorted.as_ref().unwrap(),
+                    rng,
+                    &lcs.configuration,
+                )?,
+                LookupBackend::LogUp => lookup::logup::aggregation::<_, G::ScalarField>(
+                    joint_lookup_table_d8,
+                    index.cs.domain.d1,
+                    &index.cs.gates,
+                    &witness,
+                    &lookup_context.joint_combiner.unwrap(),
+                    &lookup_context.table_id_combiner.unwrap(),
+                    gamma,
+                    &lookup_context.sorted.as_ref().unwrap()[0],
+                    rng,
+                    &lcs.configuration,
+                )?,
+            };
 
             //~~ * Commit to the aggregation polynomial.
             let aggreg_comm = index
@@ -569,6 +604,7 @@ where
 
         //~ 1. Compute the permutation aggregation polynomial $z$.
         internal_tracing::checkpoint!(internal_traces; z_permutation_aggregation_polynomial);
+        internal_tracing::next_span!(_phase; z_permutation_aggregation_polynomial);
         let z_poly = index.perm_aggreg(&witness, &beta, &gamma, rng)?;
 
         //~ 1. Commit (hidding) to the permutation aggregation polynomial $z$.
@@ -611,8 +647,10 @@ where
         };
 
         internal_tracing::checkpoint!(internal_traces; eval_witness_polynomials_over_domains);
+        internal_tracing::next_span!(_phase; eval_witness_polynomials_over_domains);
         let lagrange = index.cs.evaluate(&witness_poly, &z_poly);
         internal_tracing::checkpoint!(internal_traces; compute_index_evals);
+        internal_tracing::next_span!(_phase; compute_index_evals);
         let env = {
             let mut index_evals = HashMap::new();
             use GateType::*;
@@ -666,6 +704,14 @@ where
                 index_evals.insert(GateType::Rot64, selector);
             }
 
+            let custom_index_evals = index
+                .cs
+                .custom_gates
+                .iter()
+                .map(|custom_gate| custom_gate.id())
+                .zip(index.column_evaluations.custom_selectors8.iter())
+                .collect();
+
             let mds = &G::sponge_params().mds;
             Environment {
                 constants: Constants {
@@ -683,6 +729,7 @@ where
                 l0_1: l0_1(index.cs.domain.d1),
                 domain: index.cs.domain,
                 index: index_evals,
+                custom_index: custom_index_evals,
                 lookup: lookup_env,
             }
         };
@@ -690,10 +737,12 @@ where
         let mut cache = expr::Cache::default();
 
         internal_tracing::checkpoint!(internal_traces; compute_quotient_poly);
+        internal_tracing::next_span!(_phase; compute_quotient_poly);
 
         let quotient_poly = {
             // generic
             let mut t4 = {
+                let _span = internal_tracing::span!(generic_constraints);
                 let generic_constraint =
                     generic::Generic::combined_constraints(&all_alphas, &mut cache);
                 let generic4 = generic_constraint.evaluations(&env);
@@ -709,6 +758,7 @@ where
             };
             // permutation
             let (mut t8, bnd) = {
+                let _span = internal_tracing::span!(permutation_constraints);
                 let alphas =
                     all_alphas.get_alphas(ArgumentType::Permutation, permutation::CONSTRAINTS);
                 let (perm, bnd) = index.perm_quot(&lagrange, beta, gamma, &z_poly, alphas)?;
@@ -762,7 +812,17 @@ where
                 ]
                 .into_iter()
                 .filter_map(|(gate, is_enabled)| if is_enabled { Some(gate) } else { None })
-                {
+                // Custom gates
+                .chain(
+                    index
+                        .cs
+                        .custom_gates
+                        .iter()
+                        .map(|custom_gate| custom_gate.argument()),
+                ) {
+                    let _span = internal_tracing::span!(gate_constraints, {
+                        "argument": format!("{:?}", gate.argument_type()),
+                    });
                     let constraint = gate.combined_constraints(&all_alphas, &mut cache);
                     let eval = constraint.evaluations(&env);
                     if eval.domain().size == t4.domain().size {
@@ -779,6 +839,7 @@ where
             // lookup
             {
                 if let Some(lcs) = index.cs.lookup_constraint_system.as_ref() {
+                    let _span = internal_tracing::span!(lookup_constraints);
                     let constraints = lookup::constraints::constraints(&lcs.configuration, false);
                     let constraints_len = u32::try_from(constraints.len())
                         .expect("not expecting a large amount of constraints");
@@ -925,8 +986,10 @@ where
         //~    TODO: do we want to specify more on that? It seems unecessary except for the t polynomial (or if for some reason someone sets that to a low value)
 
         internal_tracing::checkpoint!(internal_traces; lagrange_basis_eval_zeta_poly);
+        internal_tracing::next_span!(_phase; lagrange_basis_eval_zeta_poly);
         let zeta_evals = LagrangeBasisEvaluations::new(index.cs.domain.d1, zeta);
         internal_tracing::checkpoint!(internal_traces; lagrange_basis_eval_zeta_omega_poly);
+        internal_tracing::next_span!(_phase; lagrange_basis_eval_zeta_omega_poly);
 
         let zeta_omega_evals = LagrangeBasisEvaluations::new(index.cs.domain.d1, zeta_omega);
 
@@ -943,6 +1006,7 @@ where
             };
 
         internal_tracing::checkpoint!(internal_traces; chunk_eval_zeta_omega_poly);
+        internal_tracing::next_span!(_phase; chunk_eval_zeta_omega_poly);
         let chunked_evals = ProofEvaluations::<PointEvaluations<Vec<G::ScalarField>>> {
             s: array::from_fn(|i| {
                 chunked_evals_for_evaluations(
@@ -994,6 +1058,7 @@ where
         //~ 1. Compute the ft polynomial.
         //~    This is to implement [Maller's optimization](https://o1-labs.github.io/mina-book/crypto/plonk/maller_15.html).
         internal_tracing::checkpoint!(internal_traces; compute_ft_poly);
+        internal_tracing::next_span!(_phase; compute_ft_poly);
         let ft: DensePolynomial<G::ScalarField> = {
             let f_chunked = {
                 // TODO: compute the linearization polynomial in evaluation form so
@@ -1044,6 +1109,7 @@ where
 
         //~ 1. Evaluate the ft polynomial at $\zeta\omega$ only.
         internal_tracing::checkpoint!(internal_traces; ft_eval_zeta_omega);
+        internal_tracing::next_span!(_phase; ft_eval_zeta_omega);
         let ft_eval1 = ft.evaluate(&zeta_omega);
 
         //~ 1. Setup the Fr-Sponge
@@ -1067,6 +1133,7 @@ where
 
         //~ 1. Compute evaluations for the previous recursion challenges.
         internal_tracing::checkpoint!(internal_traces; build_polynomials);
+        internal_tracing::next_span!(_phase; build_polynomials);
         let polys = prev_challenges
             .iter()
             .map(|RecursionChallenge { chals, comm }| {
@@ -1231,6 +1298,7 @@ where
 
         //~ 1. Create an aggregated evaluation proof for all of these polynomials at $\zeta$ and $\zeta\omega$ using $u$ and $v$.
         internal_tracing::checkpoint!(internal_traces; create_aggregated_evaluation_proof);
+        internal_tracing::next_span!(_phase; create_aggregated_evaluation_proof);
         let proof = index.srs.open(
             group_map,
             &polynomials,
@@ -3,19 +3,38 @@
 use crate::{
     alphas::Alphas,
     circuits::{
-        constraints::{ColumnEvaluations, ConstraintSystem},
+        constraints::{ColumnEvaluations, ConstraintSystem, FeatureFlags},
+        custom_gate::CustomGate,
+        domain_constant_evaluation::DomainConstantEvaluations,
         expr::{Linearization, PolishToken},
+        gate::Circuit,
+        lookup::runtime_tables::RuntimeTableSpec,
     },
     curve::KimchiCurve,
+    error::ProverIndexError,
     linearization::expr_linearization,
     verifier_index::VerifierIndex,
 };
-use ark_poly::EvaluationDomain;
+use ark_ff::PrimeField;
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain};
 use mina_poseidon::FqSponge;
+use o1_utils::hasher::CryptoDigest;
 use poly_commitment::srs::SRS;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_with::serde_as;
-use std::sync::Arc;
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::Arc,
+};
+
+/// The version of the format of [`ProverIndex::write`],
+/// to be increased whenever the serialization of the [`ProverIndex`] changes
+pub const PROVER_INDEX_VERSION: u32 = 3;
+
+/// The bytes that start a serialized [`ProverIndex`]
+pub const PROVER_INDEX_MAGIC: &[u8; 8] = b"kimchipi";
 
 /// The index used by the prover
 #[serde_as]
@@ -75,7 +94,8 @@ impl<G: KimchiCurve> ProverIndex<G> {
         cs.endo = endo_q;
 
         // pre-compute the linearization
-        let (linearization, powers_of_alpha) = expr_linearization(Some(&cs.feature_flags), true);
+        let (linearization, powers_of_alpha) =
+            expr_linearization(Some(&cs.feature_flags), true, &cs.custom_gates);
 
         let evaluated_column_coefficients = cs.evaluated_column_coefficients();
 
@@ -129,14 +149,222 @@ impl<G: KimchiCurve> ProverIndex<G> {
             Some(verifier_index) => verifier_index.digest::<EFqSponge>(),
         }
     }
+
+    /// The digest of the circuit of the index: of its gates and public input size (see [`Circuit`]),
+    /// and of its number of previous challenges, feature flags, lookup tables and runtime table configuration.
+    /// The other fields of the index, such as its precomputed polynomials and evaluations,
+    /// are derived from those and are not part of the digest.
+    pub fn circuit_digest(&self) -> [u8; 32] {
+        let lookup = self.cs.lookup_constraint_system.as_ref();
+        IndexCircuit {
+            circuit: Circuit::from(&self.cs).digest(),
+            prev_challenges: self.cs.prev_challenges,
+            feature_flags: self.cs.feature_flags,
+            lookup_table: lookup.map(|lookup| lookup.lookup_table.clone()),
+            table_ids: lookup.and_then(|lookup| lookup.table_ids.clone()),
+            runtime_tables: lookup.and_then(|lookup| lookup.runtime_tables.clone()),
+        }
+        .digest()
+    }
+
+    /// Writes a [`ProverIndex`] to a file, along with its precomputations,
+    /// so that [`ProverIndex::from_file`] doesn't have to compute them again.
+    /// As for the [`VerifierIndex`], the SRS is not written.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the file can't be written.
+    pub fn to_file(&self, path: &Path) -> Result<(), ProverIndexError> {
+        let io_error = |e: std::io::Error| ProverIndexError::Io(e.to_string());
+        let mut writer = BufWriter::new(File::create(path).map_err(io_error)?);
+        self.write(&mut writer)?;
+        writer.flush().map_err(io_error)
+    }
+
+    /// Writes a [`ProverIndex`] and its precomputations, after a header made of
+    /// [`PROVER_INDEX_MAGIC`], the [`PROVER_INDEX_VERSION`] and the [`ProverIndex::circuit_digest`].
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the index can't be serialized or written.
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), ProverIndexError> {
+        let io_error = |e: std::io::Error| ProverIndexError::Io(e.to_string());
+        writer.write_all(PROVER_INDEX_MAGIC).map_err(io_error)?;
+        writer
+            .write_all(&PROVER_INDEX_VERSION.to_le_bytes())
+            .map_err(io_error)?;
+        writer.write_all(&self.circuit_digest()).map_err(io_error)?;
+
+        (self, &**self.cs.precomputations())
+            .serialize(&mut rmp_serde::Serializer::new(writer))
+            .map_err(|e| ProverIndexError::Serialization(e.to_string()))
+    }
+
+    /// Reads a [`ProverIndex`] written by [`ProverIndex::to_file`], given the SRS it was created with,
+    /// and the digest of the circuit it is expected to be for.
+    /// Only the parts of the index covered by [`ProverIndex::circuit_digest`] are checked against it:
+    /// the precomputed polynomials and evaluations are read as they were written.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the file can't be read, or if the index doesn't match the SRS or the circuit digest.
+    pub fn from_file(
+        srs: Option<Arc<SRS<G>>>,
+        path: &Path,
+        circuit_digest: Option<[u8; 32]>,
+    ) -> Result<Self, ProverIndexError>
+    where
+        G::BaseField: PrimeField,
+    {
+        let file = File::open(path).map_err(|e| ProverIndexError::Io(e.to_string()))?;
+        Self::read(srs, BufReader::new(file), circuit_digest)
+    }
+
+    /// Reads a [`ProverIndex`] from bytes written by [`ProverIndex::write`].
+    /// The whole index is deserialized from the bytes, which are not borrowed by the index.
+    ///
+    /// # Errors
+    ///
+    /// See [`ProverIndex::read`].
+    pub fn from_bytes(
+        srs: Option<Arc<SRS<G>>>,
+        bytes: &[u8],
+        circuit_digest: Option<[u8; 32]>,
+    ) -> Result<Self, ProverIndexError>
+    where
+        G::BaseField: PrimeField,
+    {
+        Self::read(srs, bytes, circuit_digest)
+    }
+
+    /// Reads a [`ProverIndex`] written by [`ProverIndex::write`].
+    /// Without an SRS, the index can't be used to create proofs.
+    /// The linearization is computed again.
+    /// An index with custom gates must be read with [`ProverIndex::read_with_custom_gates`].
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the index can't be read or deserialized, if it has an unsupported version,
+    /// if it doesn't match the SRS or the circuit digest, or if it has custom gates.
+    pub fn read<R: Read>(
+        srs: Option<Arc<SRS<G>>>,
+        reader: R,
+        circuit_digest: Option<[u8; 32]>,
+    ) -> Result<Self, ProverIndexError>
+    where
+        G::BaseField: PrimeField,
+    {
+        Self::read_with_custom_gates(srs, reader, circuit_digest, vec![])
+    }
+
+    /// Reads a [`ProverIndex`] written by [`ProverIndex::write`], registering again
+    /// the `custom_gates` it was created with, as they are not serialized
+    /// (see [`ConstraintSystem::register_custom_gates`]).
+    ///
+    /// # Errors
+    ///
+    /// See [`ProverIndex::read`]. Will also give error if `custom_gates` are not the custom gates of the index.
+    pub fn read_with_custom_gates<R: Read>(
+        srs: Option<Arc<SRS<G>>>,
+        mut reader: R,
+        circuit_digest: Option<[u8; 32]>,
+        custom_gates: Vec<CustomGate<G::ScalarField>>,
+    ) -> Result<Self, ProverIndexError>
+    where
+        G::BaseField: PrimeField,
+    {
+        let mut magic = [0u8; 8];
+        reader
+            .read_exact(&mut magic)
+            .map_err(|_| ProverIndexError::InvalidFormat)?;
+        if &magic != PROVER_INDEX_MAGIC {
+            return Err(ProverIndexError::InvalidFormat);
+        }
+
+        let mut version = [0u8; 4];
+        reader
+            .read_exact(&mut version)
+            .map_err(|_| ProverIndexError::InvalidFormat)?;
+        let version = u32::from_le_bytes(version);
+        if version != PROVER_INDEX_VERSION {
+            return Err(ProverIndexError::UnsupportedVersion(
+                version,
+                PROVER_INDEX_VERSION,
+            ));
+        }
+
+        // check the digest of the header before deserializing the index
+        let mut digest = [0u8; 32];
+        reader
+            .read_exact(&mut digest)
+            .map_err(|_| ProverIndexError::InvalidFormat)?;
+        if circuit_digest.map_or(false, |expected| expected != digest) {
+            return Err(ProverIndexError::CircuitDigestMismatch);
+        }
+
+        let (mut index, precomputations): (Self, DomainConstantEvaluations<G::ScalarField>) =
+            Deserialize::deserialize(&mut rmp_serde::Deserializer::new(reader))
+                .map_err(|e| ProverIndexError::Deserialization(e.to_string()))?;
+        if index.circuit_digest() != digest {
+            return Err(ProverIndexError::CircuitDigestMismatch);
+        }
+        index
+            .cs
+            .register_custom_gates(custom_gates)
+            .map_err(|e| ProverIndexError::CustomGates(e.to_string()))?;
+
+        index.cs.set_precomputations(Arc::new(precomputations));
+
+        if let Some(mut srs) = srs {
+            if srs.g.len() != index.max_poly_size {
+                return Err(ProverIndexError::SRSSizeMismatch(
+                    index.max_poly_size,
+                    srs.g.len(),
+                ));
+            }
+            let domain = index.cs.domain.d1;
+            if !srs.lagrange_bases.contains_key(&domain.size()) {
+                Arc::make_mut(&mut srs).add_lagrange_basis(domain);
+            }
+            index.srs = srs;
+        }
+
+        let (linearization, powers_of_alpha) =
+            expr_linearization(Some(&index.cs.feature_flags), true, &index.cs.custom_gates);
+        index.linearization = linearization;
+        index.powers_of_alpha = powers_of_alpha;
+
+        Ok(index)
+    }
+}
+
+/// The parts of the constraint system of a [`ProverIndex`] covered by [`ProverIndex::circuit_digest`]
+#[serde_as]
+#[derive(Serialize)]
+struct IndexCircuit<F: PrimeField> {
+    circuit: [u8; 32],
+    prev_challenges: usize,
+    feature_flags: FeatureFlags,
+    #[serde_as(as = "Option<Vec<o1_utils::serialization::SerdeAs>>")]
+    lookup_table: Option<Vec<DensePolynomial<F>>>,
+    #[serde_as(as = "Option<o1_utils::serialization::SerdeAs>")]
+    table_ids: Option<DensePolynomial<F>>,
+    runtime_tables: Option<Vec<RuntimeTableSpec>>,
+}
+
+impl<F: PrimeField> CryptoDigest for IndexCircuit<F> {
+    const PREFIX: &'static [u8; 15] = b"kimchi-index000";
 }
 
 pub mod testing {
     use super::*;
     use crate::{
         circuits::{
+            custom_gate::CustomGate,
             gate::CircuitGate,
-            lookup::{runtime_tables::RuntimeTableCfg, tables::LookupTable},
+            lookup::{
+                lookups::LookupBackend, runtime_tables::RuntimeTableCfg, tables::LookupTable,
+            },
         },
         precomputed_srs,
     };
@@ -156,14 +384,52 @@ pub mod testing {
         runtime_tables: Option<Vec<RuntimeTableCfg<G::ScalarField>>>,
         disable_gates_checks: bool,
     ) -> ProverIndex<G>
+    where
+        G::BaseField: PrimeField,
+        G::ScalarField: PrimeField + SquareRootField,
+    {
+        new_index_for_test_with_custom_gates(
+            gates,
+            public,
+            prev_challenges,
+            lookup_tables,
+            runtime_tables,
+            LookupBackend::default(),
+            vec![],
+            disable_gates_checks,
+        )
+    }
+
+    /// Create new index for lookups, with a given lookup backend, and custom gates.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `constraint system` is not built with `gates` input.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_index_for_test_with_custom_gates<G: KimchiCurve>(
+        gates: Vec<CircuitGate<G::ScalarField>>,
+        public: usize,
+        prev_challenges: usize,
+        lookup_tables: Vec<LookupTable<G::ScalarField>>,
+        runtime_tables: Option<Vec<RuntimeTableCfg<G::ScalarField>>>,
+        lookup_backend: LookupBackend,
+        custom_gates: Vec<CustomGate<G::ScalarField>>,
+        disable_gates_checks: bool,
+    ) -> ProverIndex<G>
     where
         G::BaseField: PrimeField,
         G::ScalarField: PrimeField + SquareRootField,
     {
         // not sure if theres a smarter way instead of the double unwrap, but should be fine in the test
-        let cs = ConstraintSystem::<G::ScalarField>::create(gates)
+        let cs = custom_gates
+            .into_iter()
+            .fold(
+                ConstraintSystem::<G::ScalarField>::create(gates),
+                |builder, custom_gate| builder.custom_gate(custom_gate),
+            )
             .lookup(lookup_tables)
             .runtime(runtime_tables)
+            .lookup_backend(lookup_backend)
             .public(public)
             .prev_challenges(prev_challenges)
             .disable_gates_checks(disable_gates_checks)
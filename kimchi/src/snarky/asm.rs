@@ -1,13 +1,18 @@
-//! An ASM-like language to print a human-friendly version of a circuit.
+//! An ASM-like language to print a human-friendly version of a circuit,
+//! and to parse it back into gates.
 
 use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
 use std::hash::Hash;
+use std::str::FromStr;
 
 use crate::circuits::gate::{Circuit, CircuitGate, GateType};
 use crate::circuits::polynomials::generic::{GENERIC_COEFFS, GENERIC_REGISTERS};
-use crate::circuits::wires::Wire;
+use crate::circuits::wires::{Wire, PERMUTS};
+use crate::error::AsmError;
 use ark_ff::PrimeField;
+use num_bigint::BigUint;
+use strum::IntoEnumIterator;
 
 /// Print a field in a negative form if it's past the half point.
 fn pretty<F: ark_ff::PrimeField>(ff: F) -> String {
@@ -128,14 +133,9 @@ where
     }
 
     fn generic_cols(col: usize) -> &'static str {
-        match col {
-            0 => "l1",
-            1 => "r1",
-            2 => "o1",
-            3 => "l2",
-            4 => "r2",
-            5 => "o2",
-            x => panic!("invalid generic column: {x}"),
+        match GENERIC_COLS.get(col) {
+            Some(name) => name,
+            None => panic!("invalid generic column: {col}"),
         }
     }
 
@@ -164,6 +164,133 @@ where
     }
 }
 
+/// The names of the columns of the generic gate
+pub(crate) const GENERIC_COLS: [&str; 2 * GENERIC_REGISTERS] = ["l1", "r1", "o1", "l2", "r2", "o2"];
+
+/// Parses a circuit printed by [Circuit::generate_asm],
+/// and returns its number of public inputs and its gates.
+///
+/// # Errors
+///
+/// Will give error if the asm is malformed, or if it refers to rows or constants that do not exist.
+pub fn parse_asm<F: PrimeField>(asm: &str) -> Result<(usize, Vec<CircuitGate<F>>), AsmError> {
+    let mut constants: Vec<F> = vec![];
+    let mut gates: Vec<CircuitGate<F>> = vec![];
+    let mut public_input_size = 0;
+    // the wires to check once all the rows are known, with their line
+    let mut wires = vec![];
+
+    for (idx, line) in asm.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = line.trim();
+        let syntax = |reason: &str| AsmError::Syntax(line_no, format!("{reason}: {line}"));
+
+        if line.is_empty() {
+            continue;
+        } else if let Some(gate) = line.strip_prefix("row") {
+            // row<n>.[pub.]<type><coeffs>[<coeffs>]
+            let (row, gate) = gate
+                .split_once('.')
+                .ok_or_else(|| syntax("expected a row"))?;
+            let row: usize = row.parse().map_err(|_| syntax("invalid row"))?;
+            if row != gates.len() {
+                return Err(AsmError::UnexpectedRow(line_no, gates.len()));
+            }
+            let gate = match gate.strip_prefix("pub.") {
+                Some(gate) if public_input_size == row => {
+                    public_input_size += 1;
+                    gate
+                }
+                Some(_) => return Err(AsmError::PublicInputAfterGates(line_no)),
+                None => gate,
+            };
+
+            let (name, coeffs) = gate
+                .split_once('<')
+                .ok_or_else(|| syntax("expected coefficients"))?;
+            let typ = GateType::iter()
+                .find(|typ| format!("{typ:?}") == name)
+                .ok_or_else(|| AsmError::UnknownGate(line_no, name.to_string()))?;
+            let coeffs = coeffs
+                .strip_suffix('>')
+                .ok_or_else(|| syntax("unterminated coefficients"))?;
+            let coeffs = coeffs
+                .split("><")
+                .flat_map(|group| group.split(','))
+                .filter(|coeff| !coeff.is_empty())
+                .map(|coeff| match coeff.strip_prefix('c') {
+                    Some(idx) => idx
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|idx| constants.get(idx).copied())
+                        .ok_or_else(|| AsmError::UnknownConstant(line_no, coeff.to_string())),
+                    None => parse_field(coeff).ok_or_else(|| syntax("invalid coefficient")),
+                })
+                .collect::<Result<_, _>>()?;
+
+            gates.push(CircuitGate::new(typ, Wire::for_row(row), coeffs));
+        } else if line.starts_with('.') || line.starts_with('[') {
+            // <col> -> row<n><col>, ...
+            let gate = gates
+                .last_mut()
+                .ok_or_else(|| syntax("wires must follow a gate"))?;
+            for wire in line.split(", ") {
+                let (from, to) = wire
+                    .split_once(" -> ")
+                    .ok_or_else(|| syntax("expected a wire"))?;
+                let from = parse_col(from).ok_or_else(|| syntax("invalid column"))?;
+                let to = to
+                    .strip_prefix("row")
+                    .ok_or_else(|| syntax("expected a row"))?;
+                let split = to
+                    .find(|c: char| !c.is_ascii_digit())
+                    .ok_or_else(|| syntax("expected a column"))?;
+                let row: usize = to[..split].parse().map_err(|_| syntax("invalid row"))?;
+                let col = parse_col(&to[split..]).ok_or_else(|| syntax("invalid column"))?;
+                if from >= PERMUTS {
+                    return Err(syntax("only the first columns can be wired"));
+                }
+                gate.wires[from] = Wire::new(row, col);
+                wires.push((line_no, row, col));
+            }
+        } else if let Some((name, value)) = line.split_once(" = ") {
+            // c<n> = <value>
+            if name != format!("c{}", constants.len()) {
+                return Err(syntax("constants must be numbered in order"));
+            }
+            constants.push(parse_field(value).ok_or_else(|| syntax("invalid constant"))?);
+        } else {
+            return Err(syntax("unexpected line"));
+        }
+    }
+
+    for (line_no, row, col) in wires {
+        if row >= gates.len() || col >= PERMUTS {
+            return Err(AsmError::InvalidWire(line_no, row, col));
+        }
+    }
+
+    Ok((public_input_size, gates))
+}
+
+/// Parses a field element printed by [pretty]
+fn parse_field<F: PrimeField>(s: &str) -> Option<F> {
+    let (neg, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s),
+    };
+    let value = F::from(BigUint::from_str(digits).ok()?);
+    Some(if neg { -value } else { value })
+}
+
+/// Parses a column, printed as `.l1` for generic gates and as `[0]` otherwise
+fn parse_col(s: &str) -> Option<usize> {
+    match s.strip_prefix('.') {
+        Some(name) => GENERIC_COLS.iter().position(|col| *col == name),
+        None => s.strip_prefix('[')?.strip_suffix(']')?.parse().ok(),
+    }
+}
+
 /// Very dumb way to write an ordered hash set.
 #[derive(Default)]
 pub struct OrderedHashSet<T> {
@@ -205,9 +332,12 @@ where
 
 #[cfg(test)]
 mod tests {
-    use mina_curves::pasta::Fp;
+    use mina_curves::pasta::{Fp, Vesta};
 
+    use crate::circuits::gate::Connect;
+    use crate::circuits::polynomials::generic::GenericGateSpec;
     use crate::circuits::wires::Wirable;
+    use crate::curve::KimchiCurve;
 
     use super::*;
 
@@ -254,4 +384,122 @@ row2.Generic<1,2>
             panic!("obtained asm does not match expected asm")
         }
     }
+
+    fn assert_round_trip(public_input_size: usize, gates: &[CircuitGate<Fp>]) {
+        let asm = Circuit::new(public_input_size, gates).generate_asm();
+        let (parsed_public, parsed) = parse_asm::<Fp>(&asm).unwrap();
+
+        assert_eq!(parsed_public, public_input_size);
+        assert_eq!(parsed.len(), gates.len());
+        for (row, (parsed, gate)) in parsed.iter().zip(gates).enumerate() {
+            assert_eq!(parsed.typ, gate.typ, "row {row}");
+            assert_eq!(parsed.wires, gate.wires, "row {row}");
+            assert_eq!(parsed.coeffs, gate.coeffs, "row {row}");
+        }
+
+        // printing the parsed circuit gives back the same asm
+        assert_eq!(Circuit::new(parsed_public, &parsed).generate_asm(), asm);
+    }
+
+    #[test]
+    fn test_parse_simple_circuit_asm() {
+        let gates: Vec<CircuitGate<Fp>> = vec![
+            CircuitGate::new(
+                GateType::Generic,
+                Wire::for_row(0),
+                vec![1.into(), 2.into()],
+            ),
+            CircuitGate::new(
+                GateType::Poseidon,
+                Wire::for_row(1).wire(0, Wire::new(0, 1)),
+                vec![1.into(), 2.into()],
+            ),
+            CircuitGate::new(
+                GateType::Generic,
+                Wire::for_row(2)
+                    .wire(0, Wire::new(1, 2))
+                    .wire(3, Wire::new(2, 5))
+                    .wire(5, Wire::new(1, 1)),
+                vec![1.into(), 2.into()],
+            ),
+        ];
+
+        assert_round_trip(1, &gates);
+    }
+
+    #[test]
+    fn test_parse_gadgets_asm() {
+        // public inputs, followed by a double generic gate
+        let mut gates: Vec<CircuitGate<Fp>> = (0..2)
+            .map(|row| {
+                CircuitGate::create_generic_gadget(Wire::for_row(row), GenericGateSpec::Pub, None)
+            })
+            .collect();
+        gates.push(CircuitGate::create_generic_gadget(
+            Wire::for_row(2),
+            GenericGateSpec::Mul {
+                output_coeff: None,
+                mul_coeff: Some(-Fp::from(3u8)),
+            },
+            Some(GenericGateSpec::Const(Fp::from(123456789u64))),
+        ));
+
+        // a poseidon gadget, whose coefficients are large constants
+        let row = gates.len();
+        let (poseidon, _) = CircuitGate::create_poseidon_gadget(
+            row,
+            [Wire::for_row(row), Wire::for_row(row + 11)],
+            &Vesta::sponge_params().round_constants,
+        );
+        gates.extend(poseidon);
+        let row = gates.len();
+
+        // a foreign field multiplication and a multi range check
+        let modulus = BigUint::from(2u8).pow(255) - BigUint::from(19u8);
+        let (row, ffmul) = CircuitGate::create_foreign_field_mul(row, &modulus);
+        gates.extend(ffmul);
+        let (_, range_check) = CircuitGate::create_multi_range_check(row);
+        gates.extend(range_check);
+
+        gates.connect_cell_pair((0, 0), (2, 2));
+        gates.connect_cell_pair((1, 0), (3, 0));
+        gates.connect_cell_pair((2, 3), (15, 6));
+        gates.connect_cell_pair((15, 0), (17, 0));
+
+        assert_round_trip(2, &gates);
+    }
+
+    #[test]
+    fn test_parse_invalid_asm() {
+        let parse = |asm: &str| parse_asm::<Fp>(asm).map(|_| ());
+
+        assert_eq!(
+            parse("row0.Generic<1,0,0,0,0>\nrow2.Zero<>"),
+            Err(AsmError::UnexpectedRow(2, 1))
+        );
+        assert_eq!(
+            parse("row0.Foo<>"),
+            Err(AsmError::UnknownGate(1, "Foo".to_string()))
+        );
+        assert_eq!(
+            parse("c0 = 123456\nrow0.Generic<c0,c1>"),
+            Err(AsmError::UnknownConstant(2, "c1".to_string()))
+        );
+        assert_eq!(
+            parse("row0.Zero<>\nrow1.pub.Generic<1,0,0,0,0>"),
+            Err(AsmError::PublicInputAfterGates(2))
+        );
+        assert_eq!(
+            parse("row0.Zero<>\n[0] -> row1[0]"),
+            Err(AsmError::InvalidWire(2, 1, 0))
+        );
+        assert!(matches!(
+            parse("[0] -> row0[0]"),
+            Err(AsmError::Syntax(1, _))
+        ));
+        assert!(matches!(
+            parse("row0.Zero<>\n[9] -> row0[0]"),
+            Err(AsmError::Syntax(2, _))
+        ));
+    }
 }
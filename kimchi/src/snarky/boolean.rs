@@ -0,0 +1,37 @@
+//! Boolean variables of the snarky frontend.
+
+use super::cvar::FieldVar;
+use ark_ff::PrimeField;
+
+/// A field variable that is constrained to be 0 or 1.
+/// Booleans are created with [`RunState::exists_boolean`](super::runner::RunState::exists_boolean),
+/// from a constant, or by the logical operations of the [`RunState`](super::runner::RunState).
+#[derive(Clone, Debug)]
+pub struct Boolean<F>(FieldVar<F>);
+
+impl<F: PrimeField> Boolean<F> {
+    /// Creates a constant boolean
+    pub fn constant(b: bool) -> Self {
+        Self(FieldVar::Constant(if b { F::one() } else { F::zero() }))
+    }
+
+    /// Wraps a field variable that is already known to be a boolean
+    pub(super) fn unchecked(var: FieldVar<F>) -> Self {
+        Self(var)
+    }
+
+    /// The negation of the boolean, `1 - b`, which needs no constraint
+    pub fn not(&self) -> Self {
+        Self(FieldVar::one() - self.0.clone())
+    }
+
+    /// The boolean as a field variable
+    pub fn to_field_var(&self) -> FieldVar<F> {
+        self.0.clone()
+    }
+
+    /// Returns the value of the boolean if it is a constant
+    pub fn to_constant(&self) -> Option<bool> {
+        self.0.to_constant().map(|b| b.is_one())
+    }
+}
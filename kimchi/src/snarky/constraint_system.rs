@@ -1,6 +1,7 @@
 #![allow(clippy::all)]
 
 use crate::circuits::gate::{CircuitGate, GateType};
+use crate::circuits::lookup::{runtime_tables::RuntimeTableCfg, tables::LookupTable};
 use crate::circuits::polynomials::poseidon::{ROUNDS_PER_HASH, SPONGE_WIDTH};
 use crate::circuits::wires::{Wire, COLUMNS, PERMUTS};
 use ark_ff::PrimeField;
@@ -139,8 +140,17 @@ pub enum BasicSnarkyConstraint<Var> {
 }
 
 /** A PLONK constraint (or gate) can be [`Basic`](KimchiConstraint::Basic), [`Poseidon`](KimchiConstraint::Poseidon),
- * [`EcAddComplete`](KimchiConstraint::EcAddComplete), [`EcScale`](KimchiConstraint::EcScale),
- * [`EcEndoscale`](KimchiConstraint::EcEndoscale), or [`EcEndoscalar`](KimchiConstraint::EcEndoscalar). */
+* [`EcAddComplete`](KimchiConstraint::EcAddComplete), [`EcScale`](KimchiConstraint::EcScale),
+* [`EcEndoscale`](KimchiConstraint::EcEndoscale), [`EcEndoscalar`](KimchiConstraint::EcEndoscalar),
+* one of the gates of the range check, foreign field, XOR and rotation gadgets, or a [`Raw`](KimchiConstraint::Raw) row.
+* It can also register a lookup table used by the circuit.
+
+The rows of the gadgets are given as the variables of their cells, column by column
+(see the layout of each gate in [polynomials](crate::circuits::polynomials)).
+Cells that hold the same variable are wired together: for example, the `Zero` row of
+[`RangeCheck1`](KimchiConstraint::RangeCheck1) must reuse the variables of the 12-bit limbs
+of the two [`RangeCheck0`](KimchiConstraint::RangeCheck0) rows of a multi range check.
+The tables of the lookups performed by these gates are added by kimchi when creating the constraint system. */
 pub enum KimchiConstraint<Var, Field> {
     Basic {
         l: (Field, Var),
@@ -174,6 +184,55 @@ pub enum KimchiConstraint<Var, Field> {
     EcEndoscalar {
         state: Vec<EndoscaleScalarRound<Var>>,
     },
+    /** A `RangeCheck0` row, whose coefficient tells if the limbs are in compact form. */
+    RangeCheck0 {
+        row: Vec<Option<Var>>,
+        compact: Field,
+    },
+    /** A `RangeCheck1` row, followed by its `Zero` row. */
+    RangeCheck1 {
+        curr: Vec<Option<Var>>,
+        next: Vec<Option<Var>>,
+    },
+    /** A `ForeignFieldAdd` row, for the addition (sign 1) or the subtraction (sign -1)
+    modulo the foreign field modulus given in limbs. */
+    ForeignFieldAdd {
+        row: Vec<Option<Var>>,
+        foreign_field_modulus: [Field; 3],
+        sign: Field,
+    },
+    /** A `ForeignFieldMul` row, followed by its `Zero` row. */
+    ForeignFieldMul {
+        curr: Vec<Option<Var>>,
+        next: Vec<Option<Var>>,
+        foreign_field_modulus: [Field; 3],
+        neg_foreign_field_modulus: [Field; 3],
+    },
+    /** A `Xor16` row. A chain of XORs ends with a row holding its outputs, added with [`Raw`](KimchiConstraint::Raw). */
+    Xor16 {
+        row: Vec<Option<Var>>,
+    },
+    /** A `Rot64` row, for a rotation by the number of bits `rot` given as `2^rot`. */
+    Rot64 {
+        row: Vec<Option<Var>>,
+        two_to_rot: Field,
+    },
+    /** A row of any type, with the given coefficients. */
+    Raw {
+        kind: GateType,
+        values: Vec<Option<Var>>,
+        coeffs: Vec<Field>,
+    },
+    /** Registers a fixed lookup table. */
+    AddFixedLookupTable {
+        id: i32,
+        data: Vec<Vec<Field>>,
+    },
+    /** Registers a runtime lookup table, whose first column is fixed. */
+    AddRuntimeTableCfg {
+        id: i32,
+        first_column: Vec<Field>,
+    },
 }
 
 /* TODO: This is a Unique_id in OCaml. */
@@ -249,6 +308,10 @@ where
     as well.
     */
     union_finds: disjoint_set::DisjointSet<V>,
+    /** The fixed lookup tables registered by the circuit. */
+    lookup_tables: Vec<LookupTable<Field>>,
+    /** The runtime lookup tables registered by the circuit. */
+    runtime_tables: Vec<RuntimeTableCfg<Field>>,
 }
 
 impl<Field: PrimeField> SnarkyConstraintSystem<Field> {
@@ -300,26 +363,8 @@ impl<Field: PrimeField> SnarkyConstraintSystem<Field> {
                     None => (),
                     Some(V::External(var)) => res[col_idx][row_idx] = external_values(*var),
                     Some(V::Internal(var)) => {
-                        let (lc, c) = {
-                            match self.internal_vars.get(var) {
-                                None => panic!("Could not find {:?}", var),
-                                Some(x) => x,
-                            }
-                        };
-                        let value = {
-                            lc.iter().fold(c.unwrap_or(Field::zero()), |acc, (s, x)| {
-                                let x = match x {
-                                    V::External(x) => external_values(*x),
-                                    V::Internal(x) => match internal_values.get(x) {
-                                        None => panic!("Could not find {:?}", *x),
-                                        Some(value) => *value,
-                                    },
-                                };
-                                acc + (*s * x)
-                            })
-                        };
-                        res[col_idx][row_idx] = value;
-                        internal_values.insert(var, value);
+                        res[col_idx][row_idx] =
+                            self.internal_value(*var, &external_values, &mut internal_values)
                     }
                 }
             }
@@ -327,6 +372,34 @@ impl<Field: PrimeField> SnarkyConstraintSystem<Field> {
         res
     }
 
+    /// Computes the value of an internal variable, and of the internal variables it depends on.
+    /// These can be used in a row before the one that defines them,
+    /// when two generic gates share the same row.
+    fn internal_value<F: Fn(usize) -> Field>(
+        &self,
+        var: InternalVar,
+        external_values: &F,
+        internal_values: &mut HashMap<InternalVar, Field>,
+    ) -> Field {
+        if let Some(value) = internal_values.get(&var) {
+            return *value;
+        }
+        let (lc, c) = match self.internal_vars.get(&var) {
+            None => panic!("Could not find {:?}", var),
+            Some(x) => x,
+        };
+        let mut value = c.unwrap_or(Field::zero());
+        for (s, x) in lc {
+            let x = match x {
+                V::External(x) => external_values(*x),
+                V::Internal(x) => self.internal_value(*x, external_values, internal_values),
+            };
+            value += *s * x;
+        }
+        internal_values.insert(var, value);
+        value
+    }
+
     fn union_find(&mut self, value: V) {
         self.union_finds.make_set(value);
     }
@@ -354,6 +427,8 @@ impl<Field: PrimeField> SnarkyConstraintSystem<Field> {
             pending_generic_gate: None,
             cached_constants: HashMap::new(),
             union_finds: disjoint_set::DisjointSet::new(),
+            lookup_tables: Vec::new(),
+            runtime_tables: Vec::new(),
         }
     }
 
@@ -376,6 +451,17 @@ impl<Field: PrimeField> SnarkyConstraintSystem<Field> {
         self.auxiliary_input_size = x;
     }
 
+    /** Returns the fixed lookup tables registered by the circuit,
+    to give to the constraint system along with the gates. */
+    pub fn get_lookup_tables(&self) -> &[LookupTable<Field>] {
+        &self.lookup_tables
+    }
+
+    /** Returns the runtime lookup tables registered by the circuit. */
+    pub fn get_runtime_tables(&self) -> &[RuntimeTableCfg<Field>] {
+        &self.runtime_tables
+    }
+
     /** Sets the number of public-input. It should only be called once. */
     pub fn set_public_input_size(&mut self, x: usize) {
         self.public_input_size = Some(x);
@@ -459,7 +545,7 @@ impl<Field: PrimeField> SnarkyConstraintSystem<Field> {
             Field::zero(),
         ];
         let mut public_gates = Vec::new();
-        for row in 0..(public_input_size - 1) {
+        for row in 0..public_input_size {
             let public_var = V::External(row + 1);
             self.wire_(public_var, Row::PublicInput(row), 0);
             public_gates.push(GateSpec {
@@ -732,7 +818,7 @@ impl<Field: PrimeField> SnarkyConstraintSystem<Field> {
                 if s == Field::one() {
                     x
                 } else {
-                    let sx = self.create_internal(Some(s), vec![(s, x)]);
+                    let sx = self.create_internal(None, vec![(s, x)]);
                     // s * x - sx = 0
                     self.add_generic_constraint(
                         Some(x),
@@ -752,7 +838,7 @@ impl<Field: PrimeField> SnarkyConstraintSystem<Field> {
             (s, ConstantOrVar::Constant) => match self.cached_constants.get(&s) {
                 Some(x) => *x,
                 None => {
-                    let x = self.create_internal(None, vec![]);
+                    let x = self.create_internal(Some(s), vec![]);
                     self.add_generic_constraint(
                         Some(x),
                         None,
@@ -772,6 +858,27 @@ impl<Field: PrimeField> SnarkyConstraintSystem<Field> {
         }
     }
 
+    /** Adds a row of the given kind, whose cells hold the given variables. */
+    fn add_row_of_vars<Cvar>(
+        &mut self,
+        kind: GateType,
+        cells: Vec<Option<Cvar>>,
+        coeffs: Vec<Field>,
+    ) where
+        Cvar: SnarkyCvar<Field = Field>,
+    {
+        assert!(
+            cells.len() <= COLUMNS,
+            "a row has at most {COLUMNS} cells, got {}",
+            cells.len()
+        );
+        let vars = cells
+            .into_iter()
+            .map(|cell| cell.map(|x| self.reduce_to_var(x)))
+            .collect();
+        self.add_row(vars, kind, coeffs);
+    }
+
     /// Applies the basic `SnarkyConstraint`.
     /// Simply, place the values of `selector`(`sl`, `sr`, `so` ...) and `input`(`l`, `r`, `o`, `m`).
     ///
@@ -1090,7 +1197,7 @@ impl<Field: PrimeField> SnarkyConstraintSystem<Field> {
             KimchiConstraint::Poseidon { state } => {
                 // we expect state to be a vector of all the intermediary round states
                 // (in addition to the initial and final states)
-                assert_eq!(state.len(), ROUNDS_PER_HASH * ROUNDS_PER_HASH);
+                assert_eq!(state.len(), ROUNDS_PER_HASH + 1);
 
                 // where each state is three field elements
                 assert!(state.iter().all(|x| x.len() == SPONGE_WIDTH));
@@ -1317,6 +1424,52 @@ impl<Field: PrimeField> SnarkyConstraintSystem<Field> {
                     self.add_row(vars, GateType::EndoMulScalar, vec![]);
                 }
             }
+            KimchiConstraint::RangeCheck0 { row, compact } => {
+                self.add_row_of_vars(GateType::RangeCheck0, row, vec![compact]);
+            }
+            KimchiConstraint::RangeCheck1 { curr, next } => {
+                self.add_row_of_vars(GateType::RangeCheck1, curr, vec![]);
+                self.add_row_of_vars(GateType::Zero, next, vec![]);
+            }
+            KimchiConstraint::ForeignFieldAdd {
+                row,
+                foreign_field_modulus,
+                sign,
+            } => {
+                let mut coeffs = foreign_field_modulus.to_vec();
+                coeffs.push(sign);
+                self.add_row_of_vars(GateType::ForeignFieldAdd, row, coeffs);
+            }
+            KimchiConstraint::ForeignFieldMul {
+                curr,
+                next,
+                foreign_field_modulus,
+                neg_foreign_field_modulus,
+            } => {
+                let coeffs = [foreign_field_modulus, neg_foreign_field_modulus].concat();
+                self.add_row_of_vars(GateType::ForeignFieldMul, curr, coeffs);
+                self.add_row_of_vars(GateType::Zero, next, vec![]);
+            }
+            KimchiConstraint::Xor16 { row } => {
+                self.add_row_of_vars(GateType::Xor16, row, vec![]);
+            }
+            KimchiConstraint::Rot64 { row, two_to_rot } => {
+                self.add_row_of_vars(GateType::Rot64, row, vec![two_to_rot]);
+            }
+            KimchiConstraint::Raw {
+                kind,
+                values,
+                coeffs,
+            } => {
+                self.add_row_of_vars(kind, values, coeffs);
+            }
+            KimchiConstraint::AddFixedLookupTable { id, data } => {
+                self.lookup_tables.push(LookupTable { id, data });
+            }
+            KimchiConstraint::AddRuntimeTableCfg { id, first_column } => {
+                self.runtime_tables
+                    .push(RuntimeTableCfg::Custom { id, first_column });
+            }
         }
     }
 }
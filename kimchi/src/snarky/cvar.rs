@@ -0,0 +1,153 @@
+//! Field variables of the snarky frontend.
+
+use super::constraint_system::SnarkyCvar;
+use ark_ff::PrimeField;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// A linear combination of the variables of a circuit, plus a constant.
+/// The variable 0 is reserved for the constant one,
+/// followed by the public inputs and the witness variables created by `exists`.
+#[derive(Clone, Debug)]
+pub enum FieldVar<F> {
+    /// A constant
+    Constant(F),
+    /// A variable of the circuit
+    Var(usize),
+    /// The sum of two field variables
+    Add(Box<FieldVar<F>>, Box<FieldVar<F>>),
+    /// A field variable multiplied by a constant
+    Scale(F, Box<FieldVar<F>>),
+}
+
+impl<F: PrimeField> FieldVar<F> {
+    /// Creates a constant field variable
+    pub fn constant(c: F) -> Self {
+        Self::Constant(c)
+    }
+
+    /// The constant zero
+    pub fn zero() -> Self {
+        Self::Constant(F::zero())
+    }
+
+    /// The constant one
+    pub fn one() -> Self {
+        Self::Constant(F::one())
+    }
+
+    /// Returns the value of the field variable if it is a constant
+    pub fn to_constant(&self) -> Option<F> {
+        match self {
+            Self::Constant(c) => Some(*c),
+            Self::Var(_) => None,
+            Self::Add(x, y) => Some(x.to_constant()? + y.to_constant()?),
+            Self::Scale(s, x) => Some(*s * x.to_constant()?),
+        }
+    }
+
+    /// Computes the value of the field variable, given the values of the variables
+    pub fn eval(&self, values: &[F]) -> F {
+        match self {
+            Self::Constant(c) => *c,
+            Self::Var(i) => values[*i],
+            Self::Add(x, y) => x.eval(values) + y.eval(values),
+            Self::Scale(s, x) => *s * x.eval(values),
+        }
+    }
+
+    fn collect_terms(&self, scale: F, constant: &mut Option<F>, terms: &mut Vec<(F, usize)>) {
+        match self {
+            Self::Constant(c) => *constant = Some(constant.unwrap_or_else(F::zero) + scale * c),
+            Self::Var(i) => terms.push((scale, *i)),
+            Self::Add(x, y) => {
+                x.collect_terms(scale, constant, terms);
+                y.collect_terms(scale, constant, terms);
+            }
+            Self::Scale(s, x) => x.collect_terms(scale * s, constant, terms),
+        }
+    }
+}
+
+impl<F: PrimeField> SnarkyCvar for FieldVar<F> {
+    type Field = F;
+
+    fn to_constant_and_terms(&self) -> (Option<F>, Vec<(F, usize)>) {
+        let mut constant = None;
+        let mut terms = vec![];
+        self.collect_terms(F::one(), &mut constant, &mut terms);
+        (constant, terms)
+    }
+}
+
+impl<F: PrimeField> From<F> for FieldVar<F> {
+    fn from(c: F) -> Self {
+        Self::Constant(c)
+    }
+}
+
+impl<F: PrimeField> Add for FieldVar<F> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        match (&self, &other) {
+            (Self::Constant(x), Self::Constant(y)) => Self::Constant(*x + y),
+            (Self::Constant(x), _) if x.is_zero() => other,
+            (_, Self::Constant(y)) if y.is_zero() => self,
+            _ => Self::Add(Box::new(self), Box::new(other)),
+        }
+    }
+}
+
+impl<F: PrimeField> Add for &FieldVar<F> {
+    type Output = FieldVar<F>;
+
+    fn add(self, other: Self) -> FieldVar<F> {
+        self.clone() + other.clone()
+    }
+}
+
+impl<F: PrimeField> Neg for FieldVar<F> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self * -F::one()
+    }
+}
+
+impl<F: PrimeField> Sub for FieldVar<F> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl<F: PrimeField> Sub for &FieldVar<F> {
+    type Output = FieldVar<F>;
+
+    fn sub(self, other: Self) -> FieldVar<F> {
+        self.clone() - other.clone()
+    }
+}
+
+impl<F: PrimeField> Mul<F> for FieldVar<F> {
+    type Output = Self;
+
+    fn mul(self, scale: F) -> Self {
+        match self {
+            Self::Constant(x) => Self::Constant(x * scale),
+            _ if scale.is_zero() => Self::zero(),
+            _ if scale.is_one() => self,
+            Self::Scale(s, x) => Self::Scale(s * scale, x),
+            _ => Self::Scale(scale, Box::new(self)),
+        }
+    }
+}
+
+impl<F: PrimeField> Mul<F> for &FieldVar<F> {
+    type Output = FieldVar<F>;
+
+    fn mul(self, scale: F) -> FieldVar<F> {
+        self.clone() * scale
+    }
+}
@@ -1,3 +1,9 @@
+//! A port of the snarky constraint system, with a Rust frontend to write circuits.
+
 pub mod asm;
+pub mod boolean;
 pub mod constants;
 pub mod constraint_system;
+pub mod cvar;
+pub mod runner;
+pub mod wiring;
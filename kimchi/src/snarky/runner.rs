@@ -0,0 +1,391 @@
+//! The runner of the snarky frontend.
+//!
+//! A circuit is a Rust function that takes a [`RunState`] and the public inputs of the circuit,
+//! creates variables with [`RunState::exists`], and constrains them with the `assert_*`
+//! functions and the gadgets of the [`RunState`].
+//! The same function is run twice:
+//!
+//! - by [`compile`], which only records the constraints and returns the gates of the circuit,
+//! - by [`generate_witness`], which also runs the witness closures given to `exists`
+//!   and returns the gates along with the witness of the circuit.
+//!
+//! ```ignore
+//! fn circuit<F: PrimeField>(sys: &mut RunState<F>, public: Vec<FieldVar<F>>) {
+//!     let y = sys.exists(|sys| sys.read_var(&public[0]).sqrt().unwrap());
+//!     let y2 = sys.square(&y);
+//!     sys.assert_equal(&y2, &public[0]);
+//! }
+//! let compiled = compile(constants.clone(), 1, circuit);
+//! let (_, witness) = generate_witness(constants, &[Fp::from(4u8)], circuit)?;
+//! ```
+
+use super::{
+    boolean::Boolean,
+    constants::Constants,
+    constraint_system::{BasicSnarkyConstraint, KimchiConstraint, SnarkyConstraintSystem},
+    cvar::FieldVar,
+};
+use crate::{
+    circuits::{
+        gate::CircuitGate,
+        lookup::{runtime_tables::RuntimeTableCfg, tables::LookupTable},
+        polynomials::poseidon::{ROUNDS_PER_HASH, SPONGE_WIDTH},
+        wires::COLUMNS,
+    },
+    error::SnarkyError,
+};
+use ark_ff::{BitIteratorLE, PrimeField};
+use mina_poseidon::{constants::PlonkSpongeConstantsKimchi, permutation::full_round};
+use std::array;
+
+/// A circuit compiled by snarky
+pub struct CompiledCircuit<F: PrimeField> {
+    /// The gates of the circuit, starting with the public inputs
+    pub gates: Vec<CircuitGate<F>>,
+    /// The fixed lookup tables registered by the circuit
+    pub lookup_tables: Vec<LookupTable<F>>,
+    /// The runtime lookup tables registered by the circuit
+    pub runtime_tables: Vec<RuntimeTableCfg<F>>,
+}
+
+/// The state of a circuit being run: the constraint system being written,
+/// and the values of the variables when a witness is generated.
+pub struct RunState<F: PrimeField> {
+    system: SnarkyConstraintSystem<F>,
+    constants: Constants<F>,
+    public_input_size: usize,
+    next_var: usize,
+    /// The values of the variables, starting with the constant one and the public inputs
+    values: Option<Vec<F>>,
+    /// The first constraint that is not satisfied by the witness
+    error: Option<SnarkyError>,
+}
+
+impl<F: PrimeField> RunState<F> {
+    fn new(constants: Constants<F>, public_input_size: usize, values: Option<Vec<F>>) -> Self {
+        let mut system = SnarkyConstraintSystem::create(constants.clone());
+        system.set_public_input_size(public_input_size);
+        Self {
+            system,
+            constants,
+            public_input_size,
+            next_var: 1 + public_input_size,
+            values,
+            error: None,
+        }
+    }
+
+    /// Whether the witness is being generated, in which case the values of variables can be read
+    pub fn has_witness(&self) -> bool {
+        self.values.is_some()
+    }
+
+    /// Reads the value of a field variable.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the witness is not being generated (see [`RunState::has_witness`]),
+    /// and the variable is not a constant.
+    pub fn read_var(&self, var: &FieldVar<F>) -> F {
+        match (var.to_constant(), &self.values) {
+            (Some(c), _) => c,
+            (None, Some(values)) => var.eval(values),
+            (None, None) => {
+                panic!("the value of a variable can only be read in witness generation")
+            }
+        }
+    }
+
+    /// Reads the value of a boolean.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the witness is not being generated, and the boolean is not a constant.
+    pub fn read_boolean(&self, b: &Boolean<F>) -> bool {
+        self.read_var(&b.to_field_var()).is_one()
+    }
+
+    /// Creates a new variable of the witness, whose value is computed by `compute`.
+    /// `compute` is only called when the witness is generated.
+    pub fn exists(&mut self, compute: impl FnOnce(&Self) -> F) -> FieldVar<F> {
+        let var = self.next_var;
+        self.next_var += 1;
+        if self.has_witness() {
+            let value = compute(self);
+            if let Some(values) = self.values.as_mut() {
+                values.push(value);
+            }
+        }
+        FieldVar::Var(var)
+    }
+
+    /// Creates a new boolean of the witness, and constrains it to be 0 or 1
+    pub fn exists_boolean(&mut self, compute: impl FnOnce(&Self) -> bool) -> Boolean<F> {
+        let var = self.exists(|sys| if compute(sys) { F::one() } else { F::zero() });
+        self.assert_boolean(&var);
+        Boolean::unchecked(var)
+    }
+
+    // Records the constraint as unsatisfied if `holds` is false for the witness.
+    // Constraints between constants are checked directly, as the backend does not accept them.
+    fn check(
+        &mut self,
+        vars: &[&FieldVar<F>],
+        holds: impl FnOnce(&[F]) -> bool,
+        constraint: &str,
+    ) -> bool {
+        let constant = vars.iter().all(|var| var.to_constant().is_some());
+        if self.error.is_none() && (constant || self.has_witness()) {
+            let values: Vec<_> = vars.iter().map(|var| self.read_var(var)).collect();
+            if !holds(&values) {
+                let values: Vec<_> = values.iter().map(ToString::to_string).collect();
+                self.error = Some(SnarkyError::UnsatisfiedConstraint(format!(
+                    "{constraint} on ({})",
+                    values.join(", ")
+                )));
+            }
+        }
+        constant
+    }
+
+    /// Constrains `x` and `y` to be equal
+    pub fn assert_equal(&mut self, x: &FieldVar<F>, y: &FieldVar<F>) {
+        if !self.check(&[x, y], |v| v[0] == v[1], "assert_equal") {
+            self.system
+                .add_basic_snarky_constraint(BasicSnarkyConstraint::Equal(x.clone(), y.clone()));
+        }
+    }
+
+    /// Constrains `x * y` to be equal to `z`
+    pub fn assert_r1cs(&mut self, x: &FieldVar<F>, y: &FieldVar<F>, z: &FieldVar<F>) {
+        if !self.check(&[x, y, z], |v| v[0] * v[1] == v[2], "assert_r1cs") {
+            self.system
+                .add_basic_snarky_constraint(BasicSnarkyConstraint::R1CS(
+                    x.clone(),
+                    y.clone(),
+                    z.clone(),
+                ));
+        }
+    }
+
+    /// Constrains `x * x` to be equal to `z`
+    pub fn assert_square(&mut self, x: &FieldVar<F>, z: &FieldVar<F>) {
+        if !self.check(&[x, z], |v| v[0].square() == v[1], "assert_square") {
+            self.system
+                .add_basic_snarky_constraint(BasicSnarkyConstraint::Square(x.clone(), z.clone()));
+        }
+    }
+
+    /// Constrains `x` to be 0 or 1
+    pub fn assert_boolean(&mut self, x: &FieldVar<F>) {
+        if !self.check(&[x], |v| v[0].is_zero() || v[0].is_one(), "assert_boolean") {
+            self.system
+                .add_basic_snarky_constraint(BasicSnarkyConstraint::Boolean(x.clone()));
+        }
+    }
+
+    /// Constrains a boolean to be true
+    pub fn assert_true(&mut self, b: &Boolean<F>) {
+        self.assert_equal(&b.to_field_var(), &FieldVar::one());
+    }
+
+    /// Adds a kimchi constraint, like the rows of a gadget.
+    /// The constraint is not checked when the witness is generated.
+    pub fn add_constraint(&mut self, constraint: KimchiConstraint<FieldVar<F>, F>) {
+        self.system.add_constraint(constraint);
+    }
+
+    /// Multiplies two field variables
+    pub fn mul(&mut self, x: &FieldVar<F>, y: &FieldVar<F>) -> FieldVar<F> {
+        match (x.to_constant(), y.to_constant()) {
+            (Some(x), _) => y * x,
+            (_, Some(y)) => x * y,
+            (None, None) => {
+                let z = self.exists(|sys| sys.read_var(x) * sys.read_var(y));
+                self.assert_r1cs(x, y, &z);
+                z
+            }
+        }
+    }
+
+    /// Squares a field variable
+    pub fn square(&mut self, x: &FieldVar<F>) -> FieldVar<F> {
+        match x.to_constant() {
+            Some(x) => FieldVar::Constant(x.square()),
+            None => {
+                let z = self.exists(|sys| sys.read_var(x).square());
+                self.assert_square(x, &z);
+                z
+            }
+        }
+    }
+
+    /// Computes the inverse of a field variable, and constrains it to be non-zero
+    pub fn inv(&mut self, x: &FieldVar<F>) -> FieldVar<F> {
+        let inv = self.exists(|sys| sys.read_var(x).inverse().unwrap_or_else(F::zero));
+        self.assert_r1cs(x, &inv, &FieldVar::one());
+        inv
+    }
+
+    /// Divides `x` by `y`, and constrains `y` to be non-zero
+    pub fn div(&mut self, x: &FieldVar<F>, y: &FieldVar<F>) -> FieldVar<F> {
+        let inv = self.inv(y);
+        self.mul(x, &inv)
+    }
+
+    /// Returns `then_` if `b` is true, and `else_` otherwise
+    pub fn if_(&mut self, b: &Boolean<F>, then_: &FieldVar<F>, else_: &FieldVar<F>) -> FieldVar<F> {
+        // else + b * (then - else)
+        let diff = then_ - else_;
+        let choice = self.mul(&b.to_field_var(), &diff);
+        else_ + &choice
+    }
+
+    /// The conjunction of two booleans
+    pub fn and(&mut self, a: &Boolean<F>, b: &Boolean<F>) -> Boolean<F> {
+        Boolean::unchecked(self.mul(&a.to_field_var(), &b.to_field_var()))
+    }
+
+    /// The disjunction of two booleans
+    pub fn or(&mut self, a: &Boolean<F>, b: &Boolean<F>) -> Boolean<F> {
+        self.and(&a.not(), &b.not()).not()
+    }
+
+    /// The exclusive disjunction of two booleans
+    pub fn xor(&mut self, a: &Boolean<F>, b: &Boolean<F>) -> Boolean<F> {
+        // a + b - 2 * a * b
+        let (a, b) = (a.to_field_var(), b.to_field_var());
+        let ab = self.mul(&a, &b);
+        Boolean::unchecked(a + b - ab * F::from(2u64))
+    }
+
+    /// Decomposes a field variable into `len` booleans, in little-endian order,
+    /// and constrains the variable to fit in `len` bits.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `len` is not smaller than the bit size of the field.
+    pub fn to_bits(&mut self, x: &FieldVar<F>, len: usize) -> Vec<Boolean<F>> {
+        assert!(
+            len < F::size_in_bits(),
+            "cannot decompose a field element into {len} bits"
+        );
+        let bits: Vec<_> = (0..len)
+            .map(|i| {
+                self.exists_boolean(|sys| {
+                    BitIteratorLE::new(sys.read_var(x).into_repr())
+                        .nth(i)
+                        .unwrap_or(false)
+                })
+            })
+            .collect();
+        let packed = Self::from_bits(&bits);
+        self.assert_equal(&packed, x);
+        bits
+    }
+
+    /// Packs booleans, in little-endian order, into a field variable
+    pub fn from_bits(bits: &[Boolean<F>]) -> FieldVar<F> {
+        let mut packed = FieldVar::zero();
+        let mut power = F::one();
+        for bit in bits {
+            packed = packed + bit.to_field_var() * power;
+            power.double_in_place();
+        }
+        packed
+    }
+
+    /// Applies the Poseidon permutation of kimchi to a state,
+    /// with a [`Poseidon`](crate::circuits::gate::GateType::Poseidon) gadget.
+    pub fn poseidon(&mut self, input: [FieldVar<F>; SPONGE_WIDTH]) -> [FieldVar<F>; SPONGE_WIDTH] {
+        let mut values = self
+            .has_witness()
+            .then(|| input.iter().map(|x| self.read_var(x)).collect::<Vec<_>>());
+        let mut states = vec![input.to_vec()];
+        for round in 0..ROUNDS_PER_HASH {
+            if let Some(values) = values.as_mut() {
+                full_round::<F, PlonkSpongeConstantsKimchi>(
+                    &self.constants.poseidon,
+                    values,
+                    round,
+                );
+            }
+            let state = (0..SPONGE_WIDTH)
+                .map(|i| self.exists(|_| values.as_ref().expect("witness generation")[i]))
+                .collect();
+            states.push(state);
+        }
+        let output = states[ROUNDS_PER_HASH].clone();
+        self.system
+            .add_constraint(KimchiConstraint::Poseidon { state: states });
+        array::from_fn(|i| output[i].clone())
+    }
+
+    fn public_inputs(&self) -> Vec<FieldVar<F>> {
+        (1..=self.public_input_size).map(FieldVar::Var).collect()
+    }
+
+    fn run<C>(mut self, circuit: C) -> Self
+    where
+        C: FnOnce(&mut Self, Vec<FieldVar<F>>),
+    {
+        let public = self.public_inputs();
+        circuit(&mut self, public);
+        self.system
+            .set_auxiliary_input_size(self.next_var - 1 - self.public_input_size);
+        self
+    }
+
+    fn compiled(&mut self) -> CompiledCircuit<F> {
+        CompiledCircuit {
+            gates: self.system.finalize_and_get_gates().clone(),
+            lookup_tables: self.system.get_lookup_tables().to_vec(),
+            runtime_tables: self.system.get_runtime_tables().to_vec(),
+        }
+    }
+}
+
+/// Compiles a circuit with `public_input_size` public inputs
+pub fn compile<F, C>(
+    constants: Constants<F>,
+    public_input_size: usize,
+    circuit: C,
+) -> CompiledCircuit<F>
+where
+    F: PrimeField,
+    C: FnOnce(&mut RunState<F>, Vec<FieldVar<F>>),
+{
+    RunState::new(constants, public_input_size, None)
+        .run(circuit)
+        .compiled()
+}
+
+/// Runs a circuit on the given public inputs, and returns the compiled circuit and its witness.
+/// The public inputs are the first values of the first column of the witness.
+///
+/// # Errors
+///
+/// Will give error if a constraint of the circuit is not satisfied by the witness.
+pub fn generate_witness<F, C>(
+    constants: Constants<F>,
+    public_inputs: &[F],
+    circuit: C,
+) -> Result<(CompiledCircuit<F>, [Vec<F>; COLUMNS]), SnarkyError>
+where
+    F: PrimeField,
+    C: FnOnce(&mut RunState<F>, Vec<FieldVar<F>>),
+{
+    let mut values = vec![F::one()];
+    values.extend_from_slice(public_inputs);
+    let mut state = RunState::new(constants, public_inputs.len(), Some(values)).run(circuit);
+    if let Some(error) = state.error.take() {
+        return Err(error);
+    }
+    let compiled = state.compiled();
+    let values = state.values.unwrap_or_default();
+    let mut witness = state.system.compute_witness(|var| values[var]);
+    Ok((
+        compiled,
+        array::from_fn(|col| std::mem::take(&mut witness[col])),
+    ))
+}
@@ -0,0 +1,251 @@
+//! Exports the wiring of a circuit (the permutation of its cells) as a graph,
+//! either in the DOT language of Graphviz or as an adjacency list.
+//!
+//! Rows are grouped in gadgets, which are the runs of contiguous rows of the same gate type
+//! (the public input rows always form their own gadget).
+
+use std::fmt::Write;
+
+use crate::circuits::gate::{Circuit, GateType};
+use crate::circuits::wires::{Wire, PERMUTS};
+use crate::snarky::asm::GENERIC_COLS;
+use ark_ff::PrimeField;
+use serde::{Deserialize, Serialize};
+
+/// A row of the circuit, as a node of the wiring graph
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WiringRow {
+    pub typ: GateType,
+    pub public: bool,
+    /// The index of the gadget containing the row
+    pub gadget: usize,
+}
+
+/// A run of contiguous rows of the same gate type
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Gadget {
+    pub typ: GateType,
+    pub public: bool,
+    /// The first row of the gadget
+    pub start: usize,
+    /// The row following the last row of the gadget
+    pub end: usize,
+}
+
+/// A wire of the permutation, going from a cell to the next cell of its copy cycle
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WiringEdge {
+    pub from: Wire,
+    pub to: Wire,
+}
+
+/// The wiring of a circuit, as an adjacency list.
+/// Cells that are only wired to themselves have no edge.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Wiring {
+    pub public_input_size: usize,
+    pub rows: Vec<WiringRow>,
+    pub gadgets: Vec<Gadget>,
+    pub edges: Vec<WiringEdge>,
+}
+
+impl<'a, F> Circuit<'a, F>
+where
+    F: PrimeField,
+{
+    /// Returns the wiring of the circuit, which can be serialized (in JSON for example).
+    pub fn wiring(&self) -> Wiring {
+        let mut rows = Vec::with_capacity(self.gates.len());
+        let mut gadgets: Vec<Gadget> = vec![];
+
+        for (row, gate) in self.gates.iter().enumerate() {
+            let public = row < self.public_input_size;
+            match gadgets.last_mut() {
+                Some(gadget) if gadget.typ == gate.typ && gadget.public == public => {
+                    gadget.end = row + 1;
+                }
+                _ => gadgets.push(Gadget {
+                    typ: gate.typ,
+                    public,
+                    start: row,
+                    end: row + 1,
+                }),
+            }
+            rows.push(WiringRow {
+                typ: gate.typ,
+                public,
+                gadget: gadgets.len() - 1,
+            });
+        }
+
+        let edges = self
+            .gates
+            .iter()
+            .enumerate()
+            .flat_map(|(row, gate)| {
+                gate.wires
+                    .iter()
+                    .enumerate()
+                    .filter(move |(col, to)| to.row != row || to.col != *col)
+                    .map(move |(col, to)| WiringEdge {
+                        from: Wire { row, col },
+                        to: *to,
+                    })
+            })
+            .collect();
+
+        Wiring {
+            public_input_size: self.public_input_size,
+            rows,
+            gadgets,
+            edges,
+        }
+    }
+
+    /// Prints the wiring of the circuit in the DOT language.
+    /// Each row is a node with a port per permutable column,
+    /// each gadget is a cluster, and public input rows are highlighted.
+    pub fn generate_dot(&self) -> String {
+        let wiring = self.wiring();
+        let mut res = String::new();
+
+        res.push_str("digraph circuit {\n");
+        res.push_str("  rankdir=LR;\n");
+        res.push_str("  node [shape=record, fontname=monospace];\n");
+
+        for (idx, gadget) in wiring.gadgets.iter().enumerate() {
+            let Gadget {
+                typ,
+                public,
+                start,
+                end,
+            } = gadget;
+            let label = if *public {
+                "public input".to_string()
+            } else {
+                format!("{typ:?}")
+            };
+            writeln!(res, "  subgraph cluster_{idx} {{").unwrap();
+            writeln!(res, "    label=\"{label} (rows {start}..{end})\";").unwrap();
+
+            for row in *start..*end {
+                let ports: Vec<_> = (0..PERMUTS)
+                    .map(|col| format!("<c{col}>{}", Self::col_name(*typ, col)))
+                    .collect();
+                let style = if *public {
+                    ", style=filled, fillcolor=lightblue"
+                } else {
+                    ""
+                };
+                let is_pub = if *public { "pub." } else { "" };
+                writeln!(
+                    res,
+                    "    row{row} [label=\"{{row{row}.{is_pub}{typ:?}|{{{}}}}}\"{style}];",
+                    ports.join("|")
+                )
+                .unwrap();
+            }
+
+            res.push_str("  }\n");
+        }
+
+        for WiringEdge { from, to } in &wiring.edges {
+            writeln!(
+                res,
+                "  row{}:c{} -> row{}:c{};",
+                from.row, from.col, to.row, to.col
+            )
+            .unwrap();
+        }
+
+        res.push_str("}\n");
+        res
+    }
+
+    /// The name of a column in the DOT graph, as in the asm for generic gates
+    fn col_name(typ: GateType, col: usize) -> String {
+        match GENERIC_COLS.get(col) {
+            Some(name) if matches!(typ, GateType::Generic) => name.to_string(),
+            _ => col.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mina_curves::pasta::Fp;
+
+    use crate::circuits::gate::{CircuitGate, Connect};
+    use crate::circuits::polynomials::generic::GenericGateSpec;
+
+    use super::*;
+
+    #[test]
+    fn test_wiring_of_range_checks() {
+        // a public input, a zero, a 64-bit range check of the public input, and a multi-range check
+        let mut gates = vec![
+            CircuitGate::<Fp>::create_generic_gadget(Wire::for_row(0), GenericGateSpec::Pub, None),
+            CircuitGate::<Fp>::create_generic_gadget(
+                Wire::for_row(1),
+                GenericGateSpec::Const(Fp::from(0u8)),
+                None,
+            ),
+        ];
+        let (_, range_check) = CircuitGate::<Fp>::create_range_check(2);
+        gates.extend(range_check);
+        let (_, multi_range_check) = CircuitGate::<Fp>::create_multi_range_check(3);
+        gates.extend(multi_range_check);
+        gates.connect_cell_pair((0, 0), (2, 0));
+        gates.connect_64bit(1, 2);
+
+        let circuit = Circuit::new(1, &gates);
+        let wiring = circuit.wiring();
+
+        assert_eq!(wiring.rows.len(), gates.len());
+        assert!(wiring.rows[0].public && !wiring.rows[1].public);
+        assert_eq!(
+            wiring
+                .gadgets
+                .iter()
+                .map(|gadget| (gadget.typ, gadget.start, gadget.end))
+                .collect::<Vec<_>>(),
+            vec![
+                (GateType::Generic, 0, 1),
+                (GateType::Generic, 1, 2),
+                (GateType::RangeCheck0, 2, 5),
+                (GateType::RangeCheck1, 5, 6),
+                (GateType::Zero, 6, 7),
+            ]
+        );
+
+        // every wired cell has exactly one outgoing and one incoming edge
+        let wired = |row, col| Wire { row, col };
+        for cell in [
+            wired(0, 0),
+            wired(1, 0),
+            wired(2, 0),
+            wired(2, 1),
+            wired(2, 2),
+            wired(3, 1),
+            wired(6, 3),
+        ] {
+            assert_eq!(wiring.edges.iter().filter(|e| e.from == cell).count(), 1);
+            assert_eq!(wiring.edges.iter().filter(|e| e.to == cell).count(), 1);
+        }
+        for edge in &wiring.edges {
+            assert_eq!(gates[edge.from.row].wires[edge.from.col], edge.to);
+        }
+
+        let dot = circuit.generate_dot();
+        assert!(dot.starts_with("digraph circuit {"));
+        assert!(dot.contains("label=\"public input (rows 0..1)\""));
+        assert!(dot.contains("label=\"RangeCheck0 (rows 2..5)\""));
+        assert!(dot.contains("row0 [label=\"{row0.pub.Generic|{<c0>l1|"));
+        for edge in &wiring.edges {
+            assert!(dot.contains(&format!(
+                "row{}:c{} -> row{}:c{};",
+                edge.from.row, edge.from.col, edge.to.row, edge.to.col
+            )));
+        }
+    }
+}
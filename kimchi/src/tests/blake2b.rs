@@ -0,0 +1,162 @@
+use crate::{
+    circuits::{
+        gate::{CircuitGate, Connect},
+        polynomials::{
+            blake2b::{self, Blake2bLayout, Blake2bVariant},
+            generic::GenericGateSpec,
+        },
+        wires::{Wire, COLUMNS},
+    },
+    tests::framework::TestFramework,
+};
+use ark_ff::Zero;
+use blake2::{
+    digest::{consts::U32, Digest},
+    Blake2b, Blake2b512,
+};
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::array;
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+fn native_digest(variant: Blake2bVariant, message: &[u8]) -> Vec<u8> {
+    match variant {
+        Blake2bVariant::Blake2b256 => Blake2b::<U32>::digest(message).to_vec(),
+        Blake2bVariant::Blake2b512 => Blake2b512::digest(message).to_vec(),
+    }
+}
+
+// Creates a circuit whose public inputs are the words of the message and of the digest
+fn create_circuit(variant: Blake2bVariant, message_len: usize) -> Vec<CircuitGate<Fp>> {
+    let num_message = blake2b::num_message_words(message_len);
+    let num_public = num_message + variant.digest_words();
+    let mut gates: Vec<_> = (0..num_public)
+        .map(|row| {
+            CircuitGate::<Fp>::create_generic_gadget(Wire::for_row(row), GenericGateSpec::Pub, None)
+        })
+        .collect();
+
+    let layout = CircuitGate::extend_blake2b_gadget(&mut gates, variant, message_len);
+    for i in 0..num_message {
+        gates.connect_cell_pair((i, 0), layout.message(i));
+    }
+    for i in 0..variant.digest_words() {
+        gates.connect_cell_pair((num_message + i, 0), layout.digest(i));
+    }
+    gates
+}
+
+// Creates the witness of the circuit, and returns it along with the public inputs
+fn create_witness(
+    variant: Blake2bVariant,
+    message: &[u8],
+    digest: &[u8],
+) -> ([Vec<Fp>; COLUMNS], Vec<Fp>) {
+    let mut public = blake2b::words_of_bytes(message);
+    public.extend(blake2b::words_of_bytes::<Fp>(digest));
+
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); public.len()]);
+    witness[0].clone_from(&public);
+    blake2b::extend_blake2b_witness(&mut witness, variant, message);
+
+    (witness, public)
+}
+
+#[test]
+fn test_blake2b_witness_matches_native() {
+    let long: Vec<u8> = (0..300).map(|i| (i * 7 + 3) as u8).collect();
+    for variant in [Blake2bVariant::Blake2b256, Blake2bVariant::Blake2b512] {
+        for message in [
+            &b""[..],
+            &b"abc"[..],
+            &long[..127],
+            &long[..128],
+            &long[..129],
+            &long[..],
+        ] {
+            let (witness, digest) = blake2b::create_blake2b_witness::<Fp>(variant, message);
+            assert_eq!(digest, native_digest(variant, message));
+
+            let layout = Blake2bLayout::new(0, variant, message.len());
+            assert_eq!(witness[0].len(), layout.num_rows());
+            for (i, word) in blake2b::words_of_bytes::<Fp>(&digest)
+                .into_iter()
+                .enumerate()
+            {
+                let (row, col) = layout.digest(i);
+                assert_eq!(witness[col][row], word);
+            }
+            for (i, word) in blake2b::words_of_bytes::<Fp>(message)
+                .into_iter()
+                .enumerate()
+            {
+                let (row, col) = layout.message(i);
+                assert_eq!(witness[col][row], word);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_blake2b() {
+    let message = b"The quick brown fox jumps over the lazy dog";
+    for variant in [Blake2bVariant::Blake2b256, Blake2bVariant::Blake2b512] {
+        let digest = native_digest(variant, message);
+        let (witness, public) = create_witness(variant, message, &digest);
+
+        TestFramework::<Vesta>::default()
+            .gates(create_circuit(variant, message.len()))
+            .witness(witness)
+            .public_inputs(public)
+            .setup()
+            .prove_and_verify::<BaseSponge, ScalarSponge>()
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_blake2b_wrong_digest() {
+    let variant = Blake2bVariant::Blake2b256;
+    let message = b"abc";
+    let mut digest = native_digest(variant, message);
+    digest[0] ^= 1;
+    let (witness, public) = create_witness(variant, message, &digest);
+
+    let res = TestFramework::<Vesta>::default()
+        .gates(create_circuit(variant, message.len()))
+        .witness(witness)
+        .public_inputs(public)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>();
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_blake2b_message_word_too_large() {
+    let variant = Blake2bVariant::Blake2b256;
+    let message = b"abc";
+    let (mut witness, mut public) =
+        create_witness(variant, message, &native_digest(variant, message));
+
+    // the word of a 3-byte message must fit in 24 bits
+    let extra = Fp::from(1u64 << 24);
+    public[0] += extra;
+    witness[0][0] += extra;
+    let layout = Blake2bLayout::new(public.len(), variant, message.len());
+    let (row, col) = layout.message(0);
+    witness[col][row] += extra;
+
+    let res = TestFramework::<Vesta>::default()
+        .gates(create_circuit(variant, message.len()))
+        .witness(witness)
+        .public_inputs(public)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>();
+    assert!(res.is_err());
+}
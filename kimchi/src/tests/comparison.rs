@@ -0,0 +1,245 @@
+use crate::{
+    circuits::{
+        gate::{CircuitGate, Connect},
+        polynomials::{
+            comparison::{self, UInt},
+            generic::GenericGateSpec,
+            range_check::bits::{self, RangeCheckBitsMode},
+        },
+        wires::{Wire, COLUMNS},
+    },
+    tests::framework::TestFramework,
+};
+use ark_ff::{Field, One, Zero};
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::array;
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+const RNG_SEED: [u8; 32] = [
+    17, 230, 94, 5, 181, 64, 139, 22, 250, 7, 111, 48, 203, 36, 170, 92, 3, 145, 77, 218, 59, 10,
+    188, 124, 41, 236, 99, 160, 28, 85, 213, 66,
+];
+
+// Creates the public input rows of a circuit, and the matching witness
+fn public_rows(public: &[Fp]) -> (Vec<CircuitGate<Fp>>, [Vec<Fp>; COLUMNS]) {
+    let gates = (0..public.len())
+        .map(|row| {
+            CircuitGate::<Fp>::create_generic_gadget(Wire::for_row(row), GenericGateSpec::Pub, None)
+        })
+        .collect();
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); public.len()]);
+    witness[0] = public.to_vec();
+    (gates, witness)
+}
+
+fn prove(gates: Vec<CircuitGate<Fp>>, witness: [Vec<Fp>; COLUMNS], public: Vec<Fp>) -> bool {
+    TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .public_inputs(public)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .is_ok()
+}
+
+// Range checks a public `value` on `bits` bits
+fn prove_range_check_bits(value: Fp, bits: usize, mode: RangeCheckBitsMode) -> bool {
+    let (mut gates, mut witness) = public_rows(&[value]);
+    let (_, mut range_check) = CircuitGate::create_range_check_bits_with_mode(1, bits, mode);
+    gates.append(&mut range_check);
+    let layout = bits::RangeCheckBitsLayout::with_mode(1, bits, mode);
+    gates.connect_cell_pair((0, 0), layout.value());
+
+    let mut range_check = bits::create_bits_with_mode(value, bits, mode);
+    for col in 0..COLUMNS {
+        witness[col].append(&mut range_check[col]);
+    }
+    prove(gates, witness, vec![value])
+}
+
+#[test]
+fn test_range_check_bits_cheapest_mode() {
+    assert_eq!(RangeCheckBitsMode::cheapest(1), RangeCheckBitsMode::Generic);
+    assert_eq!(RangeCheckBitsMode::cheapest(3), RangeCheckBitsMode::Generic);
+    assert_eq!(RangeCheckBitsMode::cheapest(4), RangeCheckBitsMode::Lookup);
+    assert_eq!(
+        RangeCheckBitsMode::cheapest(253),
+        RangeCheckBitsMode::Lookup
+    );
+
+    assert_eq!(RangeCheckBitsMode::Lookup.num_rows(8), 3);
+    assert_eq!(RangeCheckBitsMode::Lookup.num_rows(64), 2);
+    assert_eq!(RangeCheckBitsMode::Lookup.num_rows(128), 3);
+    assert_eq!(RangeCheckBitsMode::Lookup.num_rows(253), 8);
+}
+
+#[test]
+fn test_range_check_bits() {
+    let rng = &mut StdRng::from_seed(RNG_SEED);
+    for bits in [1, 2, 3, 8, 32, 63, 64, 65, 128, 200, 253] {
+        let max = Fp::from(2u64).pow([bits as u64]) - Fp::one();
+        let random = Fp::from(rng.gen::<u128>() >> (128 - bits.min(128)));
+        for value in [Fp::zero(), max, random] {
+            let mode = RangeCheckBitsMode::cheapest(bits);
+            assert!(prove_range_check_bits(value, bits, mode), "{bits} bits");
+        }
+    }
+}
+
+#[test]
+fn test_range_check_bits_both_modes() {
+    for bits in [3, 8, 16] {
+        let max = Fp::from(2u64).pow([bits as u64]) - Fp::one();
+        for mode in [RangeCheckBitsMode::Generic, RangeCheckBitsMode::Lookup] {
+            assert!(
+                prove_range_check_bits(max, bits, mode),
+                "{bits} bits {mode:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_range_check_bits_out_of_range() {
+    for (bits, mode) in [
+        (1, RangeCheckBitsMode::Generic),
+        (8, RangeCheckBitsMode::Generic),
+        (8, RangeCheckBitsMode::Lookup),
+        (64, RangeCheckBitsMode::Lookup),
+        (65, RangeCheckBitsMode::Lookup),
+        (253, RangeCheckBitsMode::Lookup),
+    ] {
+        let value = Fp::from(2u64).pow([bits as u64]);
+        assert!(
+            !prove_range_check_bits(value, bits, mode),
+            "{bits} bits {mode:?}"
+        );
+    }
+}
+
+#[test]
+fn test_comparisons() {
+    let rng = &mut StdRng::from_seed(RNG_SEED);
+    for uint in [UInt::U8, UInt::U16, UInt::U32, UInt::U64, UInt::U128] {
+        let max = u128::MAX >> (128 - uint.bits());
+        let x = rng.gen::<u128>() & max;
+        let pairs = [
+            (0, 0),
+            (0, max),
+            (max, 0),
+            (max, max),
+            (x, x),
+            (x / 2, x),
+            (x, x / 2),
+        ];
+
+        // public inputs are the operands and the results of both comparisons for each pair
+        let public: Vec<_> = pairs
+            .iter()
+            .flat_map(|&(a, b)| {
+                [a, b]
+                    .map(Fp::from)
+                    .into_iter()
+                    .chain([a < b, a <= b].map(Fp::from))
+            })
+            .collect();
+        let (mut gates, mut witness) = public_rows(&public);
+
+        for (i, &(a, b)) in pairs.iter().enumerate() {
+            let lt = CircuitGate::extend_less_than(&mut gates, uint);
+            let le = CircuitGate::extend_less_or_equal(&mut gates, uint);
+            for layout in [lt, le] {
+                gates.connect_cell_pair((4 * i, 0), layout.a());
+                gates.connect_cell_pair((4 * i + 1, 0), layout.b());
+            }
+            gates.connect_cell_pair((4 * i + 2, 0), lt.out());
+            gates.connect_cell_pair((4 * i + 3, 0), le.out());
+
+            assert_eq!(
+                comparison::extend_less_than_witness(&mut witness, uint, a, b),
+                a < b
+            );
+            assert_eq!(
+                comparison::extend_less_or_equal_witness(&mut witness, uint, a, b),
+                a <= b
+            );
+        }
+
+        assert!(prove(gates, witness, public), "{uint:?}");
+    }
+}
+
+#[test]
+fn test_comparison_wrong_result() {
+    let uint = UInt::U32;
+    let public = vec![Fp::from(7u64), Fp::from(3u64)];
+    let (mut gates, mut witness) = public_rows(&public);
+    let layout = CircuitGate::extend_less_than(&mut gates, uint);
+    gates.connect_cell_pair((0, 0), layout.a());
+    gates.connect_cell_pair((1, 0), layout.b());
+
+    let (mut comparison, out) = comparison::create_less_than_witness::<Fp>(uint, 7, 3);
+    assert!(!out);
+
+    // claim that 7 < 3, adjusting the decomposition of x accordingly
+    let two_to_n = Fp::from(2u64).pow([uint.bits() as u64]);
+    comparison[3][0] = Fp::one();
+    comparison[4][0] = Fp::one();
+    comparison[0][1] = Fp::one();
+    comparison[1][1] -= two_to_n;
+    let mut range_check = bits::create_bits(comparison[1][1], uint.bits());
+    for col in 0..COLUMNS {
+        comparison[col].truncate(2);
+        witness[col].append(&mut comparison[col]);
+        witness[col].append(&mut range_check[col]);
+    }
+
+    assert!(!prove(gates, witness, public));
+}
+
+#[test]
+fn test_is_in_interval() {
+    let uint = UInt::U64;
+    let max = u64::MAX as u128;
+    for (value, lower, upper) in [
+        (5, 5, 10),
+        (10, 5, 10),
+        (7, 5, 10),
+        (4, 5, 10),
+        (11, 5, 10),
+        (max, 0, max),
+        (3, 10, 5),
+    ] {
+        let out = lower <= value && value <= upper;
+        let public: Vec<_> = [value, lower, upper]
+            .map(Fp::from)
+            .into_iter()
+            .chain([Fp::from(out)])
+            .collect();
+        let (mut gates, mut witness) = public_rows(&public);
+
+        let layout = CircuitGate::extend_is_in_interval(&mut gates, uint);
+        gates.connect_cell_pair((0, 0), layout.value());
+        gates.connect_cell_pair((1, 0), layout.lower_bound());
+        gates.connect_cell_pair((2, 0), layout.upper_bound());
+        gates.connect_cell_pair((3, 0), layout.out());
+
+        assert_eq!(
+            comparison::extend_is_in_interval_witness(&mut witness, uint, value, lower, upper),
+            out
+        );
+
+        assert!(
+            prove(gates, witness, public),
+            "{value} in [{lower}, {upper}]"
+        );
+    }
+}
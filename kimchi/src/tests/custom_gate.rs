@@ -0,0 +1,362 @@
+use crate::{
+    circuits::{
+        argument::{Argument, ArgumentEnv, ArgumentType},
+        constraints::ConstraintSystem,
+        custom_gate::CustomGate,
+        expr::{constraints::ExprOps, Cache},
+        gate::{CircuitGate, Connect},
+        lookup::{
+            lookups::{JointLookupSpec, LocalPosition, LookupTableID},
+            registry::LookupTableRegistry,
+        },
+        polynomials::generic::GenericGateSpec,
+        wires::{Wire, COLUMNS},
+    },
+    error::{ProverIndexError, SetupError},
+    proof::ProverProof,
+    prover_index::ProverIndex,
+    tests::framework::TestFramework,
+    verifier::verify,
+};
+use ark_ff::{Field, PrimeField, Zero};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use o1_utils::field_helpers::i32_to_field;
+use poly_commitment::commitment::CommitmentCurve;
+use std::{array, marker::PhantomData};
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+// Constrains w(1) = w(0)^3 and w(2) = w(0)^4
+#[derive(Default)]
+struct Cube<F>(PhantomData<F>);
+
+impl<F: PrimeField> Argument<F> for Cube<F> {
+    const ARGUMENT_TYPE: ArgumentType = ArgumentType::CustomGate(1);
+    const CONSTRAINTS: u32 = 2;
+
+    fn constraint_checks<T: ExprOps<F>>(env: &ArgumentEnv<F, T>, cache: &mut Cache) -> Vec<T> {
+        let x = env.witness_curr(0);
+        let cube = cache.cache(x.clone() * x.clone() * x.clone());
+        vec![
+            env.witness_curr(1) - cube.clone(),
+            env.witness_curr(2) - cube * x,
+        ]
+    }
+}
+
+// Constrains w(0) of the next row to be w(0) + 1
+#[derive(Default)]
+struct Increment<F>(PhantomData<F>);
+
+impl<F: PrimeField> Argument<F> for Increment<F> {
+    const ARGUMENT_TYPE: ArgumentType = ArgumentType::CustomGate(2);
+    const CONSTRAINTS: u32 = 1;
+
+    fn constraint_checks<T: ExprOps<F>>(env: &ArgumentEnv<F, T>, _cache: &mut Cache) -> Vec<T> {
+        vec![env.witness_next(0) - env.witness_curr(0) - T::one()]
+    }
+}
+
+// Constrains w(1) = w(0)^2, and performs lookups on its rows
+#[derive(Default)]
+struct Square<F>(PhantomData<F>);
+
+impl<F: PrimeField> Argument<F> for Square<F> {
+    const ARGUMENT_TYPE: ArgumentType = ArgumentType::CustomGate(3);
+    const CONSTRAINTS: u32 = 1;
+
+    fn constraint_checks<T: ExprOps<F>>(env: &ArgumentEnv<F, T>, _cache: &mut Cache) -> Vec<T> {
+        vec![env.witness_curr(1) - env.witness_curr(0).square()]
+    }
+}
+
+// The public inputs are x and x^3, followed by a cube gate on x and an increment gate on x
+fn create_circuit() -> Vec<CircuitGate<Fp>> {
+    let mut gates: Vec<_> = (0..2)
+        .map(|row| {
+            CircuitGate::<Fp>::create_generic_gadget(Wire::for_row(row), GenericGateSpec::Pub, None)
+        })
+        .collect();
+    for row in 2..5 {
+        gates.push(CircuitGate::zero(Wire::for_row(row)));
+    }
+    gates.connect_cell_pair((0, 0), (2, 0));
+    gates.connect_cell_pair((1, 0), (2, 1));
+    gates.connect_cell_pair((2, 0), (3, 0));
+    gates
+}
+
+fn custom_gates() -> Vec<CustomGate<Fp>> {
+    vec![
+        CustomGate::new(Cube::default(), vec![2]),
+        CustomGate::new(Increment::default(), vec![3]),
+    ]
+}
+
+fn create_witness(x: Fp) -> ([Vec<Fp>; COLUMNS], Vec<Fp>) {
+    let public = vec![x, x * x * x];
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); 5]);
+    witness[0][0] = public[0];
+    witness[0][1] = public[1];
+    witness[0][2] = x;
+    witness[1][2] = public[1];
+    witness[2][2] = public[1] * x;
+    witness[0][3] = x;
+    witness[0][4] = x + Fp::from(1u64);
+    (witness, public)
+}
+
+#[test]
+fn test_custom_gate() {
+    let (witness, public) = create_witness(Fp::from(7u64));
+
+    TestFramework::<Vesta>::default()
+        .gates(create_circuit())
+        .custom_gates(custom_gates())
+        .witness(witness)
+        .public_inputs(public)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
+
+#[test]
+fn test_custom_gate_wrong_witness() {
+    let (mut witness, public) = create_witness(Fp::from(7u64));
+    witness[0][4] += Fp::from(1u64);
+
+    let res = TestFramework::<Vesta>::default()
+        .gates(create_circuit())
+        .custom_gates(custom_gates())
+        .witness(witness)
+        .public_inputs(public)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>();
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_custom_gate_verifier_index() {
+    let runner = TestFramework::<Vesta>::default()
+        .gates(create_circuit())
+        .custom_gates(custom_gates())
+        .setup();
+    let verifier_index = runner.prover_index().verifier_index();
+    let ids: Vec<_> = verifier_index
+        .custom_gates
+        .iter()
+        .map(|(id, _)| *id)
+        .collect();
+    assert_eq!(ids, vec![1, 2]);
+
+    // the digest covers the custom gates
+    let runner = TestFramework::<Vesta>::default()
+        .gates(create_circuit())
+        .custom_gates(vec![CustomGate::new(Cube::default(), vec![2])])
+        .setup();
+    assert_ne!(
+        verifier_index.digest::<BaseSponge>(),
+        runner
+            .prover_index()
+            .verifier_index()
+            .digest::<BaseSponge>()
+    );
+}
+
+#[test]
+fn test_custom_gate_prover_index_serialization() {
+    let (witness, public) = create_witness(Fp::from(7u64));
+    let runner = TestFramework::<Vesta>::default()
+        .gates(create_circuit())
+        .custom_gates(custom_gates())
+        .public_inputs(public.clone())
+        .setup();
+    let index = runner.prover_index();
+    let mut bytes = vec![];
+    index.write(&mut bytes).unwrap();
+
+    // the custom gates are not serialized, and have to be registered again
+    assert!(matches!(
+        ProverIndex::<Vesta>::from_bytes(None, &bytes, None),
+        Err(ProverIndexError::CustomGates(_))
+    ));
+    let res = ProverIndex::<Vesta>::read_with_custom_gates(
+        None,
+        &bytes[..],
+        None,
+        vec![CustomGate::new(Cube::default(), vec![2])],
+    );
+    assert!(matches!(res, Err(ProverIndexError::CustomGates(_))));
+
+    let reloaded = ProverIndex::<Vesta>::read_with_custom_gates(
+        Some(index.srs.clone()),
+        &bytes[..],
+        None,
+        custom_gates(),
+    )
+    .unwrap();
+
+    // the reloaded index creates proofs for the verifier index
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &reloaded)
+            .unwrap();
+    verify::<Vesta, BaseSponge, ScalarSponge>(&group_map, &index.verifier_index(), &proof, &public)
+        .unwrap();
+}
+
+#[test]
+fn test_custom_gate_invalid_rows() {
+    // a custom gate cannot be enabled on a row that holds another gate
+    let res = ConstraintSystem::create(create_circuit())
+        .custom_gate(CustomGate::new(Cube::default(), vec![1]))
+        .build();
+    assert!(matches!(res, Err(SetupError::CustomGate(_))));
+
+    // nor outside of the circuit
+    let res = ConstraintSystem::create(create_circuit())
+        .custom_gate(CustomGate::new(Cube::default(), vec![5]))
+        .build();
+    assert!(matches!(res, Err(SetupError::CustomGate(_))));
+
+    // and two custom gates cannot share a selector id
+    let res = ConstraintSystem::create(create_circuit())
+        .custom_gate(CustomGate::new(Cube::default(), vec![2]))
+        .custom_gate(CustomGate::new(Cube::default(), vec![3]))
+        .build();
+    assert!(matches!(res, Err(SetupError::CustomGate(_))));
+
+    // or be enabled on the same row
+    let res = ConstraintSystem::create(create_circuit())
+        .custom_gate(CustomGate::new(Cube::default(), vec![2]))
+        .custom_gate(CustomGate::new(Increment::default(), vec![2]))
+        .build();
+    assert!(matches!(res, Err(SetupError::CustomGate(_))));
+}
+
+// The tables of the square gate: the squares of the digits, and the digits
+fn square_tables() -> LookupTableRegistry<Fp> {
+    let mut registry = LookupTableRegistry::new();
+    let digits: Vec<Fp> = (0..16u64).map(Into::into).collect();
+    let squares = digits.iter().map(Field::square).collect();
+    registry
+        .add_table("squares", vec![digits.clone(), squares])
+        .unwrap();
+    registry.add_table("digits", vec![digits]).unwrap();
+    registry
+}
+
+// The square gate is enabled on rows 1 and 2: it looks up (w(0), w(1)) in the squares table,
+// and w(0) of the next row in the table whose ID is in w(3)
+fn square_gate(registry: &LookupTableRegistry<Fp>) -> CustomGate<Fp> {
+    CustomGate::new(Square::default(), vec![1, 2])
+        .lookup(JointLookupSpec::from_cells(
+            LookupTableID::Constant(registry.table_id("squares").unwrap()),
+            &[LocalPosition::curr(0), LocalPosition::curr(1)],
+        ))
+        .lookup(JointLookupSpec::from_cells(
+            LookupTableID::WitnessColumn(3),
+            &[LocalPosition::next(0)],
+        ))
+}
+
+fn create_square_witness(
+    registry: &LookupTableRegistry<Fp>,
+    inputs: [u64; 3],
+) -> [Vec<Fp>; COLUMNS] {
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); 5]);
+    let digits = i32_to_field(registry.table_id("digits").unwrap());
+    for (row, x) in (1..4).zip(inputs) {
+        witness[0][row] = x.into();
+        witness[1][row] = Field::square(&witness[0][row]);
+        witness[3][row] = digits;
+    }
+    witness
+}
+
+fn prove_and_verify_square(inputs: [u64; 3]) -> Result<(), String> {
+    let registry = square_tables();
+    let witness = create_square_witness(&registry, inputs);
+    let gate = square_gate(&registry);
+    let (lookup_tables, _) = registry.into_tables();
+
+    TestFramework::<Vesta>::default()
+        .gates(
+            (0..5)
+                .map(|row| CircuitGate::zero(Wire::for_row(row)))
+                .collect(),
+        )
+        .custom_gates(vec![gate])
+        .lookup_tables(lookup_tables)
+        .witness(witness)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+}
+
+#[test]
+fn test_custom_gate_lookups() {
+    prove_and_verify_square([3, 4, 15]).unwrap();
+}
+
+#[test]
+fn test_custom_gate_wrong_lookups() {
+    // the square of 16 satisfies the constraint of the gate, but is not in the table
+    assert!(prove_and_verify_square([16, 4, 5]).is_err());
+    // and 16 is not a digit either
+    assert!(prove_and_verify_square([3, 4, 16]).is_err());
+}
+
+#[test]
+fn test_custom_gate_invalid_lookups() {
+    let registry = square_tables();
+    let build = |gates: Vec<CircuitGate<Fp>>, gate: CustomGate<Fp>| {
+        ConstraintSystem::create(gates)
+            .custom_gate(gate)
+            .lookup(registry.tables().to_vec())
+            .build()
+    };
+    let zero_gates = || {
+        (0..5)
+            .map(|row| CircuitGate::zero(Wire::for_row(row)))
+            .collect()
+    };
+
+    // a custom gate performs at most 4 lookups per row
+    let gate = (0..5).fold(CustomGate::new(Square::default(), vec![1]), |gate, _| {
+        gate.lookup(JointLookupSpec::from_cells(
+            LookupTableID::Constant(-2),
+            &[LocalPosition::curr(0)],
+        ))
+    });
+    let res = build(zero_gates(), gate);
+    assert!(matches!(res, Err(SetupError::CustomGate(_))));
+
+    // on existing columns
+    let gate = CustomGate::new(Square::default(), vec![1]).lookup(JointLookupSpec::from_cells(
+        LookupTableID::WitnessColumn(COLUMNS),
+        &[LocalPosition::curr(0)],
+    ));
+    let res = build(zero_gates(), gate);
+    assert!(matches!(res, Err(SetupError::CustomGate(_))));
+
+    // and not on a row that holds the lookups of a built-in gate, like the row after
+    // a range check
+    let (_, gates) = CircuitGate::<Fp>::create_multi_range_check(0);
+    let gate = CustomGate::new(Square::default(), vec![3]).lookup(JointLookupSpec::from_cells(
+        LookupTableID::Constant(-2),
+        &[LocalPosition::curr(0)],
+    ));
+    let res = build(gates.clone(), gate);
+    assert!(matches!(res, Err(SetupError::CustomGate(_))));
+
+    // which is fine without lookups
+    let res = build(gates, CustomGate::new(Square::default(), vec![3]));
+    assert!(res.is_ok());
+}
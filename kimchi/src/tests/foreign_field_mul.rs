@@ -3,6 +3,7 @@ use std::ops::Div;
 use crate::{
     auto_clone_array,
     circuits::{
+        builder::{BuiltCircuit, CircuitBuilder},
         constraints::ConstraintSystem,
         gate::{CircuitGate, CircuitGateError, CircuitGateResult, Connect, GateType},
         polynomial::COLUMNS,
@@ -13,7 +14,7 @@ use crate::{
     tests::framework::TestFramework,
 };
 use ark_ec::AffineCurve;
-use ark_ff::{Field, PrimeField, Zero};
+use ark_ff::{Field, PrimeField, SquareRootField, Zero};
 use mina_curves::pasta::{Fp, Fq, Pallas, PallasParameters, Vesta, VestaParameters};
 use num_bigint::BigUint;
 use num_traits::One;
@@ -1426,3 +1427,145 @@ fn test_witness_invalid_foreign_field_modulus() {
         &(BigUint::max_foreign_field_modulus::<PallasField>() + BigUint::one()),
     );
 }
+
+// Builds the circuit of `run_test` with external gates through the circuit builder.
+// Optionally, the remainder is also given as public inputs, declared after the gadgets.
+fn build_circuit<F: PrimeField + SquareRootField>(
+    foreign_field_modulus: &BigUint,
+    public_remainder: bool,
+) -> BuiltCircuit<F, (BigUint, BigUint)> {
+    let mut builder = CircuitBuilder::new();
+
+    let ffmul =
+        builder.gadget(|row| CircuitGate::create_foreign_field_mul(row, foreign_field_modulus));
+    // Result bound addition
+    let bound = builder
+        .gadget(|row| CircuitGate::create_single_ffadd(row, FFOps::Add, foreign_field_modulus));
+    for col in 0..3 {
+        builder.copy(ffmul.cell(1, col), bound.cell(0, col));
+    }
+    // Left and right input multi-range-checks
+    let left = builder.gadget(CircuitGate::create_multi_range_check);
+    let right = builder.gadget(CircuitGate::create_multi_range_check);
+    for limb in 0..3 {
+        builder.copy(ffmul.cell(0, limb), left.cell(limb, 0));
+        builder.copy(ffmul.cell(0, 3 + limb), right.cell(limb, 0));
+    }
+    // carry1_lo, product1_lo, product1_hi_0 multi-range-check
+    let carry = builder.gadget(CircuitGate::create_multi_range_check);
+    builder.copy(ffmul.cell(0, 6), carry.cell(0, 0));
+    builder.copy(ffmul.cell(1, 5), carry.cell(1, 0));
+    builder.copy(ffmul.cell(1, 6), carry.cell(2, 0));
+    // Result bound multi-range-check
+    let result = builder.gadget(CircuitGate::create_multi_range_check);
+    for limb in 0..3 {
+        builder.copy(result.cell(limb, 0), bound.cell(1, limb));
+    }
+    // Quotient bound compact-multi-range-check
+    let quotient = builder.gadget(CircuitGate::create_compact_multi_range_check);
+    builder.copy(ffmul.cell(1, 3), quotient.cell(2, 1));
+    builder.copy(ffmul.cell(1, 4), quotient.cell(0, 0));
+
+    let modulus = foreign_field_modulus.clone();
+    builder.witness(move |(left_input, right_input), witness| {
+        let (mul, external_checks) =
+            foreign_field_mul::witness::create(left_input, right_input, &modulus);
+        witness.extend(ffmul, |rows| *rows = mul);
+        witness.extend(bound, |rows| {
+            external_checks.extend_witness_bound_addition(rows, &modulus.to_field_limbs())
+        });
+        witness.extend(left, |rows| {
+            range_check::witness::extend_multi_limbs(rows, &left_input.to_field_limbs())
+        });
+        witness.extend(right, |rows| {
+            range_check::witness::extend_multi_limbs(rows, &right_input.to_field_limbs())
+        });
+        witness.extend(carry, |rows| {
+            external_checks.extend_witness_multi_range_checks(rows)
+        });
+        witness.extend(quotient, |rows| {
+            external_checks.extend_witness_compact_multi_range_checks(rows)
+        });
+    });
+
+    if public_remainder {
+        let remainder: Vec<_> = (0..3).map(|_| builder.public_input()).collect();
+        for (col, input) in remainder.iter().enumerate() {
+            builder.copy(*input, ffmul.cell(1, col));
+        }
+        builder.witness(move |_, witness| {
+            for (col, input) in remainder.iter().enumerate() {
+                witness.set(*input, witness.get(ffmul.cell(1, col)));
+            }
+        });
+    }
+
+    builder.build()
+}
+
+#[test]
+// Rebuilds the circuit of the tests with external gates through the circuit builder
+fn test_circuit_builder() {
+    let rng = &mut StdRng::from_seed(RNG_SEED);
+    let left_input = rng.gen_biguint_range(&BigUint::zero(), &secp256k1_modulus());
+    let right_input = rng.gen_biguint_range(&BigUint::zero(), &secp256k1_modulus());
+
+    let (result, expected_witness) = run_test::<Vesta, VestaBaseSponge, VestaScalarSponge>(
+        false,
+        true,
+        false,
+        &left_input,
+        &right_input,
+        &secp256k1_modulus(),
+        vec![],
+    );
+    assert_eq!(result, Ok(()));
+
+    let circuit = build_circuit::<Fp>(&secp256k1_modulus(), false);
+    assert_eq!(circuit.public, 0);
+    let witness = circuit.witness(&(left_input, right_input));
+    assert_eq!(witness, expected_witness);
+
+    TestFramework::<Vesta>::default()
+        .gates(circuit.gates)
+        .witness(witness)
+        .setup()
+        .prove_and_verify::<VestaBaseSponge, VestaScalarSponge>()
+        .unwrap();
+}
+
+#[test]
+// The public inputs declared after the gadgets are placed before them
+fn test_circuit_builder_public_inputs() {
+    let rng = &mut StdRng::from_seed(RNG_SEED);
+    let left_input = rng.gen_biguint_range(&BigUint::zero(), &secp256k1_modulus());
+    let right_input = rng.gen_biguint_range(&BigUint::zero(), &secp256k1_modulus());
+    let remainder = (&left_input * &right_input) % secp256k1_modulus();
+
+    let circuit = build_circuit::<Fp>(&secp256k1_modulus(), true);
+    assert_eq!(circuit.public, 3);
+    assert!(circuit.gates[..3]
+        .iter()
+        .all(|gate| gate.typ == GateType::Generic));
+    assert_eq!(circuit.gates[3].typ, GateType::ForeignFieldMul);
+
+    let witness = circuit.witness(&(left_input, right_input));
+    let public: Vec<Fp> = witness[0][..3].to_vec();
+    assert_eq!(public, remainder.to_field_limbs::<Fp>().to_vec());
+
+    let prove_and_verify = |witness, public| {
+        TestFramework::<Vesta>::default()
+            .gates(circuit.gates.clone())
+            .witness(witness)
+            .public_inputs(public)
+            .setup()
+            .prove_and_verify::<VestaBaseSponge, VestaScalarSponge>()
+    };
+    prove_and_verify(witness.clone(), public.clone()).unwrap();
+
+    // the public inputs are wired to the remainder
+    let mut wrong_witness = witness;
+    wrong_witness[0][1] += Fp::one();
+    let wrong_public = wrong_witness[0][..3].to_vec();
+    assert!(prove_and_verify(wrong_witness, wrong_public).is_err());
+}
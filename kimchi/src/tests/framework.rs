@@ -2,8 +2,10 @@
 
 use crate::{
     circuits::{
+        custom_gate::CustomGate,
         gate::CircuitGate,
         lookup::{
+            lookups::LookupBackend,
             runtime_tables::{RuntimeTable, RuntimeTableCfg},
             tables::LookupTable,
         },
@@ -12,7 +14,7 @@ use crate::{
     curve::KimchiCurve,
     plonk_sponge::FrSponge,
     proof::{ProverProof, RecursionChallenge},
-    prover_index::{testing::new_index_for_test_with_lookups, ProverIndex},
+    prover_index::{testing::new_index_for_test_with_custom_gates, ProverIndex},
     verifier::verify,
     verifier_index::VerifierIndex,
 };
@@ -33,6 +35,8 @@ pub(crate) struct TestFramework<G: KimchiCurve> {
     lookup_tables: Vec<LookupTable<G::ScalarField>>,
     runtime_tables_setup: Option<Vec<RuntimeTableCfg<G::ScalarField>>>,
     runtime_tables: Vec<RuntimeTable<G::ScalarField>>,
+    lookup_backend: LookupBackend,
+    custom_gates: Vec<CustomGate<G::ScalarField>>,
     recursion: Vec<RecursionChallenge<G>>,
     num_prev_challenges: usize,
     disable_gates_checks: bool,
@@ -88,6 +92,18 @@ where
         self
     }
 
+    #[must_use]
+    pub(crate) fn lookup_backend(mut self, lookup_backend: LookupBackend) -> Self {
+        self.lookup_backend = lookup_backend;
+        self
+    }
+
+    #[must_use]
+    pub(crate) fn custom_gates(mut self, custom_gates: Vec<CustomGate<G::ScalarField>>) -> Self {
+        self.custom_gates = custom_gates;
+        self
+    }
+
     #[must_use]
     pub(crate) fn disable_gates_checks(mut self, disable_gates_checks: bool) -> Self {
         self.disable_gates_checks = disable_gates_checks;
@@ -101,13 +117,16 @@ where
 
         let lookup_tables = std::mem::take(&mut self.lookup_tables);
         let runtime_tables_setup = mem::replace(&mut self.runtime_tables_setup, None);
+        let custom_gates = std::mem::take(&mut self.custom_gates);
 
-        let index = new_index_for_test_with_lookups::<G>(
+        let index = new_index_for_test_with_custom_gates::<G>(
             self.gates.take().unwrap(),
             self.public_inputs.len(),
             self.num_prev_challenges,
             lookup_tables,
             runtime_tables_setup,
+            self.lookup_backend,
+            custom_gates,
             self.disable_gates_checks,
         );
         println!(
@@ -1,26 +1,46 @@
 use super::framework::{print_witness, TestFramework};
-use crate::circuits::{
-    gate::{CircuitGate, GateType},
-    lookup::{
-        runtime_tables::{RuntimeTable, RuntimeTableCfg, RuntimeTableSpec},
-        tables::LookupTable,
+use crate::{
+    circuits::{
+        constraints::ConstraintSystem,
+        domains::EvaluationDomains,
+        gate::{CircuitGate, GateType},
+        lookup::{
+            index::{LookupConstraintSystem, LookupError},
+            lookups::{
+                CustomLookupPattern, JointLookupSpec, LocalPosition, LookupBackend, LookupTableID,
+                MAX_LOOKUPS_PER_ROW,
+            },
+            registry::LookupTableRegistry,
+            runtime_tables::{RuntimeTable, RuntimeTableCfg, RuntimeTableSpec},
+            tables::{LookupTable, RANGE_CHECK_TABLE_ID, XOR_TABLE_ID},
+        },
+        polynomial::COLUMNS,
+        polynomials::{range_check, xor},
+        wires::Wire,
     },
-    polynomial::COLUMNS,
-    wires::Wire,
+    error::SetupError,
 };
-use ark_ff::Zero;
+use ark_ff::{Field, Zero};
+use ark_poly::EvaluationDomain;
 use mina_curves::pasta::{Fp, Vesta, VestaParameters};
 use mina_poseidon::{
     constants::PlonkSpongeConstantsKimchi,
     sponge::{DefaultFqSponge, DefaultFrSponge},
 };
+use o1_utils::{field_helpers::i32_to_field, RandomField};
+use rand::{rngs::StdRng, SeedableRng};
 use std::array;
 
 type SpongeParams = PlonkSpongeConstantsKimchi;
 type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
 type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
 
-fn setup_lookup_proof(use_values_from_table: bool, num_lookups: usize, table_sizes: Vec<usize>) {
+fn setup_lookup_proof(
+    use_values_from_table: bool,
+    num_lookups: usize,
+    table_sizes: Vec<usize>,
+    lookup_backend: LookupBackend,
+) {
     let lookup_table_values: Vec<Vec<_>> = table_sizes
         .iter()
         .map(|size| (0..*size).map(|_| rand::random()).collect())
@@ -98,6 +118,7 @@ fn setup_lookup_proof(use_values_from_table: bool, num_lookups: usize, table_siz
         .gates(gates)
         .witness(witness)
         .lookup_tables(lookup_tables)
+        .lookup_backend(lookup_backend)
         .setup()
         .prove_and_verify::<BaseSponge, ScalarSponge>()
         .unwrap();
@@ -105,27 +126,49 @@ fn setup_lookup_proof(use_values_from_table: bool, num_lookups: usize, table_siz
 
 #[test]
 fn lookup_gate_proving_works() {
-    setup_lookup_proof(true, 500, vec![256])
+    setup_lookup_proof(true, 500, vec![256], LookupBackend::Plookup)
 }
 
 #[test]
 #[should_panic]
 fn lookup_gate_rejects_bad_lookups() {
-    setup_lookup_proof(false, 500, vec![256])
+    setup_lookup_proof(false, 500, vec![256], LookupBackend::Plookup)
 }
 
 #[test]
 fn lookup_gate_proving_works_multiple_tables() {
-    setup_lookup_proof(true, 500, vec![100, 50, 50, 2, 2])
+    setup_lookup_proof(true, 500, vec![100, 50, 50, 2, 2], LookupBackend::Plookup)
 }
 
 #[test]
 #[should_panic]
 fn lookup_gate_rejects_bad_lookups_multiple_tables() {
-    setup_lookup_proof(false, 500, vec![100, 50, 50, 2, 2])
+    setup_lookup_proof(false, 500, vec![100, 50, 50, 2, 2], LookupBackend::Plookup)
 }
 
-fn runtime_table(num: usize, indexed: bool) {
+#[test]
+fn logup_lookup_gate_proving_works() {
+    setup_lookup_proof(true, 500, vec![256], LookupBackend::LogUp)
+}
+
+#[test]
+#[should_panic]
+fn logup_lookup_gate_rejects_bad_lookups() {
+    setup_lookup_proof(false, 500, vec![256], LookupBackend::LogUp)
+}
+
+#[test]
+fn logup_lookup_gate_proving_works_multiple_tables() {
+    setup_lookup_proof(true, 500, vec![100, 50, 50, 2, 2], LookupBackend::LogUp)
+}
+
+#[test]
+#[should_panic]
+fn logup_lookup_gate_rejects_bad_lookups_multiple_tables() {
+    setup_lookup_proof(false, 500, vec![100, 50, 50, 2, 2], LookupBackend::LogUp)
+}
+
+fn runtime_table(num: usize, indexed: bool, lookup_backend: LookupBackend) {
     // runtime
     let mut runtime_tables_setup = vec![];
     for table_id in 0..num {
@@ -190,6 +233,7 @@ fn runtime_table(num: usize, indexed: bool) {
         .gates(gates)
         .witness(witness)
         .runtime_tables_setup(runtime_tables_setup)
+        .lookup_backend(lookup_backend)
         .setup()
         .runtime_tables(runtime_tables)
         .prove_and_verify::<BaseSponge, ScalarSponge>()
@@ -198,12 +242,297 @@ fn runtime_table(num: usize, indexed: bool) {
 
 #[test]
 fn test_indexed_runtime_table() {
-    runtime_table(5, true);
+    runtime_table(5, true, LookupBackend::Plookup);
 }
 
 #[test]
 fn test_custom_runtime_table() {
-    runtime_table(5, false);
+    runtime_table(5, false, LookupBackend::Plookup);
+}
+
+#[test]
+fn test_logup_indexed_runtime_table() {
+    runtime_table(5, true, LookupBackend::LogUp);
+}
+
+#[test]
+fn test_logup_custom_runtime_table() {
+    runtime_table(5, false, LookupBackend::LogUp);
+}
+
+const RNG_SEED: [u8; 32] = [
+    22, 4, 34, 75, 29, 255, 0, 126, 237, 193, 86, 160, 1, 90, 131, 221, 186, 168, 4, 95, 50, 48,
+    89, 29, 13, 250, 215, 172, 130, 24, 164, 162,
+];
+
+// A circuit with XOR and range check lookups, using both the XOR and the range check tables
+fn xor_and_range_check_circuit(
+    num_xors: usize,
+    num_range_checks: usize,
+) -> (Vec<CircuitGate<Fp>>, [Vec<Fp>; COLUMNS]) {
+    let rng = &mut StdRng::from_seed(RNG_SEED);
+
+    let mut gates = vec![];
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![]);
+    for _ in 0..num_xors {
+        CircuitGate::extend_xor_gadget(&mut gates, 64);
+        let input1 = rng.gen_field_with_bits(64);
+        let input2 = rng.gen_field_with_bits(64);
+        xor::extend_xor_witness(&mut witness, input1, input2, 64);
+    }
+    for _ in 0..num_range_checks {
+        let mut curr_row = gates.len();
+        CircuitGate::extend_multi_range_check(&mut gates, &mut curr_row);
+        range_check::witness::extend_multi(
+            &mut witness,
+            rng.gen_field_with_bits(88),
+            rng.gen_field_with_bits(88),
+            rng.gen_field_with_bits(88),
+        );
+    }
+    (gates, witness)
+}
+
+#[test]
+fn test_logup_xor_and_range_check() {
+    let (gates, witness) = xor_and_range_check_circuit(4, 4);
+
+    TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .lookup_backend(LookupBackend::LogUp)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
+
+#[test]
+fn test_logup_rejects_bad_xor() {
+    let (gates, mut witness) = xor_and_range_check_circuit(1, 1);
+    // the first nybble of the output of the first XOR is not the XOR of the inputs anymore,
+    // while the decomposition of the output stays consistent
+    witness[11][0] += Fp::from(1u64);
+    witness[2][0] += Fp::from(1u64);
+
+    let res = TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .lookup_backend(LookupBackend::LogUp)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>();
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_logup_single_sorted_column() {
+    let (gates, _) = xor_and_range_check_circuit(1, 1);
+
+    let runner = TestFramework::<Vesta>::default()
+        .gates(gates.clone())
+        .lookup_backend(LookupBackend::LogUp)
+        .setup();
+    let lookup_index = runner.prover_index().verifier_index().lookup_index.unwrap();
+    assert_eq!(lookup_index.lookup_info.num_sorted(), 1);
+
+    // plookup needs one more sorted column than the number of lookups per row
+    let runner = TestFramework::<Vesta>::default().gates(gates).setup();
+    let lookup_index = runner.prover_index().verifier_index().lookup_index.unwrap();
+    assert_eq!(
+        lookup_index.lookup_info.num_sorted(),
+        lookup_index.lookup_info.max_per_row + 1
+    );
+}
+
+#[test]
+fn test_logup_constraint_degree() {
+    let gates: Vec<_> = (0..8)
+        .map(|row| CircuitGate::<Fp>::zero(Wire::for_row(row)))
+        .collect();
+    let domain = EvaluationDomains::create(gates.len()).unwrap();
+    let table = LookupTable {
+        id: 0,
+        data: vec![vec![Fp::zero()]],
+    };
+    let create = |num_lookups| {
+        let pattern = CustomLookupPattern {
+            id: 1,
+            rows: vec![1],
+            lookups: (0..num_lookups)
+                .map(|_| {
+                    JointLookupSpec::from_cells(
+                        LookupTableID::Constant(0),
+                        &[LocalPosition::curr(0)],
+                    )
+                })
+                .collect(),
+        };
+        LookupConstraintSystem::create(
+            &gates,
+            vec![table.clone()],
+            None,
+            LookupBackend::LogUp,
+            vec![pattern],
+            &domain,
+        )
+    };
+
+    // the degree of the LogUp constraints grows with the number of lookups of a pattern:
+    // it fits in the quotient domain up to the maximum number of lookups per row
+    assert!(create(MAX_LOOKUPS_PER_ROW).is_ok());
+    assert!(matches!(
+        create(MAX_LOOKUPS_PER_ROW + 1),
+        Err(LookupError::ConstraintDegreeTooHigh { .. })
+    ));
 }
 
 // TODO: add a test with a runtime table with ID 0 (it should panic)
+
+// A registry with two fixed tables (squares and cubes of 0..16) and an indexed runtime table
+fn table_registry() -> LookupTableRegistry<Fp> {
+    let mut registry = LookupTableRegistry::new();
+    for (name, exp) in [("squares", 2), ("cubes", 3)] {
+        let indexes: Vec<Fp> = (0..16u64).map(Into::into).collect();
+        let values = indexes.iter().map(|x| x.pow([exp])).collect();
+        registry.add_table(name, vec![indexes, values]).unwrap();
+    }
+    registry.add_runtime_table("outputs", 5).unwrap();
+    registry
+}
+
+#[test]
+fn test_table_registry_ids() {
+    let mut registry = table_registry();
+
+    // the gate tables are registered, and the other tables get negative IDs
+    assert_eq!(registry.table_id("xor"), Some(XOR_TABLE_ID));
+    assert_eq!(registry.table_id("range_check"), Some(RANGE_CHECK_TABLE_ID));
+    assert_eq!(registry.table_id("squares"), Some(-1));
+    assert_eq!(registry.table_id("cubes"), Some(-2));
+    assert_eq!(registry.table_id("outputs"), Some(-3));
+    assert_eq!(registry.table_id("unknown"), None);
+
+    // names are unique
+    let res = registry.add_table("squares", vec![vec![Fp::zero()]]);
+    assert!(matches!(res, Err(SetupError::LookupTable(_))));
+    let res = registry.add_runtime_table("xor", 1);
+    assert!(matches!(res, Err(SetupError::LookupTable(_))));
+
+    // tables must be well formed
+    let res = registry.add_table("empty", vec![]);
+    assert!(matches!(res, Err(SetupError::LookupTable(_))));
+    let res = registry.add_table("ragged", vec![vec![Fp::zero()], vec![]]);
+    assert!(matches!(res, Err(SetupError::LookupTable(_))));
+    assert_eq!(registry.table_id("ragged"), None);
+}
+
+#[test]
+fn test_table_registry_collisions() {
+    let (gates, _) = xor_and_range_check_circuit(1, 1);
+    let table = |id| LookupTable {
+        id,
+        data: vec![vec![Fp::zero(); 4]],
+    };
+
+    // the tables of the registry are accepted next to the gate tables
+    let builder = ConstraintSystem::create(gates.clone()).table_registry(table_registry());
+    assert_eq!(builder.table_id("cubes"), Some(-2));
+    let cs = builder.build().unwrap();
+    let runtime_tables = cs.lookup_constraint_system.unwrap().runtime_tables.unwrap();
+    assert_eq!(runtime_tables[0].id, -3);
+
+    // a copy of a gate table is the same table, and is only added once
+    // (the gate tables are not added in a fixed order, so the rows are compared sorted)
+    let lookup_table = |cs: ConstraintSystem<Fp>| {
+        let columns: Vec<_> = cs
+            .lookup_constraint_system
+            .unwrap()
+            .lookup_table
+            .iter()
+            .map(|column| column.evaluate_over_domain_by_ref(cs.domain.d1).evals)
+            .collect();
+        let mut rows: Vec<Vec<Fp>> = (0..cs.domain.d1.size())
+            .map(|row| columns.iter().map(|column| column[row]).collect())
+            .collect();
+        rows.sort();
+        rows
+    };
+    let cs = ConstraintSystem::create(gates.clone())
+        .lookup(vec![
+            xor::lookup_table(),
+            range_check::gadget::lookup_table(),
+        ])
+        .build()
+        .unwrap();
+    let expected = ConstraintSystem::create(gates.clone()).build().unwrap();
+    assert_eq!(lookup_table(cs), lookup_table(expected));
+
+    // but a different fixed table cannot use the ID of a gate table
+    let res = ConstraintSystem::create(gates.clone())
+        .lookup(vec![table(XOR_TABLE_ID)])
+        .build();
+    assert!(matches!(res, Err(SetupError::LookupTable(_))));
+
+    // nor of another fixed table
+    let res = ConstraintSystem::create(gates.clone())
+        .lookup(vec![table(-1)])
+        .table_registry(table_registry())
+        .build();
+    assert!(matches!(res, Err(SetupError::LookupTable(_))));
+
+    // and a runtime table cannot use the ID of a fixed table
+    let res = ConstraintSystem::create(gates)
+        .lookup(vec![table(5)])
+        .runtime(Some(vec![RuntimeTableCfg::Indexed(RuntimeTableSpec {
+            id: 5,
+            len: 4,
+        })]))
+        .build();
+    assert!(matches!(res, Err(SetupError::LookupTable(_))));
+}
+
+#[test]
+fn test_table_registry_lookups() {
+    let registry = table_registry();
+    let squares = registry.table_id("squares").unwrap();
+    let cubes = registry.table_id("cubes").unwrap();
+    let outputs = registry.table_id("outputs").unwrap();
+    let outputs_data: Vec<Fp> = (10..15u64).map(Into::into).collect();
+
+    // each row looks up (i, i^2), (i, i^3) or (i, outputs[i]) three times
+    let num_lookups = 12;
+    let gates = (0..num_lookups)
+        .map(|row| CircuitGate::new(GateType::Lookup, Wire::for_row(row), vec![]))
+        .collect();
+    let rows: Vec<[Fp; COLUMNS]> = (0..num_lookups)
+        .map(|row| {
+            let (table_id, value): (i32, &dyn Fn(u64) -> Fp) = match row % 3 {
+                0 => (squares, &|i| Fp::from(i * i)),
+                1 => (cubes, &|i| Fp::from(i * i * i)),
+                _ => (outputs, &|i| outputs_data[i as usize]),
+            };
+            let mut cells = [Fp::zero(); COLUMNS];
+            cells[0] = i32_to_field(table_id);
+            for (j, i) in [row as u64 % 5, 4, 0].into_iter().enumerate() {
+                cells[1 + 2 * j] = Fp::from(i);
+                cells[2 + 2 * j] = value(i);
+            }
+            cells
+        })
+        .collect();
+    let witness: [Vec<Fp>; COLUMNS] =
+        array::from_fn(|col| rows.iter().map(|cells| cells[col]).collect());
+
+    let (lookup_tables, runtime_tables_setup) = registry.into_tables();
+    TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .lookup_tables(lookup_tables)
+        .runtime_tables_setup(runtime_tables_setup)
+        .setup()
+        .runtime_tables(vec![RuntimeTable {
+            id: outputs,
+            data: outputs_data.clone(),
+        }])
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
@@ -0,0 +1,155 @@
+use crate::circuits::{
+    gate::{CircuitGate, Connect},
+    lookup::runtime_tables::RuntimeTable,
+    polynomial::COLUMNS,
+    polynomials::{
+        generic::GenericGateSpec,
+        memory::{Memory, MemoryCircuit, MemoryOp, MemoryWitness},
+    },
+    wires::Wire,
+};
+use ark_ff::{Field, One, Zero};
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::array;
+
+use super::framework::TestFramework;
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+const TABLE_ID: i32 = 7;
+
+// The accesses of the program, as (kind, address, value written)
+const PROGRAM: [(MemoryOp, u64, u64); 7] = [
+    (MemoryOp::Write, 1, 10),
+    (MemoryOp::Write, 2, 20),
+    (MemoryOp::Read, 1, 0),
+    (MemoryOp::Write, 1, 11),
+    (MemoryOp::Read, 1, 0),
+    (MemoryOp::Read, 3, 0),
+    (MemoryOp::Read, 2, 0),
+];
+
+// The addresses and values of the accesses are public inputs
+fn create_circuit(gap_bits: usize) -> (Vec<CircuitGate<Fp>>, MemoryCircuit<Fp>) {
+    let public = 2 * PROGRAM.len();
+    let mut gates: Vec<_> = (0..public)
+        .map(|row| {
+            CircuitGate::<Fp>::create_generic_gadget(Wire::for_row(row), GenericGateSpec::Pub, None)
+        })
+        .collect();
+
+    let mut memory = Memory::new(TABLE_ID, gap_bits);
+    for (i, (op, _, _)) in PROGRAM.iter().enumerate() {
+        let access = match op {
+            MemoryOp::Read => memory.mem_read(&mut gates),
+            MemoryOp::Write => memory.mem_write(&mut gates),
+        };
+        gates.connect_cell_pair((2 * i, 0), access.address());
+        gates.connect_cell_pair((2 * i + 1, 0), access.value());
+    }
+    let memory = memory.finalize(&mut gates);
+    (gates, memory)
+}
+
+fn create_witness(gap_bits: usize) -> ([Vec<Fp>; COLUMNS], Vec<Fp>, RuntimeTable<Fp>) {
+    let mut public = vec![];
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); 2 * PROGRAM.len()]);
+
+    let mut memory = MemoryWitness::new(TABLE_ID, gap_bits);
+    for (op, address, value) in PROGRAM {
+        let address = Fp::from(address);
+        let value = match op {
+            MemoryOp::Read => memory.extend_read(&mut witness, address),
+            MemoryOp::Write => {
+                let value = Fp::from(value);
+                memory.extend_write(&mut witness, address, value);
+                value
+            }
+        };
+        public.extend([address, value]);
+    }
+    let runtime_table = memory.finalize(&mut witness);
+
+    for (row, input) in public.iter().enumerate() {
+        witness[0][row] = *input;
+    }
+    (witness, public, runtime_table)
+}
+
+fn prove_and_verify(
+    gap_bits: usize,
+    witness: [Vec<Fp>; COLUMNS],
+    public: Vec<Fp>,
+    runtime_table: RuntimeTable<Fp>,
+) -> Result<(), String> {
+    let (gates, memory) = create_circuit(gap_bits);
+    TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .public_inputs(public)
+        .runtime_tables_setup(vec![memory.runtime_table_cfg()])
+        .custom_gates(memory.custom_gates().to_vec())
+        .setup()
+        .runtime_tables(vec![runtime_table])
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+}
+
+#[test]
+fn test_memory() {
+    let (witness, public, runtime_table) = create_witness(3);
+
+    // the reads return the last values written, or zero
+    let reads: Vec<_> = public.chunks(2).map(|access| access[1]).collect();
+    assert_eq!(reads[2], Fp::from(10u64));
+    assert_eq!(reads[4], Fp::from(11u64));
+    assert_eq!(reads[5], Fp::zero());
+    assert_eq!(reads[6], Fp::from(20u64));
+
+    prove_and_verify(3, witness, public, runtime_table).unwrap();
+}
+
+#[test]
+fn test_memory_with_lookup_range_checks() {
+    let (witness, public, runtime_table) = create_witness(64);
+    prove_and_verify(64, witness, public, runtime_table).unwrap();
+}
+
+#[test]
+fn test_memory_stale_read() {
+    // the second read of address 1 returns the value of the first write
+    let (mut witness, mut public, mut runtime_table) = create_witness(3);
+    let stale = Fp::from(10u64);
+    public[9] = stale;
+    witness[0][9] = stale;
+    // and consistently so in the circuit and in the runtime table, so that only
+    // the consistency of the sorted accesses is violated
+    let key = Fp::from(2u64).pow([64]) + Fp::from(4u64);
+    for row in 2 * PROGRAM.len()..witness[0].len() {
+        // the lookup rows of the access
+        if witness[3][row] == key {
+            witness[4][row] = stale;
+        }
+        // the copy of the access in the next sorted access
+        if witness[0][row] == Fp::one() && witness[2][row] == Fp::from(4u64) {
+            witness[1][row] = stale;
+        }
+    }
+    runtime_table.data[PROGRAM.len() + 4] = stale;
+
+    assert!(prove_and_verify(3, witness, public, runtime_table).is_err());
+}
+
+#[test]
+fn test_memory_inconsistent_runtime_table() {
+    // the runtime table does not hold the accesses of the circuit
+    let (witness, public, mut runtime_table) = create_witness(3);
+    runtime_table.data[PROGRAM.len() + 2] = Fp::from(12u64);
+
+    assert!(prove_and_verify(3, witness, public, runtime_table).is_err());
+}
@@ -0,0 +1,134 @@
+use crate::{
+    circuits::{
+        gate::{CircuitGate, Connect},
+        polynomials::{
+            generic::GenericGateSpec,
+            merkle::{self, MerklePathLayout},
+        },
+        wires::{Wire, COLUMNS},
+    },
+    curve::KimchiCurve,
+    tests::framework::TestFramework,
+};
+use ark_ff::{UniformRand, Zero};
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_hasher::merkle::MerkleTree;
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::array;
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+const RNG_SEED: [u8; 32] = [
+    22, 4, 34, 75, 29, 255, 0, 126, 237, 193, 86, 160, 1, 90, 131, 221, 186, 168, 4, 95, 50, 48,
+    89, 29, 13, 250, 215, 172, 130, 24, 164, 162,
+];
+
+// Creates a tree of the given depth with a few random leaves
+fn create_tree(depth: usize, indices: &[u64]) -> MerkleTree {
+    let rng = &mut StdRng::from_seed(RNG_SEED);
+    let mut tree = MerkleTree::new(depth, Fp::zero());
+    for &index in indices {
+        tree.set_leaf(index, Fp::rand(rng));
+    }
+    tree
+}
+
+// Creates a circuit whose only public input is the root of the tree
+fn create_circuit(depth: usize) -> Vec<CircuitGate<Fp>> {
+    let mut gates = vec![CircuitGate::<Fp>::create_generic_gadget(
+        Wire::for_row(0),
+        GenericGateSpec::Pub,
+        None,
+    )];
+    let layout = CircuitGate::extend_merkle_path_gadget(&mut gates, depth, Vesta::sponge_params());
+    gates.connect_cell_pair((0, 0), layout.root());
+    gates
+}
+
+// Creates the witness for a leaf of the tree, with the root as public input
+fn create_witness(leaf: Fp, index: u64, siblings: &[Fp]) -> ([Vec<Fp>; COLUMNS], Fp) {
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero()]);
+    let root = merkle::extend_merkle_path_witness(
+        &mut witness,
+        Vesta::sponge_params(),
+        leaf,
+        index,
+        siblings,
+    );
+    witness[0][0] = root;
+    (witness, root)
+}
+
+#[test]
+fn test_merkle_path_witness_matches_native() {
+    let depth = 8;
+    let indices = [0, 1, 77, 128, 255];
+    let tree = create_tree(depth, &indices);
+
+    for index in indices {
+        let path = tree.path(index);
+        let (witness, root) = merkle::create_merkle_path_witness(
+            Vesta::sponge_params(),
+            tree.leaf(index),
+            index,
+            &path.siblings,
+        );
+        assert_eq!(root, tree.root());
+
+        let layout = MerklePathLayout::new(0, depth);
+        let (row, col) = layout.leaf();
+        assert_eq!(witness[col][row], tree.leaf(index));
+        let (row, col) = layout.root();
+        assert_eq!(witness[col][row], tree.root());
+        for (height, bit) in path.bits().into_iter().enumerate() {
+            let (row, col) = layout.sibling(height);
+            assert_eq!(witness[col][row], path.siblings[height]);
+            let (row, col) = layout.bit(height);
+            assert_eq!(witness[col][row], Fp::from(bit));
+        }
+    }
+}
+
+#[test]
+fn test_merkle_path() {
+    let depth = 4;
+    let index = 11;
+    let tree = create_tree(depth, &[3, index, 12]);
+    let path = tree.path(index);
+
+    let (witness, root) = create_witness(tree.leaf(index), index, &path.siblings);
+    assert_eq!(root, tree.root());
+
+    TestFramework::<Vesta>::default()
+        .gates(create_circuit(depth))
+        .witness(witness)
+        .public_inputs(vec![root])
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
+
+#[test]
+fn test_merkle_path_wrong_root() {
+    let depth = 3;
+    let index = 5;
+    let tree = create_tree(depth, &[index]);
+    let path = tree.path(index);
+
+    // prove membership of a leaf that is not in the tree
+    let (witness, _) = create_witness(tree.leaf(index) + Fp::from(1u32), index, &path.siblings);
+
+    let res = TestFramework::<Vesta>::default()
+        .gates(create_circuit(depth))
+        .witness(witness)
+        .public_inputs(vec![tree.root()])
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>();
+    assert!(res.is_err());
+}
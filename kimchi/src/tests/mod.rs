@@ -1,4 +1,7 @@
 mod and;
+mod blake2b;
+mod comparison;
+mod custom_gate;
 mod ec;
 mod endomul;
 mod endomul_scalar;
@@ -7,12 +10,19 @@ mod foreign_field_mul;
 mod framework;
 mod generic;
 mod lookup;
+mod memory;
+mod merkle;
 mod not;
 mod poseidon;
 mod range_check;
 mod recursion;
 mod rot;
+mod schnorr;
 mod serde;
+mod snarky;
+#[cfg(feature = "internal_tracing")]
+mod tracing;
 mod turshi;
 mod varbasemul;
+mod witness;
 mod xor;
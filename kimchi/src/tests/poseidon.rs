@@ -1,20 +1,26 @@
 use crate::{
     circuits::{
-        gate::CircuitGate,
+        gate::{CircuitGate, Connect},
         polynomials,
-        polynomials::poseidon::ROUNDS_PER_ROW,
+        polynomials::{
+            generic::GenericGateSpec,
+            poseidon::ROUNDS_PER_ROW,
+            poseidon_sponge::{self, PoseidonSpongeLayout},
+        },
         wires::{Wire, COLUMNS},
     },
     curve::KimchiCurve,
     tests::framework::TestFramework,
 };
-use ark_ff::Zero;
+use ark_ff::{UniformRand, Zero};
 use mina_curves::pasta::{Fp, Vesta, VestaParameters};
 use mina_poseidon::{
     constants::{PlonkSpongeConstantsKimchi, SpongeConstants},
+    poseidon::{ArithmeticSponge, Sponge},
     sponge::{DefaultFqSponge, DefaultFrSponge},
 };
 use o1_utils::math;
+use rand::{rngs::StdRng, SeedableRng};
 use std::array;
 
 // aliases
@@ -28,6 +34,11 @@ const ROUNDS_PER_HASH: usize = SpongeParams::PERM_ROUNDS_FULL;
 const POS_ROWS_PER_HASH: usize = ROUNDS_PER_HASH / ROUNDS_PER_ROW;
 const N_LOWER_BOUND: usize = (POS_ROWS_PER_HASH + 1) * NUM_POS; // Plonk domain size
 
+const RNG_SEED: [u8; 32] = [
+    0, 131, 43, 175, 229, 252, 206, 26, 67, 193, 86, 160, 1, 90, 131, 86, 186, 153, 4, 95, 50, 48,
+    89, 29, 13, 250, 215, 172, 130, 24, 164, 162,
+];
+
 #[test]
 fn test_poseidon() {
     let max_size = 1 << math::ceil_log2(N_LOWER_BOUND);
@@ -88,3 +99,121 @@ fn test_poseidon() {
         .prove_and_verify::<BaseSponge, ScalarSponge>()
         .unwrap();
 }
+
+// Hashes `inputs` natively with the kimchi arithmetic sponge
+fn native_sponge(inputs: &[Fp], num_squeezes: usize) -> Vec<Fp> {
+    let mut sponge = ArithmeticSponge::<Fp, SpongeParams>::new(Vesta::sponge_params());
+    sponge.absorb(inputs);
+    (0..num_squeezes).map(|_| sponge.squeeze()).collect()
+}
+
+// Creates a circuit with public inputs wired to the inputs of the sponge
+// and the outputs of the sponge wired to public outputs
+fn create_sponge_circuit(num_inputs: usize, num_squeezes: usize) -> Vec<CircuitGate<Fp>> {
+    let mut gates = (0..num_inputs + num_squeezes)
+        .map(|row| {
+            CircuitGate::<Fp>::create_generic_gadget(Wire::for_row(row), GenericGateSpec::Pub, None)
+        })
+        .collect::<Vec<_>>();
+
+    let layout = CircuitGate::<Fp>::extend_poseidon_sponge_gadget(
+        &mut gates,
+        num_inputs,
+        num_squeezes,
+        &Vesta::sponge_params().round_constants,
+    );
+    for i in 0..num_inputs {
+        gates.connect_cell_pair((i, 0), layout.input(i));
+    }
+    for j in 0..num_squeezes {
+        gates.connect_cell_pair((num_inputs + j, 0), layout.output(j));
+    }
+
+    gates
+}
+
+// Creates the witness of the circuit built by `create_sponge_circuit`,
+// returning it together with its public inputs
+fn create_sponge_witness(inputs: &[Fp], num_squeezes: usize) -> ([Vec<Fp>; COLUMNS], Vec<Fp>) {
+    let public_rows = inputs.len() + num_squeezes;
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); public_rows]);
+    let outputs = poseidon_sponge::extend_poseidon_sponge_witness(
+        &mut witness,
+        Vesta::sponge_params(),
+        inputs,
+        num_squeezes,
+    );
+
+    let public = inputs
+        .iter()
+        .chain(outputs.iter())
+        .copied()
+        .collect::<Vec<_>>();
+    for (row, value) in public.iter().enumerate() {
+        witness[0][row] = *value;
+    }
+
+    (witness, public)
+}
+
+#[test]
+fn test_poseidon_sponge_witness_matches_native() {
+    let rng = &mut StdRng::from_seed(RNG_SEED);
+    for num_inputs in 0..6 {
+        let inputs = (0..num_inputs).map(|_| Fp::rand(rng)).collect::<Vec<_>>();
+        for num_squeezes in 1..5 {
+            let (witness, outputs) = poseidon_sponge::create_poseidon_sponge_witness(
+                Vesta::sponge_params(),
+                &inputs,
+                num_squeezes,
+            );
+            assert_eq!(outputs, native_sponge(&inputs, num_squeezes));
+
+            let layout = PoseidonSpongeLayout::new(0, num_inputs, num_squeezes);
+            assert_eq!(witness[0].len(), layout.num_rows());
+            for (i, input) in inputs.iter().enumerate() {
+                let (row, col) = layout.input(i);
+                assert_eq!(witness[col][row], *input);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_poseidon_sponge() {
+    let rng = &mut StdRng::from_seed(RNG_SEED);
+    let inputs = (0..5).map(|_| Fp::rand(rng)).collect::<Vec<_>>();
+    let num_squeezes = 3;
+
+    let gates = create_sponge_circuit(inputs.len(), num_squeezes);
+    let (witness, public) = create_sponge_witness(&inputs, num_squeezes);
+    assert_eq!(public[inputs.len()..], native_sponge(&inputs, num_squeezes));
+
+    TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .public_inputs(public)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
+
+#[test]
+fn test_poseidon_sponge_wrong_output() {
+    let rng = &mut StdRng::from_seed(RNG_SEED);
+    let inputs = (0..3).map(|_| Fp::rand(rng)).collect::<Vec<_>>();
+
+    let gates = create_sponge_circuit(inputs.len(), 1);
+    let (witness, mut public) = create_sponge_witness(&inputs, 1);
+
+    // claim a different output in the public input
+    public[inputs.len()] += Fp::from(1u32);
+
+    let res = TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .public_inputs(public)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>();
+    assert!(res.is_err());
+}
@@ -1,7 +1,7 @@
 use crate::{
     circuits::{
         constraints::ConstraintSystem,
-        gate::{CircuitGate, CircuitGateError, GateType},
+        gate::{CircuitGate, CircuitGateError, Connect, GateType},
         polynomial::COLUMNS,
         polynomials::{
             generic::GenericGateSpec,
@@ -1145,6 +1145,52 @@ fn verify_64_bit_range_check() {
     );
 }
 
+#[test]
+fn verify_64_bit_range_check_connect_64bit() {
+    // Test circuit layout
+    //    Row Gate        Cells       Description
+    //      0 GenericPub  0 <-,-, ... Used to get a cell with zero
+    //      1 RangeCheck0 v0  0 0 ... Cells 1 and 2 wired to cell 0 of GenericPub by connect_64bit
+    let mut gates = vec![CircuitGate::<Fp>::create_generic_gadget(
+        Wire::for_row(0),
+        GenericGateSpec::Pub,
+        None,
+    )];
+    gates.append(&mut CircuitGate::<Fp>::create_range_check(1).1);
+    gates.connect_64bit(0, 1);
+
+    let witness = |value| {
+        let mut witness: [Vec<PallasField>; COLUMNS] =
+            array::from_fn(|_| vec![PallasField::zero()]);
+        range_check::witness::create::<PallasField>(value)
+            .iter_mut()
+            .enumerate()
+            .for_each(|(row, col)| witness[row].append(col));
+        witness
+    };
+    let prove_and_verify = |witness, disable_gates_checks| {
+        TestFramework::<Vesta>::default()
+            .gates(gates.clone())
+            .witness(witness)
+            .public_inputs(vec![PallasField::zero()])
+            .disable_gates_checks(disable_gates_checks)
+            .setup()
+            .prove_and_verify::<BaseSponge, ScalarSponge>()
+    };
+
+    // Positive test case
+    let valid = witness(PallasField::from(2u64).pow([64]) - PallasField::one());
+    assert_eq!(prove_and_verify(valid, false), Ok(()));
+
+    // Negative test case: only cell 1 of RangeCheck0 is not zero,
+    // which the gate checks and the permutation argument must both reject
+    let invalid = witness(PallasField::from(2u64).pow([76]));
+    assert_ne!(invalid[1][1], PallasField::zero());
+    assert_eq!(invalid[2][1], PallasField::zero());
+    assert!(prove_and_verify(invalid.clone(), false).is_err());
+    assert!(prove_and_verify(invalid, true).is_err());
+}
+
 #[test]
 fn compact_multi_range_check() {
     let rng = &mut StdRng::from_seed(RNG_SEED);
@@ -281,8 +281,8 @@ fn test_bad_constraints() {
         cs.gates[2].verify_witness::<Vesta>(2, &witness, &cs, &witness[0][0..cs.public]),
         Err(CircuitGateError::CopyConstraint {
             typ: GateType::RangeCheck0,
-            src: Wire { row: 2, col: 2 },
-            dst: Wire { row: 0, col: 0 }
+            src: Wire { row: 2, col: 1 },
+            dst: Wire { row: 2, col: 2 }
         })
     );
 }
@@ -0,0 +1,275 @@
+use crate::{
+    circuits::{
+        gate::{CircuitGate, Connect},
+        polynomials::{
+            generic::GenericGateSpec,
+            schnorr::{self, SchnorrLayout},
+        },
+        wires::{Wire, COLUMNS},
+    },
+    curve::KimchiCurve,
+    tests::framework::TestFramework,
+};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{BigInteger, PrimeField, UniformRand, Zero};
+use mina_curves::pasta::{Fp, Pallas, PallasParameters, Vesta, VestaParameters};
+use mina_hasher::{Hashable, Hasher, ROInput};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use mina_signer::{Keypair, NetworkId, PubKey, ScalarField, Signature, Signer};
+use rand::{rngs::StdRng, SeedableRng};
+use std::array;
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+const RNG_SEED: [u8; 32] = [
+    201, 13, 88, 4, 157, 61, 230, 19, 74, 102, 5, 243, 169, 30, 118, 57, 12, 199, 66, 140, 33, 251,
+    7, 92, 180, 45, 210, 3, 126, 77, 164, 8,
+];
+
+#[derive(Clone)]
+struct Transfer {
+    memo: Fp,
+    amount: u64,
+    nonce: u32,
+}
+
+impl Hashable for Transfer {
+    type D = NetworkId;
+
+    fn to_roinput(&self) -> ROInput {
+        ROInput::new()
+            .append_field(self.memo)
+            .append_u64(self.amount)
+            .append_u32(self.nonce)
+    }
+
+    fn domain_string(network_id: NetworkId) -> Option<String> {
+        match network_id {
+            NetworkId::MAINNET => "MinaSignatureMainnet",
+            NetworkId::TESTNET => "CodaSignature",
+        }
+        .to_string()
+        .into()
+    }
+}
+
+// The message hashed by mina-signer, to check the hash computed by the gadget
+#[derive(Clone)]
+struct SignedTransfer {
+    transfer: Transfer,
+    public_key: PubKey,
+    rx: Fp,
+}
+
+impl Hashable for SignedTransfer {
+    type D = NetworkId;
+
+    fn to_roinput(&self) -> ROInput {
+        self.transfer
+            .to_roinput()
+            .append_field(self.public_key.point().x)
+            .append_field(self.public_key.point().y)
+            .append_field(self.rx)
+    }
+
+    fn domain_string(network_id: NetworkId) -> Option<String> {
+        Transfer::domain_string(network_id)
+    }
+}
+
+// Signs a random transfer with a random keypair
+fn sign_transfer(network_id: NetworkId) -> (Transfer, PubKey, Signature) {
+    let rng = &mut StdRng::from_seed(RNG_SEED);
+    let keypair = Keypair::rand(rng).expect("failed to create keypair");
+    let transfer = Transfer {
+        memo: Fp::rand(rng),
+        amount: 1_000_000_000,
+        nonce: 42,
+    };
+    let mut signer = mina_signer::create_kimchi::<Transfer>(network_id);
+    let signature = signer.sign(&keypair, &transfer);
+    assert!(signer.verify(&signature, &keypair.public, &transfer));
+    (transfer, keypair.public, signature)
+}
+
+// The component `s` of a signature, as a field element of the circuit
+fn signature_s(signature: &Signature) -> Fp {
+    Fp::from_le_bytes_mod_order(&signature.s.into_repr().to_bytes_le())
+}
+
+// The number of field elements of a transfer, and of the ones packing its bits
+fn message_size(transfer: &Transfer) -> (usize, usize) {
+    let message = transfer.to_roinput();
+    let num_fields = message.num_fields();
+    (num_fields, message.to_fields().len() - num_fields)
+}
+
+// Creates a circuit whose public inputs are the message, the public key and the signature
+fn create_circuit(
+    network_id: NetworkId,
+    (num_fields, num_packed): (usize, usize),
+) -> Vec<CircuitGate<Fp>> {
+    let num_message = num_fields + num_packed;
+    let num_public = num_message + 4;
+    let mut gates: Vec<_> = (0..num_public)
+        .map(|row| {
+            CircuitGate::<Fp>::create_generic_gadget(Wire::for_row(row), GenericGateSpec::Pub, None)
+        })
+        .collect();
+
+    let initial_state =
+        schnorr::schnorr_initial_state::<Fp, Transfer>(Vesta::sponge_params(), network_id);
+    let layout = CircuitGate::extend_schnorr_gadget::<PallasParameters>(
+        &mut gates,
+        initial_state,
+        num_fields,
+        num_packed,
+        &Vesta::sponge_params().round_constants,
+    );
+
+    for i in 0..num_message {
+        gates.connect_cell_pair((i, 0), layout.message(i));
+    }
+    let [px, py] = layout.public_key();
+    gates.connect_cell_pair((num_message, 0), px);
+    gates.connect_cell_pair((num_message + 1, 0), py);
+    gates.connect_cell_pair((num_message + 2, 0), layout.rx());
+    gates.connect_cell_pair((num_message + 3, 0), layout.s());
+    gates
+}
+
+// Creates the witness of the circuit, and returns it along with the public inputs
+fn create_witness(
+    network_id: NetworkId,
+    transfer: &Transfer,
+    public_key: &PubKey,
+    signature: &Signature,
+) -> ([Vec<Fp>; COLUMNS], Vec<Fp>) {
+    let message = transfer.to_roinput();
+    let point = public_key.point();
+    let mut public = message.to_fields();
+    public.extend([point.x, point.y, signature.rx, signature_s(signature)]);
+
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); public.len()]);
+    witness[0].clone_from(&public);
+
+    let initial_state =
+        schnorr::schnorr_initial_state::<Fp, Transfer>(Vesta::sponge_params(), network_id);
+    schnorr::extend_schnorr_witness::<PallasParameters>(
+        &mut witness,
+        Vesta::sponge_params(),
+        initial_state,
+        &message,
+        *point,
+        (signature.rx, signature.s),
+    );
+
+    (witness, public)
+}
+
+#[test]
+fn test_schnorr_witness_matches_native() {
+    let (transfer, public_key, signature) = sign_transfer(NetworkId::TESTNET);
+    let message = transfer.to_roinput();
+    // the amount and the nonce of the transfer are packed after the fields of the message
+    let (num_fields, num_packed) = message_size(&transfer);
+    assert!(num_packed > 0);
+
+    let initial_state =
+        schnorr::schnorr_initial_state::<Fp, Transfer>(Vesta::sponge_params(), NetworkId::TESTNET);
+    let witness = schnorr::create_schnorr_witness::<PallasParameters>(
+        Vesta::sponge_params(),
+        initial_state,
+        &message,
+        *public_key.point(),
+        (signature.rx, signature.s),
+    );
+
+    let mut hasher = mina_hasher::create_kimchi::<SignedTransfer>(NetworkId::TESTNET);
+    let hash = hasher.hash(&SignedTransfer {
+        transfer,
+        public_key: public_key.clone(),
+        rx: signature.rx,
+    });
+
+    let layout = SchnorrLayout::new(0, num_fields, num_packed);
+    let (row, col) = layout.hash();
+    assert_eq!(witness[col][row], hash);
+
+    // the challenge of the circuit is the one of mina-signer: [s]G - [e]P has rx as x coordinate
+    let e = ScalarField::from_le_bytes_mod_order(&witness[col][row].into_repr().to_bytes_le());
+    let r = Pallas::prime_subgroup_generator().mul(signature.s.into_repr())
+        - public_key.point().mul(e.into_repr());
+    assert_eq!(r.into_affine().x, signature.rx);
+
+    for (i, field) in message.to_fields().into_iter().enumerate() {
+        let (row, col) = layout.message(i);
+        assert_eq!(witness[col][row], field);
+    }
+    let (row, col) = layout.rx();
+    assert_eq!(witness[col][row], signature.rx);
+    let (row, col) = layout.s();
+    assert_eq!(witness[col][row], signature_s(&signature));
+    for (cell, coordinate) in layout
+        .public_key()
+        .into_iter()
+        .zip([public_key.point().x, public_key.point().y])
+    {
+        assert_eq!(witness[cell.1][cell.0], coordinate);
+    }
+}
+
+#[test]
+fn test_schnorr() {
+    for network_id in [NetworkId::TESTNET, NetworkId::MAINNET] {
+        let (transfer, public_key, signature) = sign_transfer(network_id.clone());
+        let (witness, public) =
+            create_witness(network_id.clone(), &transfer, &public_key, &signature);
+
+        TestFramework::<Vesta>::default()
+            .gates(create_circuit(network_id, message_size(&transfer)))
+            .witness(witness)
+            .public_inputs(public)
+            .setup()
+            .prove_and_verify::<BaseSponge, ScalarSponge>()
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_schnorr_wrong_message() {
+    let (mut transfer, public_key, signature) = sign_transfer(NetworkId::TESTNET);
+
+    // prove the signature of another message
+    transfer.amount += 1;
+    let (witness, public) = create_witness(NetworkId::TESTNET, &transfer, &public_key, &signature);
+
+    let res = TestFramework::<Vesta>::default()
+        .gates(create_circuit(NetworkId::TESTNET, message_size(&transfer)))
+        .witness(witness)
+        .public_inputs(public)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>();
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_schnorr_wrong_network() {
+    let (transfer, public_key, signature) = sign_transfer(NetworkId::MAINNET);
+
+    // a mainnet signature is not valid on testnet
+    let (witness, public) = create_witness(NetworkId::TESTNET, &transfer, &public_key, &signature);
+
+    let res = TestFramework::<Vesta>::default()
+        .gates(create_circuit(NetworkId::TESTNET, message_size(&transfer)))
+        .witness(witness)
+        .public_inputs(public)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>();
+    assert!(res.is_err());
+}
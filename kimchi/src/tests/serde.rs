@@ -1,16 +1,21 @@
 use crate::{
     bench::BenchmarkCtx,
     circuits::{
+        gate::CircuitGate,
         polynomials::generic::testing::{create_circuit, fill_in_witness},
         wires::COLUMNS,
     },
+    error::ProverIndexError,
     proof::ProverProof,
-    prover_index::testing::new_index_for_test,
+    prover_index::{
+        testing::{new_index_for_test, new_index_for_test_with_lookups},
+        ProverIndex, PROVER_INDEX_MAGIC, PROVER_INDEX_VERSION,
+    },
     verifier::verify,
     verifier_index::VerifierIndex,
 };
 use ark_ec::short_weierstrass_jacobian::GroupAffine;
-use ark_ff::Zero;
+use ark_ff::{One, Zero};
 use groupmap::GroupMap;
 use mina_curves::pasta::{Fp, Vesta, VestaParameters};
 use mina_poseidon::{
@@ -92,4 +97,83 @@ mod tests {
         .unwrap();
         println!("- time to verify: {}ms", start.elapsed().as_millis());
     }
+
+    #[test]
+    fn test_prover_index_serialization() {
+        let public = vec![Fp::from(3u8); 5];
+        let gates = create_circuit(0, public.len());
+
+        // create witness
+        let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+        fill_in_witness(0, &mut witness, &public);
+
+        let index = new_index_for_test(gates, public.len());
+        let verifier_index = index.verifier_index();
+
+        let mut bytes = vec![];
+        index.write(&mut bytes).unwrap();
+
+        // reload the index, with its precomputations, for the same circuit
+        let start = Instant::now();
+        let reloaded = ProverIndex::<Vesta>::from_bytes(
+            Some(index.srs.clone()),
+            &bytes,
+            Some(index.circuit_digest()),
+        )
+        .unwrap();
+        println!("- time to reload: {}ms", start.elapsed().as_millis());
+        assert_eq!(
+            reloaded.verifier_index().digest::<BaseSponge>(),
+            verifier_index.digest::<BaseSponge>()
+        );
+
+        // the reloaded index creates proofs for the verifier index
+        let group_map = <Vesta as CommitmentCurve>::Map::setup();
+        let proof =
+            ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &reloaded)
+                .unwrap();
+        verify::<Vesta, BaseSponge, ScalarSponge>(&group_map, &verifier_index, &proof, &public)
+            .unwrap();
+
+        // an index for another circuit, or in another version, is rejected
+        assert!(matches!(
+            ProverIndex::<Vesta>::from_bytes(None, &bytes, Some([0; 32])),
+            Err(ProverIndexError::CircuitDigestMismatch)
+        ));
+        bytes[PROVER_INDEX_MAGIC.len()] += 1;
+        assert!(matches!(
+            ProverIndex::<Vesta>::from_bytes(None, &bytes, None),
+            Err(ProverIndexError::UnsupportedVersion(version, PROVER_INDEX_VERSION))
+                if version == PROVER_INDEX_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn test_prover_index_circuit_digest() {
+        let (_, gates) = CircuitGate::<Fp>::create_multi_range_check(0);
+        let index = new_index_for_test_with_lookups::<Vesta>(gates, 0, 0, vec![], None, false);
+        let digest = index.circuit_digest();
+        let read = |index: &ProverIndex<Vesta>| {
+            let mut bytes = vec![];
+            index.write(&mut bytes).unwrap();
+            ProverIndex::<Vesta>::from_bytes(None, &bytes, Some(digest)).map(|_| ())
+        };
+        assert!(read(&index).is_ok());
+
+        // the lookup tables and the number of previous challenges are covered by the digest
+        let mut tampered = index.clone();
+        let lookup = tampered.cs.lookup_constraint_system.as_mut().unwrap();
+        lookup.lookup_table[0].coeffs[0] += Fp::one();
+        assert!(matches!(
+            read(&tampered),
+            Err(ProverIndexError::CircuitDigestMismatch)
+        ));
+
+        let mut tampered = index.clone();
+        tampered.cs.prev_challenges += 1;
+        assert!(matches!(
+            read(&tampered),
+            Err(ProverIndexError::CircuitDigestMismatch)
+        ));
+    }
 }
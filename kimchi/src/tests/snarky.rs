@@ -0,0 +1,248 @@
+use super::framework::TestFramework;
+use crate::{
+    circuits::{
+        constraints::ConstraintSystem,
+        gate::{CircuitGate, CircuitGateError, GateType},
+        polynomials::range_check,
+        wires::COLUMNS,
+    },
+    curve::KimchiCurve,
+    error::SnarkyError,
+    snarky::{
+        constants::Constants,
+        constraint_system::KimchiConstraint,
+        cvar::FieldVar,
+        runner::{compile, generate_witness, RunState},
+    },
+};
+use ark_ff::{Field, One, PrimeField, Zero};
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    permutation::poseidon_block_cipher,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use o1_utils::FieldHelpers;
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+/// Proves the knowledge of an odd 16-bit `y` such that `x = y^3 + y` and `h = poseidon(x, y, 0)[0]`,
+/// for the public inputs `[x, h]`
+fn circuit<F: PrimeField>(secret: u16) -> impl FnOnce(&mut RunState<F>, Vec<FieldVar<F>>) {
+    move |sys, public| {
+        let (x, h) = (&public[0], &public[1]);
+        let y = sys.exists(|_| F::from(secret));
+
+        // x = y^3 + y
+        let y2 = sys.square(&y);
+        let y3 = sys.mul(&y2, &y);
+        sys.assert_equal(x, &(y3 + y.clone()));
+
+        // y is an odd 16-bit number, whose two highest bits are not both set
+        let bits = sys.to_bits(&y, 16);
+        let selected = sys.if_(&bits[0], x, &y);
+        sys.assert_equal(&selected, x);
+        let both = sys.and(&bits[14], &bits[15]);
+        sys.assert_true(&both.not());
+        let either = sys.or(&bits[14], &bits[15]);
+        let one = sys.xor(&either, &both);
+        sys.assert_equal(&one.to_field_var(), &either.to_field_var());
+
+        // h is the hash of x and y
+        let [hash, _, _] = sys.poseidon([x.clone(), y, FieldVar::zero()]);
+        sys.assert_equal(&hash, h);
+    }
+}
+
+/// Constrains three 88-bit limbs (or two compact limbs) with a multi range check,
+/// whose witness is given cell by cell
+fn multi_range_check_circuit<F: PrimeField>(
+    witness: Option<[Vec<F>; COLUMNS]>,
+    compact: bool,
+) -> impl FnOnce(&mut RunState<F>, Vec<FieldVar<F>>) {
+    move |sys, _| {
+        let mut cell = |row: usize, col: usize| {
+            sys.exists(|_| witness.as_ref().expect("witness generation")[col][row])
+        };
+        let mut rows: Vec<Vec<_>> = (0..3)
+            .map(|row| (0..COLUMNS).map(|col| cell(row, col)).collect())
+            .collect();
+        // the Zero row holds copies of the 12-bit limbs of the RangeCheck0 rows
+        let zero_row = (0..COLUMNS)
+            .map(|col| match col {
+                3 | 4 => rows[0][col - 2].clone(),
+                5 | 6 => rows[1][col - 4].clone(),
+                _ => cell(3, col),
+            })
+            .collect();
+        rows.push(zero_row);
+
+        let row = |i: usize| rows[i].iter().cloned().map(Some).collect();
+        sys.add_constraint(KimchiConstraint::RangeCheck0 {
+            row: row(0),
+            compact: F::zero(),
+        });
+        sys.add_constraint(KimchiConstraint::RangeCheck0 {
+            row: row(1),
+            compact: if compact { F::one() } else { F::zero() },
+        });
+        sys.add_constraint(KimchiConstraint::RangeCheck1 {
+            curr: row(2),
+            next: row(3),
+        });
+        sys.add_constraint(KimchiConstraint::AddFixedLookupTable {
+            id: 5,
+            data: vec![vec![F::zero(), F::one()]],
+        });
+    }
+}
+
+fn assert_same_gates<F: PrimeField>(gates: &[CircuitGate<F>], expected: &[CircuitGate<F>]) {
+    assert_eq!(gates.len(), expected.len());
+    for (gate, expected) in gates.iter().zip(expected) {
+        assert_eq!(gate.typ, expected.typ);
+        assert_eq!(gate.wires, expected.wires);
+        assert_eq!(gate.coeffs, expected.coeffs);
+    }
+}
+
+fn public_inputs(secret: u16) -> Vec<Fp> {
+    let y = Fp::from(secret);
+    let x = y * y * y + y;
+    let mut state = vec![x, y, Fp::zero()];
+    poseidon_block_cipher::<Fp, SpongeParams>(Vesta::sponge_params(), &mut state);
+    vec![x, state[0]]
+}
+
+#[test]
+fn test_snarky_circuit() {
+    let secret = 0b0100_1011_0010_1101;
+    let public = public_inputs(secret);
+
+    let compiled = compile(Constants::new::<Vesta>(), public.len(), circuit(secret));
+    let (witness_compiled, witness) =
+        generate_witness(Constants::new::<Vesta>(), &public, circuit(secret)).unwrap();
+
+    // running the circuit with or without the witness gives the same gates
+    assert_same_gates(&compiled.gates, &witness_compiled.gates);
+    for (row, input) in public.iter().enumerate() {
+        assert_eq!(witness[0][row], *input);
+    }
+
+    TestFramework::<Vesta>::default()
+        .gates(compiled.gates)
+        .witness(witness)
+        .public_inputs(public)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
+
+#[test]
+fn test_snarky_unsatisfied_constraint() {
+    let secret = 12345;
+    let mut public = public_inputs(secret);
+    public[1] += Fp::one();
+
+    let result = generate_witness(Constants::new::<Vesta>(), &public, circuit(secret));
+    assert!(matches!(
+        result,
+        Err(SnarkyError::UnsatisfiedConstraint(constraint)) if constraint.starts_with("assert_equal")
+    ));
+
+    // constraints between constants are checked directly
+    let result = generate_witness(Constants::new::<Vesta>(), &[], |sys, _| {
+        sys.assert_r1cs(
+            &FieldVar::constant(Fp::from(2u8)),
+            &FieldVar::constant(Fp::from(3u8)),
+            &FieldVar::constant(Fp::from(7u8)),
+        )
+    });
+    assert!(matches!(result, Err(SnarkyError::UnsatisfiedConstraint(_))));
+}
+
+fn test_snarky_range_check(compact: bool) {
+    let limbs = [
+        Fp::from_hex("2bc0afaa2f6f50b1d1424b000000000000000000000000000000000000000000").unwrap(),
+        Fp::from_hex("8b30889f3a39e297ac851a000000000000000000000000000000000000000000").unwrap(),
+        Fp::from_hex("c1c85ec47635e8edac5600000000000000000000000000000000000000000000").unwrap(),
+    ];
+    let expected_witness = if compact {
+        let v01 = limbs[0] + limbs[1] * Fp::from(2u64).pow([88]);
+        range_check::witness::create_multi_compact(v01, limbs[2])
+    } else {
+        range_check::witness::create_multi(limbs[0], limbs[1], limbs[2])
+    };
+
+    let compiled = compile(
+        Constants::new::<Vesta>(),
+        0,
+        multi_range_check_circuit(None, compact),
+    );
+    let (_, witness) = generate_witness(
+        Constants::new::<Vesta>(),
+        &[],
+        multi_range_check_circuit(Some(expected_witness.clone()), compact),
+    )
+    .unwrap();
+
+    // the circuit is the one of the range check gadget, with its copy constraints
+    let (_, expected_gates) = if compact {
+        CircuitGate::<Fp>::create_compact_multi_range_check(0)
+    } else {
+        CircuitGate::<Fp>::create_multi_range_check(0)
+    };
+    assert_same_gates(&compiled.gates, &expected_gates);
+    assert_eq!(witness, expected_witness);
+
+    // the lookup tables registered by the circuit are returned along with the gates
+    assert_eq!(compiled.lookup_tables.len(), 1);
+    assert_eq!(compiled.lookup_tables[0].id, 5);
+    assert!(compiled.runtime_tables.is_empty());
+
+    TestFramework::<Vesta>::default()
+        .gates(compiled.gates)
+        .witness(witness)
+        .lookup_tables(compiled.lookup_tables)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
+
+#[test]
+fn test_snarky_multi_range_check() {
+    test_snarky_range_check(false);
+}
+
+#[test]
+fn test_snarky_compact_multi_range_check() {
+    test_snarky_range_check(true);
+}
+
+#[test]
+fn test_snarky_range_check_not_in_range() {
+    let compiled = compile(
+        Constants::new::<Vesta>(),
+        0,
+        multi_range_check_circuit::<Fp>(None, false),
+    );
+    let cs = ConstraintSystem::create(compiled.gates).build().unwrap();
+
+    for v0 in [Fp::from(2u64).pow([88]), Fp::from(2u64).pow([96])] {
+        let witness = range_check::witness::create_multi(v0, Fp::zero(), Fp::zero());
+        let (_, witness) = generate_witness(
+            Constants::new::<Vesta>(),
+            &[],
+            multi_range_check_circuit(Some(witness), false),
+        )
+        .unwrap();
+
+        // the first RangeCheck0 row contains v0
+        assert_eq!(
+            cs.gates[0].verify_witness::<Vesta>(0, &witness, &cs, &[]),
+            Err(CircuitGateError::Constraint(GateType::RangeCheck0, 9))
+        );
+    }
+}
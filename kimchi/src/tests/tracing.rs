@@ -0,0 +1,66 @@
+use super::framework::TestFramework;
+use crate::circuits::polynomials::generic::testing::{create_circuit, fill_in_witness};
+use crate::circuits::wires::COLUMNS;
+use ark_ff::Zero;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::array;
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+#[test]
+fn test_prover_and_verifier_spans() {
+    let gates = create_circuit(0, 0);
+
+    // create witness
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &[]);
+
+    let collection = internal_tracing::spans::start_spans();
+    TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+    let spans = collection.take_spans();
+    drop(collection);
+
+    let children = |parent: u64| -> Vec<_> {
+        spans
+            .0
+            .iter()
+            .filter(|span| span.parent == Some(parent))
+            .map(|span| span.name)
+            .collect()
+    };
+    let prover = spans.named("create_recursive").next().unwrap();
+    let phases = children(prover.id);
+    for phase in [
+        "pad_witness",
+        "commit_to_witness_columns",
+        "compute_quotient_poly",
+        "create_aggregated_evaluation_proof",
+    ] {
+        assert!(phases.contains(&phase), "{phase}");
+    }
+    let quotient = spans
+        .named("compute_quotient_poly")
+        .find(|span| span.parent == Some(prover.id))
+        .unwrap();
+    assert!(children(quotient.id).contains(&"gate_constraints"));
+
+    let verifier = spans.named("batch_verify").next().unwrap();
+    assert!(children(verifier.id).contains(&"verify_opening_proofs"));
+
+    let trace = spans.to_chrome_trace();
+    assert_eq!(
+        trace["traceEvents"].as_array().unwrap().len(),
+        spans.0.len()
+    );
+}
@@ -0,0 +1,147 @@
+use super::framework::TestFramework;
+use crate::{
+    circuits::{
+        builder::{BuiltCircuit, CircuitBuilder, Gadget},
+        gate::CircuitGate,
+        polynomial::COLUMNS,
+        polynomials::range_check,
+        witness::{ConstantCell, VariableCell, Variables, WitnessCell, WitnessGenerator},
+    },
+    error::WitnessError,
+    variable_map, variables,
+};
+use ark_ff::Zero;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::array;
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+// A public input x, range checked along with v1 and v2,
+// which are then range checked again in the order v1, v2, x
+fn create_circuit() -> (BuiltCircuit<Fp, ()>, Gadget, Gadget) {
+    let mut builder = CircuitBuilder::new();
+    let x = builder.public_input();
+    let first = builder.gadget(CircuitGate::create_multi_range_check);
+    let second = builder.gadget(CircuitGate::create_multi_range_check);
+    builder.copy(x, first.cell(0, 0));
+    builder.copy(first.cell(1, 0), second.cell(0, 0));
+    builder.copy(first.cell(2, 0), second.cell(1, 0));
+    builder.copy(x, second.cell(2, 0));
+    (builder.build(), first, second)
+}
+
+fn public_layout() -> Vec<[Box<dyn WitnessCell<Fp>>; COLUMNS]> {
+    vec![array::from_fn(|col| -> Box<dyn WitnessCell<Fp>> {
+        if col == 0 {
+            VariableCell::create("x")
+        } else {
+            ConstantCell::create(Fp::zero())
+        }
+    })]
+}
+
+#[test]
+fn test_witness_generator() {
+    let (circuit, first, second) = create_circuit();
+    let (x, v1, v2) = (Fp::from(1u64 << 40), Fp::from(7u64), Fp::from(1u64 << 60));
+    let v12 = Fp::zero();
+
+    // only the first gadgets know their variables, the others are copied
+    let mut generator = WitnessGenerator::new(&circuit.gates);
+    generator
+        .layout(0, public_layout(), variables!(x))
+        .layout(
+            circuit.start_row(first),
+            range_check::witness::multi_layout(),
+            variables!(v1, v2, v12),
+        )
+        .layout(
+            circuit.start_row(second),
+            range_check::witness::multi_layout(),
+            variables!(v12),
+        );
+    let witness = generator.generate().unwrap();
+
+    let expected = [
+        range_check::witness::create_multi(x, v1, v2),
+        range_check::witness::create_multi(v1, v2, x),
+    ];
+    for (gadget, expected) in [first, second].into_iter().zip(expected) {
+        let start = circuit.start_row(gadget);
+        for col in 0..COLUMNS {
+            assert_eq!(witness[col][start..start + 4], expected[col]);
+        }
+    }
+    assert_eq!(witness[0][0], x);
+
+    TestFramework::<Vesta>::default()
+        .gates(circuit.gates)
+        .witness(witness)
+        .public_inputs(vec![x])
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
+
+#[test]
+fn test_witness_generator_missing_variables() {
+    let (circuit, first, second) = create_circuit();
+    let (x, v2) = (Fp::from(1u64 << 40), Fp::from(1u64 << 60));
+
+    // v1 is missing, and so is the first limb of the second range check, which is copied from it
+    let mut generator = WitnessGenerator::new(&circuit.gates);
+    generator
+        .layout(0, public_layout(), variables!(x))
+        .layout(
+            circuit.start_row(first),
+            range_check::witness::multi_layout(),
+            variable_map!("v2" => v2, "v12" => Fp::zero()),
+        )
+        .layout(
+            circuit.start_row(second),
+            range_check::witness::multi_layout(),
+            variable_map!("v12" => Fp::zero()),
+        );
+    assert_eq!(
+        generator.generate(),
+        Err(WitnessError::MissingVariables(vec![
+            ("v1".to_string(), circuit.start_row(first) + 1, 0),
+            ("v0".to_string(), circuit.start_row(second), 0),
+        ]))
+    );
+}
+
+#[test]
+fn test_witness_generator_invalid_layouts() {
+    let (circuit, first, _) = create_circuit();
+
+    // layouts cannot overlap
+    let mut generator = WitnessGenerator::new(&circuit.gates);
+    generator
+        .layout(0, public_layout(), variables!())
+        .layout(0, public_layout(), variables!());
+    assert_eq!(
+        generator.generate(),
+        Err(WitnessError::OverlappingLayouts(0, 0))
+    );
+
+    // nor exceed the circuit
+    let mut generator = WitnessGenerator::new(&circuit.gates);
+    generator.layout(
+        circuit.start_row(first) + 5,
+        range_check::witness::multi_layout(),
+        variables!(),
+    );
+    assert_eq!(
+        generator.generate(),
+        Err(WitnessError::LayoutOutOfBounds(
+            circuit.start_row(first) + 5
+        ))
+    );
+}
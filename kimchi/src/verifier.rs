@@ -85,6 +85,12 @@ impl<'a, G: KimchiCurve> Context<'a, G> {
                     Rot64 => Some(self.verifier_index.rot_comm.as_ref()?),
                 }
             }
+            CustomIndex(id) => self
+                .verifier_index
+                .custom_gates
+                .iter()
+                .find(|(custom_id, _)| *custom_id == id)
+                .map(|(_, comm)| comm),
         }
     }
 }
@@ -438,7 +444,7 @@ where
                     .lookup_index
                     .as_ref()
                     .map(|li| {
-                        (0..li.lookup_info.max_per_row + 1)
+                        (0..li.lookup_info.num_sorted())
                             .map(Column::LookupSorted)
                             .chain([Column::LookupAggreg, Column::LookupTable].into_iter())
                             .chain(
@@ -589,6 +595,7 @@ where
     check_proof_evals_len(proof)?;
 
     //~ 1. Commit to the negated public input polynomial.
+    let mut _phase = internal_tracing::span!(commit_to_public_input);
     let public_comm = {
         if public_input.len() != verifier_index.public {
             return Err(VerifyError::IncorrectPubicInputLength(
@@ -617,6 +624,7 @@ where
     };
 
     //~ 1. Run the [Fiat-Shamir argument](#fiat-shamir-argument).
+    internal_tracing::next_span!(_phase; oracles);
     let OraclesResult {
         fq_sponge,
         oracles,
@@ -649,6 +657,7 @@ where
     //~    contained in the verifier index or in the proof,
     //~    unless a polynomial has its evaluation provided by the proof
     //~    in which case the evaluation should be used in place of the commitment.
+    internal_tracing::next_span!(_phase; linearized_commitment);
     let f_comm = {
         // the permutation is written manually (not using the expr framework)
         let zkp = verifier_index.zkpm().evaluate(&oracles.zeta);
@@ -711,6 +720,7 @@ where
 
     //~ 1. List the polynomial commitments, and their associated evaluations,
     //~    that are associated to the aggregated evaluation proof in the proof:
+    internal_tracing::next_span!(_phase; list_evaluations);
     let mut evaluations = vec![];
 
     //~~ * recursion
@@ -756,7 +766,7 @@ where
             .as_ref()
             .map(|li| {
                 // add evaluations of sorted polynomials
-                (0..li.lookup_info.max_per_row + 1)
+                (0..li.lookup_info.num_sorted())
                     .map(Column::LookupSorted)
                     // add evaluations of the aggreg polynomial
                     .chain([Column::LookupAggreg].into_iter())
@@ -907,6 +917,8 @@ where
         return Ok(());
     }
 
+    let _span = internal_tracing::span!(batch_verify, { "proofs": proofs.len() });
+
     //~ 1. Ensure that all the proof's verifier index have a URS of the same length. (TODO: do they have to be the same URS though? should we check for that?)
     // TODO: Account for the different SRS lengths
     let srs = proofs[0].verifier_index.srs();
@@ -929,6 +941,7 @@ where
         public_input,
     } in proofs
     {
+        let _span = internal_tracing::span!(partial_verification);
         batch.push(to_batch::<G, EFqSponge, EFrSponge>(
             verifier_index,
             proof,
@@ -937,6 +950,7 @@ where
     }
 
     //~ 1. Use the [`PolyCom.verify`](#polynomial-commitments) to verify the partially evaluated proofs.
+    let _span = internal_tracing::span!(verify_opening_proofs);
     if srs.verify::<EFqSponge, _>(group_map, &mut batch, &mut thread_rng()) {
         Ok(())
     } else {
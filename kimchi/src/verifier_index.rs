@@ -4,6 +4,7 @@
 use crate::{
     alphas::Alphas,
     circuits::{
+        constraints::FeatureFlags,
         expr::{Linearization, PolishToken},
         lookup::{index::LookupSelectors, lookups::LookupInfo},
         polynomials::permutation::{zk_polynomial, zk_w3},
@@ -124,6 +125,10 @@ pub struct VerifierIndex<G: KimchiCurve> {
     #[serde(bound = "Option<PolyComm<G>>: Serialize + DeserializeOwned")]
     pub rot_comm: Option<PolyComm<G>>,
 
+    /// Custom gates selector polynomial commitments, along with their selector ids
+    #[serde(bound = "PolyComm<G>: Serialize + DeserializeOwned")]
+    pub custom_gates: Vec<(u32, PolyComm<G>)>,
+
     /// wire coordinate shifts
     #[serde_as(as = "[o1_utils::serialization::SerdeAs; PERMUTS]")]
     pub shift: [G::ScalarField; PERMUTS],
@@ -283,6 +288,19 @@ impl<G: KimchiCurve> ProverIndex<G> {
                 .as_ref()
                 .map(|eval8| self.srs.commit_evaluations_non_hiding(domain, eval8)),
 
+            custom_gates: self
+                .cs
+                .custom_gates
+                .iter()
+                .zip(self.column_evaluations.custom_selectors8.iter())
+                .map(|(custom_gate, eval8)| {
+                    (
+                        custom_gate.id(),
+                        self.srs.commit_evaluations_non_hiding(domain, eval8),
+                    )
+                })
+                .collect(),
+
             shift: self.cs.shift,
             zkpm: {
                 let cell = OnceCell::new();
@@ -324,6 +342,24 @@ impl<G: KimchiCurve> VerifierIndex<G> {
         self.w.get_or_init(|| zk_w3(self.domain))
     }
 
+    /// The feature flags of the circuit of the index,
+    /// which are deduced from its optional commitments
+    pub fn feature_flags(&self) -> FeatureFlags {
+        FeatureFlags {
+            range_check0: self.range_check0_comm.is_some(),
+            range_check1: self.range_check1_comm.is_some(),
+            foreign_field_add: self.foreign_field_add_comm.is_some(),
+            foreign_field_mul: self.foreign_field_mul_comm.is_some(),
+            xor: self.xor_comm.is_some(),
+            rot: self.rot_comm.is_some(),
+            lookup_features: self
+                .lookup_index
+                .as_ref()
+                .map(|lookup| lookup.lookup_info.features)
+                .unwrap_or_default(),
+        }
+    }
+
     /// Deserializes a [`VerifierIndex`] from a file, given a pointer to an SRS and an optional offset in the file.
     ///
     /// # Errors
@@ -416,6 +452,9 @@ impl<G: KimchiCurve> VerifierIndex<G> {
             xor_comm,
             rot_comm,
 
+            // Custom gates
+            custom_gates,
+
             // Lookup index; optional
             lookup_index,
 
@@ -469,6 +508,13 @@ impl<G: KimchiCurve> VerifierIndex<G> {
             fq_sponge.absorb_g(&rot_comm.unshifted);
         }
 
+        // Custom gates
+
+        for (id, comm) in custom_gates {
+            fq_sponge.absorb_fq(&[G::BaseField::from(*id)]);
+            fq_sponge.absorb_g(&comm.unshifted);
+        }
+
         // Lookup index; optional
 
         if let Some(LookupVerifierIndex {
@@ -1,15 +1,19 @@
 use ark_ff::PrimeField;
-use kimchi::circuits::gate::{Circuit, CircuitGate};
+use kimchi::{
+    circuits::gate::{Circuit, CircuitGate},
+    snarky::asm::parse_asm,
+};
 use mina_curves::pasta::Fp;
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Serialize};
+use std::io::Read;
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct DeserializableCircuit<F>
 where
     F: PrimeField,
 {
     pub public_input_size: usize,
-    #[serde(bound = "CircuitGate<F>:  DeserializeOwned")]
+    #[serde(bound = "CircuitGate<F>: Serialize + DeserializeOwned")]
     pub gates: Vec<CircuitGate<F>>,
 }
 
@@ -22,15 +26,74 @@ where
     }
 }
 
+const USAGE: &str = "usage: kimchi-asm [--assemble | --dot | --wiring] < input
+
+By default, reads a circuit in JSON and prints it in asm.
+With --assemble, reads a circuit in asm and prints it in JSON.
+With --dot, reads a circuit in JSON and prints its wiring as a Graphviz DOT graph.
+With --wiring, reads a circuit in JSON and prints its wiring as a JSON adjacency list.";
+
+/// What the tool prints
+enum Mode {
+    Asm,
+    Assemble,
+    Dot,
+    Wiring,
+}
+
 fn main() {
+    let mode = match std::env::args().nth(1).as_deref() {
+        None => Mode::Asm,
+        Some("--assemble" | "-a") => Mode::Assemble,
+        Some("--dot") => Mode::Dot,
+        Some("--wiring") => Mode::Wiring,
+        Some(_) => {
+            eprintln!("{USAGE}");
+            std::process::exit(1);
+        }
+    };
+
     // get what was piped to this binary
     let stdin = std::io::stdin();
 
-    // deserialize it to JSON
-    let circuit: DeserializableCircuit<Fp> =
-        serde_json::from_reader(stdin).expect("couldn't deserialize the circuit");
+    if let Mode::Assemble = mode {
+        let mut asm = String::new();
+        stdin
+            .lock()
+            .read_to_string(&mut asm)
+            .expect("couldn't read the circuit");
+
+        let (public_input_size, gates) = match parse_asm::<Fp>(&asm) {
+            Ok(circuit) => circuit,
+            Err(err) => {
+                eprintln!("couldn't parse the circuit: {err}");
+                std::process::exit(1);
+            }
+        };
+        let circuit = DeserializableCircuit {
+            public_input_size,
+            gates,
+        };
 
-    let circuit: Circuit<_> = (&circuit).into();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&circuit).expect("couldn't serialize the circuit")
+        );
+    } else {
+        // deserialize it to JSON
+        let circuit: DeserializableCircuit<Fp> =
+            serde_json::from_reader(stdin).expect("couldn't deserialize the circuit");
 
-    println!("{}", circuit.generate_asm());
+        let circuit: Circuit<_> = (&circuit).into();
+
+        match mode {
+            Mode::Dot => print!("{}", circuit.generate_dot()),
+            Mode::Wiring => println!(
+                "{}",
+                serde_json::to_string_pretty(&circuit.wiring())
+                    .expect("couldn't serialize the wiring")
+            ),
+            _ => println!("{}", circuit.generate_asm()),
+        }
+    }
 }
@@ -0,0 +1,191 @@
+use kimchi::bench::{compare, BenchmarkResult, GateFamily};
+use std::{collections::HashMap, fs, path::Path, process};
+use strum::IntoEnumIterator;
+
+const USAGE: &str = "usage: kimchi-bench <command> [options]
+
+commands:
+  run [--families <family,...>] [--sizes <log2,...>] [--iterations <n>] [--output <file>]
+      benchmarks the proof creation and verification of each gate family at each SRS size,
+      and writes the results in JSON (to the standard output by default)
+  compare [--threshold <percent>] <baseline> <results>
+      compares two results files, and fails if a measurement got slower by more than
+      the threshold (10% by default)
+
+families: generic, poseidon, range-check, foreign-field-mul, xor-rot, runtime-tables, recursion";
+
+const DEFAULT_SIZES: [u32; 3] = [14, 15, 16];
+const DEFAULT_ITERATIONS: usize = 3;
+const DEFAULT_THRESHOLD: f64 = 10.0;
+
+/// The options (`--name value`) and the other arguments of a command
+struct Args {
+    options: HashMap<String, String>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut args = args;
+        let mut options = HashMap::new();
+        let mut positional = vec![];
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for --{name}"))?;
+                    options.insert(name.to_string(), value);
+                }
+                None => positional.push(arg),
+            }
+        }
+        Ok(Self {
+            options,
+            positional,
+        })
+    }
+
+    /// Checks that only the `allowed` options are given, along with `positional` arguments
+    fn check(&self, allowed: &[&str], positional: usize) -> Result<(), String> {
+        if let Some(name) = self
+            .options
+            .keys()
+            .find(|name| !allowed.contains(&name.as_str()))
+        {
+            return Err(format!("unknown option --{name}"));
+        }
+        if self.positional.len() != positional {
+            return Err(format!(
+                "expected {positional} arguments, got {}",
+                self.positional.len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Parses a comma-separated list of values, or returns `default` without the option
+    fn list<T: std::str::FromStr>(&self, name: &str, default: Vec<T>) -> Result<Vec<T>, String> {
+        match self.options.get(name) {
+            None => Ok(default),
+            Some(values) => values
+                .split(',')
+                .map(|value| {
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("invalid value for --{name}: {value}"))
+                })
+                .collect(),
+        }
+    }
+
+    fn value<T: std::str::FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        match self.options.get(name) {
+            None => Ok(default),
+            Some(value) => value
+                .parse()
+                .map_err(|_| format!("invalid value for --{name}: {value}")),
+        }
+    }
+}
+
+fn read_results(path: &Path) -> Result<Vec<BenchmarkResult>, String> {
+    let file =
+        fs::File::open(path).map_err(|e| format!("couldn't open {}: {e}", path.display()))?;
+    serde_json::from_reader(file).map_err(|e| format!("invalid results {}: {e}", path.display()))
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    args.check(&["families", "sizes", "iterations", "output"], 0)?;
+    let families = args.list("families", GateFamily::iter().collect())?;
+    let sizes = args.list("sizes", DEFAULT_SIZES.to_vec())?;
+    let iterations = args.value("iterations", DEFAULT_ITERATIONS)?;
+    if iterations == 0 {
+        return Err("--iterations must be at least 1".to_string());
+    }
+
+    let mut results = vec![];
+    for family in families {
+        for &srs_size_log2 in &sizes {
+            if srs_size_log2 < family.min_srs_size_log2() {
+                eprintln!(
+                    "skipping {family} gates at SRS size 2^{srs_size_log2}, they need at least 2^{}",
+                    family.min_srs_size_log2()
+                );
+                continue;
+            }
+            let result = BenchmarkResult::measure(family, srs_size_log2, iterations);
+            eprintln!(
+                "{family} gates at SRS size 2^{srs_size_log2} ({} gates): proof created in {:.3} ms, verified in {:.3} ms",
+                result.gates,
+                result.prove as f64 / 1000.0,
+                result.verify as f64 / 1000.0
+            );
+            results.push(result);
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&results).unwrap();
+    match args.options.get("output") {
+        Some(path) => fs::write(path, json).map_err(|e| format!("couldn't write {path}: {e}")),
+        None => {
+            println!("{json}");
+            Ok(())
+        }
+    }
+}
+
+/// Returns whether no measurement regressed
+fn compare_results(args: &Args) -> Result<bool, String> {
+    args.check(&["threshold"], 2)?;
+    let threshold = args.value("threshold", DEFAULT_THRESHOLD)?;
+    let baseline = read_results(Path::new(&args.positional[0]))?;
+    let results = read_results(Path::new(&args.positional[1]))?;
+
+    for result in &results {
+        let found = baseline
+            .iter()
+            .any(|old| old.family == result.family && old.srs_size_log2 == result.srs_size_log2);
+        if !found {
+            println!(
+                "new benchmark: {} (SRS size 2^{})",
+                result.family, result.srs_size_log2
+            );
+        }
+    }
+
+    let changes = compare(&baseline, &results);
+    let mut regressions = 0;
+    for change in &changes {
+        if change.is_regression(threshold) {
+            regressions += 1;
+            println!("regression: {change}");
+        } else {
+            println!("{change}");
+        }
+    }
+
+    if regressions > 0 {
+        println!("{regressions} measurement(s) got slower by more than {threshold}%");
+    }
+    Ok(regressions == 0)
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let command = args.next();
+    let res = Args::parse(args).and_then(|args| match command.as_deref() {
+        Some("run") => run(&args).map(|()| true),
+        Some("compare") => compare_results(&args),
+        _ => Err(USAGE.to_string()),
+    });
+    match res {
+        Ok(true) => (),
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,376 @@
+//! The files read and written by the tool.
+//!
+//! Circuits, witnesses, public inputs and runtime tables are JSON files,
+//! in which field elements are hexstrings (as in the circuits read by `kimchi-asm`).
+//! SRS, indexes and proofs are binary files in the MessagePack format.
+
+use kimchi::{
+    circuits::{
+        custom_gate::CustomGate,
+        gate::CircuitGate,
+        lookup::{
+            runtime_tables::{RuntimeTable, RuntimeTableCfg, RuntimeTableSpec},
+            tables::LookupTable,
+        },
+        wires::COLUMNS,
+    },
+    linearization::expr_linearization,
+    proof::ProverProof,
+    prover_index::ProverIndex,
+    snarky::asm::parse_asm,
+    verifier_index::VerifierIndex,
+};
+use mina_curves::pasta::{Fp, Pallas, Vesta};
+use o1_utils::serialization::SerdeAs;
+use poly_commitment::srs::{endos, SRS};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_with::serde_as;
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::Arc,
+};
+
+/// A circuit, in the JSON format read by `kimchi-asm`,
+/// optionally followed by its fixed lookup tables and the configuration of its runtime tables
+#[derive(Serialize, Deserialize)]
+pub struct CircuitFile {
+    pub public_input_size: usize,
+    pub gates: Vec<CircuitGate<Fp>>,
+    #[serde(default)]
+    pub lookup_tables: Vec<LookupTableFile>,
+    #[serde(default)]
+    pub runtime_tables: Vec<RuntimeTableCfgFile>,
+}
+
+/// A fixed lookup table, given column by column
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+pub struct LookupTableFile {
+    pub id: i32,
+    #[serde_as(as = "Vec<Vec<SerdeAs>>")]
+    pub data: Vec<Vec<Fp>>,
+}
+
+/// The configuration of a runtime table, see [`RuntimeTableCfg`]
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+pub enum RuntimeTableCfgFile {
+    Indexed(RuntimeTableSpec),
+    Custom {
+        id: i32,
+        #[serde_as(as = "Vec<SerdeAs>")]
+        first_column: Vec<Fp>,
+    },
+}
+
+/// The second column of a runtime table, given at proving time
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+pub struct RuntimeTableFile {
+    pub id: i32,
+    #[serde_as(as = "Vec<SerdeAs>")]
+    pub data: Vec<Fp>,
+}
+
+/// A list of field elements, such as a public input
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FieldsFile(#[serde_as(as = "Vec<SerdeAs>")] pub Vec<Fp>);
+
+/// A witness, given column by column
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct WitnessFile(#[serde_as(as = "Vec<Vec<SerdeAs>>")] pub Vec<Vec<Fp>>);
+
+/// A proof, along with the public input it was created for
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+pub struct ProofFile {
+    pub proof: ProverProof<Vesta>,
+    #[serde_as(as = "Vec<SerdeAs>")]
+    pub public_input: Vec<Fp>,
+}
+
+impl From<LookupTableFile> for LookupTable<Fp> {
+    fn from(table: LookupTableFile) -> Self {
+        LookupTable {
+            id: table.id,
+            data: table.data,
+        }
+    }
+}
+
+impl From<RuntimeTableCfgFile> for RuntimeTableCfg<Fp> {
+    fn from(cfg: RuntimeTableCfgFile) -> Self {
+        match cfg {
+            RuntimeTableCfgFile::Indexed(spec) => RuntimeTableCfg::Indexed(spec),
+            RuntimeTableCfgFile::Custom { id, first_column } => {
+                RuntimeTableCfg::Custom { id, first_column }
+            }
+        }
+    }
+}
+
+impl From<RuntimeTableFile> for RuntimeTable<Fp> {
+    fn from(table: RuntimeTableFile) -> Self {
+        RuntimeTable {
+            id: table.id,
+            data: table.data,
+        }
+    }
+}
+
+fn open(path: &Path) -> Result<BufReader<File>, String> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| format!("couldn't open {}: {e}", path.display()))
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    serde_json::from_reader(open(path)?)
+        .map_err(|e| format!("couldn't read {}: {e}", path.display()))
+}
+
+fn read_binary<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    rmp_serde::from_read(open(path)?).map_err(|e| format!("couldn't read {}: {e}", path.display()))
+}
+
+fn write_binary<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let error = |e: &dyn std::fmt::Display| format!("couldn't write {}: {e}", path.display());
+    let file = File::create(path).map_err(|e| error(&e))?;
+    let mut writer = BufWriter::new(file);
+    value
+        .serialize(&mut rmp_serde::Serializer::new(&mut writer))
+        .map_err(|e| error(&e))?;
+    writer.flush().map_err(|e| error(&e))
+}
+
+/// Reads a circuit, in asm if the file ends in `.asm` and in JSON otherwise
+pub fn read_circuit(path: &Path) -> Result<CircuitFile, String> {
+    if path.extension().map_or(false, |ext| ext == "asm") {
+        let mut asm = String::new();
+        open(path)?
+            .read_to_string(&mut asm)
+            .map_err(|e| format!("couldn't read {}: {e}", path.display()))?;
+        let (public_input_size, gates) =
+            parse_asm(&asm).map_err(|e| format!("couldn't parse {}: {e}", path.display()))?;
+        Ok(CircuitFile {
+            public_input_size,
+            gates,
+            lookup_tables: vec![],
+            runtime_tables: vec![],
+        })
+    } else {
+        read_json(path)
+    }
+}
+
+/// Reads a witness, and checks that it has [`COLUMNS`] columns of the same length
+pub fn read_witness(path: &Path) -> Result<[Vec<Fp>; COLUMNS], String> {
+    let WitnessFile(columns) = read_json(path)?;
+    let witness: [Vec<Fp>; COLUMNS] = columns
+        .try_into()
+        .map_err(|columns: Vec<_>| format!("the witness has {} columns", columns.len()))?;
+    if witness
+        .iter()
+        .any(|column| column.len() != witness[0].len())
+    {
+        return Err("the columns of the witness have different lengths".to_string());
+    }
+    Ok(witness)
+}
+
+pub fn read_public_input(path: &Path) -> Result<Vec<Fp>, String> {
+    read_json(path).map(|FieldsFile(public_input)| public_input)
+}
+
+pub fn read_runtime_tables(path: &Path) -> Result<Vec<RuntimeTable<Fp>>, String> {
+    let tables: Vec<RuntimeTableFile> = read_json(path)?;
+    Ok(tables.into_iter().map(Into::into).collect())
+}
+
+pub fn read_srs(path: &Path) -> Result<SRS<Vesta>, String> {
+    read_binary(path)
+}
+
+pub fn read_proof(path: &Path) -> Result<ProofFile, String> {
+    read_binary(path)
+}
+
+pub fn write_proof(path: &Path, proof: &ProofFile) -> Result<(), String> {
+    write_binary(path, proof)
+}
+
+/// The endoscalar coefficient of the indexes
+pub fn endo() -> Fp {
+    endos::<Pallas>().0
+}
+
+/// Checks that an SRS is the one an index was created with
+fn check_srs(srs: &SRS<Vesta>, max_poly_size: usize) -> Result<(), String> {
+    if srs.g.len() != max_poly_size {
+        return Err(format!(
+            "the index was created with an SRS of size {max_poly_size}, not {}",
+            srs.g.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Reads a prover index written by [`write_prover_index`], see [`ProverIndex::from_file`].
+/// Without the SRS it was created with, the index can't be used to create proofs.
+pub fn read_prover_index(
+    path: &Path,
+    srs: Option<SRS<Vesta>>,
+) -> Result<ProverIndex<Vesta>, String> {
+    ProverIndex::from_file(srs.map(Arc::new), path, None)
+        .map_err(|e| format!("couldn't read {}: {e}", path.display()))
+}
+
+pub fn write_prover_index(path: &Path, index: &ProverIndex<Vesta>) -> Result<(), String> {
+    index
+        .to_file(path)
+        .map_err(|e| format!("couldn't write {}: {e}", path.display()))
+}
+
+/// Reads a verifier index written by [`write_verifier_index`], and recomputes the parts that are not serialized.
+/// Without an SRS, the SRS of the index is regenerated when it is first used.
+/// The constraints of custom gates are not serialized: the `custom_gates` of the index have to be given again.
+pub fn read_verifier_index(
+    path: &Path,
+    srs: Option<SRS<Vesta>>,
+    custom_gates: &[CustomGate<Fp>],
+) -> Result<VerifierIndex<Vesta>, String> {
+    let mut index = VerifierIndex::<Vesta>::from_file(None, path, None, endo())
+        .map_err(|e| format!("couldn't read {}: {e}", path.display()))?;
+
+    let expected: Vec<_> = index.custom_gates.iter().map(|(id, _)| *id).collect();
+    let given: Vec<_> = custom_gates.iter().map(CustomGate::id).collect();
+    if given.is_empty() && !expected.is_empty() {
+        return Err(format!(
+            "couldn't read {}: the index has custom gates with selector ids {expected:?}, which the command line doesn't support",
+            path.display()
+        ));
+    }
+    if given != expected {
+        return Err(format!(
+            "couldn't read {}: the index has custom gates with selector ids {expected:?}, not {given:?}",
+            path.display()
+        ));
+    }
+
+    if let Some(mut srs) = srs {
+        check_srs(&srs, index.max_poly_size)?;
+        srs.add_lagrange_basis(index.domain);
+        index
+            .srs
+            .set(Arc::new(srs))
+            .map_err(|_| "the SRS of the verifier index is already set".to_string())?;
+    }
+
+    let (linearization, powers_of_alpha) =
+        expr_linearization(Some(&index.feature_flags()), true, custom_gates);
+    index.linearization = linearization;
+    index.powers_of_alpha = powers_of_alpha;
+
+    Ok(index)
+}
+
+pub fn write_verifier_index(path: &Path, index: &VerifierIndex<Vesta>) -> Result<(), String> {
+    write_binary(path, index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BaseSponge, ScalarSponge};
+    use ark_ff::{PrimeField, Zero};
+    use ark_poly::EvaluationDomain;
+    use groupmap::GroupMap;
+    use kimchi::{
+        circuits::{
+            argument::{Argument, ArgumentEnv, ArgumentType},
+            constraints::ConstraintSystem,
+            expr::{constraints::ExprOps, Cache},
+            gate::Connect,
+            polynomials::generic::GenericGateSpec,
+            wires::Wire,
+        },
+        verifier::verify,
+    };
+    use poly_commitment::commitment::CommitmentCurve;
+    use std::{array, marker::PhantomData};
+
+    // Constrains w(1) = w(0)^2
+    #[derive(Default)]
+    struct Square<F>(PhantomData<F>);
+
+    impl<F: PrimeField> Argument<F> for Square<F> {
+        const ARGUMENT_TYPE: ArgumentType = ArgumentType::CustomGate(1);
+        const CONSTRAINTS: u32 = 1;
+
+        fn constraint_checks<T: ExprOps<F>>(env: &ArgumentEnv<F, T>, _cache: &mut Cache) -> Vec<T> {
+            vec![env.witness_curr(1) - env.witness_curr(0).square()]
+        }
+    }
+
+    // A file in the temporary directory, removed when dropped
+    struct TempFile(std::path::PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            let name = format!("kimchi-cli-{}-{name}", std::process::id());
+            Self(std::env::temp_dir().join(name))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_verifier_index_with_custom_gates_round_trip() {
+        // the public input x, followed by a square gate on x
+        let mut gates = vec![
+            CircuitGate::<Fp>::create_generic_gadget(Wire::for_row(0), GenericGateSpec::Pub, None),
+            CircuitGate::zero(Wire::for_row(1)),
+        ];
+        gates.connect_cell_pair((0, 0), (1, 0));
+        let custom_gates = || vec![CustomGate::new(Square::default(), vec![1])];
+        let cs = ConstraintSystem::create(gates)
+            .public(1)
+            .custom_gate(CustomGate::new(Square::default(), vec![1]))
+            .build()
+            .unwrap();
+        let mut srs = SRS::<Vesta>::create(cs.domain.d1.size());
+        srs.add_lagrange_basis(cs.domain.d1);
+        let index = ProverIndex::<Vesta>::create(cs, endo(), Arc::new(srs.clone()));
+
+        let file = TempFile::new("custom-gates-verifier-index");
+        write_verifier_index(&file.0, &index.verifier_index()).unwrap();
+
+        // the constraints of the custom gates have to be given again
+        let error = read_verifier_index(&file.0, None, &[]).unwrap_err();
+        assert!(error.contains("the command line doesn't support"));
+        let verifier_index = read_verifier_index(&file.0, Some(srs), &custom_gates()).unwrap();
+
+        let x = Fp::from(3u64);
+        let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); 2]);
+        witness[0][0] = x;
+        witness[0][1] = x;
+        witness[1][1] = x * x;
+
+        let group_map = <Vesta as CommitmentCurve>::Map::setup();
+        let proof =
+            ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index)
+                .unwrap();
+        verify::<Vesta, BaseSponge, ScalarSponge>(&group_map, &verifier_index, &proof, &[x])
+            .unwrap();
+    }
+}
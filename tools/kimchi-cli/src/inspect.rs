@@ -0,0 +1,104 @@
+//! Summaries of the files read and written by the tool.
+
+use crate::{files, BaseSponge};
+use ark_poly::EvaluationDomain;
+use kimchi::circuits::{gate::CircuitGate, lookup::runtime_tables::RuntimeTableCfg};
+use mina_curves::pasta::Fp;
+use o1_utils::FieldHelpers;
+use std::{collections::BTreeMap, path::Path};
+
+/// Prints the number of gates of each type
+fn print_gates(gates: &[CircuitGate<Fp>]) {
+    let mut counts = BTreeMap::<String, usize>::new();
+    for gate in gates {
+        *counts.entry(format!("{:?}", gate.typ)).or_default() += 1;
+    }
+    println!("gates: {}", gates.len());
+    for (typ, count) in counts {
+        println!("  {typ}: {count}");
+    }
+}
+
+fn print_fields(name: &str, fields: &[Fp]) {
+    println!("{name}: {}", fields.len());
+    for field in fields {
+        println!("  {}", field.to_hex());
+    }
+}
+
+/// Prints a summary of a file of the given kind
+pub fn inspect(kind: &str, path: &Path) -> Result<(), String> {
+    match kind {
+        "circuit" => {
+            let circuit = files::read_circuit(path)?;
+            println!("public inputs: {}", circuit.public_input_size);
+            print_gates(&circuit.gates);
+            for table in &circuit.lookup_tables {
+                let rows = table.data.first().map_or(0, Vec::len);
+                println!(
+                    "lookup table {}: {} columns, {rows} rows",
+                    table.id,
+                    table.data.len()
+                );
+            }
+            for cfg in circuit.runtime_tables {
+                let cfg = RuntimeTableCfg::from(cfg);
+                println!("runtime table {}: {} rows", cfg.id(), cfg.len());
+            }
+        }
+        "witness" => {
+            let witness = files::read_witness(path)?;
+            println!("columns: {}", witness.len());
+            println!("rows: {}", witness[0].len());
+        }
+        "public-input" => print_fields("public inputs", &files::read_public_input(path)?),
+        "runtime-tables" => {
+            for table in files::read_runtime_tables(path)? {
+                println!("runtime table {}: {} rows", table.id, table.data.len());
+            }
+        }
+        "srs" => {
+            let srs = files::read_srs(path)?;
+            println!("size: {}", srs.g.len());
+        }
+        "prover-index" => {
+            let index = files::read_prover_index(path, None)?;
+            println!("domain size: {}", index.cs.domain.d1.size());
+            println!("SRS size: {}", index.max_poly_size);
+            println!("public inputs: {}", index.cs.public);
+            println!("previous challenges: {}", index.cs.prev_challenges);
+            let digest: String = index
+                .circuit_digest()
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect();
+            println!("circuit digest: {digest}");
+            print_gates(&index.cs.gates);
+            println!("{:#?}", index.cs.feature_flags);
+        }
+        "verifier-index" => {
+            let index = files::read_verifier_index(path, None, &[])?;
+            println!("domain size: {}", index.domain.size());
+            println!("SRS size: {}", index.max_poly_size);
+            println!("public inputs: {}", index.public);
+            println!("previous challenges: {}", index.prev_challenges);
+            println!("digest: {}", index.digest::<BaseSponge>().to_hex());
+            println!("{:#?}", index.feature_flags());
+        }
+        "proof" => {
+            let files::ProofFile {
+                proof,
+                public_input,
+            } = files::read_proof(path)?;
+            print_fields("public inputs", &public_input);
+            println!(
+                "quotient chunks: {}",
+                proof.commitments.t_comm.unshifted.len()
+            );
+            println!("lookups: {}", proof.commitments.lookup.is_some());
+            println!("previous challenges: {}", proof.prev_challenges.len());
+        }
+        _ => return Err(format!("unknown kind of file: {kind}")),
+    }
+    Ok(())
+}
@@ -0,0 +1,280 @@
+mod files;
+mod inspect;
+
+use ark_poly::EvaluationDomain;
+use files::ProofFile;
+use groupmap::GroupMap;
+use kimchi::{
+    circuits::{constraints::ConstraintSystem, polynomials::permutation::ZK_ROWS},
+    diff::{CircuitDiff, VerifierIndexDiff},
+    proof::ProverProof,
+    prover_index::ProverIndex,
+    verifier::{batch_verify, Context},
+};
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use o1_utils::FieldHelpers;
+use poly_commitment::commitment::CommitmentCurve;
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+const USAGE: &str = "usage: kimchi <command> [options]
+
+Circuits are over the Vesta scalar field, in JSON (or in asm if the file ends in .asm).
+
+commands:
+  setup --circuit <file> --srs <file> --prover-index <file> --verifier-index <file>
+      creates the prover and verifier indexes of a circuit
+  prove --prover-index <file> --srs <file> --witness <file> [--public-input <file>]
+        [--runtime-tables <file>] --proof <file>
+      creates a proof, the public input defaults to the first rows of the witness
+  verify --verifier-index <file> [--srs <file>] <proof>
+      verifies a proof
+  batch-verify --verifier-index <file> [--srs <file>] <proof>...
+      verifies several proofs at once
+  diff <circuit|verifier-index> <old> <new>
+      prints the differences between two circuits or two verifier indexes
+  inspect <circuit|witness|public-input|runtime-tables|srs|prover-index|verifier-index|proof> <file>
+      prints a summary of a file";
+
+/// The options (`--name value`) and the other arguments of a command
+struct Args {
+    options: HashMap<String, String>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut args = args;
+        let mut options = HashMap::new();
+        let mut positional = vec![];
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for --{name}"))?;
+                    options.insert(name.to_string(), value);
+                }
+                None => positional.push(arg),
+            }
+        }
+        Ok(Self {
+            options,
+            positional,
+        })
+    }
+
+    /// Checks that only the `allowed` options are given, along with `positional` arguments
+    fn check(&self, allowed: &[&str], positional: usize) -> Result<(), String> {
+        if let Some(name) = self
+            .options
+            .keys()
+            .find(|name| !allowed.contains(&name.as_str()))
+        {
+            return Err(format!("unknown option --{name}"));
+        }
+        if self.positional.len() != positional {
+            return Err(format!(
+                "expected {positional} arguments, got {}",
+                self.positional.len()
+            ));
+        }
+        Ok(())
+    }
+
+    fn optional(&self, name: &str) -> Option<&Path> {
+        self.options.get(name).map(Path::new)
+    }
+
+    fn required(&self, name: &str) -> Result<&Path, String> {
+        self.optional(name)
+            .ok_or_else(|| format!("missing option --{name}"))
+    }
+}
+
+/// Reads a circuit and builds its constraint system
+fn read_constraint_system(path: &Path) -> Result<ConstraintSystem<Fp>, String> {
+    let circuit = files::read_circuit(path)?;
+    let runtime_tables = (!circuit.runtime_tables.is_empty())
+        .then(|| circuit.runtime_tables.into_iter().map(Into::into).collect());
+    ConstraintSystem::create(circuit.gates)
+        .public(circuit.public_input_size)
+        .lookup(circuit.lookup_tables.into_iter().map(Into::into).collect())
+        .runtime(runtime_tables)
+        .build()
+        .map_err(|e| format!("invalid circuit {}: {e}", path.display()))
+}
+
+fn setup(args: &Args) -> Result<(), String> {
+    args.check(&["circuit", "srs", "prover-index", "verifier-index"], 0)?;
+    let cs = read_constraint_system(args.required("circuit")?)?;
+    let mut srs = files::read_srs(args.required("srs")?)?;
+
+    let domain_size = cs.domain.d1.size();
+    if srs.g.len() < domain_size {
+        return Err(format!(
+            "the SRS has size {}, but the circuit needs {domain_size}",
+            srs.g.len()
+        ));
+    }
+    srs.add_lagrange_basis(cs.domain.d1);
+
+    let index = ProverIndex::<Vesta>::create(cs, files::endo(), Arc::new(srs));
+    let verifier_index = index.verifier_index();
+    files::write_prover_index(args.required("prover-index")?, &index)?;
+    files::write_verifier_index(args.required("verifier-index")?, &verifier_index)?;
+
+    println!(
+        "verifier index digest: {}",
+        verifier_index.digest::<BaseSponge>().to_hex()
+    );
+    Ok(())
+}
+
+fn prove(args: &Args) -> Result<(), String> {
+    args.check(
+        &[
+            "prover-index",
+            "srs",
+            "witness",
+            "public-input",
+            "runtime-tables",
+            "proof",
+        ],
+        0,
+    )?;
+    let srs = files::read_srs(args.required("srs")?)?;
+    let index = files::read_prover_index(args.required("prover-index")?, Some(srs))?;
+    let witness = files::read_witness(args.required("witness")?)?;
+
+    // the last rows of the domain are kept for the zero-knowledge rows
+    let rows = witness[0].len();
+    let max_rows = index.cs.domain.d1.size() - ZK_ROWS as usize;
+    if rows < index.cs.public || rows > max_rows {
+        return Err(format!(
+            "the witness has {rows} rows, it should have between {} and {max_rows} rows",
+            index.cs.public
+        ));
+    }
+    let public_input = match args.optional("public-input") {
+        Some(path) => files::read_public_input(path)?,
+        None => witness[0][..index.cs.public].to_vec(),
+    };
+    if public_input[..] != witness[0][..index.cs.public] {
+        return Err("the public input doesn't match the first rows of the witness".to_string());
+    }
+    let runtime_tables = match args.optional("runtime-tables") {
+        Some(path) => files::read_runtime_tables(path)?,
+        None => vec![],
+    };
+
+    index
+        .verify(&witness, &public_input)
+        .map_err(|e| format!("the witness doesn't satisfy the circuit: {e:?}"))?;
+
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof = ProverProof::create::<BaseSponge, ScalarSponge>(
+        &group_map,
+        witness,
+        &runtime_tables,
+        &index,
+    )
+    .map_err(|e| format!("couldn't create the proof: {e}"))?;
+
+    files::write_proof(
+        args.required("proof")?,
+        &ProofFile {
+            proof,
+            public_input,
+        },
+    )
+}
+
+fn verify(args: &Args, batch: bool) -> Result<(), String> {
+    let expected = if batch {
+        args.positional.len().max(1)
+    } else {
+        1
+    };
+    args.check(&["verifier-index", "srs"], expected)?;
+    let srs = args.optional("srs").map(files::read_srs).transpose()?;
+    let verifier_index = files::read_verifier_index(args.required("verifier-index")?, srs, &[])?;
+    let proofs = args
+        .positional
+        .iter()
+        .map(|path| files::read_proof(Path::new(path)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let contexts: Vec<_> = proofs
+        .iter()
+        .map(|proof| Context {
+            verifier_index: &verifier_index,
+            proof: &proof.proof,
+            public_input: &proof.public_input,
+        })
+        .collect();
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    batch_verify::<Vesta, BaseSponge, ScalarSponge>(&group_map, &contexts)
+        .map_err(|e| format!("verification failed: {e}"))?;
+
+    println!("verified {} proof(s)", proofs.len());
+    Ok(())
+}
+
+fn diff(args: &Args) -> Result<(), String> {
+    args.check(&[], 3)?;
+    let (old, new) = (
+        Path::new(&args.positional[1]),
+        Path::new(&args.positional[2]),
+    );
+    let report = match args.positional[0].as_str() {
+        "circuit" => CircuitDiff::new(&read_constraint_system(old)?, &read_constraint_system(new)?)
+            .to_string(),
+        "verifier-index" => VerifierIndexDiff::new(
+            &files::read_verifier_index(old, None, &[])?,
+            &files::read_verifier_index(new, None, &[])?,
+        )
+        .to_string(),
+        kind => return Err(format!("can't diff files of kind {kind}")),
+    };
+
+    if report.is_empty() {
+        println!("no differences");
+    } else {
+        print!("{report}");
+    }
+    Ok(())
+}
+
+fn run(command: &str, args: &Args) -> Result<(), String> {
+    match command {
+        "setup" => setup(args),
+        "prove" => prove(args),
+        "verify" => verify(args, false),
+        "batch-verify" => verify(args, true),
+        "diff" => diff(args),
+        "inspect" => {
+            args.check(&[], 2)?;
+            inspect::inspect(&args.positional[0], Path::new(&args.positional[1]))
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let command = args.next().unwrap_or_default();
+    let result = Args::parse(args).and_then(|args| run(&command, &args));
+
+    if let Err(err) = result {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
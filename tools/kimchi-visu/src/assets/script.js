@@ -1,5 +1,13 @@
-const cs = index.cs;
-let public = cs.public;
+const { gates, witness, failures, cycles, constraints } = data;
+let public = data.public;
+
+// the copy-constraint cycle of each cell, by "row,col"
+const cellCycles = {};
+cycles.forEach((cycle, i) => {
+    cycle.forEach((cell) => {
+        cellCycles[`${cell.row},${cell.col}`] = i;
+    });
+});
 
 //
 // Main logic
@@ -10,11 +18,11 @@ if (witness) {
     document.querySelector("#gates thead tr").innerHTML += '<th colspan="15" scope="col" class="px-6 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider">Registers</th > ';
 
     // pad witness
-    const witness_padding = cs.gates.length - witness.inner[0].length;
+    const witness_padding = gates.length - witness[0].length;
     if (witness_padding > 0) {
         for (let col = 0; col < 15; col++) {
             for (let i = 0; i < witness_padding; i++) {
-                witness.inner[col].push(0);
+                witness[col].push(0);
             }
         }
     }
@@ -22,7 +30,7 @@ if (witness) {
 
 // display gates
 let poseidon = false
-cs.gates.forEach((g, row) => {
+gates.forEach((g, row) => {
     let coeffs = '';
 
     const coeffs_padding = 15 - g.coeffs.length;
@@ -53,12 +61,19 @@ cs.gates.forEach((g, row) => {
 
     let witness_cols = '';
     if (witness) {
-        witness.inner.forEach((col) => {
-            witness_cols += `<td class="px-6 py-4 whitespace-nowrap text-xs text-gray-500">${toTruncatedHex(col[row])}</td>`;
+        witness.forEach((col, i) => {
+            const cycle = cellCycles[`${row},${i}`];
+            const title = cycle === undefined ? '' : ` title="copy cycle ${cycle}"`;
+            witness_cols += `<td class="px-6 py-4 whitespace-nowrap text-xs text-gray-500"${title}>${toTruncatedHex(col[row])}</td>`;
         });
     }
 
-    const gate = `<tr>
+    // highlight the rows whose constraints fail on the witness
+    const failure = failures[row];
+    const rowClass = failure ? ' class="bg-red-100"' : '';
+    const rowTitle = failure ? ` title="${failure}"` : '';
+
+    const gate = `<tr${rowClass}${rowTitle}>
                 <td class="px-6 py-4 whitespace-nowrap">${row}</td>
                 <td class="px-6 py-4 whitespace-nowrap ${gateColor(g.typ)}">${typ}</td>
                 <td class="px-6 py-4 whitespace-nowrap">${wiring}</td>
@@ -68,10 +83,29 @@ cs.gates.forEach((g, row) => {
     document.querySelector("#gates tbody").innerHTML += gate;
 });
 
+// display the failing constraints
+const failuresDiv = document.querySelector("#failures");
+if (witness && Object.keys(failures).length == 0) {
+    failuresDiv.innerHTML = '<p>The witness satisfies all the constraints.</p>';
+}
+for (const [row, failure] of Object.entries(failures)) {
+    failuresDiv.innerHTML += `<li>row ${row}: ${failure}</li>`;
+}
+
+// display the copy-constraint cycles
+const cyclesDiv = document.querySelector("#cycles");
+cycles.forEach((cycle, i) => {
+    const cells = cycle.map((cell) => `(row: ${cell.row}, col: ${cell.col})`).join(' -> ');
+    cyclesDiv.innerHTML += `<li>cycle ${i}: ${cells}</li>`;
+});
+
 // display constraints
 for (const [gate, cs] of Object.entries(constraints)) {
     const div = document.querySelector("#constraints");
     div.innerHTML += `<h2 class="text-3xl font-normal leading-normal mt-0 mb-2 text-grey-800">${gate}</h2>`;
+    if (cs.length == 0) {
+        div.innerHTML += `<p>no constraints of its own</p>`;
+    }
     cs.forEach((constraint, i) => {
         div.innerHTML += `<h3>constraint ${i}:</h3><ul>`;
         for (const eq of constraint) {
@@ -92,7 +126,8 @@ function parseWiring(row, wires) {
     wires.forEach((w, col) => {
         if (col != w.col || row != w.row) {
             permutation = true;
-            wiring += `<li>col ${col} -> (row: ${w.row}, col: ${w.col})</li>`;
+            const cycle = cellCycles[`${row},${col}`];
+            wiring += `<li>col ${col} -> (row: ${w.row}, col: ${w.col}) [cycle ${cycle}]</li>`;
         }
     });
 
@@ -103,11 +138,14 @@ function parseWiring(row, wires) {
     }
 }
 
-// bytearrays to hex
+// hexstrings or bytearrays to hex
 function toHexString(byteArray) {
     if (byteArray == 0) {
         return '0';
     }
+    if (typeof byteArray == "string") {
+        return byteArray;
+    }
     return Array.from(byteArray, function (byte) {
         return ('0' + (byte & 0xFF).toString(16)).slice(-2);
     }).join('');
@@ -145,6 +183,16 @@ function gateColor(gate) {
         return "bg-red-300";
     } else if (gate == "EndoMulScalar") {
         return "bg-orange-300";
+    } else if (gate.startsWith("Cairo")) {
+        return "bg-pink-300";
+    } else if (gate.startsWith("RangeCheck")) {
+        return "bg-teal-300";
+    } else if (gate.startsWith("ForeignField")) {
+        return "bg-indigo-300";
+    } else if (gate == "Xor16" || gate == "Rot64") {
+        return "bg-lime-300";
+    } else if (gate == "Lookup") {
+        return "bg-cyan-300";
     } else { // Zero
         return "bg-gray-300";
     }
@@ -1,27 +1,40 @@
 //! Implements a tool to visualize a circuit as an HTML page.
 
-use ark_ff::PrimeField;
+use ark_ff::{PrimeField, Zero};
 use kimchi::{
     circuits::{
         argument::Argument,
+        constraints::ConstraintSystem,
         expr,
+        gate::{CircuitGate, GateType},
+        polynomial::COLUMNS,
         polynomials::{
-            complete_add::CompleteAdd, endomul_scalar::EndomulScalar, endosclmul::EndosclMul,
-            poseidon::Poseidon, varbasemul::VarbaseMul,
+            complete_add::CompleteAdd,
+            endomul_scalar::EndomulScalar,
+            endosclmul::EndosclMul,
+            foreign_field_add::circuitgates::ForeignFieldAdd,
+            foreign_field_mul::circuitgates::ForeignFieldMul,
+            generic::Generic,
+            poseidon::Poseidon,
+            range_check::circuitgates::{RangeCheck0, RangeCheck1},
+            rot::Rot64,
+            turshi::{Claim, Flags, Instruction, Transition},
+            varbasemul::VarbaseMul,
+            xor::Xor16,
         },
+        wires::{Wire, PERMUTS},
     },
     curve::KimchiCurve,
-    prover_index::ProverIndex,
 };
-use poly_commitment::commitment::CommitmentCurve;
 use serde::Serialize;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::Display,
     fs::{self, File},
     io::Write,
     path::Path,
 };
+use strum::IntoEnumIterator;
 use tinytemplate::TinyTemplate;
 
 pub mod witness;
@@ -35,6 +48,18 @@ struct Context {
     data: String,
 }
 
+/// The circuit and its analysis, as given to the script of the page
+#[derive(Serialize)]
+#[serde(bound = "CircuitGate<F>: Serialize, Witness<F>: Serialize")]
+struct Data<'a, F: PrimeField> {
+    public: usize,
+    gates: &'a [CircuitGate<F>],
+    witness: Option<Witness<F>>,
+    failures: BTreeMap<usize, String>,
+    cycles: Vec<Vec<Wire>>,
+    constraints: HashMap<String, Vec<Vec<String>>>,
+}
+
 /// Allows us to quickly implement a LaTeX encoder for each gate
 trait LaTeX<F>: Argument<F>
 where
@@ -56,42 +81,136 @@ where
 {
 }
 
-///
-pub fn latex_constraints<G>() -> HashMap<&'static str, Vec<Vec<String>>>
+/// The constraints of a gate type in LaTeX.
+/// [`GateType::Zero`] and [`GateType::Lookup`] have no constraints of their own.
+pub fn gate_latex_constraints<F>(typ: GateType) -> Vec<Vec<String>>
+where
+    F: PrimeField + Display,
+{
+    match typ {
+        GateType::Zero | GateType::Lookup => vec![],
+        GateType::Generic => Generic::<F>::latex(),
+        GateType::Poseidon => Poseidon::<F>::latex(),
+        GateType::CompleteAdd => CompleteAdd::<F>::latex(),
+        GateType::VarBaseMul => VarbaseMul::<F>::latex(),
+        GateType::EndoMul => EndosclMul::<F>::latex(),
+        GateType::EndoMulScalar => EndomulScalar::<F>::latex(),
+        GateType::CairoClaim => Claim::<F>::latex(),
+        GateType::CairoInstruction => Instruction::<F>::latex(),
+        GateType::CairoFlags => Flags::<F>::latex(),
+        GateType::CairoTransition => Transition::<F>::latex(),
+        GateType::RangeCheck0 => RangeCheck0::<F>::latex(),
+        GateType::RangeCheck1 => RangeCheck1::<F>::latex(),
+        GateType::ForeignFieldAdd => ForeignFieldAdd::<F>::latex(),
+        GateType::ForeignFieldMul => ForeignFieldMul::<F>::latex(),
+        GateType::Xor16 => Xor16::<F>::latex(),
+        GateType::Rot64 => Rot64::<F>::latex(),
+    }
+}
+
+/// The constraints of every gate type in LaTeX, by gate type
+pub fn latex_constraints<G>() -> HashMap<String, Vec<Vec<String>>>
 where
-    G: CommitmentCurve,
+    G: KimchiCurve,
 {
-    let mut map = HashMap::new();
-    map.insert("Poseidon", Poseidon::<G::ScalarField>::latex());
-    map.insert("CompleteAdd", CompleteAdd::<G::ScalarField>::latex());
-    map.insert("VarBaseMul", VarbaseMul::<G::ScalarField>::latex());
-    map.insert("EndoMul", EndosclMul::<G::ScalarField>::latex());
-    map.insert("EndoMulScalar", EndomulScalar::<G::ScalarField>::latex());
-    map
+    GateType::iter()
+        .map(|typ| {
+            (
+                format!("{typ:?}"),
+                gate_latex_constraints::<G::ScalarField>(typ),
+            )
+        })
+        .collect()
+}
+
+/// The cycles of the permutation of the circuit, made of the cells that are constrained to be equal.
+/// Cells that are only wired to themselves are not part of any cycle.
+pub fn copy_cycles<F: PrimeField>(gates: &[CircuitGate<F>]) -> Vec<Vec<Wire>> {
+    let mut visited = HashSet::new();
+    let mut cycles = vec![];
+
+    for row in 0..gates.len() {
+        for col in 0..PERMUTS {
+            let mut cell = Wire { row, col };
+            let mut cycle = vec![];
+            // wires that are out of the circuit end the cycle
+            while cell.row < gates.len()
+                && cell.col < PERMUTS
+                && visited.insert((cell.row, cell.col))
+            {
+                cycle.push(cell);
+                cell = gates[cell.row].wires[cell.col];
+            }
+            if cycle.len() > 1 {
+                cycles.push(cycle);
+            }
+        }
+    }
+
+    cycles
 }
 
-/// Produces a `circuit.html` in the current folder.
+/// The rows of the circuit whose constraints (including the copy constraints) fail on the witness,
+/// with the reason of the failure.
+/// The public input is read from the witness.
+pub fn failing_rows<G: KimchiCurve>(
+    cs: &ConstraintSystem<G::ScalarField>,
+    witness: &[Vec<G::ScalarField>; COLUMNS],
+) -> BTreeMap<usize, String> {
+    // pad the witness, as some gates read the next row
+    let witness: [Vec<_>; COLUMNS] = std::array::from_fn(|col| {
+        let mut column = witness[col].clone();
+        column.resize(cs.gates.len() + 1, G::ScalarField::zero());
+        column
+    });
+    let public = &witness[0][..cs.public];
+
+    cs.gates
+        .iter()
+        .enumerate()
+        .filter_map(|(row, gate)| {
+            let result = match gate.typ {
+                GateType::Generic => gate
+                    .verify_witness::<G>(row, &witness, cs, public)
+                    .map_err(|e| e.to_string())
+                    .and_then(|_| gate.verify_generic(row, &witness, public)),
+                _ => gate
+                    .verify_witness::<G>(row, &witness, cs, public)
+                    .map_err(|e| e.to_string()),
+            };
+            result.err().map(|err| (row, err))
+        })
+        .collect()
+}
+
+/// Produces an HTML page at `output`, which shows the circuit of `cs`, the LaTeX of the constraints of its gates,
+/// and the cycles of its permutation.
+/// With a witness, the page also shows the witness, and highlights the rows whose constraints fail.
 ///
 /// # Panics
 ///
 /// Will panic if `TinyTemplate::render()` returns `Error` or `std::fs::File::create()` returns `Error`.
-pub fn visu<G: KimchiCurve>(index: &ProverIndex<G>, witness: Option<Witness<G::ScalarField>>) {
-    // serialize index
-    let index = serde_json::to_string(index).expect("couldn't serialize index");
-    let mut data = format!("const index = {index};");
-
-    // serialize witness
-    if let Some(witness) = witness {
-        let witness = serde_json::to_string(&witness).expect("couldn't serialize witness");
-        data = format!("{data}const witness = {witness};");
-    } else {
-        data.push_str("const witness = null;");
-    }
+pub fn visu<G: KimchiCurve>(
+    cs: &ConstraintSystem<G::ScalarField>,
+    witness: Option<Witness<G::ScalarField>>,
+    output: &Path,
+) {
+    let failures = witness
+        .as_ref()
+        .map(|witness| failing_rows::<G>(cs, witness.as_ref()))
+        .unwrap_or_default();
 
-    // serialize constraints
-    let constraints = latex_constraints::<G>();
-    let constraints = serde_json::to_string(&constraints).expect("couldn't serialize constraints");
-    data = format!("{data}const constraints = {constraints};");
+    // serialize the circuit and its analysis
+    let data = Data {
+        public: cs.public,
+        gates: &cs.gates,
+        witness,
+        failures,
+        cycles: copy_cycles(&cs.gates),
+        constraints: latex_constraints::<G>(),
+    };
+    let data = serde_json::to_string(&data).expect("couldn't serialize the circuit");
+    let data = format!("const data = {data};");
 
     // create template
     let template_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/assets/template.html");
@@ -108,10 +227,6 @@ pub fn visu<G: KimchiCurve>(index: &ProverIndex<G>, witness: Option<Witness<G::S
         .expect("could not create template");
 
     // render
-    let html_output = std::env::current_dir()
-        .expect("no current directory?")
-        .join("circuit.html");
-
     let js_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/assets/script.js");
     let js = fs::read_to_string(&js_path)
         .unwrap_or_else(|e| format!("could not read js file {}: {e}", js_path.display()));
@@ -122,6 +237,68 @@ pub fn visu<G: KimchiCurve>(index: &ProverIndex<G>, witness: Option<Witness<G::S
         .render("circuit", &context)
         .unwrap_or_else(|e| panic!("template file can't be rendered: {e}"));
 
-    let mut file = File::create(html_output).unwrap_or_else(|e| panic!("{e}"));
+    let mut file = File::create(output).unwrap_or_else(|e| panic!("{e}"));
     write!(&mut file, "{rendered}").expect("couldn't write the file on disk");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kimchi::circuits::{gate::Connect, polynomials::generic::GenericGateSpec};
+    use mina_curves::pasta::{Fp, Vesta};
+
+    #[test]
+    fn test_latex_constraints_of_every_gate() {
+        let constraints = latex_constraints::<Vesta>();
+        for typ in GateType::iter() {
+            let latex = &constraints[&format!("{typ:?}")];
+            assert_eq!(
+                latex.is_empty(),
+                matches!(typ, GateType::Zero | GateType::Lookup),
+                "{typ:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_failing_rows_and_copy_cycles() {
+        // a public input, copied into a gate that doubles it
+        let mut gates = vec![
+            CircuitGate::<Fp>::create_generic_gadget(Wire::for_row(0), GenericGateSpec::Pub, None),
+            CircuitGate::<Fp>::create_generic_gadget(
+                Wire::for_row(1),
+                GenericGateSpec::Add {
+                    left_coeff: None,
+                    right_coeff: None,
+                    output_coeff: None,
+                },
+                None,
+            ),
+        ];
+        gates.connect_cell_pair((0, 0), (1, 0));
+        gates.connect_cell_pair((1, 0), (1, 1));
+
+        let cycles = copy_cycles(&gates);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].len(), 3);
+
+        let cs = ConstraintSystem::create(gates).public(1).build().unwrap();
+        let x = Fp::from(3u8);
+        let mut witness: [Vec<Fp>; COLUMNS] = std::array::from_fn(|_| vec![Fp::zero(); 2]);
+        witness[0] = vec![x, x];
+        witness[1][1] = x;
+        witness[2][1] = x + x;
+        assert!(failing_rows::<Vesta>(&cs, &witness).is_empty());
+
+        // a wrong output fails the constraint of the second row
+        witness[2][1] = x;
+        let failures = failing_rows::<Vesta>(&cs, &witness);
+        assert_eq!(failures.keys().collect::<Vec<_>>(), vec![&1]);
+
+        // a wrong copy fails a copy constraint of the second row
+        witness[1][1] = x + x;
+        witness[2][1] = x + x + x;
+        let failures = failing_rows::<Vesta>(&cs, &witness);
+        assert!(failures[&1].contains("copy constraint"));
+    }
+}
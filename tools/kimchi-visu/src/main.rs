@@ -1,71 +1,85 @@
 use kimchi::{
-    circuits::{
-        gate::CircuitGate,
-        polynomials::{generic::GenericGateSpec, poseidon::generate_witness},
-        wires::Wire,
-    },
-    curve::KimchiCurve,
-    prover_index::testing::new_index_for_test,
+    circuits::{constraints::ConstraintSystem, gate::CircuitGate},
+    snarky::asm::parse_asm,
 };
 use kimchi_visu::{visu, Witness};
-use mina_curves::pasta::{Fp, Vesta};
+use mina_curves::pasta::{Fp, Pallas, Vesta};
+use poly_commitment::srs::endos;
+use serde::Deserialize;
+use std::{fs, path::Path};
 
-fn main() {
-    let public = 3;
-    let poseidon_params = Vesta::sponge_params();
+const USAGE: &str = "usage: kimchi-visu <circuit> [witness] [--output <file>]
 
-    // create circuit
-    let (gates, row) = {
-        let mut gates = vec![];
+Reads a circuit in JSON (or in asm if the file ends in .asm), and optionally its witness
+in JSON (the list of its columns), and writes an HTML page showing them (circuit.html by default).
+Rows whose constraints fail on the witness are highlighted.";
 
-        // public input
-        let row = {
-            for i in 0..public {
-                let g = CircuitGate::<Fp>::create_generic_gadget(
-                    Wire::for_row(i),
-                    GenericGateSpec::Pub,
-                    None,
-                );
-                gates.push(g);
-            }
-            public
-        };
+/// A circuit in the JSON format of `kimchi-asm`
+#[derive(Deserialize)]
+struct DeserializableCircuit {
+    public_input_size: usize,
+    gates: Vec<CircuitGate<Fp>>,
+}
 
-        // poseidon
-        let row = {
-            let round_constants = &poseidon_params.round_constants;
-            let (g, row) = CircuitGate::<Fp>::create_poseidon_gadget(
-                row,
-                [Wire::for_row(row), Wire::for_row(row + 11)],
-                round_constants,
-            );
-            gates.extend(g);
-            row
-        };
+fn read_circuit(path: &Path) -> Result<DeserializableCircuit, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {e}", path.display()))?;
+    if path.extension().map_or(false, |ext| ext == "asm") {
+        let (public_input_size, gates) =
+            parse_asm(&content).map_err(|e| format!("couldn't parse {}: {e}", path.display()))?;
+        Ok(DeserializableCircuit {
+            public_input_size,
+            gates,
+        })
+    } else {
+        serde_json::from_str(&content)
+            .map_err(|e| format!("couldn't deserialize {}: {e}", path.display()))
+    }
+}
 
-        // public input is output of poseidon
-        {
-            gates[0].wires[0] = Wire { row, col: 0 };
-            gates[1].wires[0] = Wire { row, col: 1 };
-            gates[2].wires[0] = Wire { row, col: 2 };
+fn read_witness(path: &Path) -> Result<Witness<Fp>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {e}", path.display()))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("couldn't deserialize {}: {e}", path.display()))
+}
 
-            let poseidon_output = &mut gates[row].wires;
-            poseidon_output[0] = Wire { row: 0, col: 0 };
-            poseidon_output[1] = Wire { row: 0, col: 1 };
-            poseidon_output[2] = Wire { row: 0, col: 2 };
+fn run() -> Result<(), String> {
+    let mut args = std::env::args().skip(1);
+    let mut paths = vec![];
+    let mut output = String::from("circuit.html");
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" | "-o" => output = args.next().ok_or(USAGE)?,
+            _ if arg.starts_with('-') => return Err(USAGE.to_string()),
+            _ => paths.push(arg),
         }
+    }
 
-        (gates, row)
+    let (circuit, witness) = match paths.as_slice() {
+        [circuit] => (read_circuit(Path::new(circuit))?, None),
+        [circuit, witness] => (
+            read_circuit(Path::new(circuit))?,
+            Some(read_witness(Path::new(witness))?),
+        ),
+        _ => return Err(USAGE.to_string()),
     };
 
-    // create the index
-    let index = new_index_for_test::<Vesta>(gates, public);
+    let mut cs = ConstraintSystem::create(circuit.gates)
+        .public(circuit.public_input_size)
+        .build()
+        .map_err(|e| format!("invalid circuit: {e}"))?;
+    // the endoscalar coefficient of the prover index, used by the EndoMul gates
+    cs.endo = endos::<Pallas>().0;
 
-    // create the witness
-    let mut witness = Witness::new(row + 1).inner();
-    let input = [1u32.into(), 2u32.into(), 3u32.into()];
-    generate_witness(3, poseidon_params, &mut witness, input);
+    visu::<Vesta>(&cs, witness, Path::new(&output));
+    println!("wrote {output}");
+    Ok(())
+}
 
-    // create the HTML
-    visu(&index, Some(witness.into()));
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
 }
@@ -1,14 +1,16 @@
 use ark_ff::Field;
 use kimchi::circuits::polynomial::COLUMNS;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use std::array;
 
 /// The type that represents the execution trace.
 /// It represents a table of [COLUMNS] columns, with `n` rows.
 /// `n` being the maximum size of the circuit, and the size of the domain.
+/// It is serialized column by column.
 #[serde_as]
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct Witness<F>
 where
     F: Field,
@@ -44,3 +46,12 @@ where
         Witness { inner }
     }
 }
+
+impl<F> AsRef<[Vec<F>; COLUMNS]> for Witness<F>
+where
+    F: Field,
+{
+    fn as_ref(&self) -> &[Vec<F>; COLUMNS] {
+        &self.inner
+    }
+}
@@ -0,0 +1,57 @@
+//! Cairo programs shared by the tests of the crate
+
+use crate::memory::CairoMemory;
+use mina_curves::pasta::Fp as F;
+
+/// The bytecode of a program involving builtins, imports and outputs,
+/// generated with the playground at https://www.cairo-lang.org/playground/:
+///
+/// ```text
+/// %builtins output
+/// from starkware.cairo.common.serialize import serialize_word
+/// func main{output_ptr : felt*}():
+///     tempvar x = 10
+///     tempvar y = x + x
+///     tempvar z = y * y + x
+///     serialize_word(x)
+///     serialize_word(y)
+///     serialize_word(z)
+///     return ()
+/// end
+/// ```
+pub(crate) const OUTPUT_PROGRAM: [i64; 20] = [
+    0x400380007ffc7ffd,
+    0x482680017ffc8000,
+    1,
+    0x208b7fff7fff7ffe,
+    0x480680017fff8000,
+    10,
+    0x48307fff7fff8000,
+    0x48507fff7fff8000,
+    0x48307ffd7fff8000,
+    0x480a7ffd7fff8000,
+    0x48127ffb7fff8000,
+    0x1104800180018000,
+    -11,
+    0x48127ff87fff8000,
+    0x1104800180018000,
+    -14,
+    0x48127ff67fff8000,
+    0x1104800180018000,
+    -17,
+    0x208b7fff7fff7ffe,
+];
+
+/// The bytecode of [`OUTPUT_PROGRAM`] as field elements
+pub(crate) fn output_program() -> Vec<F> {
+    OUTPUT_PROGRAM.iter().map(|&word| F::from(word)).collect()
+}
+
+/// The memory of [`OUTPUT_PROGRAM`], followed by the input of its `main` function at pc 5
+pub(crate) fn output_program_memory() -> CairoMemory<F> {
+    let mut mem = CairoMemory::new(output_program());
+    mem.write(F::from(21u32), F::from(41u32)); // beginning of outputs
+    mem.write(F::from(22u32), F::from(44u32)); // end of outputs
+    mem.write(F::from(23u32), F::from(44u32)); // end of program
+    mem
+}
@@ -4,14 +4,20 @@
 //! and obtain a memory instantiation after the execution. It uses some code to
 //! represent Cairo instructions and their decomposition, together with their logic
 //! which is represented as steps of computation making up the full program.
+#[cfg(test)]
+mod fixtures;
 pub mod flags;
 pub mod helper;
+pub mod loader;
 pub mod memory;
+pub mod relocated;
 pub mod runner;
 pub mod word;
 
 pub use self::{
+    loader::{CompiledProgram, LoadedProgram, LoaderError},
     memory::CairoMemory,
-    runner::{CairoInstruction, CairoProgram, Pointers},
+    relocated::{read_memory, read_trace, write_memory, write_trace, TraceEntry},
+    runner::{CairoError, CairoInstruction, CairoProgram, Pointers},
     word::{FlagBits, Offsets},
 };
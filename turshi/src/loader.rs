@@ -0,0 +1,345 @@
+//! This module loads compiled Cairo programs, as output by `cairo-compile` in JSON,
+//! into the Cairo memory. The program occupies the first entries of the memory, followed
+//! by the initial stack of its `main` function, as in the relocated memory of `cairo-run`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::helper::CairoFieldHelpers;
+use crate::memory::CairoMemory;
+use crate::runner::{CairoError, CairoProgram, CairoState, Pointers};
+use ark_ff::PrimeField;
+use num_bigint::BigUint;
+use o1_utils::FieldHelpers;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// The prime of the field of Cairo programs, `2^251 + 17 * 2^192 + 1`
+pub const CAIRO_PRIME: &str = "0x800000000000011000000000000000000000000000000000000000000000001";
+
+/// Errors that can arise when loading a compiled Cairo program
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LoaderError {
+    /// The file could not be read
+    #[error("could not read the program: {0}")]
+    Io(String),
+    /// The file is not a compiled program in JSON
+    #[error("invalid JSON: {0}")]
+    Json(String),
+    /// The program is compiled for a field other than the one of Cairo
+    #[error("unsupported prime {0}, expected {}", CAIRO_PRIME)]
+    UnsupportedPrime(String),
+    /// A word of the program is not a field element
+    #[error("invalid word {word} at offset {offset} of the program")]
+    InvalidWord {
+        /// the offset of the word in the program
+        offset: usize,
+        /// the word, as in the JSON
+        word: String,
+    },
+    /// The program has no entry point of this name
+    #[error("no function {0} in the program")]
+    MissingEntryPoint(String),
+    /// The initial stack doesn't have a pointer for each builtin
+    #[error("expected {expected} builtin pointers, got {got}")]
+    BuiltinPointers {
+        /// the number of builtins of the program
+        expected: usize,
+        /// the number of pointers given
+        got: usize,
+    },
+}
+
+/// An identifier of a compiled program
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Identifier {
+    /// The kind of identifier (`function`, `label`, `const`, `struct`...)
+    #[serde(rename = "type")]
+    pub typ: String,
+    /// The offset in the program of functions and labels
+    pub pc: Option<u64>,
+}
+
+/// The fields of the JSON output by `cairo-compile` that the loader uses
+#[derive(Deserialize)]
+struct CompiledJson {
+    prime: String,
+    data: Vec<String>,
+    #[serde(default)]
+    builtins: Vec<String>,
+    #[serde(default)]
+    identifiers: BTreeMap<String, Identifier>,
+    #[serde(default = "default_main_scope")]
+    main_scope: String,
+}
+
+fn default_main_scope() -> String {
+    "__main__".to_string()
+}
+
+/// Parses a hexadecimal (or decimal) number of the JSON
+fn parse_number(number: &str) -> Option<BigUint> {
+    match number.strip_prefix("0x") {
+        Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+        None => BigUint::parse_bytes(number.as_bytes(), 10),
+    }
+}
+
+/// Returns the Cairo prime
+pub(crate) fn cairo_prime() -> BigUint {
+    parse_number(CAIRO_PRIME).expect("invalid Cairo prime")
+}
+
+/// Maps an element of the Cairo field to `F`, where the ones that are larger than
+/// half the Cairo prime are negative numbers (such as the offsets of backward jumps)
+pub(crate) fn felt_to_field<F: PrimeField>(felt: &BigUint) -> Option<F> {
+    let prime = cairo_prime();
+    if *felt <= &prime >> 1 {
+        F::from_biguint(felt).ok()
+    } else if *felt < prime {
+        F::from_biguint(&(prime - felt)).ok().map(|elem: F| -elem)
+    } else {
+        None
+    }
+}
+
+/// Maps an element of `F` back to the Cairo field, if it is the image of one by [felt_to_field]
+pub(crate) fn field_to_felt<F: PrimeField>(elem: F) -> Option<BigUint> {
+    let half = cairo_prime() >> 1;
+    let (positive, negative) = (elem.to_biguint(), (-elem).to_biguint());
+    if positive <= half {
+        Some(positive)
+    } else if negative <= half {
+        Some(cairo_prime() - negative)
+    } else {
+        None
+    }
+}
+
+/// A compiled Cairo program
+#[derive(Debug, Clone)]
+pub struct CompiledProgram<F> {
+    /// the words of the program
+    data: Vec<F>,
+    /// the builtins used by the program, in the order of the arguments of `main`
+    builtins: Vec<String>,
+    /// the identifiers of the program, by their full name
+    identifiers: BTreeMap<String, Identifier>,
+    /// the scope of the main module
+    main_scope: String,
+}
+
+/// A compiled Cairo program loaded in memory, ready to be executed
+pub struct LoadedProgram<F> {
+    /// the memory, with the program and the initial stack
+    pub memory: CairoMemory<F>,
+    /// the initial pointers of the execution
+    pub ini: CairoState<F>,
+    /// the addresses of the public memory: the program and the initial stack
+    pub public_memory: Range<u64>,
+}
+
+impl<F: PrimeField> CompiledProgram<F> {
+    /// Parses a program compiled by `cairo-compile`.
+    /// Words that are larger than half the Cairo prime are negative numbers
+    /// (such as the offsets of backward jumps), and are mapped to their negation in `F`.
+    pub fn from_json(json: &str) -> Result<Self, LoaderError> {
+        let compiled: CompiledJson =
+            serde_json::from_str(json).map_err(|e| LoaderError::Json(e.to_string()))?;
+
+        if parse_number(&compiled.prime) != Some(cairo_prime()) {
+            return Err(LoaderError::UnsupportedPrime(compiled.prime));
+        }
+
+        let data = compiled
+            .data
+            .into_iter()
+            .enumerate()
+            .map(|(offset, word)| {
+                parse_number(&word)
+                    .and_then(|felt| felt_to_field(&felt))
+                    .ok_or(LoaderError::InvalidWord { offset, word })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            data,
+            builtins: compiled.builtins,
+            identifiers: compiled.identifiers,
+            main_scope: compiled.main_scope,
+        })
+    }
+
+    /// Reads a program compiled by `cairo-compile` from a file
+    pub fn from_file(path: &Path) -> Result<Self, LoaderError> {
+        let json = fs::read_to_string(path).map_err(|e| LoaderError::Io(e.to_string()))?;
+        Self::from_json(&json)
+    }
+
+    /// Returns the words of the program
+    pub fn data(&self) -> &[F] {
+        &self.data
+    }
+
+    /// Returns the builtins used by the program, in the order of the arguments of `main`
+    pub fn builtins(&self) -> &[String] {
+        &self.builtins
+    }
+
+    /// Returns the identifiers of the program, by their full name
+    pub fn identifiers(&self) -> &BTreeMap<String, Identifier> {
+        &self.identifiers
+    }
+
+    /// Returns the offset in the program of a function or label,
+    /// given by its full name or by its name in the main scope
+    pub fn offset_of(&self, name: &str) -> Option<u64> {
+        self.identifiers
+            .get(&format!("{}.{name}", self.main_scope))
+            .or_else(|| self.identifiers.get(name))
+            .and_then(|identifier| identifier.pc)
+    }
+
+    /// Loads the program in memory (from address 1), followed by the initial stack of `main`:
+    /// the pointers to the segments of its builtins, then `end` twice, as the frame pointer
+    /// and the program counter that `main` returns to.
+    /// As in the relocated memory of `cairo-run`, the builtin segments and `end` follow
+    /// the memory used by the execution: the runner stops when `main` returns to `end`,
+    /// which must not be below the final allocation pointer.
+    pub fn load(&self, builtin_pointers: &[F], end: F) -> Result<LoadedProgram<F>, LoaderError> {
+        if builtin_pointers.len() != self.builtins.len() {
+            return Err(LoaderError::BuiltinPointers {
+                expected: self.builtins.len(),
+                got: builtin_pointers.len(),
+            });
+        }
+        let main = self
+            .offset_of("main")
+            .ok_or_else(|| LoaderError::MissingEntryPoint(format!("{}.main", self.main_scope)))?;
+
+        let mut memory = CairoMemory::new(self.data.clone());
+        for elem in builtin_pointers.iter().chain([&end, &end]) {
+            memory.write(F::from(memory.len()), *elem);
+        }
+
+        // the stack starts right after the program and its arguments
+        let ap = F::from(memory.len());
+        Ok(LoadedProgram {
+            public_memory: 1..memory.len(),
+            memory,
+            ini: CairoState::new(F::from(1 + main), ap, ap),
+        })
+    }
+}
+
+impl<F: PrimeField> LoadedProgram<F> {
+    /// Executes the program from its initial pointers
+    pub fn execute(&mut self) -> CairoProgram<F> {
+        CairoProgram::new(&mut self.memory, self.ini.pc().to_u64())
+    }
+
+    /// Executes the program from its initial pointers, for at most `max_steps` steps
+    pub fn try_execute(&mut self, max_steps: u64) -> Result<CairoProgram<F>, CairoError> {
+        CairoProgram::try_new(&mut self.memory, self.ini.pc().to_u64(), max_steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::output_program;
+    use mina_curves::pasta::Fp as F;
+
+    // The program of `fixtures::OUTPUT_PROGRAM`, as compiled by `cairo-compile`
+    fn output_program_json() -> String {
+        let data: Vec<_> = output_program()
+            .into_iter()
+            .map(|word| format!("\"{:#x}\"", field_to_felt(word).unwrap()))
+            .collect();
+        r#"{
+        "attributes": [],
+        "builtins": ["output"],
+        "data": [DATA],
+        "hints": {},
+        "identifiers": {
+            "__main__.main": { "decorators": [], "pc": 4, "type": "function" },
+            "__main__.serialize_word": {
+                "destination": "starkware.cairo.common.serialize.serialize_word",
+                "type": "alias"
+            },
+            "starkware.cairo.common.serialize.serialize_word": {
+                "decorators": [], "pc": 0, "type": "function"
+            }
+        },
+        "main_scope": "__main__",
+        "prime": "0x800000000000011000000000000000000000000000000000000000000000001",
+        "reference_manager": { "references": [] }
+    }"#
+        .replace("DATA", &data.join(", "))
+    }
+
+    #[test]
+    fn test_load_compiled_program() {
+        let program = CompiledProgram::<F>::from_json(&output_program_json()).unwrap();
+        assert_eq!(program.data(), output_program());
+        assert_eq!(program.data()[12], -F::from(11u32));
+        assert_eq!(program.builtins(), ["output".to_string()]);
+        assert_eq!(program.offset_of("main"), Some(4));
+        assert_eq!(program.offset_of("serialize_word"), None);
+        assert_eq!(
+            program.offset_of("starkware.cairo.common.serialize.serialize_word"),
+            Some(0)
+        );
+
+        // the output segment starts at 41, and the program ends at 44
+        let mut loaded = program.load(&[F::from(41u32)], F::from(44u32)).unwrap();
+        assert_eq!(loaded.public_memory, 1..24);
+        assert_eq!(loaded.ini.pc(), F::from(5u32));
+        assert_eq!(loaded.ini.ap(), F::from(24u32));
+        assert_eq!(loaded.ini.fp(), F::from(24u32));
+
+        let prog = loaded.try_execute(1000).unwrap();
+        assert_eq!(prog.fin().pc(), F::from(20u32));
+        assert_eq!(prog.fin().ap(), F::from(41u32));
+        assert_eq!(prog.fin().fp(), F::from(24u32));
+        assert_eq!(loaded.memory.read(F::from(43u32)).unwrap(), F::from(410u32));
+
+        assert_eq!(
+            program.load(&[], F::from(44u32)).err(),
+            Some(LoaderError::BuiltinPointers {
+                expected: 1,
+                got: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_load_invalid_programs() {
+        let other_prime = output_program_json().replace(
+            "0x800000000000011000000000000000000000000000000000000000000000001",
+            "0x40000000000000000000000000000000224698fc094cf91b992d30ed00000001",
+        );
+        assert!(matches!(
+            CompiledProgram::<F>::from_json(&other_prime),
+            Err(LoaderError::UnsupportedPrime(_))
+        ));
+
+        let invalid_word = output_program_json().replace("\"0xa\"", "\"ten\"");
+        assert_eq!(
+            CompiledProgram::<F>::from_json(&invalid_word).err(),
+            Some(LoaderError::InvalidWord {
+                offset: 5,
+                word: "ten".to_string()
+            })
+        );
+
+        let no_main = output_program_json().replace("__main__.main", "__main__.start");
+        let program = CompiledProgram::<F>::from_json(&no_main).unwrap();
+        assert_eq!(
+            program.load(&[F::from(41u32)], F::from(44u32)).err(),
+            Some(LoaderError::MissingEntryPoint("__main__.main".to_string()))
+        );
+    }
+}
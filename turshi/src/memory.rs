@@ -93,6 +93,18 @@ impl<F: Field> CairoMemory<F> {
         self[addr] = Some(CairoWord::new(elem));
     }
 
+    /// Write element in memory address, unless it already contains another element,
+    /// which is returned as an error
+    pub fn try_write(&mut self, addr: F, elem: F) -> std::result::Result<(), F> {
+        match self.read(addr) {
+            Some(prev) if prev != elem => Err(prev),
+            _ => {
+                self.write(addr, elem);
+                Ok(())
+            }
+        }
+    }
+
     /// Read element in memory address
     pub fn read(&mut self, addr: F) -> Option<F> {
         self.resize(addr.to_u64()); // Resize if necessary
@@ -134,5 +146,15 @@ mod tests {
         // Check we have 6 words, excluding the dummy entry
         assert_eq!(6, memory.len() - 1);
         memory.read(F::from(10u32));
+        // Check that an address can only be overwritten with the same element
+        assert_eq!(
+            memory.try_write(F::one(), F::from(0x480680017fff8000u64)),
+            Ok(())
+        );
+        assert_eq!(
+            memory.try_write(F::one(), F::from(7u64)),
+            Err(F::from(0x480680017fff8000u64))
+        );
+        assert_eq!(memory.try_write(F::from(8u64), F::from(7u64)), Ok(()));
     }
 }
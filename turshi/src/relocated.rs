@@ -0,0 +1,205 @@
+//! This module reads and writes the trace and the memory of an execution in the binary
+//! formats of the relocated trace and memory files output by `cairo-run`.
+//! A trace file is a sequence of entries made of the allocation pointer, the frame pointer
+//! and the program counter before each step, each in 8 little-endian bytes.
+//! A memory file is a sequence of addresses in 8 little-endian bytes, each followed by
+//! its content as an element of the Cairo field in 32 little-endian bytes.
+
+use std::io::{self, Read, Write};
+
+use crate::loader::{felt_to_field, field_to_felt};
+use crate::memory::CairoMemory;
+use ark_ff::PrimeField;
+use num_bigint::BigUint;
+
+/// Size in bytes of an entry of a trace file
+const TRACE_ENTRY_BYTES: usize = 24;
+
+/// Size in bytes of an element of the Cairo field in a memory file
+const FELT_BYTES: usize = 32;
+
+/// Size in bytes of a cell of a memory file
+const MEMORY_CELL_BYTES: usize = 8 + FELT_BYTES;
+
+/// The pointers before a step of a relocated trace
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// Program counter
+    pub pc: u64,
+    /// Allocation pointer
+    pub ap: u64,
+    /// Frame pointer
+    pub fp: u64,
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes[..8].try_into().expect("8 bytes"))
+}
+
+/// Writes a trace in the format of the relocated trace files of `cairo-run`
+pub fn write_trace<W: Write>(mut writer: W, trace: &[TraceEntry]) -> io::Result<()> {
+    for entry in trace {
+        for word in [entry.ap, entry.fp, entry.pc] {
+            writer.write_all(&word.to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads a trace in the format of the relocated trace files of `cairo-run`
+pub fn read_trace<R: Read>(mut reader: R) -> io::Result<Vec<TraceEntry>> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+    if bytes.len() % TRACE_ENTRY_BYTES != 0 {
+        return Err(invalid_data(format!(
+            "the trace has {} bytes, which is not a multiple of {TRACE_ENTRY_BYTES}",
+            bytes.len()
+        )));
+    }
+    Ok(bytes
+        .chunks_exact(TRACE_ENTRY_BYTES)
+        .map(|entry| TraceEntry {
+            ap: read_u64(&entry[0..]),
+            fp: read_u64(&entry[8..]),
+            pc: read_u64(&entry[16..]),
+        })
+        .collect())
+}
+
+/// Writes the initialized cells of a memory in the format of the relocated memory files
+/// of `cairo-run`, by increasing address
+/// Fails if a cell does not contain the image of an element of the Cairo field
+/// (see [CompiledProgram::from_json](crate::loader::CompiledProgram::from_json))
+pub fn write_memory<F: PrimeField, W: Write>(
+    mut writer: W,
+    memory: &CairoMemory<F>,
+) -> io::Result<()> {
+    // the 0th entry of the memory is a dummy one
+    for addr in 1..memory.len() {
+        if let Some(word) = memory[F::from(addr)] {
+            let felt = field_to_felt(word.word()).ok_or_else(|| {
+                invalid_data(format!(
+                    "the content of address {addr} is not an element of the Cairo field"
+                ))
+            })?;
+            let mut bytes = felt.to_bytes_le();
+            bytes.resize(FELT_BYTES, 0);
+            writer.write_all(&addr.to_le_bytes())?;
+            writer.write_all(&bytes)?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads a memory in the format of the relocated memory files of `cairo-run`
+pub fn read_memory<F: PrimeField, R: Read>(mut reader: R) -> io::Result<CairoMemory<F>> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+    if bytes.len() % MEMORY_CELL_BYTES != 0 {
+        return Err(invalid_data(format!(
+            "the memory has {} bytes, which is not a multiple of {MEMORY_CELL_BYTES}",
+            bytes.len()
+        )));
+    }
+
+    let mut memory = CairoMemory::new(vec![]);
+    for cell in bytes.chunks_exact(MEMORY_CELL_BYTES) {
+        let addr = read_u64(cell);
+        if addr == 0 {
+            return Err(invalid_data(
+                "address 0 is not a relocated address".to_string(),
+            ));
+        }
+        let felt = BigUint::from_bytes_le(&cell[8..]);
+        let elem = felt_to_field(&felt).ok_or_else(|| {
+            invalid_data(format!(
+                "the content of address {addr} is not an element of the Cairo field"
+            ))
+        })?;
+        memory
+            .try_write(F::from(addr), elem)
+            .map_err(|_| invalid_data(format!("address {addr} has two different contents")))?;
+    }
+    Ok(memory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::output_program_memory,
+        runner::{CairoError, CairoProgram, Pointers},
+    };
+    use mina_curves::pasta::Fp as F;
+
+    #[test]
+    fn test_relocated_trace_and_memory() {
+        let mut mem = output_program_memory();
+        let prog = CairoProgram::new(&mut mem, 5);
+        let instructions = prog.trace().clone();
+        let fin = prog.fin();
+        let trace = prog.relocated_trace();
+
+        // export the trace and the memory
+        let mut trace_file = vec![];
+        write_trace(&mut trace_file, &trace).unwrap();
+        assert_eq!(trace_file.len(), TRACE_ENTRY_BYTES * trace.len());
+        // the first entry is ap = fp = 24, pc = 5
+        assert_eq!(read_u64(&trace_file[0..]), 24);
+        assert_eq!(read_u64(&trace_file[8..]), 24);
+        assert_eq!(read_u64(&trace_file[16..]), 5);
+
+        let mut memory_file = vec![];
+        write_memory(&mut memory_file, &mem).unwrap();
+        // a cell for each initialized address
+        let cells = (1..mem.len())
+            .filter(|&addr| mem[F::from(addr)].is_some())
+            .count();
+        assert_eq!(memory_file.len(), MEMORY_CELL_BYTES * cells);
+        // -11 at address 13 is written modulo the Cairo prime
+        let cell = &memory_file[12 * MEMORY_CELL_BYTES..13 * MEMORY_CELL_BYTES];
+        assert_eq!(read_u64(cell), 13);
+        assert_eq!(
+            BigUint::from_bytes_le(&cell[8..]),
+            crate::loader::cairo_prime() - BigUint::from(11u32)
+        );
+
+        // import them back, and replay the trace
+        let read = read_trace(&trace_file[..]).unwrap();
+        assert_eq!(read, trace);
+        let mut read_mem = read_memory::<F, _>(&memory_file[..]).unwrap();
+        for addr in 1..mem.len() {
+            assert_eq!(read_mem.read(F::from(addr)), mem.read(F::from(addr)));
+        }
+        let replayed = CairoProgram::from_trace(&mut read_mem, &read).unwrap();
+        assert_eq!(replayed.trace().len(), instructions.len());
+        for (replayed, original) in replayed.trace().iter().zip(&instructions) {
+            assert_eq!(replayed.pc(), original.pc());
+            assert_eq!(replayed.instr(), original.instr());
+            assert_eq!(replayed.res(), original.res());
+            assert_eq!(replayed.dst(), original.dst());
+            assert_eq!(replayed.op0(), original.op0());
+            assert_eq!(replayed.op1(), original.op1());
+        }
+        assert_eq!(replayed.ini().pc(), F::from(5u32));
+        assert_eq!(replayed.fin().pc(), fin.pc());
+        assert_eq!(replayed.fin().ap(), fin.ap());
+        assert_eq!(replayed.steps(), F::from(instructions.len() as u64));
+
+        // a trace that doesn't follow the execution
+        let mut wrong = read;
+        wrong[1].ap += 1;
+        assert_eq!(
+            CairoProgram::from_trace(&mut read_mem, &wrong).err(),
+            Some(CairoError::TraceMismatch { pc: 5, step: 0 })
+        );
+
+        // truncated files
+        assert!(read_trace(&trace_file[1..]).is_err());
+        assert!(read_memory::<F, _>(&memory_file[1..]).is_err());
+    }
+}
@@ -2,9 +2,12 @@
 //! execution steps, each of which define the execution logic of Cairo instructions
 
 use crate::flags::*;
+use crate::helper::CairoFieldHelpers;
 use crate::memory::CairoMemory;
+use crate::relocated::TraceEntry;
 use crate::word::{CairoWord, FlagBits, FlagSets, Offsets};
 use ark_ff::Field;
+use thiserror::Error;
 
 /// A structure to store program counter, allocation pointer and frame pointer
 #[derive(Clone, Copy)]
@@ -244,6 +247,61 @@ impl<F: Field> FlagBits<F> for CairoInstruction<F> {
     }
 }
 
+/// Errors that can arise when executing a Cairo program,
+/// with the program counter and the index of the failing step
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CairoError {
+    /// A flagset of the instruction has an invalid combination of bits
+    #[error("invalid {flags} flagset at pc {pc} (step {step})")]
+    InvalidFlags {
+        /// program counter of the instruction
+        pc: u64,
+        /// index of the step in the execution
+        step: u64,
+        /// name of the invalid flagset
+        flags: &'static str,
+    },
+    /// The instruction needs the content of a memory cell that was never written
+    #[error("read of uninitialized address {addr} at pc {pc} (step {step})")]
+    UninitializedMemory {
+        /// program counter of the instruction
+        pc: u64,
+        /// index of the step in the execution
+        step: u64,
+        /// address of the memory cell
+        addr: u64,
+    },
+    /// The instruction writes a value different from the content of a memory cell
+    #[error("conflicting write at address {addr} at pc {pc} (step {step})")]
+    ConflictingWrite {
+        /// program counter of the instruction
+        pc: u64,
+        /// index of the step in the execution
+        step: u64,
+        /// address of the memory cell
+        addr: u64,
+    },
+    /// The program did not terminate within the maximum number of steps
+    #[error("no termination within {step} steps, at pc {pc}")]
+    StepLimit {
+        /// program counter of the next instruction
+        pc: u64,
+        /// number of steps executed
+        step: u64,
+    },
+    /// A step of a given trace does not lead to the pointers of the next step
+    #[error("the trace diverges from the execution at pc {pc} (step {step})")]
+    TraceMismatch {
+        /// program counter of the instruction
+        pc: u64,
+        /// index of the step in the execution
+        step: u64,
+    },
+    /// A given trace has no steps
+    #[error("the trace is empty")]
+    EmptyTrace,
+}
+
 /// A data structure to store a current step of Cairo computation
 pub struct CairoStep<'a, F> {
     /// state of the computation
@@ -255,6 +313,8 @@ pub struct CairoStep<'a, F> {
     next: Option<CairoState<F>>,
     /// state auxiliary variables
     vars: CairoContext<F>,
+    /// index of the step in the execution
+    time: u64,
 }
 
 impl<'a, F: Field> CairoStep<'a, F> {
@@ -265,188 +325,269 @@ impl<'a, F: Field> CairoStep<'a, F> {
             curr: ptrs,
             next: None,
             vars: CairoContext::default(),
+            time: 0,
+        }
+    }
+
+    /// Returns an error for an invalid flagset of the current instruction
+    fn invalid(&self, flags: &'static str) -> CairoError {
+        CairoError::InvalidFlags {
+            pc: self.curr.pc.to_u64(),
+            step: self.time,
+            flags,
         }
     }
 
+    /// Returns the content of a memory cell read by the current instruction,
+    /// or an error if it was never written
+    fn known(&self, value: Option<F>, addr: F) -> Result<F, CairoError> {
+        value.ok_or_else(|| CairoError::UninitializedMemory {
+            pc: self.curr.pc.to_u64(),
+            step: self.time,
+            addr: addr.to_u64(),
+        })
+    }
+
+    /// Writes in a memory cell, or returns an error if it already has another content
+    fn write(&mut self, addr: F, elem: F) -> Result<(), CairoError> {
+        self.mem
+            .try_write(addr, elem)
+            .map_err(|_| CairoError::ConflictingWrite {
+                pc: self.curr.pc.to_u64(),
+                step: self.time,
+                addr: addr.to_u64(),
+            })
+    }
+
     /// Executes a Cairo step from the current registers
+    /// Panics if the step fails, see [CairoStep::try_execute]
     pub fn execute(&mut self) -> CairoInstruction<F> {
+        self.try_execute().unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Executes a Cairo step from the current registers
+    /// Fails on invalid instructions, reads of uninitialized memory and conflicting writes
+    pub fn try_execute(&mut self) -> Result<CairoInstruction<F>, CairoError> {
         // This order is important in order to allocate the memory in time
-        self.set_op0();
-        self.set_op1();
-        self.set_res();
-        self.set_dst();
-        // If the Option<> is not a guarantee for continuation of the program, we may be removing this
-        let next_pc = self.next_pc();
-        let (next_ap, next_fp) = self.next_apfp();
-        self.next = Some(CairoState::new(
-            next_pc.expect("Empty next program counter"),
-            next_ap.expect("Empty next allocation pointer"),
-            next_fp.expect("Empty next frame pointer"),
-        ));
-        CairoInstruction::new(self.instr(), self.curr, self.vars)
+        self.try_set_op0()?;
+        self.try_set_op1()?;
+        self.try_set_res()?;
+        self.try_set_dst()?;
+        let next_pc = self.try_next_pc()?;
+        let (next_ap, next_fp) = self.next_apfp()?;
+        self.next = Some(CairoState::new(next_pc, next_ap, next_fp));
+        Ok(CairoInstruction::new(
+            self.try_instr()?,
+            self.curr,
+            self.vars,
+        ))
     }
 
     /// This function returns the current word instruction being executed
+    /// Panics if pc points to an uninitialized cell
     pub fn instr(&mut self) -> CairoWord<F> {
-        CairoWord::new(self.mem.read(self.curr.pc).expect("pc points to None cell"))
+        self.try_instr().unwrap_or_else(|err| panic!("{err}"))
     }
 
     /// This function computes the first operand address
     pub fn set_op0(&mut self) {
-        let reg = match self.instr().op0_reg() {
+        self.try_set_op0().unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// This function computes the second operand address and content and the instruction size
+    /// Panics if the flagset `OP1_SRC` has more than 1 nonzero bit
+    pub fn set_op1(&mut self) {
+        self.try_set_op1().unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// This function computes the value of the result of the arithmetic operation
+    /// Panics if a `jnz` instruction is used with an invalid format
+    ///     or if the flagset `RES_LOG` has more than 1 nonzero bit
+    pub fn set_res(&mut self) {
+        self.try_set_res().unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// This function computes the destination address
+    pub fn set_dst(&mut self) {
+        self.try_set_dst().unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// This function computes the next program counter
+    /// Panics if the flagset `PC_UP` has more than 1 nonzero bit
+    pub fn next_pc(&mut self) -> Option<F> {
+        Some(self.try_next_pc().unwrap_or_else(|err| panic!("{err}")))
+    }
+
+    /// This function returns the current word instruction being executed
+    fn try_instr(&mut self) -> Result<CairoWord<F>, CairoError> {
+        let word = self.mem.read(self.curr.pc);
+        Ok(CairoWord::new(self.known(word, self.curr.pc)?))
+    }
+
+    /// This function computes the first operand address
+    fn try_set_op0(&mut self) -> Result<(), CairoError> {
+        let reg = match self.try_instr()?.op0_reg() {
             /*0*/ OP0_AP => self.curr.ap, // reads first word from allocated memory
             /*1*/ _ => self.curr.fp, // reads first word from input stack
         }; // no more values than 0 and 1 because op0_reg is one bit
-        self.vars.adr_op0 = reg + self.instr().off_op0();
+        self.vars.adr_op0 = reg + self.try_instr()?.off_op0();
         self.vars.op0 = self.mem.read(self.vars.adr_op0);
+        Ok(())
     }
 
     /// This function computes the second operand address and content and the instruction size
-    /// Panics if the flagset `OP1_SRC` has more than 1 nonzero bit
-    pub fn set_op1(&mut self) {
-        let (reg, size) = match self.instr().op1_src() {
+    /// Fails if the flagset `OP1_SRC` has more than 1 nonzero bit
+    fn try_set_op1(&mut self) -> Result<(), CairoError> {
+        let (reg, size) = match self.try_instr()?.op1_src() {
             /*0*/
-            OP1_DBL => (self.vars.op0.expect("None op0 for OP1_DBL"), F::one()), // double indexing, op0 should be positive for address
+            OP1_DBL => (self.known(self.vars.op0, self.vars.adr_op0)?, F::one()), // double indexing, op0 should be positive for address
             /*1*/
             OP1_VAL => (self.curr.pc, F::from(2u32)), // off_op1 will be 1 and then op1 contains an immediate value
             /*2*/ OP1_FP => (self.curr.fp, F::one()),
             /*4*/ OP1_AP => (self.curr.ap, F::one()),
-            _ => panic!("Invalid op1_src flagset"),
+            _ => return Err(self.invalid("op1_src")),
         };
         self.vars.size = size;
-        self.vars.adr_op1 = reg + self.instr().off_op1(); // apply second offset to corresponding register
+        self.vars.adr_op1 = reg + self.try_instr()?.off_op1(); // apply second offset to corresponding register
         self.vars.op1 = self.mem.read(self.vars.adr_op1);
+        Ok(())
     }
 
     /// This function computes the value of the result of the arithmetic operation
-    /// Panics if a `jnz` instruction is used with an invalid format
+    /// Fails if a `jnz` instruction is used with an invalid format
     ///     or if the flagset `RES_LOG` has more than 1 nonzero bit
-    pub fn set_res(&mut self) {
-        if self.instr().pc_up() == PC_JNZ {
+    fn try_set_res(&mut self) -> Result<(), CairoError> {
+        let instr = self.try_instr()?;
+        if instr.pc_up() == PC_JNZ {
             /*4*/
             // jnz instruction
-            if self.instr().res_log() == RES_ONE /*0*/
-                && self.instr().opcode() == OPC_JMP_INC /*0*/
-                && self.instr().ap_up() != AP_ADD
+            if instr.res_log() == RES_ONE /*0*/
+                && instr.opcode() == OPC_JMP_INC /*0*/
+                && instr.ap_up() != AP_ADD
             /* not 1*/
             {
                 self.vars.res = Some(F::zero()); // "unused"
             } else {
-                panic!("Invalid JNZ instruction");
+                return Err(self.invalid("jnz"));
             }
-        } else if self.instr().pc_up() == PC_SIZ /*0*/
-            || self.instr().pc_up() == PC_ABS /*1*/
-            || self.instr().pc_up() == PC_REL
+        } else if instr.pc_up() == PC_SIZ /*0*/
+            || instr.pc_up() == PC_ABS /*1*/
+            || instr.pc_up() == PC_REL
         /*2*/
         {
             // rest of types of updates
             // common increase || absolute jump || relative jump
-            match self.instr().res_log() {
+            match instr.res_log() {
                 /*0*/
                 RES_ONE => self.vars.res = self.vars.op1, // right part is single operand
                 /*1*/
                 RES_ADD => {
                     self.vars.res = Some(
-                        self.vars.op0.expect("None op0 after RES_ADD")
-                            + self.vars.op1.expect("None op1 after RES_ADD"),
+                        self.known(self.vars.op0, self.vars.adr_op0)?
+                            + self.known(self.vars.op1, self.vars.adr_op1)?,
                     )
                 } // right part is addition
                 /*2*/
                 RES_MUL => {
                     self.vars.res = Some(
-                        self.vars.op0.expect("None op0 after RES_MUL")
-                            * self.vars.op1.expect("None op1 after RES_MUL"),
+                        self.known(self.vars.op0, self.vars.adr_op0)?
+                            * self.known(self.vars.op1, self.vars.adr_op1)?,
                     )
                 } // right part is multiplication
-                _ => panic!("Invalid res_log flagset"),
+                _ => return Err(self.invalid("res_log")),
             }
         } else {
             // multiple bits take value 1
-            panic!("Invalid pc_up flagset");
+            return Err(self.invalid("pc_up"));
         }
+        Ok(())
     }
 
     /// This function computes the destination address
-    pub fn set_dst(&mut self) {
-        let reg = match self.instr().dst_reg() {
+    fn try_set_dst(&mut self) -> Result<(), CairoError> {
+        let reg = match self.try_instr()?.dst_reg() {
             /*0*/ DST_AP => self.curr.ap, // read from stack
             /*1*/ _ => self.curr.fp, // read from parameters
         }; // no more values than 0 and 1 because op0_reg is one bit
-        self.vars.adr_dst = reg + self.instr().off_dst();
+        self.vars.adr_dst = reg + self.try_instr()?.off_dst();
         self.vars.dst = self.mem.read(self.vars.adr_dst);
+        Ok(())
     }
 
     /// This function computes the next program counter
-    /// Panics if the flagset `PC_UP` has more than 1 nonzero bit
-    pub fn next_pc(&mut self) -> Option<F> {
-        match self.instr().pc_up() {
+    /// Fails if the flagset `PC_UP` has more than 1 nonzero bit
+    fn try_next_pc(&mut self) -> Result<F, CairoError> {
+        let pc_up = self.try_instr()?.pc_up();
+        // the result is only unknown when it is the second operand
+        let res = || self.known(self.vars.res, self.vars.adr_op1);
+        match pc_up {
             /*0*/
-            PC_SIZ => Some(self.curr.pc + self.vars.size), // common case, next instruction is right after the current one
+            PC_SIZ => Ok(self.curr.pc + self.vars.size), // common case, next instruction is right after the current one
             /*1*/
-            PC_ABS => Some(self.vars.res.expect("None res after PC_ABS")), // absolute jump, next instruction is in res,
+            PC_ABS => res(), // absolute jump, next instruction is in res,
             /*2*/
-            PC_REL => Some(self.curr.pc + self.vars.res.expect("None res after PC_REL")), // relative jump, go to some address relative to pc
+            PC_REL => Ok(self.curr.pc + res()?), // relative jump, go to some address relative to pc
             /*4*/
             PC_JNZ => {
                 // conditional relative jump (jnz)
-                if self.vars.dst == Some(F::zero()) {
-                    Some(self.curr.pc + self.vars.size) // if condition false, common case
+                if self.known(self.vars.dst, self.vars.adr_dst)? == F::zero() {
+                    Ok(self.curr.pc + self.vars.size) // if condition false, common case
                 } else {
                     // if condition true, relative jump with second operand
-                    Some(self.curr.pc + self.vars.op1.expect("None op1 after PC_JNZ"))
+                    Ok(self.curr.pc + self.known(self.vars.op1, self.vars.adr_op1)?)
                 }
             }
-            _ => panic!("Invalid pc_up flagset"),
+            _ => Err(self.invalid("pc_up")),
         }
     }
 
     /// This function computes the next values of the allocation and frame pointers
-    /// Panics if in a `call` instruction the flagset [AP_UP] is incorrect
+    /// Fails if in a `call` instruction the flagset [AP_UP] is incorrect
     ///     or if in any other instruction the flagset AP_UP has more than 1 nonzero bit
     ///     or if the flagset `OPCODE` has more than 1 nonzero bit
-    fn next_apfp(&mut self) -> (Option<F>, Option<F>) {
+    fn next_apfp(&mut self) -> Result<(F, F), CairoError> {
         let (next_ap, next_fp);
+        let instr = self.try_instr()?;
         // The following branches don't include the assertions. That is done in the verification.
-        if self.instr().opcode() == OPC_CALL {
+        if instr.opcode() == OPC_CALL {
             /*1*/
             // "call" instruction
-            self.mem.write(self.curr.ap, self.curr.fp); // Save current fp
+            self.write(self.curr.ap, self.curr.fp)?; // Save current fp
             self.vars.dst = self.mem.read(self.curr.ap); // update dst content
-            self.mem
-                .write(self.curr.ap + F::one(), self.curr.pc + self.vars.size); // Save next instruction
+            self.write(self.curr.ap + F::one(), self.curr.pc + self.vars.size)?; // Save next instruction
             self.vars.op0 = self.mem.read(self.curr.ap + F::one()); //update op0 content
 
             // Update fp
-            next_fp = Some(self.curr.ap + F::from(2u32)); // pointer for next frame is after current fp and instruction after call
-                                                          // Update ap
-            match self.instr().ap_up() {
+            next_fp = self.curr.ap + F::from(2u32); // pointer for next frame is after current fp and instruction after call
+                                                    // Update ap
+            match instr.ap_up() {
                 /*0*/
-                AP_Z2 => next_ap = Some(self.curr.ap + F::from(2u32)), // two words were written so advance 2 positions
-                _ => panic!("ap increment in call instruction"), // ap increments not allowed in call instructions
+                AP_Z2 => next_ap = self.curr.ap + F::from(2u32), // two words were written so advance 2 positions
+                _ => return Err(self.invalid("call ap_up")), // ap increments not allowed in call instructions
             };
-        } else if self.instr().opcode() == OPC_JMP_INC /*0*/
-            || self.instr().opcode() == OPC_RET /*2*/
-            || self.instr().opcode() == OPC_AEQ
+        } else if instr.opcode() == OPC_JMP_INC /*0*/
+            || instr.opcode() == OPC_RET /*2*/
+            || instr.opcode() == OPC_AEQ
         /*4*/
         {
             // rest of types of instruction
             // jumps and increments || return || assert equal
-            match self.instr().ap_up() {
-                /*0*/ AP_Z2 => next_ap = Some(self.curr.ap), // no modification on ap
+            match instr.ap_up() {
+                /*0*/ AP_Z2 => next_ap = self.curr.ap, // no modification on ap
                 /*1*/
                 AP_ADD => {
                     // ap += <op> should be larger than current ap
-                    next_ap = Some(self.curr.ap + self.vars.res.expect("None res after AP_ADD"))
+                    next_ap = self.curr.ap + self.known(self.vars.res, self.vars.adr_op1)?
                 }
-                /*2*/ AP_ONE => next_ap = Some(self.curr.ap + F::one()), // ap++
-                _ => panic!("Invalid ap_up flagset"),
+                /*2*/ AP_ONE => next_ap = self.curr.ap + F::one(), // ap++
+                _ => return Err(self.invalid("ap_up")),
             }
 
-            match self.instr().opcode() {
+            match instr.opcode() {
                 /*0*/
-                OPC_JMP_INC => next_fp = Some(self.curr.fp), // no modification on fp
+                OPC_JMP_INC => next_fp = self.curr.fp, // no modification on fp
                 /*2*/
-                OPC_RET => next_fp = Some(self.vars.dst.expect("None dst after OPC_RET")), // ret sets fp to previous fp that was in [ap-2]
+                OPC_RET => next_fp = self.known(self.vars.dst, self.vars.adr_dst)?, // ret sets fp to previous fp that was in [ap-2]
                 /*4*/
                 OPC_AEQ => {
                     // The following conditional is a fix that is not explained in the whitepaper
@@ -456,32 +597,28 @@ impl<'a, F: Field> CairoStep<'a, F> {
                     // case where res can be None is when res = op1 and thus res_dir = adr_op1
                     if self.vars.res.is_none() {
                         // res = dst
-                        self.mem.write(
-                            self.vars.adr_op1,
-                            self.vars.dst.expect("None dst after OPC_AEQ"),
-                        );
+                        let dst = self.known(self.vars.dst, self.vars.adr_dst)?;
+                        self.write(self.vars.adr_op1, dst)?;
                         // update the value of the variable as well
                         self.vars.op1 = self.mem.read(self.vars.adr_op1);
                         self.vars.res = self.mem.read(self.vars.adr_op1);
                     } else {
                         // dst = res
-                        self.mem.write(
-                            self.vars.adr_dst,
-                            self.vars.res.expect("None res after OPC_AEQ"),
-                        );
+                        let res = self.known(self.vars.res, self.vars.adr_op1)?;
+                        self.write(self.vars.adr_dst, res)?;
                         // update the value of the variable as well
                         self.vars.dst = self.mem.read(self.vars.adr_dst);
                     }
-                    next_fp = Some(self.curr.fp); // no modification on fp
+                    next_fp = self.curr.fp; // no modification on fp
                 }
                 _ => {
-                    panic!("This case must never happen")
+                    unreachable!("This case must never happen")
                 }
             }
         } else {
-            panic!("Invalid opcode flagset");
+            return Err(self.invalid("opcode"));
         }
-        (next_ap, next_fp)
+        Ok((next_ap, next_fp))
     }
 }
 
@@ -501,7 +638,21 @@ pub struct CairoProgram<'a, F> {
 
 impl<'a, F: Field> CairoProgram<'a, F> {
     /// Creates a Cairo execution from the public information (memory and initial pointers)
+    /// Panics if the execution fails, see [CairoProgram::try_new]
     pub fn new(mem: &mut CairoMemory<F>, pc: u64) -> CairoProgram<F> {
+        match Self::try_new(mem, pc, u64::MAX) {
+            Ok(prog) => prog,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Creates a Cairo execution from the public information (memory and initial pointers),
+    /// executing at most `max_steps` steps
+    pub fn try_new(
+        mem: &mut CairoMemory<F>,
+        pc: u64,
+        max_steps: u64,
+    ) -> Result<CairoProgram<F>, CairoError> {
         let ap = mem.len();
         let mut prog = CairoProgram {
             steps: F::zero(),
@@ -510,8 +661,49 @@ impl<'a, F: Field> CairoProgram<'a, F> {
             fin: CairoState::new(F::zero(), F::zero(), F::zero()),
             trace: Vec::new(),
         };
-        prog.execute();
-        prog
+        prog.execute(max_steps)?;
+        Ok(prog)
+    }
+
+    /// Creates a Cairo execution from its memory and a trace produced elsewhere,
+    /// for example read from a relocated trace file of `cairo-run`, by replaying each step.
+    /// Fails if a step fails, or if it does not lead to the pointers of the next entry
+    pub fn from_trace(
+        mem: &'a mut CairoMemory<F>,
+        entries: &[TraceEntry],
+    ) -> Result<CairoProgram<'a, F>, CairoError> {
+        let state = |entry: &TraceEntry| {
+            CairoState::new(F::from(entry.pc), F::from(entry.ap), F::from(entry.fp))
+        };
+        let (first, last) = match (entries.first(), entries.last()) {
+            (Some(first), Some(last)) => (state(first), state(last)),
+            _ => return Err(CairoError::EmptyTrace),
+        };
+        let mut trace = Vec::with_capacity(entries.len());
+        for (n, entry) in entries.iter().enumerate() {
+            let mut step = CairoStep::new(mem, state(entry));
+            step.time = n as u64;
+            trace.push(step.try_execute()?);
+            if let Some(next) = entries.get(n + 1) {
+                let expected = state(next);
+                let matches = step.next.map_or(false, |ptrs| {
+                    ptrs.pc == expected.pc && ptrs.ap == expected.ap && ptrs.fp == expected.fp
+                });
+                if !matches {
+                    return Err(CairoError::TraceMismatch {
+                        pc: entry.pc,
+                        step: n as u64,
+                    });
+                }
+            }
+        }
+        Ok(CairoProgram {
+            steps: F::from(entries.len() as u64),
+            mem,
+            ini: first,
+            fin: last,
+            trace,
+        })
     }
 
     /// Outputs the total number of steps of the execution carried out by the runner
@@ -534,9 +726,22 @@ impl<'a, F: Field> CairoProgram<'a, F> {
         &self.trace
     }
 
+    /// Returns the pointers of each step, as in a relocated trace file of `cairo-run`
+    pub fn relocated_trace(&self) -> Vec<TraceEntry> {
+        self.trace
+            .iter()
+            .map(|instr| TraceEntry {
+                pc: instr.pc().to_u64(),
+                ap: instr.ap().to_u64(),
+                fp: instr.fp().to_u64(),
+            })
+            .collect()
+    }
+
     /// This function simulates an execution of the Cairo program received as input.
-    /// It generates the full memory stack and the execution trace
-    fn execute(&mut self) {
+    /// It generates the full memory stack and the execution trace.
+    /// It fails if a step fails, or if the program does not terminate within `max_steps` steps
+    fn execute(&mut self, max_steps: u64) -> Result<(), CairoError> {
         // set finishing flag to false, as it just started
         let mut end = false;
         // saves local copy of the initial (claimed) pointers of the program
@@ -546,21 +751,28 @@ impl<'a, F: Field> CairoProgram<'a, F> {
         let mut n: u64 = 0;
         // keep executing steps until the end is reached
         while !end {
+            if n == max_steps {
+                return Err(CairoError::StepLimit {
+                    pc: next.pc.to_u64(),
+                    step: n,
+                });
+            }
             // create current step of computation
             let mut step = CairoStep::new(self.mem, next);
+            step.time = n;
             // save current value of the pointers
             curr = step.curr;
             // execute current step and increase time counter
-            let instr = step.execute();
+            let instr = step.try_execute()?;
             self.trace.push(instr);
             n += 1;
             match step.next {
                 None => end = true, // if find no next pointers, end
-                _ => {
+                Some(ptrs) => {
                     // if there are next pointers
                     end = false;
                     // update next value of pointers
-                    next = step.next.expect("Empty next pointers");
+                    next = ptrs;
                     if curr.ap <= next.pc {
                         // if reading from unallocated memory, end
                         end = true;
@@ -570,12 +782,14 @@ impl<'a, F: Field> CairoProgram<'a, F> {
         }
         self.steps = F::from(n);
         self.fin = CairoState::new(curr.pc, curr.ap, curr.fp);
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures::output_program_memory;
     use mina_curves::pasta::Fp as F;
 
     #[test]
@@ -617,58 +831,69 @@ mod tests {
         println!("{}", prog.mem);
     }
 
+    #[test]
+    fn test_cairo_errors() {
+        let memory = |instrs: Vec<i64>| {
+            let mut mem = CairoMemory::<F>::new(instrs.into_iter().map(F::from).collect());
+            mem.write(F::from(mem.len()), F::from(7u32));
+            mem
+        };
+
+        // [ap] = 10; ap++ with both the immediate and the fp flags for op1
+        let mut mem = memory(vec![0x480e80017fff8000, 10]);
+        assert_eq!(
+            CairoProgram::try_new(&mut mem, 1, 100).err(),
+            Some(CairoError::InvalidFlags {
+                pc: 1,
+                step: 0,
+                flags: "op1_src"
+            })
+        );
+
+        // the same error from a single step
+        let ptrs = CairoState::new(F::from(1u32), F::from(4u32), F::from(4u32));
+        let mut step = CairoStep::new(&mut mem, ptrs);
+        assert!(matches!(
+            step.try_execute(),
+            Err(CairoError::InvalidFlags {
+                flags: "op1_src",
+                ..
+            })
+        ));
+
+        // [ap - 1] = 10, while [ap - 1] already contains 7
+        let mut mem = memory(vec![0x400680017fff7fff, 10]);
+        assert_eq!(
+            CairoProgram::try_new(&mut mem, 1, 100).err(),
+            Some(CairoError::ConflictingWrite {
+                pc: 1,
+                step: 0,
+                addr: 3
+            })
+        );
+
+        // pc points to an uninitialized cell
+        let mut mem = memory(vec![0x480680017fff8000, 10]);
+        assert_eq!(
+            CairoProgram::try_new(&mut mem, 10, 100).err(),
+            Some(CairoError::UninitializedMemory {
+                pc: 10,
+                step: 0,
+                addr: 10
+            })
+        );
+
+        // the program of test_cairo_output, stopped after 3 steps
+        let mut mem = output_program_memory();
+        let err = CairoProgram::try_new(&mut mem, 5, 3).err().unwrap();
+        assert_eq!(err, CairoError::StepLimit { pc: 9, step: 3 });
+        assert_eq!(err.to_string(), "no termination within 3 steps, at pc 9");
+    }
+
     #[test]
     fn test_cairo_output() {
         // This is a test for a longer program, involving builtins, imports and outputs
-        // One can generate more tests here: https://www.cairo-lang.org/playground/
-        /*
-        %builtins output
-        from starkware.cairo.common.serialize import serialize_word
-        func main{output_ptr : felt*}():
-            tempvar x = 10
-            tempvar y = x + x
-            tempvar z = y * y + x
-            serialize_word(x)
-            serialize_word(y)
-            serialize_word(z)
-            return ()
-        end
-        */
-        let instrs = vec![
-            0x400380007ffc7ffd,
-            0x482680017ffc8000,
-            1,
-            0x208b7fff7fff7ffe,
-            0x480680017fff8000,
-            10,
-            0x48307fff7fff8000,
-            0x48507fff7fff8000,
-            0x48307ffd7fff8000,
-            0x480a7ffd7fff8000,
-            0x48127ffb7fff8000,
-            0x1104800180018000,
-            -11,
-            0x48127ff87fff8000,
-            0x1104800180018000,
-            -14,
-            0x48127ff67fff8000,
-            0x1104800180018000,
-            -17,
-            0x208b7fff7fff7ffe,
-            /*41, // beginning of outputs
-            44,   // end of outputs
-            44,   // input
-            */
-        ]
-        .iter()
-        .map(|&i: &i64| F::from(i))
-        .collect();
-
-        let mut mem = CairoMemory::<F>::new(instrs);
-        // Need to know how to find out
-        mem.write(F::from(21u32), F::from(41u32)); // beginning of outputs
-        mem.write(F::from(22u32), F::from(44u32)); // end of outputs
-        mem.write(F::from(23u32), F::from(44u32)); //end of program
+        let mut mem = output_program_memory();
         let prog = CairoProgram::new(&mut mem, 5);
         assert_eq!(prog.fin().pc, F::from(20u32));
         assert_eq!(prog.fin().ap, F::from(41u32));